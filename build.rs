@@ -1,10 +1,17 @@
 use std::collections::HashMap;
-use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{env, fs};
 
 /// Required environment variables for the application
 const REQUIRED_ENV_VARS: &[(&str, &str)] = &[
     ("WIFI_SSID", "WiFi network name"),
     ("WIFI_PASS", "WiFi password"),
+];
+
+/// Environment variables required unless the clock runs standalone, without MQTT
+const MQTT_ENV_VARS: &[(&str, &str)] = &[
     ("MQTT_HOST", "MQTT broker hostname or IP"),
     ("MQTT_PORT", "MQTT broker port (e.g., 1883)"),
     ("MQTT_CLIENT_ID", "Unique MQTT client identifier"),
@@ -41,8 +48,10 @@ fn main() {
     }
 
     // Validate required environment variables
+    let standalone = env_vars.get("STANDALONE").is_some_and(|v| v == "true");
     let missing: Vec<_> = REQUIRED_ENV_VARS
         .iter()
+        .chain(MQTT_ENV_VARS.iter().filter(|_| !standalone))
         .filter(|(key, _)| {
             let value = env_vars.get(*key);
             value.is_none() || value.is_some_and(|v| v.is_empty())
@@ -61,5 +70,77 @@ fn main() {
         println!("cargo:warning===========================================");
     }
 
+    embed_mqtt_ca_cert(env_vars.get("MQTT_CA_CERT"));
+    emit_build_info();
+
     embuild::espidf::sysenv::output();
 }
+
+/// Sets `GIT_HASH` to the short hash of the checked out commit, with a
+/// `-dirty` suffix for uncommitted changes, and `BUILD_DATE` to today's UTC
+/// date, for the firmware to report.
+fn emit_build_info() {
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=.git/index");
+    let git = |args: &[&str]| {
+        Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    let hash = match (
+        git(&["rev-parse", "--short", "HEAD"]),
+        git(&["status", "--porcelain"]),
+    ) {
+        (Some(hash), Some(changes)) if !changes.is_empty() => format!("{}-dirty", hash),
+        (Some(hash), _) => hash,
+        (None, _) => "unknown".to_string(),
+    };
+    println!("cargo:rustc-env=GIT_HASH={}", hash);
+
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / 86_400) as i64;
+    let (year, month, day) = civil_from_days(days);
+    println!(
+        "cargo:rustc-env=BUILD_DATE={:04}-{:02}-{:02}",
+        year, month, day
+    );
+}
+
+/// Converts days since 1970-01-01 into a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Howard Hinnant's algorithm, counting in 400-year eras from 0000-03-01
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Embeds the PEM file named by `MQTT_CA_CERT`, NUL-terminated as ESP-IDF
+/// expects, or an empty file without one.
+fn embed_mqtt_ca_cert(path: Option<&String>) {
+    let mut cert = Vec::new();
+    if let Some(path) = path.filter(|path| !path.is_empty()) {
+        println!("cargo:rerun-if-changed={}", path);
+        match fs::read(path) {
+            Ok(pem) => {
+                cert = pem;
+                cert.push(0);
+            }
+            Err(e) => println!("cargo:warning=Cannot read MQTT_CA_CERT {}: {}", path, e),
+        }
+    }
+    let out_dir = env::var("OUT_DIR").expect("cargo sets OUT_DIR");
+    fs::write(Path::new(&out_dir).join("mqtt_ca_cert.pem"), cert)
+        .expect("cannot write the embedded MQTT CA certificate");
+}
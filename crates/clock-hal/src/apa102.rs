@@ -0,0 +1,235 @@
+//! APA102 (DotStar) LEDs on an embedded-hal SPI bus or two output pins.
+
+use clock_pure::APA102_MAX_BRIGHTNESS;
+use embedded_hal::digital::{OutputPin, PinState};
+use embedded_hal::spi::SpiBus;
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+
+/// Drives a ring of APA102 LEDs from an embedded-hal SPI bus.
+///
+/// APA102 LEDs take their own clock line, so any SPI mode 0 clock up to a few
+/// MHz works, and the strip may be sent in pieces. Every LED gets the same
+/// 5-bit global brightness, set when the driver is created.
+pub struct Apa102Spi<SPI> {
+    spi: SPI,
+    brightness: u8,
+}
+
+impl<SPI: SpiBus> Apa102Spi<SPI> {
+    /// Creates a driver writing to `spi`, which must already be configured.
+    ///
+    /// `brightness` is the global brightness of every LED (0-31).
+    pub fn new(spi: SPI, brightness: u8) -> Self {
+        Self { spi, brightness }
+    }
+
+    /// Returns the SPI bus the driver writes to.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI: SpiBus> SmartLedsWrite for Apa102Spi<SPI> {
+    type Error = SPI::Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let colors = iterator.into_iter().map(Into::into);
+        send_apa102(colors, self.brightness, |bytes| self.spi.write(bytes))?;
+        self.spi.flush()
+    }
+}
+
+/// Drives a ring of APA102 LEDs by toggling a data and a clock pin.
+///
+/// For boards without a free SPI bus: the LEDs sample the data pin on the
+/// rising clock edge and have no timing requirements, so plain output pins
+/// suffice. Both pins must share one error type.
+pub struct Apa102Gpio<DATA, CLOCK> {
+    data: DATA,
+    clock: CLOCK,
+    brightness: u8,
+}
+
+impl<DATA, CLOCK, E> Apa102Gpio<DATA, CLOCK>
+where
+    DATA: OutputPin<Error = E>,
+    CLOCK: OutputPin<Error = E>,
+{
+    /// Creates a driver sending on `data`, clocked on `clock`.
+    ///
+    /// `brightness` is the global brightness of every LED (0-31).
+    pub fn new(data: DATA, clock: CLOCK, brightness: u8) -> Self {
+        Self {
+            data,
+            clock,
+            brightness,
+        }
+    }
+
+    /// Returns the data and clock pins.
+    pub fn into_inner(self) -> (DATA, CLOCK) {
+        (self.data, self.clock)
+    }
+
+    /// Clocks out `bytes`, most significant bit first.
+    fn shift_out(&mut self, bytes: &[u8]) -> Result<(), E> {
+        for &byte in bytes {
+            for bit in (0..8).rev() {
+                self.data
+                    .set_state(PinState::from(byte & (1 << bit) != 0))?;
+                self.clock.set_high()?;
+                self.clock.set_low()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<DATA, CLOCK, E> SmartLedsWrite for Apa102Gpio<DATA, CLOCK>
+where
+    DATA: OutputPin<Error = E>,
+    CLOCK: OutputPin<Error = E>,
+{
+    type Error = E;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        self.clock.set_low()?;
+        let colors = iterator.into_iter().map(Into::into);
+        let brightness = self.brightness;
+        send_apa102(colors, brightness, |bytes| self.shift_out(bytes))
+    }
+}
+
+/// Sends the APA102 start frame, one frame per LED, and the end frame.
+///
+/// Matches [`encode_apa102`](clock_pure::encode_apa102) for a 12-LED ring.
+/// The end frame clocks out at least half a bit per LED, so longer strips
+/// get a longer one.
+fn send_apa102<E>(
+    colors: impl Iterator<Item = RGB8>,
+    brightness: u8,
+    mut send: impl FnMut(&[u8]) -> Result<(), E>,
+) -> Result<(), E> {
+    let header = 0xe0 | brightness.min(APA102_MAX_BRIGHTNESS);
+    send(&[0; 4])?;
+    let mut leds: usize = 0;
+    for color in colors {
+        send(&[header, color.b, color.g, color.r])?;
+        leds += 1;
+    }
+    let end_bytes = leds.div_ceil(16).max(4);
+    for _ in 0..end_bytes {
+        send(&[0xff])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::RecordingBus;
+    use clock_pure::{encode_apa102, Frame, LED_COUNT};
+    use core::cell::RefCell;
+    use core::convert::Infallible;
+    use embedded_hal::digital::ErrorType;
+    use std::rc::Rc;
+
+    /// Shared state of the two pins: the data level and the bits clocked in.
+    #[derive(Default)]
+    struct Line {
+        data: bool,
+        clock: bool,
+        bits: Vec<bool>,
+    }
+
+    struct Pin {
+        line: Rc<RefCell<Line>>,
+        is_clock: bool,
+    }
+
+    impl ErrorType for Pin {
+        type Error = Infallible;
+    }
+
+    impl OutputPin for Pin {
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            let mut line = self.line.borrow_mut();
+            if self.is_clock {
+                line.clock = false;
+            } else {
+                line.data = false;
+            }
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            let mut line = self.line.borrow_mut();
+            if self.is_clock {
+                // The LEDs sample the data on the rising edge
+                if !line.clock {
+                    let bit = line.data;
+                    line.bits.push(bit);
+                }
+                line.clock = true;
+            } else {
+                line.data = true;
+            }
+            Ok(())
+        }
+    }
+
+    fn pixels() -> [RGB8; LED_COUNT] {
+        core::array::from_fn(|i| RGB8::new(i as u8, 100, 255 - i as u8))
+    }
+
+    #[test]
+    fn test_spi_matches_ring_encoding() {
+        let frame = Frame::from_pixels(pixels().map(|c| (c.r, c.g, c.b)));
+        let mut ring = Apa102Spi::new(RecordingBus::default(), 7);
+        ring.write(pixels()).unwrap();
+        let bus = ring.into_inner();
+        assert_eq!(bus.sent, encode_apa102(&frame, 7));
+        assert_eq!(bus.flushes, 1);
+    }
+
+    #[test]
+    fn test_long_strip_gets_longer_end_frame() {
+        let mut ring = Apa102Spi::new(RecordingBus::default(), 31);
+        ring.write([RGB8::default(); 100]).unwrap();
+        let sent = ring.into_inner().sent;
+        assert_eq!(sent.len(), 4 + 4 * 100 + 7);
+        assert!(sent[404..].iter().all(|&b| b == 0xff));
+    }
+
+    #[test]
+    fn test_gpio_clocks_out_same_bytes() {
+        let line = Rc::new(RefCell::new(Line::default()));
+        let pin = |is_clock| Pin {
+            line: Rc::clone(&line),
+            is_clock,
+        };
+        let mut ring = Apa102Gpio::new(pin(false), pin(true), 7);
+        ring.write(pixels()).unwrap();
+
+        let mut spi = Apa102Spi::new(RecordingBus::default(), 7);
+        spi.write(pixels()).unwrap();
+        let bytes: Vec<u8> = line
+            .borrow()
+            .bits
+            .chunks(8)
+            .map(|bits| bits.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)))
+            .collect();
+        assert_eq!(bytes, spi.into_inner().sent);
+    }
+}
@@ -0,0 +1,66 @@
+//! embedded-hal LED backends for the RGB clock.
+//!
+//! The clock's logic in `clock-pure` runs without std, so any microcontroller
+//! with an embedded-hal 1.0 implementation (RP2040, STM32, nRF, ...) can draw
+//! the clock face. This crate provides smart-leds drivers on top of the
+//! embedded-hal traits:
+//!
+//! - [`Ws2812Spi`] sends the WS2812 bit stream on the MOSI line of an SPI bus.
+//! - [`Apa102Spi`] drives APA102 (DotStar) LEDs from an SPI bus.
+//! - [`Apa102Gpio`] bit-bangs APA102 LEDs on two output pins.
+//!
+//! WS2812 LEDs need sub-microsecond timing that plain GPIO pins and delays
+//! cannot guarantee, so they are only driven over SPI.
+
+#![cfg_attr(not(test), no_std)]
+
+mod apa102;
+mod ws2812;
+
+pub use apa102::{Apa102Gpio, Apa102Spi};
+pub use ws2812::Ws2812Spi;
+
+#[cfg(test)]
+mod tests {
+    use core::convert::Infallible;
+    use embedded_hal::spi::{ErrorType, SpiBus};
+
+    /// An SPI bus recording the bytes written to it.
+    #[derive(Default)]
+    pub(crate) struct RecordingBus {
+        pub(crate) sent: Vec<u8>,
+        pub(crate) flushes: usize,
+    }
+
+    impl ErrorType for RecordingBus {
+        type Error = Infallible;
+    }
+
+    impl SpiBus for RecordingBus {
+        fn read(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            words.fill(0);
+            Ok(())
+        }
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            self.sent.extend_from_slice(words);
+            Ok(())
+        }
+
+        fn transfer(&mut self, read: &mut [u8], write: &[u8]) -> Result<(), Self::Error> {
+            read.fill(0);
+            self.write(write)
+        }
+
+        fn transfer_in_place(&mut self, words: &mut [u8]) -> Result<(), Self::Error> {
+            self.write(words)?;
+            words.fill(0);
+            Ok(())
+        }
+
+        fn flush(&mut self) -> Result<(), Self::Error> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! WS2812 LEDs on the MOSI line of an embedded-hal SPI bus.
+
+use clock_pure::{encode_ws2812_spi, ws2812_spi_len, WS2812_SPI_LED_BYTES};
+use embedded_hal::spi::SpiBus;
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+
+/// Drives a WS2812 ring from the MOSI line of an embedded-hal SPI bus.
+///
+/// The bus must be clocked at [`WS2812_SPI_HZ`](clock_pure::WS2812_SPI_HZ)
+/// with 8-bit words. The strip is encoded into `buffer` and sent in one
+/// transfer, so gaps between words cannot latch the LEDs halfway through.
+/// Size the buffer with [`ws2812_spi_len`] for the LEDs on the strip; LEDs
+/// beyond it are not written.
+///
+/// # Example
+///
+/// ```
+/// use clock_hal::Ws2812Spi;
+/// use clock_pure::ws2812_spi_len;
+/// use embedded_hal::spi::SpiBus;
+/// use rgb::RGB8;
+/// use smart_leds_trait::SmartLedsWrite;
+///
+/// // `spi` is the HAL's SPI bus, clocked at 2.4 MHz
+/// fn show_blue<SPI: SpiBus>(spi: SPI) -> Result<(), SPI::Error> {
+///     let mut buffer = [0; ws2812_spi_len(24)];
+///     let mut ring = Ws2812Spi::new(spi, &mut buffer);
+///     ring.write([RGB8::new(0, 0, 255); 24])
+/// }
+/// ```
+pub struct Ws2812Spi<'b, SPI> {
+    spi: SPI,
+    buffer: &'b mut [u8],
+}
+
+impl<'b, SPI: SpiBus> Ws2812Spi<'b, SPI> {
+    /// Creates a driver writing to `spi`, which must already be configured,
+    /// and encoding into `buffer`.
+    pub fn new(spi: SPI, buffer: &'b mut [u8]) -> Self {
+        Self { spi, buffer }
+    }
+
+    /// Returns the most LEDs the buffer holds.
+    pub fn capacity(&self) -> usize {
+        self.buffer.len().saturating_sub(ws2812_spi_len(0)) / WS2812_SPI_LED_BYTES
+    }
+
+    /// Returns the SPI bus the driver writes to.
+    pub fn into_inner(self) -> SPI {
+        self.spi
+    }
+}
+
+impl<SPI: SpiBus> SmartLedsWrite for Ws2812Spi<'_, SPI> {
+    type Error = SPI::Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let capacity = self.capacity();
+        // Every LED is encoded with the reset behind it, which the next LED
+        // overwrites, so the reset always follows the last LED
+        let mut len = encode_ws2812_spi(&[], self.buffer);
+        for (led, color) in iterator.into_iter().take(capacity).enumerate() {
+            let color: RGB8 = color.into();
+            let start = led * WS2812_SPI_LED_BYTES;
+            let pixel = [(color.r, color.g, color.b)];
+            len = start + encode_ws2812_spi(&pixel, &mut self.buffer[start..]);
+        }
+        self.spi.write(&self.buffer[..len])?;
+        self.spi.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::RecordingBus;
+
+    #[test]
+    fn test_matches_whole_strip_encoding() {
+        let pixels = [(1, 2, 3), (255, 0, 128), (0, 0, 0)];
+        let mut expected = [0; ws2812_spi_len(3)];
+        encode_ws2812_spi(&pixels, &mut expected);
+
+        let mut buffer = [0xaa; ws2812_spi_len(12)];
+        let mut ring = Ws2812Spi::new(RecordingBus::default(), &mut buffer);
+        assert_eq!(ring.capacity(), 12);
+        ring.write(pixels.iter().map(|&(r, g, b)| RGB8::new(r, g, b)))
+            .unwrap();
+        let bus = ring.into_inner();
+        assert_eq!(bus.sent, expected);
+        assert_eq!(bus.flushes, 1);
+    }
+
+    #[test]
+    fn test_leds_beyond_buffer_are_dropped() {
+        let mut buffer = [0; ws2812_spi_len(2)];
+        let mut ring = Ws2812Spi::new(RecordingBus::default(), &mut buffer);
+        ring.write([RGB8::new(9, 9, 9); 5]).unwrap();
+        let sent = ring.into_inner().sent;
+        assert_eq!(sent.len(), ws2812_spi_len(2));
+        assert!(sent[2 * WS2812_SPI_LED_BYTES..].iter().all(|&b| b == 0));
+    }
+}
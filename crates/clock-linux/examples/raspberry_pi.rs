@@ -0,0 +1,59 @@
+//! Shows the time on a WS2812 ring connected to SPI0 MOSI (GPIO10) of a Raspberry Pi.
+//!
+//! ```sh
+//! LED_COUNT=24 TIMEZONE="CET-1CEST,M3.5.0,M10.5.0/3" cargo run -p clock-linux --features spidev --example raspberry_pi
+//! ```
+//!
+//! `SPI_DEVICE` selects another SPI device than `/dev/spidev0.0`, and
+//! `BRIGHTNESS` (0-255, default 10) dims the hands.
+
+use clock_linux::Ws2812Spi;
+use clock_pure::{
+    fade_color, ClockFace, HandColors, OverlapPolicy, SecondHand, TimeZone, MAX_FACE_LEDS,
+};
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+// How often the ring is redrawn
+const FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let device = std::env::var("SPI_DEVICE").unwrap_or_else(|_| "/dev/spidev0.0".into());
+    let face: ClockFace = match std::env::var("LED_COUNT") {
+        Ok(leds) => leds.parse()?,
+        Err(_) => ClockFace::default(),
+    };
+    let zone = match std::env::var("TIMEZONE") {
+        Ok(tz) => TimeZone::from_posix(&tz)?,
+        Err(_) => TimeZone::utc(),
+    };
+    let brightness = match std::env::var("BRIGHTNESS") {
+        Ok(level) => clock_pure::parse_brightness(level.as_bytes())?,
+        Err(_) => 10,
+    };
+
+    let mut ring = Ws2812Spi::open(&device)?;
+    println!("Clock of {} LEDs on {}", face.leds(), device);
+    let started = Instant::now();
+    loop {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let time = zone.local_time(now as i64);
+        let elapsed_ms = started.elapsed().as_millis() as u32;
+        let mut pixels = [(0, 0, 0); MAX_FACE_LEDS];
+        let pixels = &mut pixels[..face.leds()];
+        face.render(
+            time,
+            HandColors::default(),
+            SecondHand::Step,
+            OverlapPolicy::default(),
+            elapsed_ms,
+            pixels,
+        );
+        ring.write(pixels.iter().map(|&color| {
+            let (r, g, b) = fade_color(color, brightness);
+            RGB8::new(r, g, b)
+        }))?;
+        std::thread::sleep(FRAME_INTERVAL);
+    }
+}
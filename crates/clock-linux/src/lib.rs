@@ -0,0 +1,118 @@
+//! Linux LED backends for the RGB clock.
+//!
+//! The clock's logic in `clock-pure` runs on any platform; this crate drives
+//! the ring from Linux, e.g. a WS2812 ring on the SPI bus of a Raspberry Pi.
+//! [`Ws2812Spi`] is a smart-leds driver that writes the WS2812 bit stream to
+//! any byte sink; with the `spidev` feature, [`Ws2812Spi::open`] opens a
+//! Linux SPI device for it.
+//!
+//! [`MockDriver`] records frames instead of lighting LEDs, so host tests can
+//! assert exactly what the clock would show.
+
+mod mock;
+
+pub use mock::MockDriver;
+
+use clock_pure::{encode_ws2812_spi, ws2812_spi_len, Rgb};
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+use std::io::{self, Write};
+
+/// Drives a WS2812 ring from the MOSI line of an SPI bus.
+///
+/// The bus must be clocked at [`WS2812_SPI_HZ`](clock_pure::WS2812_SPI_HZ)
+/// with 8-bit words; every write sends the whole strip in one transfer.
+///
+/// # Example
+///
+/// ```
+/// use clock_linux::Ws2812Spi;
+/// use rgb::RGB8;
+/// use smart_leds_trait::SmartLedsWrite;
+///
+/// let mut ring = Ws2812Spi::new(Vec::new());
+/// ring.write([RGB8::new(0, 0, 255); 12]).unwrap();
+/// assert_eq!(ring.into_inner().len(), clock_pure::ws2812_spi_len(12));
+/// ```
+pub struct Ws2812Spi<W> {
+    bus: W,
+    /// The encoded strip, kept to avoid allocating for every frame.
+    bytes: Vec<u8>,
+}
+
+impl<W: Write> Ws2812Spi<W> {
+    /// Creates a driver writing to `bus`, which must already be configured.
+    pub fn new(bus: W) -> Self {
+        Self {
+            bus,
+            bytes: Vec::new(),
+        }
+    }
+
+    /// Returns the bus the driver writes to.
+    pub fn into_inner(self) -> W {
+        self.bus
+    }
+}
+
+#[cfg(feature = "spidev")]
+impl Ws2812Spi<spidev::Spidev> {
+    /// Opens the SPI device at `path`, e.g. `/dev/spidev0.0`, and configures
+    /// it for WS2812 LEDs.
+    ///
+    /// On a Raspberry Pi, enable SPI and connect the ring's data input to
+    /// MOSI (GPIO10) of SPI0.
+    pub fn open(path: impl AsRef<std::path::Path>) -> io::Result<Self> {
+        use spidev::{SpiModeFlags, Spidev, SpidevOptions};
+
+        let mut spi = Spidev::open(path)?;
+        let options = SpidevOptions::new()
+            .bits_per_word(8)
+            .max_speed_hz(clock_pure::WS2812_SPI_HZ)
+            .mode(SpiModeFlags::SPI_MODE_0)
+            .build();
+        spi.configure(&options)?;
+        Ok(Self::new(spi))
+    }
+}
+
+impl<W: Write> SmartLedsWrite for Ws2812Spi<W> {
+    type Error = io::Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let pixels: Vec<Rgb> = iterator
+            .into_iter()
+            .map(|color| {
+                let color: RGB8 = color.into();
+                (color.r, color.g, color.b)
+            })
+            .collect();
+        self.bytes.resize(ws2812_spi_len(pixels.len()), 0);
+        encode_ws2812_spi(&pixels, &mut self.bytes);
+        self.bus.write_all(&self.bytes)?;
+        self.bus.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_write_sends_the_whole_strip() {
+        let mut ring = Ws2812Spi::new(Vec::new());
+        ring.write([RGB8::new(255, 255, 255); 24]).unwrap();
+        ring.write([RGB8::default(); 12]).unwrap();
+        let sent = ring.into_inner();
+        assert_eq!(sent.len(), ws2812_spi_len(24) + ws2812_spi_len(12));
+        // The dark strip is all zero bits, then the reset
+        let dark = &sent[ws2812_spi_len(24)..];
+        assert!(dark[..12 * 9].chunks(3).all(|c| c == [0x92, 0x49, 0x24]));
+        assert!(dark[12 * 9..].iter().all(|&b| b == 0));
+    }
+}
@@ -0,0 +1,169 @@
+//! A driver that records frames instead of lighting LEDs, for host tests.
+
+use clock_pure::Rgb;
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Records every frame written to it instead of lighting LEDs.
+///
+/// It is a smart-leds driver, so the firmware's `LedDriver` and anything
+/// else taking a strip driver accept it, and tests can then assert the exact
+/// frames that were sent. Clones share one recording, so a test keeps a
+/// clone while the code under test owns the driver.
+///
+/// # Example
+///
+/// ```
+/// use clock_linux::MockDriver;
+/// use rgb::RGB8;
+/// use smart_leds_trait::SmartLedsWrite;
+///
+/// let recording = MockDriver::new();
+/// let mut driver = recording.clone();
+/// driver.write([RGB8::new(1, 2, 3); 2]).unwrap();
+/// driver.write([RGB8::default(); 2]).unwrap();
+/// assert_eq!(recording.frames(), [vec![(1, 2, 3); 2], vec![(0, 0, 0); 2]]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockDriver {
+    frames: Arc<Mutex<Vec<Vec<Rgb>>>>,
+}
+
+impl MockDriver {
+    /// Creates a driver with nothing recorded.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns every frame written so far, oldest first.
+    pub fn frames(&self) -> Vec<Vec<Rgb>> {
+        self.recording().clone()
+    }
+
+    /// Returns the frame written last, i.e. what the LEDs would show.
+    pub fn last_frame(&self) -> Option<Vec<Rgb>> {
+        self.recording().last().cloned()
+    }
+
+    /// Returns how many frames were written.
+    pub fn frame_count(&self) -> usize {
+        self.recording().len()
+    }
+
+    /// Forgets the frames written so far.
+    pub fn clear(&self) {
+        self.recording().clear();
+    }
+
+    fn recording(&self) -> MutexGuard<'_, Vec<Vec<Rgb>>> {
+        // A test that panicked while writing has failed anyway
+        self.frames.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SmartLedsWrite for MockDriver {
+    type Error = Infallible;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let frame = iterator
+            .into_iter()
+            .map(|color| {
+                let color: RGB8 = color.into();
+                (color.r, color.g, color.b)
+            })
+            .collect();
+        self.recording().push(frame);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock_pure::effects::{Effect, Identify};
+    use clock_pure::{
+        ClockFace, HandColors, LocalTime, Notification, OverlapPolicy, SecondHand, LED_COUNT,
+    };
+
+    fn write(driver: &mut MockDriver, pixels: &[Rgb]) {
+        let colors = pixels.iter().map(|&(r, g, b)| RGB8::new(r, g, b));
+        driver.write(colors).unwrap();
+    }
+
+    fn clock_face(time: LocalTime) -> [Rgb; LED_COUNT] {
+        let mut pixels = [(0, 0, 0); LED_COUNT];
+        ClockFace::default().render(
+            time,
+            HandColors::default(),
+            SecondHand::Step,
+            OverlapPolicy::Additive,
+            0,
+            &mut pixels,
+        );
+        pixels
+    }
+
+    #[test]
+    fn test_time_updates() {
+        let recording = MockDriver::new();
+        let mut driver = recording.clone();
+        for second in [0, 4, 5] {
+            write(
+                &mut driver,
+                &clock_face(LocalTime::new(3, 30, second).unwrap()),
+            );
+        }
+
+        let colors = HandColors::default();
+        let frames = recording.frames();
+        assert_eq!(frames.len(), 3);
+        // The second hand stays at 12 o'clock until five seconds past
+        assert_eq!(frames[0], frames[1]);
+        assert_eq!(frames[1][11], colors.second);
+        assert_eq!(frames[2][11], (0, 0, 0));
+        assert_eq!(frames[2][0], colors.second);
+        assert_eq!(frames[2][2], colors.hour);
+        assert_eq!(frames[2][5], colors.minute);
+    }
+
+    #[test]
+    fn test_notification_flashes_over_the_face() {
+        let face = clock_face(LocalTime::new(9, 0, 30).unwrap());
+        let doorbell = Notification::new((0, 0, 255));
+        let recording = MockDriver::new();
+        let mut driver = recording.clone();
+        for elapsed_ms in [0, 250, doorbell.duration_ms] {
+            let mut pixels = face;
+            doorbell.overlay(elapsed_ms, &mut pixels);
+            write(&mut driver, &pixels);
+        }
+
+        let frames = recording.frames();
+        assert!(frames[0].iter().all(|&(_, _, b)| b > 0));
+        assert_eq!(frames[1], face);
+        assert_eq!(recording.last_frame().as_deref(), Some(&face[..]));
+    }
+
+    #[test]
+    fn test_effect_sequence() {
+        let recording = MockDriver::new();
+        let mut driver = recording.clone();
+        for frame in Identify::new((255, 255, 255)).frames(10).take(3) {
+            write(&mut driver, frame.pixels());
+        }
+
+        let frames = recording.frames();
+        assert_eq!(frames[0], [(255, 255, 255); LED_COUNT]);
+        assert_eq!(frames[1], [(0, 0, 0); LED_COUNT]);
+        recording.clear();
+        assert_eq!(recording.frame_count(), 0);
+        assert_eq!(driver.frame_count(), 0);
+    }
+}
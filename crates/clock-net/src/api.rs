@@ -0,0 +1,157 @@
+//! The REST API of the clock's web server, an alternative to MQTT.
+//!
+//! | Request                | Answer                                                                                        |
+//! |------------------------|-----------------------------------------------------------------------------------------------|
+//! | `GET /`                | The web interface                                                                             |
+//! | `GET /api/state`       | The state as published on `clock/state`                                                       |
+//! | `GET /api/frame`       | The frame on the LEDs, see [`frame_json`]                                                     |
+//! | `GET /api/alarm`       | The alarms, see [`Alarms::to_json`](clock_pure::Alarms::to_json)                                 |
+//! | `GET /api/network`     | The network settings without the password                                                     |
+//! | `POST /api/network`    | The network settings, after saving the changes in the body                                    |
+//! | `POST /api/restart`    | 202, then the clock restarts                                                                  |
+//! | `POST /api/<resource>` | The state, after handling the body on [`api_topic`]'s topic                                   |
+//! | `GET /ws/frames`       | A WebSocket streaming the frames on the LEDs as they change, as `GET /api/frame` returns them |
+//!
+//! Every `POST` needs the server's token, see [`authorized`].
+
+use clock_pure::{Frame, Rgb, Topic};
+use serde_json::json;
+
+/// Every resource the REST API changes with a `POST` to `/api/<resource>`,
+/// and the topic handling the request body, taken like a payload there.
+const API_RESOURCES: [(&str, Topic); 15] = [
+    ("brightness", Topic::Brightness),
+    ("mode", Topic::Mode),
+    ("colors", Topic::Colors),
+    ("scene", Topic::SceneRecall),
+    ("power", Topic::Power),
+    ("night", Topic::Night),
+    ("dnd", Topic::DoNotDisturb),
+    ("alarm", Topic::Alarm),
+    ("pomodoro", Topic::Pomodoro),
+    ("stopwatch", Topic::Stopwatch),
+    ("notify", Topic::Notify),
+    ("frame", Topic::Frame),
+    ("timezone", Topic::TimeZone),
+    ("cmd", Topic::Cmd),
+    ("config", Topic::Config),
+];
+
+/// Returns the topic handling a `POST` to `/api/<resource>`, or `None` for
+/// an unknown resource.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::api_topic;
+/// use clock_pure::Topic;
+///
+/// assert_eq!(api_topic("brightness"), Some(Topic::Brightness));
+/// assert_eq!(api_topic("scene"), Some(Topic::SceneRecall));
+/// assert_eq!(api_topic("tick"), None);
+/// ```
+pub fn api_topic(resource: &str) -> Option<Topic> {
+    API_RESOURCES
+        .iter()
+        .find(|(name, _)| *name == resource)
+        .map(|&(_, topic)| topic)
+}
+
+/// Returns whether the `Authorization` header of a request that changes the
+/// clock carries `token`, as in `Bearer <token>`. An empty token allows nothing.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::authorized;
+///
+/// assert!(authorized(Some("Bearer s3cret"), "s3cret"));
+/// assert!(!authorized(Some("Bearer guess"), "s3cret"));
+/// assert!(!authorized(None, "s3cret"));
+/// ```
+pub fn authorized(header: Option<&str>, token: &str) -> bool {
+    let Some(given) = header.and_then(|header| header.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    // Compared to the end whatever differs, so the time taken does not give the token away
+    let difference = given
+        .bytes()
+        .zip(token.bytes())
+        .fold(0, |difference, (a, b)| difference | (a ^ b));
+    !token.is_empty() && given.len() == token.len() && difference == 0
+}
+
+/// Returns a snapshot of `frame` as JSON, with its twelve `pixels` in hex,
+/// LED 0 first, as a [`PushedFrame`](clock_pure::PushedFrame) reads them back.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::frame_json;
+/// use clock_pure::{Frame, PushedFrame};
+///
+/// let mut frame = Frame::new();
+/// frame[0] = (255, 0, 0);
+/// let json = frame_json(&frame);
+/// assert!(json.starts_with(r##"{"pixels":["#ff0000","#000000","##));
+/// assert_eq!(PushedFrame::parse(json.as_bytes()).unwrap().frame, frame);
+/// ```
+pub fn frame_json(frame: &Frame) -> String {
+    let pixels: Vec<String> = frame.pixels().iter().copied().map(hex).collect();
+    json!({ "pixels": pixels }).to_string()
+}
+
+/// Formats a color as `#rrggbb`, as [`parse_color`](clock_pure::parse_color) reads it.
+fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock_pure::PushedFrame;
+
+    #[test]
+    fn test_resources() {
+        for (resource, topic) in API_RESOURCES {
+            assert_eq!(api_topic(resource), Some(topic));
+        }
+        assert_eq!(api_topic(""), None);
+        assert_eq!(api_topic("Brightness"), None);
+        assert_eq!(api_topic("scene/recall"), None);
+    }
+
+    #[test]
+    fn test_authorized() {
+        assert!(authorized(Some("Bearer token"), "token"));
+        for header in [
+            "token",
+            "bearer token",
+            "Bearer  token",
+            "Bearer Token",
+            "Bearer tok",
+            "Bearer token2",
+            "Bearer ",
+        ] {
+            assert!(!authorized(Some(header), "token"), "{header}");
+        }
+        assert!(!authorized(Some("Bearer "), ""));
+        assert!(!authorized(None, ""));
+    }
+
+    #[test]
+    fn test_frame_snapshot() {
+        let mut frame = Frame::new();
+        for (i, pixel) in frame.pixels_mut().iter_mut().enumerate() {
+            *pixel = (i as u8, 255 - i as u8, 0x10 * i as u8);
+        }
+        let json: serde_json::Value = serde_json::from_str(&frame_json(&frame)).unwrap();
+        assert_eq!(
+            json["pixels"].as_array().map(Vec::len),
+            Some(clock_pure::LED_COUNT)
+        );
+        assert_eq!(json["pixels"][11], "#0bf4b0");
+        let pushed = PushedFrame::parse(frame_json(&frame).as_bytes()).unwrap();
+        assert_eq!(pushed.frame, frame);
+    }
+}
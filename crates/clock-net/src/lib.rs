@@ -0,0 +1,14 @@
+//! The clock on the network: the topics it uses on the broker and the clock
+//! each message is for, the REST API of its web server, and signed firmware
+//! updates over the air.
+//!
+//! Unlike `clock-pure`, this crate needs `std`; it keeps the JSON and
+//! signature handling of the firmware testable on the host.
+
+mod api;
+mod ota;
+mod topics;
+
+pub use api::{api_topic, authorized, frame_json};
+pub use ota::{FirmwareKey, ImageVerifier, OtaRequest, OtaStatus, SIGNATURE_BYTES};
+pub use topics::{route, Route, TopicLayout};
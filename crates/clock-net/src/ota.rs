@@ -0,0 +1,300 @@
+//! Firmware updates over the air: the request starting one, the check of
+//! the image's signature, and the progress.
+
+use clock_pure::SettingsError;
+use ed25519_compact::{PublicKey, Signature, VerifyingState};
+use serde_json::json;
+
+/// The length of an image signature in bytes.
+pub const SIGNATURE_BYTES: usize = Signature::BYTES;
+
+/// A request to update the firmware from an image on a web server.
+///
+/// The payload is a JSON object with the image's URL in `url` and its
+/// Ed25519 signature as hex in `signature`. Only `http://` and `https://`
+/// URLs are accepted.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::OtaRequest;
+///
+/// let signature = "ab".repeat(64);
+/// let payload = format!(r#"{{"url":"http://192.168.1.5/fw.bin","signature":"{signature}"}}"#);
+/// let request = OtaRequest::parse(payload.as_bytes()).unwrap();
+/// assert_eq!(request.url, "http://192.168.1.5/fw.bin");
+/// assert_eq!(request.signature, [0xab; 64]);
+/// assert!(OtaRequest::parse(br#"{"url":"http://192.168.1.5/fw.bin"}"#).is_err());
+/// assert!(OtaRequest::parse(b"https://example.com/fw.bin").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtaRequest {
+    pub url: String,
+    pub signature: [u8; SIGNATURE_BYTES],
+}
+
+impl OtaRequest {
+    /// Parses a request, see [`OtaRequest`].
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Request {
+            url: String,
+            signature: String,
+        }
+
+        std::str::from_utf8(payload).map_err(|_| SettingsError::InvalidUtf8)?;
+        let Request { url, signature } =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let signature = parse_hex(&signature)?;
+        let host = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .ok_or(SettingsError::InvalidValue)?;
+        if host.is_empty() || url.contains(char::is_whitespace) {
+            return Err(SettingsError::InvalidValue);
+        }
+        Ok(Self { url, signature })
+    }
+}
+
+/// The Ed25519 public key firmware images must be signed with, parsed from
+/// the 64 hex digits of the raw key.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::FirmwareKey;
+///
+/// let hex = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a";
+/// assert!(hex.parse::<FirmwareKey>().is_ok());
+/// assert!("d75a98".parse::<FirmwareKey>().is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirmwareKey(PublicKey);
+
+impl FirmwareKey {
+    /// Starts checking an image against its `signature`, failing if the
+    /// signature cannot be valid for any image.
+    pub fn verifier(
+        &self,
+        signature: &[u8; SIGNATURE_BYTES],
+    ) -> Result<ImageVerifier, SettingsError> {
+        self.0
+            .verify_incremental(&Signature::new(*signature))
+            .map(ImageVerifier)
+            .map_err(|_| SettingsError::InvalidValue)
+    }
+}
+
+impl std::str::FromStr for FirmwareKey {
+    type Err = SettingsError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Ok(Self(PublicKey::new(parse_hex(text.trim())?)))
+    }
+}
+
+/// Checks a firmware image against its signature while it is downloaded.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::{FirmwareKey, OtaRequest};
+///
+/// // The first test vector of RFC 8032, signing an empty image
+/// let key: FirmwareKey = "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+///     .parse()
+///     .unwrap();
+/// let signature = "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155\
+///                  5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b";
+/// let payload = format!(r#"{{"url":"https://example.com/fw.bin","signature":"{signature}"}}"#);
+/// let request = OtaRequest::parse(payload.as_bytes()).unwrap();
+/// let mut verifier = key.verifier(&request.signature).unwrap();
+/// assert!(verifier.verify());
+/// verifier.update(b"tampered");
+/// assert!(!verifier.verify());
+/// ```
+pub struct ImageVerifier(VerifyingState);
+
+impl ImageVerifier {
+    /// Adds the next `chunk` of the image.
+    pub fn update(&mut self, chunk: &[u8]) {
+        self.0.absorb(chunk);
+    }
+
+    /// Returns whether the signature is valid for the image added so far.
+    pub fn verify(&self) -> bool {
+        self.0.verify().is_ok()
+    }
+}
+
+/// Parses exactly `N` bytes written as hex digits.
+fn parse_hex<const N: usize>(text: &str) -> Result<[u8; N], SettingsError> {
+    if text.len() != 2 * N || !text.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(SettingsError::InvalidValue);
+    }
+    let mut bytes = [0; N];
+    for (byte, digits) in bytes.iter_mut().zip(text.as_bytes().chunks(2)) {
+        let digits = std::str::from_utf8(digits).map_err(|_| SettingsError::InvalidValue)?;
+        *byte = u8::from_str_radix(digits, 16).map_err(|_| SettingsError::InvalidValue)?;
+    }
+    Ok(bytes)
+}
+
+/// How far a firmware update got, published while it runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OtaStatus {
+    /// `received` bytes of the image were written, of `total` if the server told
+    Downloading { received: u32, total: Option<u32> },
+    /// The image is complete and being checked
+    Verifying,
+    /// The image is valid and boots next
+    Rebooting,
+    /// The update stopped, keeping the running firmware
+    Failed(String),
+}
+
+impl OtaStatus {
+    /// Returns the share of the image written so far in percent, if the
+    /// size of the image is known.
+    pub fn percent(&self) -> Option<u8> {
+        match *self {
+            OtaStatus::Downloading {
+                received,
+                total: Some(total),
+            } if total > 0 => Some((u64::from(received.min(total)) * 100 / u64::from(total)) as u8),
+            _ => None,
+        }
+    }
+
+    /// Returns the status as JSON, e.g.
+    /// `{"state":"downloading","received":65536,"total":1048576,"percent":6}`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_net::OtaStatus;
+    ///
+    /// assert_eq!(OtaStatus::Rebooting.to_json(), r#"{"state":"rebooting"}"#);
+    /// let failed = OtaStatus::Failed("HTTP status 404".into());
+    /// assert_eq!(failed.to_json(), r#"{"error":"HTTP status 404","state":"failed"}"#);
+    /// ```
+    pub fn to_json(&self) -> String {
+        match self {
+            OtaStatus::Downloading { received, total } => json!({
+                "state": "downloading",
+                "received": received,
+                "total": total,
+                "percent": self.percent(),
+            }),
+            OtaStatus::Verifying => json!({"state": "verifying"}),
+            OtaStatus::Rebooting => json!({"state": "rebooting"}),
+            OtaStatus::Failed(error) => json!({"state": "failed", "error": error}),
+        }
+        .to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(url: &str) -> String {
+        format!(r#"{{"url":"{url}","signature":"{}"}}"#, "00".repeat(64))
+    }
+
+    #[test]
+    fn test_invalid_requests() {
+        for invalid in [
+            "firmware.bin",
+            "https://",
+            "http://host/a b",
+            "ftp://h/fw.bin",
+        ] {
+            assert_eq!(
+                OtaRequest::parse(signed(invalid).as_bytes()),
+                Err(SettingsError::InvalidValue),
+                "{invalid}"
+            );
+        }
+        for invalid in [
+            "https://h/fw.bin",
+            r#"{"url":1}"#,
+            r#"{"url":"http://h/fw.bin"}"#,
+            r#"{"url":"http://h/fw.bin","sha":"0"}"#,
+            "{",
+        ] {
+            assert_eq!(
+                OtaRequest::parse(invalid.as_bytes()),
+                Err(SettingsError::InvalidJson),
+                "{invalid}"
+            );
+        }
+        assert_eq!(OtaRequest::parse(&[0xff]), Err(SettingsError::InvalidUtf8));
+    }
+
+    #[test]
+    fn test_signed_request() {
+        let signature = "00".repeat(63) + "ff";
+        let payload = format!(r#"{{"url":"https://h/fw.bin","signature":"{signature}"}}"#);
+        let request = OtaRequest::parse(payload.as_bytes()).unwrap();
+        let mut expected = [0; SIGNATURE_BYTES];
+        expected[63] = 0xff;
+        assert_eq!(request.signature, expected);
+        for invalid in ["00", "+0".repeat(64).as_str(), "é".repeat(64).as_str()] {
+            let payload = format!(r#"{{"url":"https://h/fw.bin","signature":"{invalid}"}}"#);
+            assert_eq!(
+                OtaRequest::parse(payload.as_bytes()),
+                Err(SettingsError::InvalidValue)
+            );
+        }
+        assert_eq!(
+            OtaRequest::parse(br#"{"url":"https://h/fw.bin","signature":1}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+
+    #[test]
+    fn test_image_signature() {
+        use ed25519_compact::{KeyPair, Seed};
+
+        let pair = KeyPair::from_seed(Seed::new([7; 32]));
+        let image: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+        let signature = *pair.sk.sign(&image, None);
+        let key = FirmwareKey(pair.pk);
+        let verify = |image: &[u8], chunk: usize| {
+            let mut verifier = key.verifier(&signature).unwrap();
+            for part in image.chunks(chunk) {
+                verifier.update(part);
+            }
+            verifier.verify()
+        };
+        assert!(verify(&image, 4096));
+        assert!(verify(&image, 1));
+        assert!(!verify(&image[..9_999], 4096));
+        let mut tampered = image.clone();
+        tampered[5_000] ^= 1;
+        assert!(!verify(&tampered, 4096));
+        let other = FirmwareKey(KeyPair::from_seed(Seed::new([8; 32])).pk);
+        let mut verifier = other.verifier(&signature).unwrap();
+        verifier.update(&image);
+        assert!(!verifier.verify());
+    }
+
+    #[test]
+    fn test_progress() {
+        let downloading = |received, total| OtaStatus::Downloading { received, total };
+        assert_eq!(downloading(0, Some(1000)).percent(), Some(0));
+        assert_eq!(downloading(999, Some(1000)).percent(), Some(99));
+        assert_eq!(downloading(2000, Some(1000)).percent(), Some(100));
+        assert_eq!(downloading(10, Some(0)).percent(), None);
+        assert_eq!(downloading(10, None).percent(), None);
+        let json: serde_json::Value =
+            serde_json::from_str(&downloading(500, None).to_json()).unwrap();
+        assert_eq!(
+            json,
+            json!({"state": "downloading", "received": 500, "total": null, "percent": null})
+        );
+    }
+}
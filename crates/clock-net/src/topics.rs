@@ -0,0 +1,312 @@
+//! Where the clock's topics live on the broker, and which clock they are for.
+
+use clock_pure::Topic;
+
+/// Topics that belong to the device rather than to the clock shown, e.g.
+/// its availability; they are always under the client ID.
+const DEVICE_TOPICS: [&str; 8] = [
+    "cmd",
+    "config",
+    "availability",
+    "info",
+    "state",
+    "telemetry",
+    "ota",
+    "ota/status",
+];
+
+/// Maps the clock's topics, such as `tick`, `clock/brightness`, or
+/// `clock/bedroom/mode` for a second clock, to topics on the broker and back.
+///
+/// By default, the clock's topics are shared by every clock on the broker,
+/// except for the device topics such as `clock/cmd`, which are placed under
+/// the client ID as `clock/<client_id>/cmd`. With a prefix, all topics are
+/// namespaced as `<prefix>/<client_id>/…`, with `tick` becoming
+/// `<prefix>/<client_id>/tick` and `clock/<rest>` becoming
+/// `<prefix>/<client_id>/<rest>`, so several clocks on one broker keep their
+/// time and settings apart.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::TopicLayout;
+///
+/// let shared = TopicLayout::new(None, "kitchen");
+/// assert_eq!(shared.broker_topic("clock/brightness"), "clock/brightness");
+/// assert_eq!(shared.broker_topic("clock/cmd"), "clock/kitchen/cmd");
+///
+/// let namespaced = TopicLayout::new(Some("home/clocks"), "kitchen");
+/// assert_eq!(namespaced.broker_topic("tick"), "home/clocks/kitchen/tick");
+/// assert_eq!(namespaced.broker_topic("clock/cmd"), "home/clocks/kitchen/cmd");
+/// assert_eq!(
+///     namespaced.clock_topic("home/clocks/kitchen/brightness").as_deref(),
+///     Some("clock/brightness")
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TopicLayout<'a> {
+    prefix: Option<&'a str>,
+    client_id: &'a str,
+}
+
+impl<'a> TopicLayout<'a> {
+    /// Creates the layout of the clock `client_id`, namespaced under `prefix`
+    /// if there is one.
+    pub const fn new(prefix: Option<&'a str>, client_id: &'a str) -> Self {
+        Self { prefix, client_id }
+    }
+
+    /// Returns the broker topic of the clock's `topic`.
+    pub fn broker_topic(&self, topic: &str) -> String {
+        let rest = topic.strip_prefix("clock/");
+        match (self.prefix, rest) {
+            (Some(prefix), Some(rest)) => format!("{}/{}/{}", prefix, self.client_id, rest),
+            (Some(prefix), None) => format!("{}/{}/{}", prefix, self.client_id, topic),
+            (None, Some(rest)) if DEVICE_TOPICS.contains(&rest) => {
+                format!("clock/{}/{}", self.client_id, rest)
+            }
+            (None, _) => topic.into(),
+        }
+    }
+
+    /// Returns the clock's topic of the broker topic `topic`, or `None` if
+    /// it lies outside the clock's namespace.
+    pub fn clock_topic(&self, topic: &str) -> Option<String> {
+        match self.prefix {
+            Some(prefix) => {
+                let rest = topic
+                    .strip_prefix(prefix)?
+                    .strip_prefix('/')?
+                    .strip_prefix(self.client_id)?
+                    .strip_prefix('/')?;
+                Some(match rest {
+                    "tick" => rest.into(),
+                    _ => format!("clock/{}", rest),
+                })
+            }
+            None => {
+                let device = topic
+                    .strip_prefix("clock/")
+                    .and_then(|rest| rest.strip_prefix(self.client_id))
+                    .and_then(|rest| rest.strip_prefix('/'))
+                    .filter(|rest| DEVICE_TOPICS.contains(rest));
+                Some(match device {
+                    Some(rest) => format!("clock/{}", rest),
+                    None => topic.into(),
+                })
+            }
+        }
+    }
+}
+
+/// Topics of a clock face, which second clocks take as well; the others,
+/// e.g. the time zone every clock shows or firmware updates, change the
+/// whole device, and only the first clock takes them.
+const FACE_TOPICS: [Topic; 20] = [
+    Topic::Playlist,
+    Topic::Brightness,
+    Topic::Colors,
+    Topic::Night,
+    Topic::DoNotDisturb,
+    Topic::Mode,
+    Topic::Pomodoro,
+    Topic::Stopwatch,
+    Topic::Alarm,
+    Topic::Notify,
+    Topic::Frame,
+    Topic::Weather,
+    Topic::Temperature,
+    Topic::Presence,
+    Topic::Identify,
+    Topic::Animation,
+    Topic::Power,
+    Topic::SceneRecall,
+    Topic::Calibration,
+    Topic::DeadPixels,
+];
+
+/// A message's topic and the clock it is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route {
+    /// The second clock the message is for, as its index among the names
+    /// routed by, or `None` for the first clock
+    pub second: Option<usize>,
+    /// The topic handling the message
+    pub topic: Topic,
+}
+
+impl Route {
+    /// Returns the route of a message on `topic` for the first clock.
+    pub const fn first(topic: Topic) -> Self {
+        Self {
+            second: None,
+            topic,
+        }
+    }
+
+    /// Returns the route of a message on `topic` for the second clock `i`,
+    /// or `None` if `topic` is not one of a clock face.
+    pub fn second(i: usize, topic: Topic) -> Option<Self> {
+        let route = Self {
+            second: Some(i),
+            topic,
+        };
+        FACE_TOPICS.contains(&topic).then_some(route)
+    }
+
+    /// Returns the route of a message on `topic` for the same clock, e.g. of
+    /// a JSON command, or `None` if that clock takes no messages there.
+    pub fn to(self, topic: Topic) -> Option<Self> {
+        match self.second {
+            None => Some(Self::first(topic)),
+            Some(i) => Self::second(i, topic),
+        }
+    }
+}
+
+/// Routes a message on the clock's topic `topic`, e.g. `clock/mode`, to the
+/// first clock, or on `clock/<name>/mode` to the second clock called `name`,
+/// the `i`th of `seconds`.
+///
+/// Returns `None` for a topic no clock handles messages on, e.g.
+/// `clock/<name>/timezone` or `clock/<name>/ota`, as second clocks only
+/// take the topics of their face.
+///
+/// # Example
+///
+/// ```
+/// use clock_net::{route, Route};
+/// use clock_pure::Topic;
+///
+/// let seconds = ["bedroom"];
+/// assert_eq!(route("clock/mode", seconds), Some(Route::first(Topic::Mode)));
+/// assert_eq!(
+///     route("clock/bedroom/mode", seconds),
+///     Some(Route { second: Some(0), topic: Topic::Mode })
+/// );
+/// assert_eq!(route("clock/hall/mode", seconds), None);
+/// ```
+pub fn route<'a>(topic: &str, seconds: impl IntoIterator<Item = &'a str>) -> Option<Route> {
+    if let Some(topic) = Topic::parse(topic) {
+        return Some(Route::first(topic));
+    }
+    let rest = topic.strip_prefix("clock/")?;
+    seconds.into_iter().enumerate().find_map(|(i, name)| {
+        let rest = rest.strip_prefix(name)?.strip_prefix('/')?;
+        Route::second(i, Topic::parse(&format!("clock/{}", rest))?)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_layout() {
+        let layout = TopicLayout::new(None, "rgb-clock");
+        for topic in [
+            "tick",
+            "clock/mode",
+            "clock/bedroom/mode",
+            "clock/alarm/next",
+        ] {
+            assert_eq!(layout.broker_topic(topic), topic);
+            assert_eq!(layout.clock_topic(topic).as_deref(), Some(topic));
+        }
+        assert_eq!(
+            layout.broker_topic("clock/availability"),
+            "clock/rgb-clock/availability"
+        );
+        assert_eq!(
+            layout.clock_topic("clock/rgb-clock/config").as_deref(),
+            Some("clock/config")
+        );
+        assert_eq!(
+            layout.broker_topic("clock/ota/status"),
+            "clock/rgb-clock/ota/status"
+        );
+        // A second clock's device topics are routed by its name
+        assert_eq!(
+            layout.broker_topic("clock/bedroom/cmd"),
+            "clock/bedroom/cmd"
+        );
+    }
+
+    #[test]
+    fn test_namespaced_layout() {
+        let layout = TopicLayout::new(Some("clocks"), "kitchen");
+        for (topic, broker) in [
+            ("tick", "clocks/kitchen/tick"),
+            ("clock/alarm/next", "clocks/kitchen/alarm/next"),
+            ("clock/bedroom/mode", "clocks/kitchen/bedroom/mode"),
+            ("clock/bedroom/#", "clocks/kitchen/bedroom/#"),
+            ("clock/state", "clocks/kitchen/state"),
+        ] {
+            assert_eq!(layout.broker_topic(topic), broker);
+            assert_eq!(layout.clock_topic(broker).as_deref(), Some(topic));
+        }
+        for outside in [
+            "tick",
+            "clocks/hall/tick",
+            "clocks/kitchenette/tick",
+            "clocks/kitchen",
+        ] {
+            assert_eq!(layout.clock_topic(outside), None, "{outside}");
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        let seconds = ["bedroom", "hall"];
+        for topic in Topic::ALL {
+            assert_eq!(route(topic.name(), seconds), Some(Route::first(topic)));
+        }
+        for (topic, second, expected) in [
+            ("clock/bedroom/mode", 0, Topic::Mode),
+            ("clock/hall/scene/recall", 1, Topic::SceneRecall),
+            ("clock/hall/calibration/dead", 1, Topic::DeadPixels),
+        ] {
+            let expected = Route {
+                second: Some(second),
+                topic: expected,
+            };
+            assert_eq!(route(topic, seconds), Some(expected), "{topic}");
+        }
+        for unknown in [
+            "clock/state",
+            "clock/bedroom",
+            "clock/bedroom/",
+            "clock/bedroom/tick",
+            "clock/bedroom/timezone",
+            "clock/bedroom/ota",
+            "clock/bedroom/selftest",
+            "clock/bedroom/cmd",
+            "clock/bedroom/config",
+            "clock/bedroom/schedule",
+            "clock/bedroom/buzzer",
+            "clock/bedroomx/mode",
+            "clock/kitchen/mode",
+            "bedroom/mode",
+        ] {
+            assert_eq!(route(unknown, seconds), None, "{unknown}");
+        }
+        assert_eq!(route("clock/bedroom/mode", []), None);
+    }
+
+    #[test]
+    fn test_face_topics() {
+        let second = Route::second(0, Topic::Mode).unwrap();
+        assert_eq!(second.to(Topic::Colors), Route::second(0, Topic::Colors));
+        for topic in Topic::ALL {
+            let face = FACE_TOPICS.contains(&topic);
+            assert_eq!(Route::second(1, topic).is_some(), face, "{topic:?}");
+        }
+        assert_eq!(Route::second(0, Topic::TimeZone), None);
+        assert_eq!(second.to(Topic::TimeZone), None);
+        let first = Route::first(Topic::Cmd);
+        assert_eq!(
+            first.to(Topic::TimeZone),
+            Some(Route::first(Topic::TimeZone))
+        );
+    }
+}
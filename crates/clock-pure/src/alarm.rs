@@ -0,0 +1,832 @@
+//! Alarms that repeat on selected weekdays and ring until dismissed.
+
+use crate::date::{Date, LocalDateTime, Weekday};
+use crate::settings::SettingsError;
+use crate::time::LocalTime;
+use crate::{fade_color, Rgb};
+#[cfg(feature = "std")]
+use std::format;
+#[cfg(feature = "std")]
+use std::string::{String, ToString};
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// Number of alarm slots.
+pub const MAX_ALARMS: usize = 8;
+
+const DEFAULT_COLOR: Rgb = (255, 160, 64);
+
+/// On and off time of the flashing alarm animation.
+const FLASH_INTERVAL_MS: u32 = 500;
+
+/// How long the ramping alarm animation takes to reach full brightness.
+pub const ALARM_RAMP_MS: u32 = 60_000;
+
+const MS_PER_DAY: u32 = 86_400_000;
+
+/// The weekdays an alarm rings on, one bit per day starting with Monday.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{AlarmDays, Weekday};
+///
+/// let days: AlarmDays = "mon,wed,fri".parse().unwrap();
+/// assert!(days.contains(Weekday::Wednesday));
+/// assert!(!days.contains(Weekday::Tuesday));
+/// assert_eq!("weekend".parse(), Ok(AlarmDays::WEEKEND));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AlarmDays(u8);
+
+impl AlarmDays {
+    /// No day at all; an alarm with these days never rings.
+    pub const NONE: AlarmDays = AlarmDays(0);
+    /// Monday to Friday.
+    pub const WEEKDAYS: AlarmDays = AlarmDays(0b001_1111);
+    /// Saturday and Sunday.
+    pub const WEEKEND: AlarmDays = AlarmDays(0b110_0000);
+    /// Every day of the week.
+    pub const EVERY_DAY: AlarmDays = AlarmDays(0b111_1111);
+
+    /// Creates the set from its bits (bit 0 is Monday); unused bits are ignored.
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits & Self::EVERY_DAY.0)
+    }
+
+    /// Returns one bit per day, bit 0 being Monday.
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the set with `day` added.
+    pub const fn with(self, day: Weekday) -> Self {
+        Self(self.0 | 1 << day.days_from_monday())
+    }
+
+    /// Returns true if the set includes `day`.
+    pub const fn contains(self, day: Weekday) -> bool {
+        self.0 & 1 << day.days_from_monday() != 0
+    }
+}
+
+impl core::str::FromStr for AlarmDays {
+    type Err = SettingsError;
+
+    /// Parses `daily`, `weekdays`, `weekend`, or a comma-separated list of
+    /// day names such as `mon,tue,sat` (full names work too, case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("daily") => return Ok(AlarmDays::EVERY_DAY),
+            t if t.eq_ignore_ascii_case("weekdays") => return Ok(AlarmDays::WEEKDAYS),
+            t if t.eq_ignore_ascii_case("weekend") => return Ok(AlarmDays::WEEKEND),
+            _ => {}
+        }
+
+        text.split(',').try_fold(AlarmDays::NONE, |days, name| {
+            let name = name.trim();
+            let day = Weekday::ALL
+                .into_iter()
+                .find(|day| {
+                    name.len() >= 3
+                        && day.name().len() >= name.len()
+                        && day.name()[..name.len()].eq_ignore_ascii_case(name)
+                })
+                .ok_or(SettingsError::InvalidValue)?;
+            Ok(days.with(day))
+        })
+    }
+}
+
+impl core::fmt::Display for AlarmDays {
+    /// Writes the days the way `parse()` reads them: `daily`, `weekdays`,
+    /// `weekend`, or a list such as `mon,wed,fri`; no days at all are empty.
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            AlarmDays::EVERY_DAY => return f.write_str("daily"),
+            AlarmDays::WEEKDAYS => return f.write_str("weekdays"),
+            AlarmDays::WEEKEND => return f.write_str("weekend"),
+            _ => {}
+        }
+        let mut days = Weekday::ALL.into_iter().filter(|day| self.contains(*day));
+        if let Some(first) = days.next() {
+            f.write_str(&first.name()[..3])?;
+        }
+        for day in days {
+            write!(f, ",{}", &day.name()[..3])?;
+        }
+        Ok(())
+    }
+}
+
+/// How a ringing alarm is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlarmStyle {
+    /// The whole ring blinks
+    #[default]
+    Flash,
+    /// The whole ring brightens gradually over [`ALARM_RAMP_MS`], then stays lit
+    Ramp,
+}
+
+impl AlarmStyle {
+    /// Returns the lowercase name, as accepted by `parse()`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            AlarmStyle::Flash => "flash",
+            AlarmStyle::Ramp => "ramp",
+        }
+    }
+}
+
+impl core::str::FromStr for AlarmStyle {
+    type Err = SettingsError;
+
+    /// Parses `flash` or `ramp` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("flash") => Ok(AlarmStyle::Flash),
+            t if t.eq_ignore_ascii_case("ramp") => Ok(AlarmStyle::Ramp),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// An alarm time with the days it repeats on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Alarm {
+    /// When the alarm rings; seconds are ignored.
+    pub time: LocalTime,
+    pub days: AlarmDays,
+    pub enabled: bool,
+}
+
+impl Alarm {
+    /// Size of one alarm in the binary form used by [`Alarms::to_bytes`].
+    pub const BYTES: usize = 4;
+
+    /// Creates an enabled alarm.
+    pub const fn new(time: LocalTime, days: AlarmDays) -> Self {
+        Self {
+            time,
+            days,
+            enabled: true,
+        }
+    }
+
+    /// Returns true if the alarm is enabled and repeats on `day`.
+    pub const fn rings_on(&self, day: Weekday) -> bool {
+        self.enabled && self.days.contains(day)
+    }
+
+    /// Returns the minutes since midnight the alarm rings at.
+    const fn minute_of_day(&self) -> u16 {
+        self.time.hour as u16 * 60 + self.time.minute as u16
+    }
+}
+
+/// The alarm slots, and the alarm currently ringing.
+///
+/// Call [`Alarms::update`] regularly with the current date and time; an
+/// alarm starts ringing in its minute and keeps ringing until
+/// [`Alarms::dismiss`] is called.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Alarm, AlarmDays, Alarms, Date, LocalTime};
+///
+/// let mut alarms = Alarms::new();
+/// let wake_up = Alarm::new(LocalTime::new(7, 30, 0).unwrap(), AlarmDays::WEEKDAYS);
+/// alarms.set(0, wake_up).unwrap();
+///
+/// // Friday evening: the next alarm is on Monday
+/// let friday = Date::new(2024, 6, 7).unwrap();
+/// let (slot, next) = alarms.next_after(friday, LocalTime::new(20, 0, 0).unwrap()).unwrap();
+/// assert_eq!((slot, next.date), (0, Some(Date::new(2024, 6, 10).unwrap())));
+///
+/// let monday = Date::new(2024, 6, 10).unwrap();
+/// assert_eq!(alarms.update(monday, LocalTime::new(7, 30, 5).unwrap(), 1_000), Some(0));
+/// assert!(alarms.dismiss());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Alarms {
+    slots: [Option<Alarm>; MAX_ALARMS],
+    style: AlarmStyle,
+    color: Rgb,
+    /// The ringing slot and when it started.
+    ringing: Option<(usize, u32)>,
+    /// The date and minute of the last update, so an alarm rings once per minute.
+    last_checked: Option<(Date, u16)>,
+}
+
+impl Alarms {
+    /// Size of all slots in the binary form used by [`Alarms::to_bytes`].
+    pub const BYTES: usize = MAX_ALARMS * Alarm::BYTES;
+
+    /// Creates empty alarm slots that flash in warm white.
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; MAX_ALARMS],
+            style: AlarmStyle::Flash,
+            color: DEFAULT_COLOR,
+            ringing: None,
+            last_checked: None,
+        }
+    }
+
+    /// Sets how a ringing alarm is shown.
+    pub const fn with_style(mut self, style: AlarmStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the color of a ringing alarm.
+    pub const fn with_color(mut self, color: Rgb) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Returns how a ringing alarm is shown.
+    pub const fn style(&self) -> AlarmStyle {
+        self.style
+    }
+
+    /// Returns the alarm in `slot`, if any.
+    pub fn get(&self, slot: usize) -> Option<Alarm> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    /// Returns the occupied slots and their alarms.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Alarm)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, alarm)| alarm.map(|alarm| (slot, alarm)))
+    }
+
+    /// Stores `alarm` in `slot`, replacing any alarm there.
+    pub fn set(&mut self, slot: usize, alarm: Alarm) -> Result<(), SettingsError> {
+        *self.slot_mut(slot)? = Some(alarm);
+        Ok(())
+    }
+
+    /// Clears `slot`, dismissing its alarm if it is ringing.
+    pub fn remove(&mut self, slot: usize) -> Result<(), SettingsError> {
+        *self.slot_mut(slot)? = None;
+        if self.ringing_slot() == Some(slot) {
+            self.ringing = None;
+        }
+        Ok(())
+    }
+
+    /// Returns the slot of the ringing alarm, if one is ringing.
+    pub fn ringing_slot(&self) -> Option<usize> {
+        self.ringing.map(|(slot, _)| slot)
+    }
+
+    /// Returns true while an alarm is ringing.
+    pub const fn is_ringing(&self) -> bool {
+        self.ringing.is_some()
+    }
+
+    /// Stops the ringing alarm; returns false if none was ringing.
+    pub fn dismiss(&mut self) -> bool {
+        self.ringing.take().is_some()
+    }
+
+    /// Checks the alarms at the given local date and time.
+    ///
+    /// Returns the slot of an alarm that started ringing. Each alarm rings at
+    /// most once in its minute, however often this is called.
+    pub fn update(&mut self, date: Date, time: LocalTime, now_ms: u32) -> Option<usize> {
+        let minute = time.hour as u16 * 60 + time.minute as u16;
+        if self.last_checked == Some((date, minute)) {
+            return None;
+        }
+        self.last_checked = Some((date, minute));
+        if self.is_ringing() {
+            return None;
+        }
+
+        let weekday = date.weekday();
+        let (slot, _) = self
+            .iter()
+            .find(|(_, alarm)| alarm.rings_on(weekday) && alarm.minute_of_day() == minute)?;
+        self.ringing = Some((slot, now_ms));
+        Some(slot)
+    }
+
+    /// Returns the next alarm to ring after the given local date and time,
+    /// with its slot. An alarm in the current minute counts as already rung.
+    pub fn next_after(&self, date: Date, time: LocalTime) -> Option<(usize, LocalDateTime)> {
+        let minute = time.hour as u16 * 60 + time.minute as u16;
+        // A week later the alarms repeat, so one extra day covers today's earlier alarms
+        (0..=7).find_map(|offset| {
+            let day = date.add_days(offset);
+            self.iter()
+                .filter(|(_, alarm)| alarm.rings_on(day.weekday()))
+                .filter(|(_, alarm)| offset > 0 || alarm.minute_of_day() > minute)
+                .min_by_key(|(_, alarm)| alarm.minute_of_day())
+                .map(|(slot, alarm)| {
+                    let time = LocalTime {
+                        second: 0,
+                        ..alarm.time
+                    };
+                    (slot, LocalDateTime::new(time, Some(day)))
+                })
+        })
+    }
+
+    /// Returns the milliseconds from `millis_since_midnight` on `date` until
+    /// the next alarm rings, as found by [`Alarms::next_after`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{Alarm, AlarmDays, Alarms, Date, LocalTime};
+    ///
+    /// let mut alarms = Alarms::new();
+    /// alarms.set(0, Alarm::new(LocalTime::new(7, 0, 0).unwrap(), AlarmDays::EVERY_DAY));
+    ///
+    /// let today = Date::new(2024, 6, 10).unwrap();
+    /// assert_eq!(alarms.ms_until_next(today, 6 * 3_600_000 + 59 * 60_000), Some(60_000));
+    /// assert_eq!(alarms.ms_until_next(today, 23 * 3_600_000), Some(8 * 3_600_000));
+    /// ```
+    pub fn ms_until_next(&self, date: Date, millis_since_midnight: u32) -> Option<u32> {
+        let time = LocalTime::from_seconds(millis_since_midnight / 1000);
+        let (_, next) = self.next_after(date, time)?;
+        let days = next.date?.days_since_epoch() - date.days_since_epoch();
+        let at_ms = days as u32 * MS_PER_DAY + next.millis_since_midnight();
+        Some(at_ms - millis_since_midnight)
+    }
+
+    /// Renders the ringing alarm at `now_ms` into `buffer`.
+    ///
+    /// Every pixel is overwritten; the ring is dark when no alarm is ringing.
+    pub fn render(&self, now_ms: u32, buffer: &mut [Rgb]) {
+        let Some((_, since)) = self.ringing else {
+            buffer.fill((0, 0, 0));
+            return;
+        };
+
+        let elapsed = now_ms.wrapping_sub(since);
+        let color = match self.style {
+            AlarmStyle::Flash if (elapsed / FLASH_INTERVAL_MS) % 2 == 1 => (0, 0, 0),
+            AlarmStyle::Flash => self.color,
+            AlarmStyle::Ramp => {
+                let level = elapsed.min(ALARM_RAMP_MS) as u64 * 255 / ALARM_RAMP_MS as u64;
+                fade_color(self.color, level as u8)
+            }
+        };
+        buffer.fill(color);
+    }
+
+    /// Returns the slots in their binary form for persistent storage.
+    ///
+    /// Each slot takes [`Alarm::BYTES`]: hour, minute, days, and a flags byte
+    /// (bit 0: occupied, bit 1: enabled). The style and color are not included.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        for (slot, alarm) in self.iter() {
+            let flags = 0b01 | if alarm.enabled { 0b10 } else { 0 };
+            bytes[slot * Alarm::BYTES..][..Alarm::BYTES].copy_from_slice(&[
+                alarm.time.hour,
+                alarm.time.minute,
+                alarm.days.bits(),
+                flags,
+            ]);
+        }
+        bytes
+    }
+
+    /// Restores the slots from the form written by [`Alarms::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        if bytes.len() != Self::BYTES {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        }
+
+        let mut alarms = Self::new();
+        for (slot, chunk) in bytes.chunks_exact(Alarm::BYTES).enumerate() {
+            let [hour, minute, days, flags] = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            if flags & 0b01 == 0 {
+                continue;
+            }
+            let time = LocalTime::new(hour, minute, 0).map_err(|_| SettingsError::OutOfRange {
+                field: "alarm time",
+                value: u32::from(hour) * 100 + u32::from(minute),
+            })?;
+            alarms.slots[slot] = Some(Alarm {
+                time,
+                days: AlarmDays::from_bits(days),
+                enabled: flags & 0b10 != 0,
+            });
+        }
+        Ok(alarms)
+    }
+
+    /// Returns the alarms with an update from an MQTT payload applied.
+    ///
+    /// The payload is a JSON object naming a `slot` (0-7) and the fields to
+    /// change: `time` (`HH:MM`), `days` (see [`AlarmDays`]), and `enabled`.
+    /// A new alarm needs a `time` and defaults to every day; `"delete": true`
+    /// clears the slot. Nothing changes on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{AlarmDays, Alarms};
+    ///
+    /// let alarms = Alarms::new()
+    ///     .with_update(br#"{"slot":1,"time":"06:45","days":"weekdays"}"#)
+    ///     .unwrap();
+    /// assert_eq!(alarms.get(1).unwrap().days, AlarmDays::WEEKDAYS);
+    ///
+    /// let alarms = alarms.with_update(br#"{"slot":1,"enabled":false}"#).unwrap();
+    /// assert!(!alarms.get(1).unwrap().enabled);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_update(mut self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            slot: usize,
+            time: Option<String>,
+            days: Option<String>,
+            enabled: Option<bool>,
+            #[serde(default)]
+            delete: bool,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        if update.delete {
+            self.remove(update.slot)?;
+            return Ok(self);
+        }
+
+        let current = self.get(update.slot);
+        let time = match (update.time, current) {
+            (Some(text), _) => text.parse().map_err(|_| SettingsError::InvalidValue)?,
+            (None, Some(alarm)) => alarm.time,
+            (None, None) => return Err(SettingsError::InvalidValue),
+        };
+        let days = match (update.days, current) {
+            (Some(text), _) => text.parse()?,
+            (None, Some(alarm)) => alarm.days,
+            (None, None) => AlarmDays::EVERY_DAY,
+        };
+        let enabled = update
+            .enabled
+            .or(current.map(|alarm| alarm.enabled))
+            .unwrap_or(true);
+        self.set(
+            update.slot,
+            Alarm {
+                time,
+                days,
+                enabled,
+            },
+        )?;
+        Ok(self)
+    }
+
+    /// Returns the occupied slots as a JSON array, each alarm in the form
+    /// [`Alarms::with_update`] takes, e.g.
+    /// `[{"days":"weekdays","enabled":true,"slot":0,"time":"06:45"}]`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::Alarms;
+    ///
+    /// let alarms = Alarms::new()
+    ///     .with_update(br#"{"slot":2,"time":"07:05","days":"mon,fri"}"#)
+    ///     .unwrap();
+    /// assert_eq!(
+    ///     alarms.to_json(),
+    ///     r#"[{"days":"mon,fri","enabled":true,"slot":2,"time":"07:05"}]"#
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn to_json(&self) -> String {
+        let alarms: Vec<serde_json::Value> = self
+            .iter()
+            .map(|(slot, alarm)| {
+                serde_json::json!({
+                    "slot": slot,
+                    "time": format!("{:02}:{:02}", alarm.time.hour, alarm.time.minute),
+                    "days": alarm.days.to_string(),
+                    "enabled": alarm.enabled,
+                })
+            })
+            .collect();
+        serde_json::Value::Array(alarms).to_string()
+    }
+
+    fn slot_mut(&mut self, slot: usize) -> Result<&mut Option<Alarm>, SettingsError> {
+        self.slots.get_mut(slot).ok_or(SettingsError::OutOfRange {
+            field: "alarm slot",
+            value: slot as u32,
+        })
+    }
+}
+
+impl Default for Alarms {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLOR: Rgb = (200, 100, 0);
+
+    fn time(hour: u8, minute: u8) -> LocalTime {
+        LocalTime::new(hour, minute, 0).unwrap()
+    }
+
+    fn date(day: u8) -> Date {
+        // June 2024 starts on a Saturday; the 3rd is a Monday
+        Date::new(2024, 6, day).unwrap()
+    }
+
+    fn alarms(entries: &[(usize, u8, u8, AlarmDays)]) -> Alarms {
+        let mut alarms = Alarms::new().with_color(COLOR);
+        for &(slot, hour, minute, days) in entries {
+            alarms
+                .set(slot, Alarm::new(time(hour, minute), days))
+                .unwrap();
+        }
+        alarms
+    }
+
+    // ===== AlarmDays tests =====
+
+    #[test]
+    fn test_days_parse() {
+        assert_eq!("Daily".parse(), Ok(AlarmDays::EVERY_DAY));
+        assert_eq!("weekdays".parse(), Ok(AlarmDays::WEEKDAYS));
+        assert_eq!("sat, Sunday".parse::<AlarmDays>(), Ok(AlarmDays::WEEKEND));
+        assert_eq!("tue".parse(), Ok(AlarmDays::NONE.with(Weekday::Tuesday)));
+        for text in ["", "mo", "mon,,tue", "someday"] {
+            assert_eq!(
+                text.parse::<AlarmDays>(),
+                Err(SettingsError::InvalidValue),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_days_display() {
+        assert_eq!(AlarmDays::EVERY_DAY.to_string(), "daily");
+        assert_eq!(AlarmDays::NONE.to_string(), "");
+        for bits in 1..=0x7f {
+            let days = AlarmDays::from_bits(bits);
+            assert_eq!(days.to_string().parse(), Ok(days), "{days}");
+        }
+    }
+
+    #[test]
+    fn test_days_bits() {
+        assert_eq!(AlarmDays::from_bits(0xff), AlarmDays::EVERY_DAY);
+        assert_eq!(AlarmDays::NONE.with(Weekday::Monday).bits(), 1);
+        assert!(AlarmDays::WEEKEND.contains(Weekday::Sunday));
+        assert!(!AlarmDays::WEEKEND.contains(Weekday::Friday));
+    }
+
+    // ===== Triggering tests =====
+
+    #[test]
+    fn test_rings_once_in_its_minute() {
+        let mut alarms = alarms(&[(2, 7, 0, AlarmDays::EVERY_DAY)]);
+        assert_eq!(alarms.update(date(3), time(6, 59), 0), None);
+        assert_eq!(alarms.update(date(3), time(7, 0), 100), Some(2));
+        assert_eq!(alarms.ringing_slot(), Some(2));
+
+        // Dismissed within the minute, it does not ring again
+        assert!(alarms.dismiss());
+        assert!(!alarms.dismiss());
+        assert_eq!(alarms.update(date(3), time(7, 0), 200), None);
+        assert_eq!(alarms.update(date(4), time(7, 0), 300), Some(2));
+    }
+
+    #[test]
+    fn test_respects_days_and_enabled() {
+        let mut alarms = alarms(&[(0, 7, 0, AlarmDays::WEEKDAYS)]);
+        // Saturday
+        assert_eq!(alarms.update(date(1), time(7, 0), 0), None);
+
+        let mut disabled = alarms.get(0).unwrap();
+        disabled.enabled = false;
+        alarms.set(0, disabled).unwrap();
+        assert_eq!(alarms.update(date(3), time(7, 0), 0), None);
+    }
+
+    #[test]
+    fn test_keeps_ringing_until_dismissed() {
+        let mut alarms = alarms(&[
+            (0, 7, 0, AlarmDays::EVERY_DAY),
+            (1, 7, 1, AlarmDays::EVERY_DAY),
+        ]);
+        assert_eq!(alarms.update(date(3), time(7, 0), 0), Some(0));
+        assert_eq!(alarms.update(date(3), time(7, 1), 60_000), None);
+        assert_eq!(alarms.update(date(3), time(9, 0), 7_200_000), None);
+        assert_eq!(alarms.ringing_slot(), Some(0));
+    }
+
+    #[test]
+    fn test_removing_ringing_alarm_dismisses_it() {
+        let mut alarms = alarms(&[(0, 7, 0, AlarmDays::EVERY_DAY)]);
+        alarms.update(date(3), time(7, 0), 0);
+        alarms.remove(0).unwrap();
+        assert!(!alarms.is_ringing());
+        assert_eq!(
+            alarms.remove(MAX_ALARMS),
+            Err(SettingsError::OutOfRange {
+                field: "alarm slot",
+                value: 8
+            })
+        );
+    }
+
+    // ===== Next alarm tests =====
+
+    #[test]
+    fn test_next_alarm_later_today() {
+        let alarms = alarms(&[
+            (0, 7, 0, AlarmDays::EVERY_DAY),
+            (3, 18, 15, AlarmDays::EVERY_DAY),
+        ]);
+        let (slot, next) = alarms.next_after(date(3), time(7, 0)).unwrap();
+        assert_eq!(slot, 3);
+        assert_eq!(next.date, Some(date(3)));
+        assert_eq!(next.time, time(18, 15));
+    }
+
+    #[test]
+    fn test_next_alarm_skips_days() {
+        let alarms = alarms(&[(0, 6, 30, AlarmDays::NONE.with(Weekday::Monday))]);
+        // Monday after the alarm: a week later
+        let (_, next) = alarms.next_after(date(3), time(6, 31)).unwrap();
+        assert_eq!(next.date, Some(date(10)));
+    }
+
+    #[test]
+    fn test_no_next_alarm() {
+        let mut alarms = alarms(&[(0, 6, 30, AlarmDays::NONE)]);
+        alarms
+            .set(
+                1,
+                Alarm {
+                    enabled: false,
+                    ..Alarm::new(time(8, 0), AlarmDays::EVERY_DAY)
+                },
+            )
+            .unwrap();
+        assert_eq!(alarms.next_after(date(3), time(0, 0)), None);
+    }
+
+    #[test]
+    fn test_ms_until_next_alarm() {
+        let alarms = alarms(&[(0, 6, 30, AlarmDays::NONE.with(Weekday::Monday))]);
+        // Monday 06:29:59.500
+        let millis = time(6, 29).to_seconds() * 1000 + 59_500;
+        assert_eq!(alarms.ms_until_next(date(3), millis), Some(500));
+        // A week less a minute after it rang
+        let millis = time(6, 31).to_seconds() * 1000;
+        assert_eq!(
+            alarms.ms_until_next(date(3), millis),
+            Some(7 * MS_PER_DAY - 60_000)
+        );
+        assert_eq!(Alarms::new().ms_until_next(date(3), 0), None);
+    }
+
+    // ===== Rendering tests =====
+
+    #[test]
+    fn test_render_flash() {
+        let mut alarms = alarms(&[(0, 7, 0, AlarmDays::EVERY_DAY)]);
+        let mut buffer = [(9, 9, 9); 12];
+        alarms.render(0, &mut buffer);
+        assert_eq!(buffer, [(0, 0, 0); 12]);
+
+        alarms.update(date(3), time(7, 0), 1_000);
+        alarms.render(1_200, &mut buffer);
+        assert_eq!(buffer, [COLOR; 12]);
+        alarms.render(1_700, &mut buffer);
+        assert_eq!(buffer, [(0, 0, 0); 12]);
+    }
+
+    #[test]
+    fn test_render_ramp() {
+        let mut alarms = alarms(&[(0, 7, 0, AlarmDays::EVERY_DAY)]).with_style(AlarmStyle::Ramp);
+        let mut buffer = [(0, 0, 0); 12];
+        alarms.update(date(3), time(7, 0), 0);
+
+        alarms.render(0, &mut buffer);
+        assert_eq!(buffer[0], (0, 0, 0));
+        alarms.render(ALARM_RAMP_MS / 2, &mut buffer);
+        assert_eq!(buffer[0], fade_color(COLOR, 127));
+        alarms.render(ALARM_RAMP_MS * 3, &mut buffer);
+        assert_eq!(buffer, [COLOR; 12]);
+    }
+
+    // ===== Storage and update tests =====
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut alarms = alarms(&[
+            (0, 7, 0, AlarmDays::WEEKDAYS),
+            (7, 23, 59, AlarmDays::WEEKEND),
+        ]);
+        let mut disabled = alarms.get(7).unwrap();
+        disabled.enabled = false;
+        alarms.set(7, disabled).unwrap();
+
+        let restored = Alarms::from_bytes(&alarms.to_bytes()).unwrap();
+        assert_eq!(
+            restored.iter().collect::<Vec<_>>(),
+            alarms.iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        assert_eq!(
+            Alarms::from_bytes(&[0; 3]),
+            Err(SettingsError::InvalidLength(3))
+        );
+        let mut bytes = [0; Alarms::BYTES];
+        bytes[..4].copy_from_slice(&[24, 0, 0x7f, 0b11]);
+        assert!(matches!(
+            Alarms::from_bytes(&bytes),
+            Err(SettingsError::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_update_creates_and_deletes() {
+        let alarms = Alarms::new()
+            .with_update(br#"{"slot":0,"time":"07:15"}"#)
+            .unwrap();
+        assert_eq!(
+            alarms.get(0),
+            Some(Alarm::new(time(7, 15), AlarmDays::EVERY_DAY))
+        );
+
+        let alarms = alarms.with_update(br#"{"slot":0,"days":"sat"}"#).unwrap();
+        assert_eq!(alarms.get(0).unwrap().time, time(7, 15));
+        assert_eq!(
+            alarms.get(0).unwrap().days,
+            AlarmDays::NONE.with(Weekday::Saturday)
+        );
+
+        let alarms = alarms.with_update(br#"{"slot":0,"delete":true}"#).unwrap();
+        assert_eq!(alarms.get(0), None);
+    }
+
+    #[test]
+    fn test_update_errors() {
+        let alarms = Alarms::new();
+        for payload in [
+            &br#"{"slot":0}"#[..],
+            br#"{"slot":0,"time":"7"}"#,
+            br#"{"slot":0,"time":"07:00","days":"someday"}"#,
+        ] {
+            assert_eq!(
+                alarms.clone().with_update(payload),
+                Err(SettingsError::InvalidValue)
+            );
+        }
+        assert_eq!(
+            alarms.clone().with_update(br#"{"slot":0,"snooze":5}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert!(matches!(
+            alarms.with_update(br#"{"slot":8,"time":"07:00"}"#),
+            Err(SettingsError::OutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let alarms = alarms(&[(0, 6, 45, AlarmDays::WEEKDAYS), (5, 22, 0, AlarmDays::NONE)])
+            .with_update(br#"{"slot":0,"enabled":false}"#)
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_str(&alarms.to_json()).unwrap();
+        let mut restored = Alarms::new();
+        for alarm in json.as_array().unwrap() {
+            if alarm["days"] == "" {
+                continue;
+            }
+            restored = restored.with_update(alarm.to_string().as_bytes()).unwrap();
+        }
+        assert_eq!(restored.get(0), alarms.get(0));
+        assert_eq!(json[1]["days"], "");
+        assert_eq!(Alarms::new().to_json(), "[]");
+    }
+}
@@ -0,0 +1,372 @@
+//! Time sources and frame pacing for animations.
+//!
+//! Effects are rendered from the milliseconds elapsed since they started
+//! (see [`crate::effects::Effect`]). An [`AnimationClock`] provides that time,
+//! and a [`Ticker`] decides how long to wait before the next frame, so the
+//! same animation code runs on the device, on the host, and in tests.
+//! [`AnimationConfig`] holds the playback speed and frame rate users can
+//! adjust at runtime.
+
+use crate::settings::SettingsError;
+
+/// Playback speeds accepted by [`AnimationConfig::with_update`], in percent.
+const SPEED_RANGE: core::ops::RangeInclusive<u32> = 10..=1000;
+
+/// Frame intervals accepted by [`AnimationConfig::with_update`], in milliseconds.
+#[cfg(feature = "std")]
+const FRAME_INTERVAL_RANGE: core::ops::RangeInclusive<u32> = 10..=1000;
+
+/// A monotonic millisecond time source for animations.
+pub trait AnimationClock {
+    /// Returns the milliseconds elapsed since the clock was started.
+    fn now_ms(&self) -> u32;
+}
+
+/// A manually advanced clock for tests and offline rendering.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::animation::{AnimationClock, ManualClock};
+///
+/// let mut clock = ManualClock::new();
+/// clock.advance(40);
+/// assert_eq!(clock.now_ms(), 40);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ManualClock {
+    now_ms: u32,
+}
+
+impl ManualClock {
+    /// Creates a clock starting at 0 ms.
+    pub const fn new() -> Self {
+        Self { now_ms: 0 }
+    }
+
+    /// Moves the clock forward by `ms` milliseconds.
+    pub fn advance(&mut self, ms: u32) {
+        self.now_ms = self.now_ms.saturating_add(ms);
+    }
+}
+
+impl AnimationClock for ManualClock {
+    fn now_ms(&self) -> u32 {
+        self.now_ms
+    }
+}
+
+/// An [`AnimationClock`] backed by [`std::time::Instant`], started on creation.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct MonotonicClock {
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "std")]
+impl MonotonicClock {
+    /// Creates a clock that starts counting now.
+    pub fn new() -> Self {
+        Self {
+            start: std::time::Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Default for MonotonicClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "std")]
+impl AnimationClock for MonotonicClock {
+    fn now_ms(&self) -> u32 {
+        u32::try_from(self.start.elapsed().as_millis()).unwrap_or(u32::MAX)
+    }
+}
+
+/// Paces frames at a fixed interval without accumulating drift.
+///
+/// The ticker keeps a schedule of frame deadlines. Rendering time is
+/// subtracted from the wait, and when a frame is late the schedule skips
+/// ahead instead of rendering a burst of catch-up frames.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::animation::Ticker;
+///
+/// let mut ticker = Ticker::new(30);
+/// // Rendering the first frame took 10 ms, so wait the remaining 20 ms.
+/// assert_eq!(ticker.delay_until_next(10), 20);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ticker {
+    interval_ms: u32,
+    next_ms: u32,
+}
+
+impl Ticker {
+    /// Creates a ticker with the given frame interval; the first deadline is one interval from 0.
+    pub const fn new(interval_ms: u32) -> Self {
+        let interval_ms = if interval_ms == 0 { 1 } else { interval_ms };
+        Self {
+            interval_ms,
+            next_ms: interval_ms,
+        }
+    }
+
+    /// Returns the frame interval in milliseconds.
+    pub const fn interval_ms(&self) -> u32 {
+        self.interval_ms
+    }
+
+    /// Returns how many milliseconds to wait from `now_ms` until the next frame
+    /// and schedules the frame after it.
+    pub fn delay_until_next(&mut self, now_ms: u32) -> u32 {
+        if now_ms >= self.next_ms {
+            // Late: skip the missed deadlines and align to the interval grid.
+            let missed = (now_ms - self.next_ms) / self.interval_ms + 1;
+            self.next_ms = self
+                .next_ms
+                .saturating_add(missed.saturating_mul(self.interval_ms));
+        }
+        let delay = self.next_ms - now_ms;
+        self.next_ms = self.next_ms.saturating_add(self.interval_ms);
+        delay
+    }
+}
+
+/// Playback speed and frame rate for effects.
+///
+/// Effects render from elapsed time, so changing the speed scales the time
+/// they see: at 200 % an effect runs twice as fast and finishes in half the
+/// time. The frame interval only changes how smoothly they are drawn.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::animation::AnimationConfig;
+///
+/// let slow = AnimationConfig::default().with_update(b"50").unwrap();
+/// assert_eq!(slow.scale(1_000), 500);
+///
+/// let smooth = slow.with_update(br#"{"frame_ms":20}"#).unwrap();
+/// assert_eq!((smooth.speed_percent, smooth.frame_interval_ms), (50, 20));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationConfig {
+    /// Playback speed in percent; 100 plays effects as designed
+    pub speed_percent: u16,
+    /// Time between frames in milliseconds
+    pub frame_interval_ms: u32,
+}
+
+impl AnimationConfig {
+    /// Creates a configuration playing effects at normal speed every `frame_interval_ms`.
+    pub const fn new(frame_interval_ms: u32) -> Self {
+        Self {
+            speed_percent: 100,
+            frame_interval_ms,
+        }
+    }
+
+    /// Converts real `elapsed_ms` into the effect time at the configured speed.
+    pub const fn scale(&self, elapsed_ms: u32) -> u32 {
+        let scaled = elapsed_ms as u64 * self.speed_percent as u64 / 100;
+        if scaled > u32::MAX as u64 {
+            u32::MAX
+        } else {
+            scaled as u32
+        }
+    }
+
+    /// Applies an animation command received over MQTT.
+    ///
+    /// The payload is a playback speed in percent (10-1000), or a JSON object
+    /// with any of `speed` and `frame_ms` (10-1000). Fields left out keep
+    /// their current values. Nothing changes on error.
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.starts_with('{') {
+            return self.with_json_update(payload);
+        }
+        let speed: u32 = text.parse().map_err(|_| SettingsError::InvalidValue)?;
+        Ok(Self {
+            speed_percent: checked("speed", speed, SPEED_RANGE)? as u16,
+            ..self
+        })
+    }
+
+    #[cfg(feature = "std")]
+    fn with_json_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            speed: Option<u32>,
+            frame_ms: Option<u32>,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        Ok(Self {
+            speed_percent: match update.speed {
+                Some(speed) => checked("speed", speed, SPEED_RANGE)? as u16,
+                None => self.speed_percent,
+            },
+            frame_interval_ms: match update.frame_ms {
+                Some(frame_ms) => checked("frame_ms", frame_ms, FRAME_INTERVAL_RANGE)?,
+                None => self.frame_interval_ms,
+            },
+        })
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_json_update(self, _payload: &[u8]) -> Result<Self, SettingsError> {
+        Err(SettingsError::InvalidJson)
+    }
+}
+
+/// Returns `value` if it lies within `range`, or an out-of-range error for `field`.
+fn checked(
+    field: &'static str,
+    value: u32,
+    range: core::ops::RangeInclusive<u32>,
+) -> Result<u32, SettingsError> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(SettingsError::OutOfRange { field, value })
+    }
+}
+
+impl Default for AnimationConfig {
+    /// Normal speed at about 33 frames per second.
+    fn default() -> Self {
+        Self::new(30)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_advances() {
+        let mut clock = ManualClock::new();
+        assert_eq!(clock.now_ms(), 0);
+        clock.advance(30);
+        clock.advance(30);
+        assert_eq!(clock.now_ms(), 60);
+    }
+
+    #[test]
+    fn test_manual_clock_saturates() {
+        let mut clock = ManualClock::new();
+        clock.advance(u32::MAX);
+        clock.advance(1);
+        assert_eq!(clock.now_ms(), u32::MAX);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_monotonic_clock_never_goes_backwards() {
+        let clock = MonotonicClock::new();
+        let a = clock.now_ms();
+        let b = clock.now_ms();
+        assert!(b >= a);
+    }
+
+    #[test]
+    fn test_ticker_compensates_render_time() {
+        let mut ticker = Ticker::new(30);
+        assert_eq!(ticker.delay_until_next(0), 30);
+        assert_eq!(ticker.delay_until_next(35), 25);
+        assert_eq!(ticker.delay_until_next(60), 30);
+    }
+
+    #[test]
+    fn test_ticker_holds_fixed_rate_without_drift() {
+        let mut ticker = Ticker::new(30);
+        let mut clock = ManualClock::new();
+        for _ in 0..100 {
+            clock.advance(7); // render time
+            let delay = ticker.delay_until_next(clock.now_ms());
+            clock.advance(delay);
+        }
+        assert_eq!(clock.now_ms(), 3000);
+    }
+
+    #[test]
+    fn test_ticker_skips_missed_frames() {
+        let mut ticker = Ticker::new(30);
+        // A 100 ms stall misses the deadlines at 30, 60, and 90
+        assert_eq!(ticker.delay_until_next(100), 20);
+        assert_eq!(ticker.delay_until_next(120), 30);
+    }
+
+    #[test]
+    fn test_ticker_deadline_exactly_now() {
+        let mut ticker = Ticker::new(30);
+        assert_eq!(ticker.delay_until_next(30), 30);
+    }
+
+    #[test]
+    fn test_ticker_zero_interval_is_clamped() {
+        let ticker = Ticker::new(0);
+        assert_eq!(ticker.interval_ms(), 1);
+    }
+
+    #[test]
+    fn test_config_scale() {
+        let mut config = AnimationConfig::default();
+        assert_eq!(config.scale(1_234), 1_234);
+        config.speed_percent = 250;
+        assert_eq!(config.scale(1_000), 2_500);
+        config.speed_percent = 1000;
+        assert_eq!(config.scale(u32::MAX), u32::MAX);
+    }
+
+    #[test]
+    fn test_config_update_speed() {
+        let config = AnimationConfig::default();
+        assert_eq!(config.with_update(b" 400\n").unwrap().speed_percent, 400);
+        assert_eq!(
+            config.with_update(b"5"),
+            Err(SettingsError::OutOfRange {
+                field: "speed",
+                value: 5
+            })
+        );
+        assert_eq!(
+            config.with_update(b"fast"),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(config.with_update(&[0xff]), Err(SettingsError::InvalidUtf8));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_config_update_json() {
+        let config = AnimationConfig::default()
+            .with_update(br#"{"speed":75,"frame_ms":50}"#)
+            .unwrap();
+        assert_eq!(config.speed_percent, 75);
+        assert_eq!(config.frame_interval_ms, 50);
+        assert_eq!(
+            config.with_update(br#"{"frame_ms":5000}"#),
+            Err(SettingsError::OutOfRange {
+                field: "frame_ms",
+                value: 5000
+            })
+        );
+        assert_eq!(
+            config.with_update(br#"{"fps":60}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+}
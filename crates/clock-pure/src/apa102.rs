@@ -0,0 +1,61 @@
+//! Encodes frames for APA102 (DotStar) LEDs, which are driven over SPI.
+
+use crate::{Frame, LED_COUNT};
+
+/// Highest APA102 global brightness level; the field is five bits wide.
+pub const APA102_MAX_BRIGHTNESS: u8 = 31;
+
+/// Size of an encoded APA102 frame for the ring.
+///
+/// Four zero bytes start the frame, then every LED takes four bytes. The end
+/// frame must clock out at least half a bit per LED; four bytes cover it.
+pub const APA102_FRAME_BYTES: usize = 4 + 4 * LED_COUNT + 4;
+
+/// Encodes `frame` as one SPI transfer for a ring of APA102 LEDs.
+///
+/// Every LED gets the same global brightness (0-31, higher values are
+/// capped), which the LEDs apply on top of the 8-bit colors. Colors are sent
+/// in the blue, green, red order the LEDs expect.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{encode_apa102, Frame};
+///
+/// let mut frame = Frame::new();
+/// frame[0] = (255, 128, 1);
+/// let bytes = encode_apa102(&frame, 31);
+/// assert_eq!(bytes[..4], [0, 0, 0, 0]);
+/// assert_eq!(bytes[4..8], [0xff, 1, 128, 255]);
+/// ```
+pub fn encode_apa102(frame: &Frame, brightness: u8) -> [u8; APA102_FRAME_BYTES] {
+    let header = 0xe0 | brightness.min(APA102_MAX_BRIGHTNESS);
+    let mut bytes = [0xff; APA102_FRAME_BYTES];
+    bytes[..4].fill(0);
+    for (led, &(r, g, b)) in bytes[4..].chunks_exact_mut(4).zip(frame.pixels()) {
+        led.copy_from_slice(&[header, b, g, r]);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dark_frame() {
+        let bytes = encode_apa102(&Frame::new(), 31);
+        for led in bytes[4..4 + 4 * LED_COUNT].chunks_exact(4) {
+            assert_eq!(led, [0xff, 0, 0, 0]);
+        }
+        assert_eq!(bytes[APA102_FRAME_BYTES - 4..], [0xff; 4]);
+    }
+
+    #[test]
+    fn test_global_brightness() {
+        let frame = Frame::from_pixels([(1, 2, 3); LED_COUNT]);
+        assert_eq!(encode_apa102(&frame, 0)[4], 0xe0);
+        assert_eq!(encode_apa102(&frame, 7)[8], 0xe7);
+        assert_eq!(encode_apa102(&frame, 200)[44], 0xff);
+    }
+}
@@ -0,0 +1,95 @@
+//! Exponential backoff with jitter between reconnection attempts.
+
+/// Delay before the first reconnection attempt, in milliseconds.
+pub const BACKOFF_INITIAL_MS: u32 = 1_000;
+
+/// Longest delay between reconnection attempts, in milliseconds.
+pub const BACKOFF_MAX_MS: u32 = 5 * 60 * 1000;
+
+/// Delays between attempts to reach a server that went away.
+///
+/// The delay doubles with every failed attempt, from [`BACKOFF_INITIAL_MS`]
+/// up to [`BACKOFF_MAX_MS`]. Each delay is randomly shortened by up to half,
+/// so clocks that lost the same broker do not all come back at once.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Backoff, BACKOFF_INITIAL_MS};
+///
+/// let mut backoff = Backoff::new();
+/// // Without jitter, the delays double
+/// assert_eq!(backoff.next_delay_ms(u32::MAX), BACKOFF_INITIAL_MS);
+/// assert_eq!(backoff.next_delay_ms(u32::MAX), 2 * BACKOFF_INITIAL_MS);
+/// // With it, they are shortened by up to half
+/// assert!(backoff.next_delay_ms(0) >= 2 * BACKOFF_INITIAL_MS);
+///
+/// backoff.reset();
+/// assert_eq!(backoff.attempts(), 0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Backoff {
+    attempts: u32,
+}
+
+impl Backoff {
+    /// Creates a backoff before its first attempt.
+    pub const fn new() -> Self {
+        Self { attempts: 0 }
+    }
+
+    /// Returns how many attempts were made since the last reset.
+    pub const fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Returns the delay before the next attempt and counts it.
+    ///
+    /// `random` is any random number; it picks the jitter, with
+    /// `u32::MAX` keeping the full delay.
+    pub fn next_delay_ms(&mut self, random: u32) -> u32 {
+        // Past 16 doublings, any delay is capped anyway
+        let doubled = u64::from(BACKOFF_INITIAL_MS) << self.attempts.min(16);
+        let delay = doubled.min(BACKOFF_MAX_MS.into()) as u32;
+        self.attempts = self.attempts.saturating_add(1);
+        let half = delay / 2;
+        delay - half + (u64::from(half) * u64::from(random) / u64::from(u32::MAX)) as u32
+    }
+
+    /// Starts over after a successful attempt.
+    pub fn reset(&mut self) {
+        self.attempts = 0;
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delays_are_capped() {
+        let mut backoff = Backoff::new();
+        let delays: [u32; 12] = core::array::from_fn(|_| backoff.next_delay_ms(u32::MAX));
+        assert_eq!(delays[8], 256_000);
+        assert_eq!(delays[9], BACKOFF_MAX_MS);
+        assert_eq!(delays[11], BACKOFF_MAX_MS);
+
+        let mut backoff = Backoff { attempts: u32::MAX };
+        assert_eq!(backoff.next_delay_ms(u32::MAX), BACKOFF_MAX_MS);
+        assert_eq!(backoff.attempts(), u32::MAX);
+    }
+
+    #[test]
+    fn test_jitter() {
+        let mut backoff = Backoff { attempts: 2 };
+        assert_eq!(backoff.next_delay_ms(0), 2_000);
+        backoff.attempts = 2;
+        assert_eq!(backoff.next_delay_ms(u32::MAX / 2), 2_999);
+    }
+}
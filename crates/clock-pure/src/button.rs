@@ -0,0 +1,169 @@
+//! Debouncing and press classification for a push button.
+
+/// A raw state must hold this long before it is accepted.
+pub const DEBOUNCE_MS: u32 = 30;
+
+/// Presses held at least this long count as long presses.
+pub const LONG_PRESS_MS: u32 = 1000;
+
+/// A completed button press.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ButtonPress {
+    /// Released before [`LONG_PRESS_MS`]
+    Short,
+    /// Held for at least [`LONG_PRESS_MS`]
+    Long,
+}
+
+/// Turns sampled button levels into debounced presses.
+///
+/// Feed it the raw pressed state at regular intervals; a press is reported
+/// once the button is released.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Button, ButtonPress};
+///
+/// let mut button = Button::new();
+/// assert_eq!(button.update(true, 0), None);
+/// assert_eq!(button.update(true, 50), None);
+/// assert_eq!(button.update(false, 200), None);
+/// assert_eq!(button.update(false, 250), Some(ButtonPress::Short));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Button {
+    /// The debounced state.
+    pressed: bool,
+    /// When the debounced state became pressed.
+    pressed_at: u32,
+    /// The last raw state and when it was first seen.
+    raw: bool,
+    raw_since: u32,
+}
+
+impl Button {
+    /// Creates a button that is not pressed.
+    pub const fn new() -> Self {
+        Self {
+            pressed: false,
+            pressed_at: 0,
+            raw: false,
+            raw_since: 0,
+        }
+    }
+
+    /// Returns true while the debounced button is held down.
+    pub const fn is_pressed(&self) -> bool {
+        self.pressed
+    }
+
+    /// Samples the raw button state at `now_ms`.
+    ///
+    /// Returns the completed press when a debounced release is detected.
+    pub fn update(&mut self, pressed: bool, now_ms: u32) -> Option<ButtonPress> {
+        if pressed != self.raw {
+            self.raw = pressed;
+            self.raw_since = now_ms;
+        }
+        if self.raw == self.pressed || now_ms.wrapping_sub(self.raw_since) < DEBOUNCE_MS {
+            return None;
+        }
+
+        self.pressed = self.raw;
+        if self.pressed {
+            self.pressed_at = self.raw_since;
+            return None;
+        }
+        let held_ms = self.raw_since.wrapping_sub(self.pressed_at);
+        Some(if held_ms >= LONG_PRESS_MS {
+            ButtonPress::Long
+        } else {
+            ButtonPress::Short
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    /// Feeds `(pressed, now_ms)` samples and collects the reported presses.
+    fn feed(button: &mut Button, samples: &[(bool, u32)]) -> Vec<ButtonPress> {
+        samples
+            .iter()
+            .filter_map(|&(pressed, now)| button.update(pressed, now))
+            .collect()
+    }
+
+    #[test]
+    fn test_short_press() {
+        let mut button = Button::new();
+        let presses = feed(
+            &mut button,
+            &[(true, 0), (true, 40), (false, 300), (false, 340)],
+        );
+        assert_eq!(presses, [ButtonPress::Short]);
+        assert!(!button.is_pressed());
+    }
+
+    #[test]
+    fn test_long_press() {
+        let mut button = Button::new();
+        let presses = feed(
+            &mut button,
+            &[
+                (true, 0),
+                (true, 40),
+                (true, 900),
+                (false, 1000),
+                (false, 1040),
+            ],
+        );
+        assert_eq!(presses, [ButtonPress::Long]);
+    }
+
+    #[test]
+    fn test_bounces_are_ignored() {
+        let mut button = Button::new();
+        let samples = [
+            (true, 0),
+            (false, 5),
+            (true, 10),
+            (true, 45),
+            (false, 200),
+            (true, 205),
+            (false, 210),
+            (false, 245),
+        ];
+        assert_eq!(feed(&mut button, &samples), [ButtonPress::Short]);
+    }
+
+    #[test]
+    fn test_glitch_is_not_a_press() {
+        let mut button = Button::new();
+        assert!(feed(&mut button, &[(true, 0), (false, 10), (false, 100)]).is_empty());
+        assert!(!button.is_pressed());
+    }
+
+    #[test]
+    fn test_held_button_reports_nothing_until_release() {
+        let mut button = Button::new();
+        assert!(feed(&mut button, &[(true, 0), (true, 40), (true, 5_000)]).is_empty());
+        assert!(button.is_pressed());
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let mut button = Button::new();
+        let start = u32::MAX - 100;
+        let samples = [
+            (true, start),
+            (true, start.wrapping_add(40)),
+            (false, start.wrapping_add(1200)),
+            (false, start.wrapping_add(1240)),
+        ];
+        assert_eq!(feed(&mut button, &samples), [ButtonPress::Long]);
+    }
+}
@@ -0,0 +1,226 @@
+//! Per-LED brightness correction for rings that light unevenly.
+
+use crate::settings::SettingsError;
+use crate::{fade_color, Frame, LED_COUNT};
+
+/// A brightness scale factor for every LED on the ring.
+///
+/// Diffusers rarely spread light evenly, so some LEDs, e.g. those near a
+/// seam, look brighter than the rest. Each LED's color is scaled by its
+/// factor (0-255, where 255 leaves it unchanged) just before the frame is
+/// sent to the LEDs, so everything drawn on the ring is corrected alike.
+/// LEDs are numbered by position, from LED 0 at 1 o'clock to LED 11 at
+/// 12 o'clock, as in the self-test.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Calibration, Frame};
+///
+/// let calibration = Calibration::default().with_scale(11, 128).unwrap();
+/// let mut frame = Frame::from_pixels([(200, 100, 50); 12]);
+/// calibration.apply(&mut frame);
+/// assert_eq!(frame[11], (100, 50, 25));
+/// assert_eq!(frame[0], (200, 100, 50));
+/// assert_eq!(Calibration::from_bytes(&calibration.to_bytes()), Ok(calibration));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Calibration {
+    scales: [u8; LED_COUNT],
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::new([u8::MAX; LED_COUNT])
+    }
+}
+
+impl Calibration {
+    /// Size of the binary form used by [`Calibration::to_bytes`].
+    pub const BYTES: usize = LED_COUNT;
+
+    /// Creates a calibration from one scale factor per LED.
+    pub const fn new(scales: [u8; LED_COUNT]) -> Self {
+        Self { scales }
+    }
+
+    /// Sets the scale factor of the LED at `led`.
+    pub fn with_scale(mut self, led: usize, scale: u8) -> Result<Self, SettingsError> {
+        let Some(slot) = self.scales.get_mut(led) else {
+            return Err(SettingsError::OutOfRange {
+                field: "led",
+                value: led as u32,
+            });
+        };
+        *slot = scale;
+        Ok(self)
+    }
+
+    /// Returns the scale factor of every LED.
+    pub const fn scales(&self) -> &[u8; LED_COUNT] {
+        &self.scales
+    }
+
+    /// Returns true if no LED is scaled.
+    pub fn is_uniform(&self) -> bool {
+        self.scales.iter().all(|&scale| scale == u8::MAX)
+    }
+
+    /// Scales every pixel of `frame` by its LED's factor.
+    pub fn apply(&self, frame: &mut Frame) {
+        for (pixel, &scale) in frame.pixels_mut().iter_mut().zip(&self.scales) {
+            *pixel = fade_color(*pixel, scale);
+        }
+    }
+
+    /// Encodes the scale factors for persistent storage.
+    pub const fn to_bytes(self) -> [u8; Self::BYTES] {
+        self.scales
+    }
+
+    /// Decodes a calibration stored with [`Calibration::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        bytes
+            .try_into()
+            .map(Self::new)
+            .map_err(|_| SettingsError::InvalidLength(bytes.len()))
+    }
+
+    /// Applies a calibration command received over MQTT.
+    ///
+    /// The payload is `reset` to scale no LED, a JSON array of twelve scale
+    /// factors (0-255) replacing all of them, or a JSON object with `led`
+    /// (0-11) and `scale` changing a single LED. Nothing changes on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::Calibration;
+    ///
+    /// let seam = Calibration::default().with_update(br#"{"led":11,"scale":180}"#).unwrap();
+    /// assert_eq!(seam.scales()[11], 180);
+    /// assert!(seam.with_update(b"reset").unwrap().is_uniform());
+    /// ```
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.eq_ignore_ascii_case("reset") {
+            Ok(Self::default())
+        } else if text.starts_with('{') || text.starts_with('[') {
+            self.with_json_update(text)
+        } else {
+            Err(SettingsError::InvalidValue)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn with_json_update(self, text: &str) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Single {
+            led: usize,
+            scale: u8,
+        }
+
+        if text.starts_with('[') {
+            let scales: [u8; LED_COUNT] =
+                serde_json::from_str(text).map_err(|_| SettingsError::InvalidJson)?;
+            return Ok(Self::new(scales));
+        }
+        let Single { led, scale } =
+            serde_json::from_str(text).map_err(|_| SettingsError::InvalidJson)?;
+        self.with_scale(led, scale)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_json_update(self, _text: &str) -> Result<Self, SettingsError> {
+        Err(SettingsError::InvalidJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_changes_nothing() {
+        let mut frame = Frame::from_pixels([(255, 128, 1); LED_COUNT]);
+        Calibration::default().apply(&mut frame);
+        assert_eq!(frame, Frame::from_pixels([(255, 128, 1); LED_COUNT]));
+        assert!(Calibration::default().is_uniform());
+    }
+
+    #[test]
+    fn test_scales_each_led() {
+        let mut scales = [u8::MAX; LED_COUNT];
+        scales[0] = 0;
+        scales[5] = 51;
+        let mut frame = Frame::from_pixels([(255, 255, 255); LED_COUNT]);
+        Calibration::new(scales).apply(&mut frame);
+        assert_eq!(frame[0], (0, 0, 0));
+        assert_eq!(frame[5], (51, 51, 51));
+        assert_eq!(frame[6], (255, 255, 255));
+    }
+
+    #[test]
+    fn test_scale_out_of_range() {
+        assert_eq!(
+            Calibration::default().with_scale(12, 0),
+            Err(SettingsError::OutOfRange {
+                field: "led",
+                value: 12
+            })
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_wrong_length() {
+        assert_eq!(
+            Calibration::from_bytes(&[255; 11]),
+            Err(SettingsError::InvalidLength(11))
+        );
+    }
+
+    #[test]
+    fn test_update_errors() {
+        assert_eq!(
+            Calibration::default().with_update(b"dim"),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            Calibration::default().with_update(&[0xff]),
+            Err(SettingsError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_json() {
+        let all = Calibration::default()
+            .with_update(b"[200,255,255,255,255,255,255,255,255,255,220,180]")
+            .unwrap();
+        assert_eq!(all.scales()[0], 200);
+        assert_eq!(all.scales()[11], 180);
+
+        let one = all.with_update(br#"{"led":0,"scale":255}"#).unwrap();
+        assert_eq!(one.scales()[0], 255);
+        assert_eq!(one.scales()[11], 180);
+
+        assert_eq!(
+            all.with_update(br#"{"led":12,"scale":0}"#),
+            Err(SettingsError::OutOfRange {
+                field: "led",
+                value: 12
+            })
+        );
+        assert_eq!(
+            all.with_update(b"[255,255]"),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(
+            all.with_update(br#"{"led":0,"scale":255,"gain":2}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+}
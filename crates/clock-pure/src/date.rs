@@ -0,0 +1,425 @@
+//! Calendar dates and time payloads that carry a date.
+//!
+//! Dates are optional in tick payloads: time-only payloads stay valid, and
+//! date-dependent features simply have no date to work with until a
+//! publisher (or a Unix timestamp tick) provides one.
+
+use crate::time::{ConvertError, LocalTime};
+
+/// A day of the week.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    /// All weekdays, starting with Monday.
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Returns the weekday of a day counted from 1970-01-01, which was a Thursday.
+    pub const fn from_days_since_epoch(days: i64) -> Self {
+        Self::ALL[(days + 3).rem_euclid(7) as usize]
+    }
+
+    /// Returns the days since Monday (Monday = 0, Sunday = 6).
+    pub const fn days_from_monday(self) -> u8 {
+        self as u8
+    }
+
+    /// Returns true for Saturday and Sunday.
+    pub const fn is_weekend(self) -> bool {
+        matches!(self, Weekday::Saturday | Weekday::Sunday)
+    }
+
+    /// Returns the lowercase English name, e.g. `"monday"`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Weekday::Monday => "monday",
+            Weekday::Tuesday => "tuesday",
+            Weekday::Wednesday => "wednesday",
+            Weekday::Thursday => "thursday",
+            Weekday::Friday => "friday",
+            Weekday::Saturday => "saturday",
+            Weekday::Sunday => "sunday",
+        }
+    }
+}
+
+/// A date in the Gregorian calendar.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Date, Weekday};
+///
+/// let date = Date::new(2024, 12, 31).unwrap();
+/// assert_eq!(date.weekday(), Weekday::Tuesday);
+/// assert_eq!(date.add_days(1), Date::new(2025, 1, 1).unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Creates a date, checking that the month and the day within it exist.
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, ConvertError> {
+        if !(1..=12).contains(&month) {
+            return Err(ConvertError::OutOfRange {
+                field: "month",
+                value: month.into(),
+            });
+        }
+        if day == 0 || day > days_in_month(year.into(), month) {
+            return Err(ConvertError::OutOfRange {
+                field: "day",
+                value: day.into(),
+            });
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// Returns the date of a day counted from 1970-01-01.
+    pub const fn from_days_since_epoch(days: i64) -> Self {
+        let (year, month, day) = civil_from_days(days);
+        Self {
+            year: year as u16,
+            month,
+            day,
+        }
+    }
+
+    /// Returns the number of days since 1970-01-01.
+    pub const fn days_since_epoch(self) -> i64 {
+        days_from_civil(self.year as i64, self.month, self.day)
+    }
+
+    /// Returns the date `days` later (or earlier, if negative).
+    pub const fn add_days(self, days: i64) -> Self {
+        Self::from_days_since_epoch(self.days_since_epoch() + days)
+    }
+
+    /// Returns the day of the week.
+    pub const fn weekday(self) -> Weekday {
+        Weekday::from_days_since_epoch(self.days_since_epoch())
+    }
+}
+
+/// A local time with an optional date and millisecond part, as received in a tick.
+///
+/// Any [`LocalTime`] converts into a `LocalDateTime` without a date, so
+/// time-only sources keep working.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalDateTime {
+    pub time: LocalTime,
+    pub date: Option<Date>,
+    /// Milliseconds into the current second (0-999)
+    pub millis: u16,
+}
+
+impl LocalDateTime {
+    /// Combines a time with an optional date, at the start of the second.
+    pub const fn new(time: LocalTime, date: Option<Date>) -> Self {
+        Self {
+            time,
+            date,
+            millis: 0,
+        }
+    }
+
+    /// Sets the milliseconds into the current second, clamped to 999.
+    pub const fn with_millis(mut self, millis: u16) -> Self {
+        self.millis = if millis > 999 { 999 } else { millis };
+        self
+    }
+
+    /// Returns the milliseconds since midnight.
+    pub const fn millis_since_midnight(&self) -> u32 {
+        self.time.to_seconds() * 1000 + self.millis as u32
+    }
+}
+
+impl From<LocalTime> for LocalDateTime {
+    fn from(time: LocalTime) -> Self {
+        Self::new(time, None)
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<crate::time::RawLocalTime> for LocalDateTime {
+    type Error = ConvertError;
+
+    fn try_from(raw: crate::time::RawLocalTime) -> Result<Self, Self::Error> {
+        let date = match (raw.year, raw.month, raw.day) {
+            (None, None, None) => None,
+            (Some(year), Some(month), Some(day)) => {
+                let year = u16::try_from(year).map_err(|_| ConvertError::OutOfRange {
+                    field: "year",
+                    value: year,
+                })?;
+                let month = u8::try_from(month).map_err(|_| ConvertError::OutOfRange {
+                    field: "month",
+                    value: month,
+                })?;
+                let day = u8::try_from(day).map_err(|_| ConvertError::OutOfRange {
+                    field: "day",
+                    value: day,
+                })?;
+                Some(Date::new(year, month, day)?)
+            }
+            _ => return Err(ConvertError::IncompleteDate),
+        };
+        let millis = match raw.millis {
+            Some(millis @ 0..=999) => millis as u16,
+            Some(millis) => {
+                return Err(ConvertError::OutOfRange {
+                    field: "millis",
+                    value: millis,
+                })
+            }
+            None => 0,
+        };
+        Ok(LocalDateTime::new(LocalTime::try_from(raw)?, date).with_millis(millis))
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<&[u8]> for LocalDateTime {
+    type Error = ConvertError;
+
+    /// Parses a JSON tick with optional `year`, `month`, `day`, and `millis` fields, or a binary tick.
+    ///
+    /// A `weekday` field is accepted but ignored; the weekday is derived from the date.
+    fn try_from(message: &[u8]) -> Result<Self, Self::Error> {
+        use crate::time::BINARY_TICK_BYTES;
+
+        if (BINARY_TICK_BYTES..=BINARY_TICK_BYTES + 1).contains(&message.len()) {
+            return LocalTime::from_binary(message).map(Self::from);
+        }
+        let json = std::str::from_utf8(message).map_err(|_| ConvertError::InvalidUtf8)?;
+        let raw: crate::time::RawLocalTime =
+            serde_json::from_str(json).map_err(|_| ConvertError::InvalidJson)?;
+        LocalDateTime::try_from(raw)
+    }
+}
+
+/// Returns true if `year` is a leap year in the Gregorian calendar.
+pub(crate) const fn is_leap_year(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// Returns the number of days in `month` (1-12) of `year`.
+pub(crate) const fn days_in_month(year: i64, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the days since 1970-01-01 of a Gregorian date.
+pub(crate) const fn days_from_civil(year: i64, month: u8, day: u8) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_from_march = (month as i64 + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Returns the Gregorian `(year, month, day)` of a day counted from 1970-01-01.
+pub(crate) const fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_from_march + 2) / 5 + 1) as u8;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    } as u8;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // ===== calendar tests =====
+
+    #[test]
+    fn test_days_from_civil_known_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 2, 29), 11_016);
+        assert_eq!(days_from_civil(2024, 1, 1), 19_723);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+    }
+
+    #[test]
+    fn test_civil_from_days_roundtrip() {
+        for days in (-800_000..800_000).step_by(997) {
+            let (y, m, d) = civil_from_days(days);
+            assert_eq!(days_from_civil(y, m, d), days);
+        }
+    }
+
+    #[test]
+    fn test_leap_years() {
+        assert!(is_leap_year(2024));
+        assert!(is_leap_year(2000));
+        assert!(!is_leap_year(1900));
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(2024, 2), 29);
+    }
+
+    // ===== date tests =====
+
+    fn date(year: u16, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_date_validation() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert_eq!(
+            Date::new(2023, 2, 29),
+            Err(ConvertError::OutOfRange {
+                field: "day",
+                value: 29
+            })
+        );
+        assert_eq!(
+            Date::new(2024, 13, 1),
+            Err(ConvertError::OutOfRange {
+                field: "month",
+                value: 13
+            })
+        );
+        assert!(Date::new(2024, 4, 0).is_err());
+    }
+
+    #[test]
+    fn test_weekdays() {
+        assert_eq!(date(1970, 1, 1).weekday(), Weekday::Thursday);
+        assert_eq!(date(2024, 3, 31).weekday(), Weekday::Sunday);
+        assert_eq!(date(2000, 1, 3).weekday(), Weekday::Monday);
+        assert!(Weekday::Saturday.is_weekend());
+        assert!(!Weekday::Friday.is_weekend());
+        assert_eq!(Weekday::Sunday.days_from_monday(), 6);
+    }
+
+    #[test]
+    fn test_add_days_across_month_and_year() {
+        assert_eq!(date(2024, 2, 28).add_days(1), date(2024, 2, 29));
+        assert_eq!(date(2024, 2, 29).add_days(1), date(2024, 3, 1));
+        assert_eq!(date(2025, 1, 1).add_days(-1), date(2024, 12, 31));
+    }
+
+    #[test]
+    fn test_days_since_epoch_roundtrip() {
+        let d = date(2024, 7, 15);
+        assert_eq!(Date::from_days_since_epoch(d.days_since_epoch()), d);
+    }
+
+    // ===== payload tests =====
+
+    #[cfg(feature = "std")]
+    fn parse(json: &str) -> Result<LocalDateTime, ConvertError> {
+        LocalDateTime::try_from(json.as_bytes())
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_time_only_payload() {
+        let dt = parse(r#"{"hour":14,"minute":23,"second":45}"#).unwrap();
+        assert_eq!(dt.time, LocalTime::new(14, 23, 45).unwrap());
+        assert_eq!(dt.date, None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_payload_with_date_and_weekday() {
+        let dt = parse(
+            r#"{"hour":8,"minute":0,"second":0,"year":2024,"month":12,"day":24,"weekday":1}"#,
+        )
+        .unwrap();
+        assert_eq!(dt.date, Some(date(2024, 12, 24)));
+        assert_eq!(dt.date.unwrap().weekday(), Weekday::Tuesday);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_incomplete_date() {
+        assert_eq!(
+            parse(r#"{"hour":8,"minute":0,"second":0,"year":2024}"#),
+            Err(ConvertError::IncompleteDate)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_invalid_date() {
+        assert_eq!(
+            parse(r#"{"hour":8,"minute":0,"second":0,"year":2024,"month":2,"day":30}"#),
+            Err(ConvertError::OutOfRange {
+                field: "day",
+                value: 30
+            })
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_millis() {
+        let dt = parse(r#"{"hour":8,"minute":0,"second":5,"millis":250}"#).unwrap();
+        assert_eq!(dt.millis, 250);
+        assert_eq!(dt.millis_since_midnight(), 8 * 3_600_000 + 5_250);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_millis_out_of_range() {
+        assert_eq!(
+            parse(r#"{"hour":8,"minute":0,"second":5,"millis":1000}"#),
+            Err(ConvertError::OutOfRange {
+                field: "millis",
+                value: 1000
+            })
+        );
+    }
+
+    #[test]
+    fn test_with_millis_clamps() {
+        let dt = LocalDateTime::from(LocalTime::new(1, 2, 3).unwrap()).with_millis(5000);
+        assert_eq!(dt.millis, 999);
+    }
+
+    #[test]
+    fn test_from_local_time_has_no_date() {
+        let dt = LocalDateTime::from(LocalTime::new(1, 2, 3).unwrap());
+        assert_eq!(dt.date, None);
+    }
+}
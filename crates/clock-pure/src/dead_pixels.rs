@@ -0,0 +1,361 @@
+//! Keeps a ring with failed LEDs usable by moving their content elsewhere.
+
+use crate::settings::SettingsError;
+use crate::{Frame, Rgb, LED_COUNT};
+#[cfg(feature = "std")]
+use std::string::String;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// What happens to the content of a dead LED.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeadPixelPolicy {
+    /// Show it on the nearest working LED instead
+    #[default]
+    Remap,
+    /// Leave the LED dark, e.g. when it still lights up in the wrong color
+    Skip,
+}
+
+impl core::str::FromStr for DeadPixelPolicy {
+    type Err = SettingsError;
+
+    /// Parses `remap` or `skip` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("remap") => Ok(DeadPixelPolicy::Remap),
+            t if t.eq_ignore_ascii_case("skip") => Ok(DeadPixelPolicy::Skip),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// The LEDs on the ring that no longer work, and how to deal with them.
+///
+/// Dead LEDs are switched off, and with [`DeadPixelPolicy::Remap`] their
+/// content moves to the nearest working LED, so a hand on a dead LED shows
+/// next to it instead of disappearing. When both neighbors are equally far,
+/// the clockwise one is used. Content landing on an LED that is already lit
+/// is merged, keeping the brighter value of each channel. LEDs are numbered
+/// by position, as in the self-test.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{DeadPixels, Frame};
+///
+/// let dead = DeadPixels::none().with_dead(2).unwrap();
+/// let mut frame = Frame::new();
+/// frame[2] = (0, 0, 255);
+/// dead.apply(&mut frame);
+/// assert_eq!(frame[2], (0, 0, 0));
+/// assert_eq!(frame[3], (0, 0, 255));
+/// assert_eq!(DeadPixels::from_bytes(&dead.to_bytes()), Ok(dead));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DeadPixels {
+    /// Bit `n` is set if LED `n` is dead
+    mask: u16,
+    pub policy: DeadPixelPolicy,
+}
+
+impl DeadPixels {
+    /// Size of the binary form used by [`DeadPixels::to_bytes`].
+    pub const BYTES: usize = 3;
+
+    /// Marks no LED as dead.
+    pub const fn none() -> Self {
+        Self {
+            mask: 0,
+            policy: DeadPixelPolicy::Remap,
+        }
+    }
+
+    /// Marks the LED at `led` as dead.
+    pub fn with_dead(mut self, led: usize) -> Result<Self, SettingsError> {
+        if led >= LED_COUNT {
+            return Err(SettingsError::OutOfRange {
+                field: "led",
+                value: led as u32,
+            });
+        }
+        self.mask |= 1 << led;
+        Ok(self)
+    }
+
+    /// Sets what happens to the content of dead LEDs.
+    pub const fn with_policy(mut self, policy: DeadPixelPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns true if the LED at `led` is marked dead.
+    pub const fn is_dead(&self, led: usize) -> bool {
+        led < LED_COUNT && self.mask & (1 << led) != 0
+    }
+
+    /// Returns the dead LEDs in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..LED_COUNT).filter(|&led| self.is_dead(led))
+    }
+
+    /// Returns the working LED nearest to `led`, or `None` if every LED is dead.
+    pub fn nearest_working(&self, led: usize) -> Option<usize> {
+        (0..=LED_COUNT / 2)
+            .flat_map(|distance| {
+                [
+                    (led + distance) % LED_COUNT,
+                    (led + LED_COUNT - distance) % LED_COUNT,
+                ]
+            })
+            .find(|&candidate| !self.is_dead(candidate))
+    }
+
+    /// Switches off the dead LEDs in `frame`, moving their content to
+    /// working ones if the policy says so.
+    pub fn apply(&self, frame: &mut Frame) {
+        if self.mask == 0 {
+            return;
+        }
+        let source = *frame;
+        for led in self.iter() {
+            frame[led] = (0, 0, 0);
+        }
+        if self.policy == DeadPixelPolicy::Skip {
+            return;
+        }
+        for led in self.iter() {
+            if let Some(target) = self.nearest_working(led) {
+                frame[target] = brighter(frame[target], source[led]);
+            }
+        }
+    }
+
+    /// Encodes the dead LEDs and policy for persistent storage.
+    pub const fn to_bytes(self) -> [u8; Self::BYTES] {
+        let [low, high] = self.mask.to_le_bytes();
+        let policy = match self.policy {
+            DeadPixelPolicy::Remap => 0,
+            DeadPixelPolicy::Skip => 1,
+        };
+        [low, high, policy]
+    }
+
+    /// Decodes settings stored with [`DeadPixels::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        let &[low, high, policy] = bytes else {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        };
+        let mask = u16::from_le_bytes([low, high]);
+        if mask >> LED_COUNT != 0 {
+            return Err(SettingsError::InvalidValue);
+        }
+        let policy = match policy {
+            0 => DeadPixelPolicy::Remap,
+            1 => DeadPixelPolicy::Skip,
+            _ => return Err(SettingsError::InvalidValue),
+        };
+        Ok(Self { mask, policy })
+    }
+
+    /// Applies a dead-pixel command received over MQTT.
+    ///
+    /// The payload is `none` to mark every LED as working, `remap` or `skip`
+    /// to change the policy, or a JSON object with any of `dead` (a list of
+    /// LED numbers, 0-11, replacing the current one) and `mode` (`remap` or
+    /// `skip`). Fields left out keep their current values. Nothing changes on
+    /// error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{DeadPixelPolicy, DeadPixels};
+    ///
+    /// let dead = DeadPixels::none().with_update(br#"{"dead":[4,9]}"#).unwrap();
+    /// assert_eq!(dead.iter().collect::<Vec<_>>(), [4, 9]);
+    /// assert_eq!(dead.with_update(b"skip").unwrap().policy, DeadPixelPolicy::Skip);
+    /// assert_eq!(dead.with_update(b"none").unwrap(), DeadPixels::none());
+    /// ```
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.eq_ignore_ascii_case("none") {
+            Ok(Self::none())
+        } else if text.starts_with('{') {
+            self.with_json_update(payload)
+        } else {
+            text.parse().map(|policy| self.with_policy(policy))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn with_json_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            dead: Option<Vec<usize>>,
+            mode: Option<String>,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let mut updated = match update.dead {
+            Some(dead) => dead
+                .into_iter()
+                .try_fold(Self::none(), |pixels, led| pixels.with_dead(led))?,
+            None => self,
+        };
+        updated.policy = match update.mode {
+            Some(mode) => mode.parse()?,
+            None => self.policy,
+        };
+        Ok(updated)
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_json_update(self, _payload: &[u8]) -> Result<Self, SettingsError> {
+        Err(SettingsError::InvalidJson)
+    }
+}
+
+/// Merges two colors, keeping the brighter value of each channel.
+fn brighter(a: Rgb, b: Rgb) -> Rgb {
+    (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgb = (255, 0, 0);
+    const BLUE: Rgb = (0, 0, 255);
+
+    fn dead(leds: &[usize]) -> DeadPixels {
+        leds.iter().fold(DeadPixels::none(), |pixels, &led| {
+            pixels.with_dead(led).unwrap()
+        })
+    }
+
+    #[test]
+    fn test_none_changes_nothing() {
+        let mut frame = Frame::from_pixels([RED; LED_COUNT]);
+        DeadPixels::none().apply(&mut frame);
+        assert_eq!(frame, Frame::from_pixels([RED; LED_COUNT]));
+    }
+
+    #[test]
+    fn test_remap_merges_with_neighbor() {
+        let mut frame = Frame::new();
+        frame[5] = RED;
+        frame[6] = BLUE;
+        dead(&[5]).apply(&mut frame);
+        assert_eq!(frame[5], (0, 0, 0));
+        assert_eq!(frame[6], (255, 0, 255));
+    }
+
+    #[test]
+    fn test_remap_wraps_around_the_ring() {
+        let mut frame = Frame::new();
+        frame[11] = RED;
+        dead(&[11]).apply(&mut frame);
+        assert_eq!(frame[0], RED);
+
+        let mut frame = Frame::new();
+        frame[0] = RED;
+        dead(&[0, 1]).apply(&mut frame);
+        assert_eq!(frame[11], RED);
+    }
+
+    #[test]
+    fn test_remap_skips_dead_neighbors() {
+        let pixels = dead(&[3, 4, 5]);
+        assert_eq!(pixels.nearest_working(4), Some(6));
+        assert_eq!(pixels.nearest_working(3), Some(2));
+        assert_eq!(pixels.nearest_working(7), Some(7));
+    }
+
+    #[test]
+    fn test_all_dead() {
+        let pixels = dead(&(0..LED_COUNT).collect::<Vec<_>>());
+        assert_eq!(pixels.nearest_working(0), None);
+        let mut frame = Frame::from_pixels([RED; LED_COUNT]);
+        pixels.apply(&mut frame);
+        assert_eq!(frame, Frame::new());
+    }
+
+    #[test]
+    fn test_skip_leaves_dark() {
+        let mut frame = Frame::new();
+        frame[5] = RED;
+        dead(&[5])
+            .with_policy(DeadPixelPolicy::Skip)
+            .apply(&mut frame);
+        assert_eq!(frame, Frame::new());
+    }
+
+    #[test]
+    fn test_out_of_range() {
+        assert_eq!(
+            DeadPixels::none().with_dead(12),
+            Err(SettingsError::OutOfRange {
+                field: "led",
+                value: 12
+            })
+        );
+        assert!(!DeadPixels::none().is_dead(40));
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        assert_eq!(
+            DeadPixels::from_bytes(&[0, 0]),
+            Err(SettingsError::InvalidLength(2))
+        );
+        assert_eq!(
+            DeadPixels::from_bytes(&[0, 0x10, 0]),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            DeadPixels::from_bytes(&[0, 0, 2]),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_update_policy() {
+        assert_eq!(
+            dead(&[1]).with_update(b"Skip\n").unwrap(),
+            dead(&[1]).with_policy(DeadPixelPolicy::Skip)
+        );
+        assert_eq!(
+            DeadPixels::none().with_update(b"ignore"),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_json() {
+        let pixels = dead(&[1, 2]).with_policy(DeadPixelPolicy::Skip);
+        assert_eq!(
+            pixels.with_update(br#"{"dead":[7]}"#).unwrap(),
+            dead(&[7]).with_policy(DeadPixelPolicy::Skip)
+        );
+        assert_eq!(
+            pixels.with_update(br#"{"mode":"remap"}"#).unwrap(),
+            dead(&[1, 2])
+        );
+        assert_eq!(
+            pixels.with_update(br#"{"dead":[12]}"#),
+            Err(SettingsError::OutOfRange {
+                field: "led",
+                value: 12
+            })
+        );
+        assert_eq!(
+            pixels.with_update(br#"{"broken":[1]}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+}
@@ -0,0 +1,263 @@
+//! A quiet period in which the clock stays calm and dim.
+
+use crate::night::{NightSwitch, TimeWindow};
+use crate::settings::SettingsError;
+use crate::time::LocalTime;
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// A daily do-not-disturb window.
+///
+/// While it is active the clock drops notifications, chimes, and effect
+/// playlists, and the face never gets brighter than `brightness`.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{DoNotDisturb, LocalTime, NightSwitch};
+///
+/// let dnd = DoNotDisturb::new("13:00-15:00".parse().unwrap(), 4).with_switch(NightSwitch::Auto);
+/// let nap = LocalTime::new(14, 0, 0).unwrap();
+/// assert!(dnd.is_active(nap));
+/// assert_eq!(dnd.brightness_at(nap, 40), 4);
+/// assert_eq!(DoNotDisturb::from_bytes(&dnd.to_bytes()), Ok(dnd));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DoNotDisturb {
+    pub window: TimeWindow,
+    /// Highest brightness level (0-255) while do-not-disturb is active
+    pub brightness: u8,
+    pub switch: NightSwitch,
+}
+
+impl DoNotDisturb {
+    /// Size of the binary form used by [`DoNotDisturb::to_bytes`].
+    pub const BYTES: usize = 6;
+
+    /// Creates a switched-off window from `window`, capping the brightness at `brightness`.
+    ///
+    /// Use [`DoNotDisturb::with_switch`] with [`NightSwitch::Auto`] to follow the window.
+    pub const fn new(window: TimeWindow, brightness: u8) -> Self {
+        Self {
+            window,
+            brightness,
+            switch: NightSwitch::Off,
+        }
+    }
+
+    /// Sets whether the window applies or do-not-disturb is forced on or off.
+    pub const fn with_switch(mut self, switch: NightSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Returns true if the clock should keep quiet at `time`.
+    pub const fn is_active(&self, time: LocalTime) -> bool {
+        match self.switch {
+            NightSwitch::Auto => self.window.contains(time),
+            NightSwitch::On => true,
+            NightSwitch::Off => false,
+        }
+    }
+
+    /// Returns `brightness` capped while do-not-disturb is active at `time`.
+    pub fn brightness_at(&self, time: LocalTime, brightness: u8) -> u8 {
+        if self.is_active(time) {
+            brightness.min(self.brightness)
+        } else {
+            brightness
+        }
+    }
+
+    /// Encodes the window, brightness, and switch for persistent storage.
+    pub const fn to_bytes(self) -> [u8; Self::BYTES] {
+        let switch = match self.switch {
+            NightSwitch::Auto => 0,
+            NightSwitch::On => 1,
+            NightSwitch::Off => 2,
+        };
+        [
+            self.window.start.hour,
+            self.window.start.minute,
+            self.window.end.hour,
+            self.window.end.minute,
+            self.brightness,
+            switch,
+        ]
+    }
+
+    /// Decodes settings stored with [`DoNotDisturb::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        let &[start_hour, start_minute, end_hour, end_minute, brightness, switch] = bytes else {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        };
+        let time =
+            |hour, minute| LocalTime::new(hour, minute, 0).map_err(|_| SettingsError::InvalidValue);
+        let switch = match switch {
+            0 => NightSwitch::Auto,
+            1 => NightSwitch::On,
+            2 => NightSwitch::Off,
+            _ => return Err(SettingsError::InvalidValue),
+        };
+        let window = TimeWindow::new(time(start_hour, start_minute)?, time(end_hour, end_minute)?);
+        Ok(Self::new(window, brightness).with_switch(switch))
+    }
+
+    /// Applies a do-not-disturb command received over MQTT.
+    ///
+    /// The payload is `auto`, `on`, or `off`, or a JSON object with any of
+    /// `start`, `end` (`HH:MM`), `brightness`, and `mode` (`auto`, `on`,
+    /// `off`), just like night mode. Fields left out keep their current
+    /// values. Nothing changes on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{DoNotDisturb, NightSwitch};
+    ///
+    /// let dnd = DoNotDisturb::new("22:00-07:00".parse().unwrap(), 8);
+    /// assert_eq!(dnd.with_update(b"on").unwrap().switch, NightSwitch::On);
+    ///
+    /// let nap = dnd.with_update(br#"{"start":"13:00","end":"15:00","mode":"auto"}"#).unwrap();
+    /// assert_eq!(nap.window, "13:00-15:00".parse().unwrap());
+    /// ```
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.starts_with('{') {
+            self.with_json_update(payload)
+        } else {
+            text.parse().map(|switch| self.with_switch(switch))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn with_json_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            start: Option<String>,
+            end: Option<String>,
+            brightness: Option<u8>,
+            mode: Option<String>,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        // Stored windows keep whole minutes only
+        let time = |text: Option<String>, current: LocalTime| match text {
+            Some(text) => match text.parse::<LocalTime>() {
+                Ok(time) if time.second == 0 => Ok(time),
+                _ => Err(SettingsError::InvalidValue),
+            },
+            None => Ok(current),
+        };
+        Ok(Self {
+            window: TimeWindow::new(
+                time(update.start, self.window.start)?,
+                time(update.end, self.window.end)?,
+            ),
+            brightness: update.brightness.unwrap_or(self.brightness),
+            switch: match update.mode {
+                Some(mode) => mode.parse()?,
+                None => self.switch,
+            },
+        })
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_json_update(self, _payload: &[u8]) -> Result<Self, SettingsError> {
+        Err(SettingsError::InvalidJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u8, minute: u8) -> LocalTime {
+        LocalTime::new(hour, minute, 0).unwrap()
+    }
+
+    fn dnd() -> DoNotDisturb {
+        DoNotDisturb::new("22:00-07:00".parse().unwrap(), 8)
+    }
+
+    #[test]
+    fn test_off_by_default() {
+        assert!(!dnd().is_active(time(23, 0)));
+        assert_eq!(dnd().brightness_at(time(23, 0), 40), 40);
+    }
+
+    #[test]
+    fn test_follows_window_and_switch() {
+        let auto = dnd().with_switch(NightSwitch::Auto);
+        assert!(auto.is_active(time(23, 0)));
+        assert!(!auto.is_active(time(12, 0)));
+        assert!(dnd().with_switch(NightSwitch::On).is_active(time(12, 0)));
+    }
+
+    #[test]
+    fn test_caps_brightness() {
+        let on = dnd().with_switch(NightSwitch::On);
+        assert_eq!(on.brightness_at(time(12, 0), 40), 8);
+        assert_eq!(on.brightness_at(time(12, 0), 3), 3);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        for switch in [NightSwitch::Auto, NightSwitch::On, NightSwitch::Off] {
+            let dnd = dnd().with_switch(switch);
+            assert_eq!(DoNotDisturb::from_bytes(&dnd.to_bytes()), Ok(dnd));
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        assert_eq!(
+            DoNotDisturb::from_bytes(&[22, 0, 7, 0, 8]),
+            Err(SettingsError::InvalidLength(5))
+        );
+        assert_eq!(
+            DoNotDisturb::from_bytes(&[24, 0, 7, 0, 8, 0]),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            DoNotDisturb::from_bytes(&[22, 0, 7, 0, 8, 3]),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_update_switch() {
+        assert_eq!(
+            dnd().with_update(b"Auto\n").unwrap().switch,
+            NightSwitch::Auto
+        );
+        assert_eq!(
+            dnd().with_update(b"quiet"),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_json() {
+        let updated = dnd()
+            .with_update(br#"{"end":"08:00","brightness":2,"mode":"on"}"#)
+            .unwrap();
+        assert_eq!(
+            updated,
+            DoNotDisturb::new("22:00-08:00".parse().unwrap(), 2).with_switch(NightSwitch::On)
+        );
+        assert_eq!(
+            dnd().with_update(br#"{"start":"22:00:30"}"#),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            dnd().with_update(br#"{"silent":true}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+}
@@ -0,0 +1,167 @@
+//! Register encoding for the DS3231 real-time clock, which keeps the time
+//! on a backup battery while the clock is unpowered.
+
+use crate::date::{Date, LocalDateTime};
+use crate::time::{ConvertError, LocalTime};
+
+/// I2C address of the DS3231.
+pub const DS3231_ADDRESS: u8 = 0x68;
+
+/// Register of the seconds; the other six time registers follow it.
+pub const DS3231_TIME_REGISTER: u8 = 0x00;
+
+/// Register of the status flags.
+pub const DS3231_STATUS_REGISTER: u8 = 0x0f;
+
+/// Oscillator stop flag in the status register, set when the RTC ran out of
+/// power and its time is no longer valid. Setting the time does not clear it.
+pub const DS3231_OSF: u8 = 0x80;
+
+/// Decodes the seven time registers of a DS3231, starting at
+/// [`DS3231_TIME_REGISTER`].
+///
+/// Times set in the 12-hour format are read as well; the weekday register
+/// is ignored, as the date determines it.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{decode_ds3231, Date, LocalTime};
+///
+/// let time = decode_ds3231(&[0x45, 0x23, 0x14, 0x02, 0x31, 0x12, 0x24]).unwrap();
+/// assert_eq!(time.time, LocalTime::new(14, 23, 45).unwrap());
+/// assert_eq!(time.date, Some(Date::new(2024, 12, 31).unwrap()));
+/// ```
+pub fn decode_ds3231(registers: &[u8; 7]) -> Result<LocalDateTime, ConvertError> {
+    let [seconds, minutes, hours, _, day, month, year] = *registers;
+    let hour = if hours & 0x40 != 0 {
+        // 12-hour format: bit 5 marks PM, and 12 o'clock is the first hour
+        bcd("hour", hours & 0x1f)? % 12 + if hours & 0x20 != 0 { 12 } else { 0 }
+    } else {
+        bcd("hour", hours & 0x3f)?
+    };
+    let time = LocalTime::new(hour, bcd("minute", minutes)?, bcd("second", seconds)?)?;
+    let century = if month & 0x80 != 0 { 100 } else { 0 };
+    let year = 2000 + century + u16::from(bcd("year", year)?);
+    let date = Date::new(year, bcd("month", month & 0x1f)?, bcd("day", day)?)?;
+    Ok(LocalDateTime::new(time, Some(date)))
+}
+
+/// Encodes a date and time for the seven time registers of a DS3231, in the
+/// 24-hour format.
+///
+/// The DS3231 counts the years 2000 to 2199; other years are out of range.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{decode_ds3231, encode_ds3231, Date, LocalDateTime, LocalTime};
+///
+/// let time = LocalTime::new(7, 30, 0).unwrap();
+/// let date = Date::new(2025, 3, 1).unwrap();
+/// let registers = encode_ds3231(time, date).unwrap();
+/// assert_eq!(decode_ds3231(&registers), Ok(LocalDateTime::new(time, Some(date))));
+/// ```
+pub fn encode_ds3231(time: LocalTime, date: Date) -> Result<[u8; 7], ConvertError> {
+    if !(2000..2200).contains(&date.year) {
+        return Err(ConvertError::OutOfRange {
+            field: "year",
+            value: date.year.into(),
+        });
+    }
+    let century = if date.year >= 2100 { 0x80 } else { 0 };
+    Ok([
+        to_bcd(time.second),
+        to_bcd(time.minute),
+        to_bcd(time.hour),
+        // The weekday counts 1-7; the clock starts the week on Monday
+        date.weekday().days_from_monday() + 1,
+        to_bcd(date.day),
+        century | to_bcd(date.month),
+        to_bcd((date.year % 100) as u8),
+    ])
+}
+
+/// Decodes a binary-coded decimal register value of `field`.
+fn bcd(field: &'static str, value: u8) -> Result<u8, ConvertError> {
+    let (tens, ones) = (value >> 4, value & 0x0f);
+    if tens > 9 || ones > 9 {
+        return Err(ConvertError::OutOfRange {
+            field,
+            value: value.into(),
+        });
+    }
+    Ok(tens * 10 + ones)
+}
+
+/// Encodes a value below 100 as binary-coded decimal.
+const fn to_bcd(value: u8) -> u8 {
+    ((value / 10) << 4) | (value % 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let date = Date::new(2099, 12, 31).unwrap();
+        for seconds in (0..86_400).step_by(3_607) {
+            let time = LocalTime::from_seconds(seconds);
+            for date in [date, date.add_days(1)] {
+                let registers = encode_ds3231(time, date).unwrap();
+                assert_eq!(
+                    decode_ds3231(&registers),
+                    Ok(LocalDateTime::new(time, Some(date)))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_registers() {
+        let time = LocalTime::new(23, 59, 58).unwrap();
+        let date = Date::new(2100, 2, 28).unwrap();
+        // 2100-02-28 is a Sunday, so weekday 7
+        assert_eq!(
+            encode_ds3231(time, date),
+            Ok([0x58, 0x59, 0x23, 7, 0x28, 0x82, 0x00])
+        );
+        assert_eq!(
+            encode_ds3231(time, Date::new(1999, 1, 1).unwrap()),
+            Err(ConvertError::OutOfRange {
+                field: "year",
+                value: 1999
+            })
+        );
+    }
+
+    #[test]
+    fn test_twelve_hour_format() {
+        let at = |hours| decode_ds3231(&[0, 0, hours, 1, 1, 1, 0x24]).map(|t| t.time.hour);
+        // 12 AM, 9 AM, 12 PM, 11 PM
+        assert_eq!(at(0x52), Ok(0));
+        assert_eq!(at(0x49), Ok(9));
+        assert_eq!(at(0x72), Ok(12));
+        assert_eq!(at(0x71), Ok(23));
+    }
+
+    #[test]
+    fn test_invalid_registers() {
+        // Registers of a DS3231 that was never set, or garbage on the bus
+        assert_eq!(
+            decode_ds3231(&[0xff; 7]),
+            Err(ConvertError::OutOfRange {
+                field: "hour",
+                value: 0x1f
+            })
+        );
+        assert_eq!(
+            decode_ds3231(&[0x00, 0x00, 0x00, 1, 0x00, 0x01, 0x24]),
+            Err(ConvertError::OutOfRange {
+                field: "day",
+                value: 0
+            })
+        );
+    }
+}
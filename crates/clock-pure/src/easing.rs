@@ -0,0 +1,133 @@
+//! Eases levels from one value to the next instead of jumping.
+
+/// Eases `progress` (0-255) in and out: slow at both ends, fastest halfway.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::ease_in_out;
+///
+/// assert_eq!(ease_in_out(0), 0);
+/// assert_eq!(ease_in_out(128), 128);
+/// assert_eq!(ease_in_out(255), 255);
+/// assert!(ease_in_out(32) < 32);
+/// ```
+pub const fn ease_in_out(progress: u8) -> u8 {
+    let t = progress as u32;
+    (t * t * (3 * 255 - 2 * t) / (255 * 255)) as u8
+}
+
+/// A level eased from one value to another over a fixed time.
+///
+/// Changing the target while the ramp is still running starts over from the
+/// level reached so far, so the level never jumps.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::Ramp;
+///
+/// let mut ramp = Ramp::at(10);
+/// ramp.retarget(200, 1_000, 500);
+/// assert_eq!(ramp.level(1_000), 10);
+/// assert_eq!(ramp.level(1_250), 103);
+/// assert_eq!(ramp.level(1_500), 200);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ramp {
+    from: u8,
+    to: u8,
+    started_ms: u32,
+    duration_ms: u32,
+}
+
+impl Ramp {
+    /// Creates a ramp resting at `level`.
+    pub const fn at(level: u8) -> Self {
+        Self {
+            from: level,
+            to: level,
+            started_ms: 0,
+            duration_ms: 0,
+        }
+    }
+
+    /// Returns the level the ramp is heading for.
+    pub const fn target(&self) -> u8 {
+        self.to
+    }
+
+    /// Returns true once the target is reached at `now_ms`.
+    pub const fn is_finished(&self, now_ms: u32) -> bool {
+        now_ms.wrapping_sub(self.started_ms) >= self.duration_ms
+    }
+
+    /// Returns the level at `now_ms`.
+    pub const fn level(&self, now_ms: u32) -> u8 {
+        let elapsed_ms = now_ms.wrapping_sub(self.started_ms);
+        if elapsed_ms >= self.duration_ms {
+            return self.to;
+        }
+        let progress = ease_in_out((elapsed_ms as u64 * 255 / self.duration_ms as u64) as u8);
+        let (from, to) = (self.from as i32, self.to as i32);
+        (from + (to - from) * progress as i32 / 255) as u8
+    }
+
+    /// Heads for `to` at `now_ms` from the level reached so far, taking `duration_ms`.
+    pub fn retarget(&mut self, to: u8, now_ms: u32, duration_ms: u32) {
+        *self = Self {
+            from: self.level(now_ms),
+            to,
+            started_ms: now_ms,
+            duration_ms,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ease_is_monotonic() {
+        for progress in 1..=255u8 {
+            assert!(ease_in_out(progress) >= ease_in_out(progress - 1));
+        }
+    }
+
+    #[test]
+    fn test_ramp_down() {
+        let mut ramp = Ramp::at(200);
+        ramp.retarget(2, 0, 500);
+        assert_eq!(ramp.target(), 2);
+        assert!(ramp.level(100) < 200 && ramp.level(100) > 101);
+        assert!(!ramp.is_finished(499));
+        assert_eq!(ramp.level(500), 2);
+        assert!(ramp.is_finished(500));
+    }
+
+    #[test]
+    fn test_retarget_midway_continues_from_current_level() {
+        let mut ramp = Ramp::at(0);
+        ramp.retarget(255, 0, 500);
+        let reached = ramp.level(250);
+        ramp.retarget(0, 250, 500);
+        assert_eq!(ramp.level(250), reached);
+        assert_eq!(ramp.level(750), 0);
+    }
+
+    #[test]
+    fn test_zero_duration_is_instant() {
+        let mut ramp = Ramp::at(10);
+        ramp.retarget(50, 100, 0);
+        assert_eq!(ramp.level(100), 50);
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let mut ramp = Ramp::at(0);
+        ramp.retarget(255, u32::MAX - 99, 400);
+        assert_eq!(ramp.level(100), ease_in_out(127));
+        assert!(ramp.is_finished(300));
+    }
+}
@@ -0,0 +1,197 @@
+use super::Effect;
+use crate::{fade_color, Rgb};
+
+/// Wraps an effect with a fade-in at the start and a fade-out at the end.
+///
+/// Effects with a known duration fade out during their final `fade_out_ms`.
+/// Endless effects keep running until [`Envelope::release`] is called, then
+/// fade out and finish, so an animation can be stopped gracefully instead of
+/// freezing on its last frame.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, Envelope, RainbowSwirl};
+///
+/// let mut faded = Envelope::new(RainbowSwirl::new(), 500, 1000);
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// faded.render(0, &mut buffer);
+/// assert_eq!(buffer[0], (0, 0, 0)); // fading in from black
+///
+/// faded.release(5000);
+/// assert_eq!(faded.duration_ms(), Some(6000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Envelope<E> {
+    inner: E,
+    fade_in_ms: u32,
+    fade_out_ms: u32,
+    released_at: Option<u32>,
+}
+
+impl<E: Effect> Envelope<E> {
+    /// Wraps `inner` with the given fade-in and fade-out durations.
+    pub fn new(inner: E, fade_in_ms: u32, fade_out_ms: u32) -> Self {
+        Self {
+            inner,
+            fade_in_ms,
+            fade_out_ms,
+            released_at: None,
+        }
+    }
+
+    /// Starts the fade-out at `elapsed_ms`; the effect finishes `fade_out_ms` later.
+    ///
+    /// Releasing an envelope that is already fading out has no effect.
+    pub fn release(&mut self, elapsed_ms: u32) {
+        if self.released_at.is_none() {
+            self.released_at = Some(elapsed_ms);
+        }
+    }
+
+    /// Returns true once [`Envelope::release`] has been called.
+    pub fn is_released(&self) -> bool {
+        self.released_at.is_some()
+    }
+
+    /// Returns the wrapped effect.
+    pub fn into_inner(self) -> E {
+        self.inner
+    }
+
+    /// Returns the time at which the fade-out starts, if known.
+    fn fade_out_start(&self) -> Option<u32> {
+        self.released_at.or_else(|| {
+            self.inner
+                .duration_ms()
+                .map(|d| d.saturating_sub(self.fade_out_ms))
+        })
+    }
+
+    /// Returns the brightness level (0-255) of the envelope at `elapsed_ms`.
+    pub fn level(&self, elapsed_ms: u32) -> u8 {
+        let fade_in = ramp(elapsed_ms, self.fade_in_ms);
+        let fade_out = match self.fade_out_start() {
+            Some(start) if elapsed_ms >= start => {
+                u8::MAX - ramp(elapsed_ms - start, self.fade_out_ms)
+            }
+            _ => u8::MAX,
+        };
+        fade_in.min(fade_out)
+    }
+}
+
+/// Linear ramp from 0 to 255 over `duration_ms`; instant when the duration is zero.
+fn ramp(elapsed_ms: u32, duration_ms: u32) -> u8 {
+    if elapsed_ms >= duration_ms {
+        u8::MAX
+    } else {
+        (u64::from(elapsed_ms) * 255 / u64::from(duration_ms)) as u8
+    }
+}
+
+impl<E: Effect> Effect for Envelope<E> {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        self.inner.render(elapsed_ms, buffer);
+        let level = self.level(elapsed_ms);
+        if level < u8::MAX {
+            for pixel in buffer.iter_mut() {
+                *pixel = fade_color(*pixel, level);
+            }
+        }
+    }
+
+    fn duration_ms(&self) -> Option<u32> {
+        match self.released_at {
+            Some(at) => Some(at.saturating_add(self.fade_out_ms)),
+            None => self.inner.duration_ms(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Full white forever, or for a fixed duration.
+    struct White(Option<u32>);
+
+    impl Effect for White {
+        fn render(&mut self, _elapsed_ms: u32, buffer: &mut [Rgb]) {
+            buffer.fill((255, 255, 255));
+        }
+
+        fn duration_ms(&self) -> Option<u32> {
+            self.0
+        }
+    }
+
+    fn first_pixel(envelope: &mut Envelope<White>, elapsed_ms: u32) -> Rgb {
+        let mut buffer = [(0, 0, 0); 12];
+        envelope.render(elapsed_ms, &mut buffer);
+        buffer[0]
+    }
+
+    #[test]
+    fn test_fade_in_ramps_up() {
+        let mut envelope = Envelope::new(White(None), 1000, 1000);
+        assert_eq!(first_pixel(&mut envelope, 0), (0, 0, 0));
+        assert_eq!(first_pixel(&mut envelope, 500), (127, 127, 127));
+        assert_eq!(first_pixel(&mut envelope, 1000), (255, 255, 255));
+    }
+
+    #[test]
+    fn test_endless_effect_stays_full_until_released() {
+        let mut envelope = Envelope::new(White(None), 0, 1000);
+        assert_eq!(first_pixel(&mut envelope, 60_000), (255, 255, 255));
+        assert_eq!(envelope.duration_ms(), None);
+    }
+
+    #[test]
+    fn test_release_fades_out_and_finishes() {
+        let mut envelope = Envelope::new(White(None), 0, 1000);
+        envelope.release(2000);
+        assert!(envelope.is_released());
+        assert_eq!(envelope.duration_ms(), Some(3000));
+        assert_eq!(first_pixel(&mut envelope, 2000), (255, 255, 255));
+        assert_eq!(first_pixel(&mut envelope, 2500), (128, 128, 128));
+        assert_eq!(first_pixel(&mut envelope, 3000), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_second_release_is_ignored() {
+        let mut envelope = Envelope::new(White(None), 0, 1000);
+        envelope.release(2000);
+        envelope.release(2500);
+        assert_eq!(envelope.duration_ms(), Some(3000));
+    }
+
+    #[test]
+    fn test_finite_effect_fades_out_before_end() {
+        let mut envelope = Envelope::new(White(Some(5000)), 0, 1000);
+        assert_eq!(envelope.duration_ms(), Some(5000));
+        assert_eq!(first_pixel(&mut envelope, 3999), (255, 255, 255));
+        assert_eq!(first_pixel(&mut envelope, 4500), (128, 128, 128));
+    }
+
+    #[test]
+    fn test_release_during_fade_in_takes_lower_level() {
+        let envelope = {
+            let mut e = Envelope::new(White(None), 1000, 1000);
+            e.release(200);
+            e
+        };
+        // Fade-in is at 191, fade-out already down to 115: the dimmer one wins
+        assert_eq!(envelope.level(750), 115);
+    }
+
+    #[test]
+    fn test_zero_length_fades_are_instant() {
+        let mut envelope = Envelope::new(White(None), 0, 0);
+        assert_eq!(first_pixel(&mut envelope, 0), (255, 255, 255));
+        envelope.release(10);
+        assert_eq!(envelope.duration_ms(), Some(10));
+        assert_eq!(envelope.level(10), 0);
+    }
+}
@@ -0,0 +1,110 @@
+use super::Effect;
+use crate::Frame;
+
+/// An iterator rendering an [`Effect`] at a fixed frame rate.
+///
+/// Frame `n` shows the effect at `n * 1000 / fps` milliseconds, so
+/// animations can be consumed by tests or offline tools without a real-time
+/// loop. The iterator never ends; use [`Iterator::take`] to bound it.
+///
+/// Created by [`Effect::frames`].
+#[derive(Debug, Clone)]
+pub struct Frames<E> {
+    effect: E,
+    fps: u32,
+    index: u32,
+}
+
+impl<E: Effect> Frames<E> {
+    pub(super) fn new(effect: E, fps: u32) -> Self {
+        Self {
+            effect,
+            fps: fps.max(1),
+            index: 0,
+        }
+    }
+
+    /// Returns the timestamp of the next frame in milliseconds since the effect started.
+    pub fn next_elapsed_ms(&self) -> u32 {
+        (u64::from(self.index) * 1000 / u64::from(self.fps)) as u32
+    }
+
+    /// Consumes the iterator and returns the wrapped effect.
+    pub fn into_inner(self) -> E {
+        self.effect
+    }
+}
+
+impl<E: Effect> Iterator for Frames<E> {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        let mut frame = Frame::new();
+        self.effect
+            .render(self.next_elapsed_ms(), frame.pixels_mut());
+        self.index = self.index.wrapping_add(1);
+        Some(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::effects::LarsonScanner;
+    use crate::Rgb;
+    use std::vec;
+    use std::vec::Vec;
+
+    /// Lights the LED whose index equals the elapsed time in tens of milliseconds.
+    struct Probe;
+
+    impl Effect for Probe {
+        fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+            buffer.fill((0, 0, 0));
+            let idx = (elapsed_ms / 10) as usize % buffer.len();
+            buffer[idx] = (1, 1, 1);
+        }
+    }
+
+    fn lit(frame: &Frame) -> usize {
+        frame.pixels().iter().position(|&p| p != (0, 0, 0)).unwrap()
+    }
+
+    #[test]
+    fn test_frames_advance_by_frame_interval() {
+        // 100 fps -> 10 ms per frame -> one LED per frame
+        let positions: Vec<usize> = Probe.frames(100).take(4).map(|f| lit(&f)).collect();
+        assert_eq!(positions, vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_frames_rate_independent_of_count() {
+        // 50 fps -> 20 ms per frame -> every other LED
+        let positions: Vec<usize> = Probe.frames(50).take(3).map(|f| lit(&f)).collect();
+        assert_eq!(positions, vec![0, 2, 4]);
+    }
+
+    #[test]
+    fn test_frames_next_elapsed_ms() {
+        let mut frames = Probe.frames(30);
+        assert_eq!(frames.next_elapsed_ms(), 0);
+        frames.nth(29);
+        assert_eq!(frames.next_elapsed_ms(), 1000);
+    }
+
+    #[test]
+    fn test_frames_zero_fps_does_not_panic() {
+        assert_eq!(Probe.frames(0).take(2).count(), 2);
+    }
+
+    #[test]
+    fn test_frames_match_direct_rendering() {
+        let scanner = LarsonScanner::new((255, 0, 0)).with_period_ms(1000);
+        let mut direct = scanner.clone();
+        for (n, frame) in scanner.frames(25).take(50).enumerate() {
+            let mut expected = Frame::new();
+            direct.render(n as u32 * 40, expected.pixels_mut());
+            assert_eq!(frame, expected, "frame {}", n);
+        }
+    }
+}
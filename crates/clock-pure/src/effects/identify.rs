@@ -0,0 +1,135 @@
+use super::Effect;
+use crate::Rgb;
+
+/// How long [`Identify`] runs unless told otherwise.
+pub const IDENTIFY_MS: u32 = 6000;
+
+/// One round of the pattern: three flashes, then one spin around the ring.
+const FLASH_MS: u32 = 100;
+const FLASHES: u32 = 3;
+const SPIN_MS: u32 = 600;
+const CYCLE_MS: u32 = 2 * FLASH_MS * FLASHES + SPIN_MS;
+
+/// A pattern that stands out from everything else the clock shows, to tell
+/// which of several clocks is which.
+///
+/// The whole ring flashes three times, then a single LED spins once around
+/// from 12 o'clock, over and over until the effect ends.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, Identify};
+///
+/// let mut identify = Identify::new((255, 255, 255));
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// identify.render(0, &mut buffer);
+/// assert_eq!(buffer, [(255, 255, 255); 12]);
+/// identify.render(100, &mut buffer);
+/// assert_eq!(buffer, [(0, 0, 0); 12]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Identify {
+    color: Rgb,
+    duration_ms: u32,
+}
+
+impl Identify {
+    /// Creates the pattern in `color`, running for [`IDENTIFY_MS`].
+    pub fn new(color: Rgb) -> Self {
+        Self {
+            color,
+            duration_ms: IDENTIFY_MS,
+        }
+    }
+
+    /// Sets how long the pattern runs in milliseconds.
+    pub fn with_duration_ms(mut self, duration_ms: u32) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+}
+
+impl Effect for Identify {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill((0, 0, 0));
+        let len = buffer.len();
+        if len == 0 || elapsed_ms >= self.duration_ms {
+            return;
+        }
+
+        let phase = elapsed_ms % CYCLE_MS;
+        let flashing = 2 * FLASH_MS * FLASHES;
+        if phase < flashing {
+            let between_flashes = (phase / FLASH_MS) % 2 == 1;
+            if !between_flashes {
+                buffer.fill(self.color);
+            }
+        } else {
+            let step = (phase - flashing) as usize * len / SPIN_MS as usize;
+            // Start at 12 o'clock, the last LED on the ring
+            buffer[(step + len - 1) % len] = self.color;
+        }
+    }
+
+    fn duration_ms(&self) -> Option<u32> {
+        Some(self.duration_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    const WHITE: Rgb = (255, 255, 255);
+
+    fn lit(buffer: &[Rgb]) -> Vec<usize> {
+        buffer
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p != (0, 0, 0))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_identify_flashes_three_times() {
+        let mut identify = Identify::new(WHITE);
+        let mut buffer = [(0, 0, 0); 12];
+        let flashes = (0..2 * FLASH_MS * FLASHES)
+            .step_by(FLASH_MS as usize)
+            .filter(|&t| {
+                identify.render(t, &mut buffer);
+                lit(&buffer).len() == 12
+            })
+            .count();
+        assert_eq!(flashes, 3);
+    }
+
+    #[test]
+    fn test_identify_spins_from_twelve() {
+        let mut identify = Identify::new(WHITE);
+        let mut buffer = [(0, 0, 0); 12];
+        let spin_start = 2 * FLASH_MS * FLASHES;
+        identify.render(spin_start, &mut buffer);
+        assert_eq!(lit(&buffer), vec![11]);
+        identify.render(spin_start + SPIN_MS / 12, &mut buffer);
+        assert_eq!(lit(&buffer), vec![0]);
+        identify.render(CYCLE_MS - 1, &mut buffer);
+        assert_eq!(lit(&buffer), vec![10]);
+    }
+
+    #[test]
+    fn test_identify_repeats_until_done() {
+        let mut identify = Identify::new(WHITE).with_duration_ms(3_000);
+        let mut buffer = [(0, 0, 0); 12];
+        identify.render(CYCLE_MS, &mut buffer);
+        assert_eq!(lit(&buffer).len(), 12);
+        identify.render(3_000, &mut buffer);
+        assert!(lit(&buffer).is_empty());
+        assert_eq!(identify.duration_ms(), Some(3_000));
+    }
+}
@@ -0,0 +1,178 @@
+use super::Effect;
+use crate::{mix_colors, Rgb};
+
+/// The color of the whole ring at a point in a [`Keyframes`] animation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keyframe {
+    pub at_ms: u32,
+    pub color: Rgb,
+}
+
+impl Keyframe {
+    /// Creates a keyframe showing `color` at `at_ms`.
+    pub const fn new(at_ms: u32, color: Rgb) -> Self {
+        Self { at_ms, color }
+    }
+}
+
+/// A dawn from dim red through orange to bright warm white.
+///
+/// Stretch it with [`Keyframes::with_duration_ms`] to the length of the sunrise.
+pub const SUNRISE: [Keyframe; 5] = [
+    Keyframe::new(0, (8, 0, 0)),
+    Keyframe::new(250, (96, 8, 0)),
+    Keyframe::new(500, (224, 64, 0)),
+    Keyframe::new(750, (255, 128, 24)),
+    Keyframe::new(1000, (255, 180, 96)),
+];
+
+/// Fills the ring with a color blended between keyframes.
+///
+/// Keyframes must be sorted by time. Before the first keyframe the ring shows
+/// its color, and the last keyframe's color holds once the animation is over.
+/// The keyframe times can be stretched to any duration, so one set of
+/// keyframes serves a short transition as well as a half-hour sunrise.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, Keyframe, Keyframes};
+///
+/// let frames = [Keyframe::new(0, (0, 0, 0)), Keyframe::new(100, (255, 0, 0))];
+/// let mut fade_in = Keyframes::new(&frames).with_duration_ms(2_000);
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// fade_in.render(1_000, &mut buffer);
+/// assert_eq!(buffer[0], (127, 0, 0)); // halfway
+/// assert_eq!(fade_in.duration_ms(), Some(2_000));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Keyframes<'a> {
+    frames: &'a [Keyframe],
+    duration_ms: u32,
+}
+
+impl<'a> Keyframes<'a> {
+    /// Creates an animation running through `frames` at their own pace.
+    pub const fn new(frames: &'a [Keyframe]) -> Self {
+        let duration_ms = match frames {
+            [.., last] => last.at_ms,
+            [] => 0,
+        };
+        Self {
+            frames,
+            duration_ms,
+        }
+    }
+
+    /// Stretches or squeezes the keyframe times so the animation lasts `duration_ms`.
+    pub const fn with_duration_ms(mut self, duration_ms: u32) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+
+    /// Returns the color of the ring at `elapsed_ms`, black if there are no keyframes.
+    pub fn color_at(&self, elapsed_ms: u32) -> Rgb {
+        let (Some(first), Some(last)) = (self.frames.first(), self.frames.last()) else {
+            return (0, 0, 0);
+        };
+        if elapsed_ms >= self.duration_ms {
+            return last.color;
+        }
+        // Map onto the keyframes' own timeline
+        let t =
+            (u64::from(elapsed_ms) * u64::from(last.at_ms) / u64::from(self.duration_ms)) as u32;
+        if t < first.at_ms {
+            return first.color;
+        }
+        self.frames
+            .windows(2)
+            .find(|pair| t < pair[1].at_ms)
+            .map_or(last.color, |pair| {
+                let (from, to) = (pair[0], pair[1]);
+                let span = u64::from(to.at_ms - from.at_ms);
+                let amount = u64::from(t - from.at_ms) * 255 / span;
+                mix_colors(from.color, to.color, amount as u8)
+            })
+    }
+}
+
+impl Effect for Keyframes<'_> {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill(self.color_at(elapsed_ms));
+    }
+
+    fn duration_ms(&self) -> Option<u32> {
+        Some(self.duration_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAMES: [Keyframe; 3] = [
+        Keyframe::new(100, (0, 0, 0)),
+        Keyframe::new(200, (200, 0, 0)),
+        Keyframe::new(400, (200, 0, 200)),
+    ];
+
+    #[test]
+    fn test_keyframes_hit_their_colors() {
+        let keyframes = Keyframes::new(&FRAMES);
+        assert_eq!(keyframes.duration_ms(), Some(400));
+        for frame in FRAMES {
+            assert_eq!(keyframes.color_at(frame.at_ms), frame.color);
+        }
+    }
+
+    #[test]
+    fn test_keyframes_interpolate() {
+        let keyframes = Keyframes::new(&FRAMES);
+        assert_eq!(
+            keyframes.color_at(150),
+            mix_colors((0, 0, 0), (200, 0, 0), 127)
+        );
+        assert_eq!(
+            keyframes.color_at(300),
+            mix_colors((200, 0, 0), (200, 0, 200), 127)
+        );
+    }
+
+    #[test]
+    fn test_keyframes_hold_ends() {
+        let keyframes = Keyframes::new(&FRAMES);
+        assert_eq!(keyframes.color_at(0), (0, 0, 0));
+        assert_eq!(keyframes.color_at(10_000), (200, 0, 200));
+    }
+
+    #[test]
+    fn test_keyframes_stretch() {
+        let keyframes = Keyframes::new(&FRAMES).with_duration_ms(4_000);
+        assert_eq!(keyframes.color_at(2_000), (200, 0, 0));
+        assert_eq!(keyframes.color_at(3_999).0, 200);
+        assert_eq!(keyframes.color_at(4_000), (200, 0, 200));
+    }
+
+    #[test]
+    fn test_empty_keyframes_are_dark() {
+        let mut keyframes = Keyframes::new(&[]);
+        let mut buffer = [(9, 9, 9); 12];
+        keyframes.render(0, &mut buffer);
+        assert_eq!(buffer, [(0, 0, 0); 12]);
+    }
+
+    #[test]
+    fn test_sunrise_brightens() {
+        let sunrise = Keyframes::new(&SUNRISE).with_duration_ms(30 * 60_000);
+        let mut previous = 0;
+        for minute in 0..=30 {
+            let (r, g, b) = sunrise.color_at(minute * 60_000);
+            let total = u16::from(r) + u16::from(g) + u16::from(b);
+            assert!(total >= previous, "minute {minute}");
+            previous = total;
+        }
+        assert_eq!(sunrise.color_at(0), (8, 0, 0));
+        assert_eq!(sunrise.color_at(30 * 60_000), (255, 180, 96));
+    }
+}
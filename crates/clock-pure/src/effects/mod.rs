@@ -0,0 +1,89 @@
+//! Time-driven LED animations for the clock face.
+//!
+//! Every effect implements [`Effect`] and renders into a caller-provided
+//! buffer (see ADR-001), so effects work with any LED count and never allocate.
+//! Chaining effects into a [`Sequence`] or [`Playlist`] requires the `std` feature.
+
+mod envelope;
+mod frames;
+mod identify;
+mod keyframes;
+#[cfg(feature = "std")]
+mod playlist;
+mod ripple;
+mod scanner;
+mod selftest;
+#[cfg(feature = "std")]
+mod sequence;
+mod startup;
+
+pub use envelope::Envelope;
+pub use frames::Frames;
+pub use identify::{Identify, IDENTIFY_MS};
+pub use keyframes::{Keyframe, Keyframes, SUNRISE};
+#[cfg(feature = "std")]
+pub use playlist::{EffectSpec, Playlist, PlaylistStep};
+pub use ripple::{Ripple, RIPPLE_MS};
+pub use scanner::LarsonScanner;
+pub use selftest::{SelfTest, SELF_TEST_COLORS, SELF_TEST_STEP_MS};
+#[cfg(feature = "std")]
+pub use sequence::Sequence;
+pub use startup::{
+    HandSweep, RainbowSwirl, SpiralFill, StartupAnimation, StartupChoice, StartupEffect,
+    UnknownAnimation,
+};
+
+use crate::Rgb;
+
+/// A time-driven animation that renders into a caller-provided pixel buffer.
+///
+/// Effects are rendered from the time elapsed since the effect started rather
+/// than from a frame counter, so the animation speed does not depend on the
+/// rate at which frames are pushed to the LEDs.
+pub trait Effect {
+    /// Renders the effect at `elapsed_ms` milliseconds after its start into `buffer`.
+    ///
+    /// The buffer length is the number of LEDs on the ring; every pixel is
+    /// overwritten.
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]);
+
+    /// Returns how long the effect runs in milliseconds, or `None` if it runs forever.
+    fn duration_ms(&self) -> Option<u32> {
+        None
+    }
+
+    /// Turns the effect into an iterator of frames rendered at `fps` frames per second.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::effects::{Effect, LarsonScanner};
+    ///
+    /// let frames: Vec<_> = LarsonScanner::new((255, 0, 0)).frames(30).take(30).collect();
+    /// assert_eq!(frames.len(), 30);
+    /// ```
+    fn frames(self, fps: u32) -> Frames<Self>
+    where
+        Self: Sized,
+    {
+        Frames::new(self, fps)
+    }
+
+    /// Wraps the effect in an [`Envelope`] that fades in and out over the given durations.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::effects::{Effect, SpiralFill};
+    ///
+    /// let mut spiral = SpiralFill::new().faded(300, 500);
+    /// spiral.release(10_000);
+    /// assert_eq!(spiral.duration_ms(), Some(10_500));
+    /// ```
+    fn faded(self, fade_in_ms: u32, fade_out_ms: u32) -> Envelope<Self>
+    where
+        Self: Sized,
+    {
+        Envelope::new(self, fade_in_ms, fade_out_ms)
+    }
+}
@@ -0,0 +1,218 @@
+use super::{Effect, LarsonScanner, RainbowSwirl, Sequence, SpiralFill};
+use crate::Rgb;
+use serde::{Deserialize, Serialize};
+use std::boxed::Box;
+use std::vec::Vec;
+
+/// A named effect with its parameters, as used in playlists.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "effect", rename_all = "snake_case")]
+pub enum EffectSpec {
+    /// A [`LarsonScanner`] sweeping between `from` and `to`.
+    Scanner {
+        color: Rgb,
+        #[serde(default)]
+        from: Option<usize>,
+        #[serde(default)]
+        to: Option<usize>,
+        #[serde(default)]
+        period_ms: Option<u32>,
+    },
+    /// A [`RainbowSwirl`] rotating around the ring.
+    Rainbow {
+        #[serde(default)]
+        period_ms: Option<u32>,
+        #[serde(default)]
+        brightness: Option<u8>,
+    },
+    /// A [`SpiralFill`] filling and emptying the ring.
+    Spiral {
+        #[serde(default)]
+        step_ms: Option<u32>,
+        #[serde(default)]
+        brightness: Option<u8>,
+    },
+}
+
+impl EffectSpec {
+    /// Creates the effect described by this spec.
+    pub fn build(&self) -> Box<dyn Effect + Send> {
+        match *self {
+            EffectSpec::Scanner {
+                color,
+                from,
+                to,
+                period_ms,
+            } => {
+                let mut scanner = LarsonScanner::new(color);
+                if let (Some(from), Some(to)) = (from, to) {
+                    scanner = scanner.with_range(from, to);
+                }
+                if let Some(period_ms) = period_ms {
+                    scanner = scanner.with_period_ms(period_ms);
+                }
+                Box::new(scanner)
+            }
+            EffectSpec::Rainbow {
+                period_ms,
+                brightness,
+            } => {
+                let mut swirl = RainbowSwirl::new();
+                if let Some(period_ms) = period_ms {
+                    swirl = swirl.with_period_ms(period_ms);
+                }
+                if let Some(brightness) = brightness {
+                    swirl = swirl.with_brightness(brightness);
+                }
+                Box::new(swirl)
+            }
+            EffectSpec::Spiral {
+                step_ms,
+                brightness,
+            } => {
+                let mut spiral = SpiralFill::new();
+                if let Some(step_ms) = step_ms {
+                    spiral = spiral.with_step_ms(step_ms);
+                }
+                if let Some(brightness) = brightness {
+                    spiral = spiral.with_brightness(brightness);
+                }
+                Box::new(spiral)
+            }
+        }
+    }
+}
+
+/// One playlist entry: an effect and how long it plays.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PlaylistStep {
+    #[serde(flatten)]
+    pub effect: EffectSpec,
+    pub duration_ms: u32,
+}
+
+/// A serializable effect sequence, e.g. received over MQTT.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, Playlist};
+///
+/// let json = r#"{
+///     "steps": [
+///         {"effect": "scanner", "color": [255, 0, 0], "duration_ms": 10000},
+///         {"effect": "scanner", "color": [0, 0, 255], "duration_ms": 5000}
+///     ]
+/// }"#;
+/// let playlist: Playlist = serde_json::from_str(json).unwrap();
+/// assert_eq!(playlist.to_sequence().duration_ms(), Some(15_000));
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Playlist {
+    pub steps: Vec<PlaylistStep>,
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+impl Playlist {
+    /// Builds a [`Sequence`] that plays this playlist.
+    pub fn to_sequence(&self) -> Sequence {
+        let mut sequence = Sequence::new().repeat(self.repeat);
+        for step in &self.steps {
+            sequence.push(step.effect.build(), step.duration_ms);
+        }
+        sequence
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+
+    #[test]
+    fn test_parse_playlist_defaults() {
+        let playlist: Playlist = serde_json::from_str(
+            r#"{"steps":[{"effect":"scanner","color":[1,2,3],"duration_ms":100}]}"#,
+        )
+        .unwrap();
+        assert!(!playlist.repeat);
+        assert_eq!(
+            playlist.steps[0],
+            PlaylistStep {
+                effect: EffectSpec::Scanner {
+                    color: (1, 2, 3),
+                    from: None,
+                    to: None,
+                    period_ms: None,
+                },
+                duration_ms: 100,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rainbow_and_spiral_steps() {
+        let playlist: Playlist = serde_json::from_str(
+            r#"{"steps":[
+                {"effect":"rainbow","brightness":30,"duration_ms":10000},
+                {"effect":"spiral","duration_ms":5000}
+            ],"repeat":true}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            playlist.steps[0].effect,
+            EffectSpec::Rainbow {
+                period_ms: None,
+                brightness: Some(30)
+            }
+        );
+        assert_eq!(playlist.to_sequence().duration_ms(), None);
+    }
+
+    #[test]
+    fn test_parse_playlist_rejects_unknown_effect() {
+        let result: Result<Playlist, _> =
+            serde_json::from_str(r#"{"steps":[{"effect":"fireworks","duration_ms":100}]}"#);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_playlist_roundtrip() {
+        let playlist = Playlist {
+            steps: vec![PlaylistStep {
+                effect: EffectSpec::Scanner {
+                    color: (255, 0, 0),
+                    from: Some(9),
+                    to: Some(3),
+                    period_ms: Some(800),
+                },
+                duration_ms: 2000,
+            }],
+            repeat: true,
+        };
+        let json = serde_json::to_string(&playlist).unwrap();
+        assert_eq!(serde_json::from_str::<Playlist>(&json).unwrap(), playlist);
+    }
+
+    #[test]
+    fn test_playlist_builds_matching_sequence() {
+        let playlist = Playlist {
+            steps: vec![PlaylistStep {
+                effect: EffectSpec::Scanner {
+                    color: (255, 0, 0),
+                    from: Some(2),
+                    to: Some(6),
+                    period_ms: None,
+                },
+                duration_ms: 1000,
+            }],
+            repeat: false,
+        };
+        let mut sequence = playlist.to_sequence();
+        let mut buffer = [(0, 0, 0); 12];
+        sequence.render(0, &mut buffer);
+        assert_eq!(buffer[2], (255, 0, 0));
+        assert_eq!(sequence.duration_ms(), Some(1000));
+    }
+}
@@ -0,0 +1,152 @@
+use super::Effect;
+use crate::{fade_color, Rgb};
+
+/// How long a [`Ripple`] runs.
+pub const RIPPLE_MS: u32 = 2000;
+
+/// Waves start this far apart, and each takes this long to move one LED.
+const WAVE_INTERVAL_MS: u32 = 400;
+const WAVES: u32 = 3;
+const STEP_MS: u32 = 100;
+
+/// A short celebratory ripple, e.g. to chime the hour.
+///
+/// Three waves run from 12 o'clock down both sides of the ring and meet at
+/// 6 o'clock, each a little fainter than the one before, and the ring is dark
+/// again after [`RIPPLE_MS`].
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, Ripple};
+///
+/// let mut ripple = Ripple::new((255, 180, 40));
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// // The first wave starts at 12 o'clock, the last LED on the ring
+/// ripple.render(0, &mut buffer);
+/// assert_eq!(buffer[11], (255, 180, 40));
+/// assert_eq!(buffer[5], (0, 0, 0));
+///
+/// ripple.render(2_000, &mut buffer);
+/// assert_eq!(buffer, [(0, 0, 0); 12]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Ripple {
+    color: Rgb,
+    strength: u8,
+}
+
+impl Ripple {
+    /// Creates a ripple in `color` at full strength.
+    pub fn new(color: Rgb) -> Self {
+        Self {
+            color,
+            strength: u8::MAX,
+        }
+    }
+
+    /// Sets how bright the waves are (0-255).
+    pub fn with_strength(mut self, strength: u8) -> Self {
+        self.strength = strength;
+        self
+    }
+
+    /// Returns the level (0-255) of one wave at `distance` LEDs from 12
+    /// o'clock, `age_ms` after the wave started.
+    fn wave_level(age_ms: u32, distance: u32) -> u32 {
+        // The wave is two steps wide
+        let behind_ms = age_ms.abs_diff(distance * STEP_MS);
+        (2 * STEP_MS).saturating_sub(behind_ms) * 255 / (2 * STEP_MS)
+    }
+}
+
+impl Effect for Ripple {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        let len = buffer.len();
+        for (index, pixel) in buffer.iter_mut().enumerate() {
+            // 12 o'clock is the last LED, so LED 0 is one step away from it
+            let clockwise = (index + 1) % len;
+            let distance = clockwise.min(len - clockwise) as u32;
+            let level = (0..WAVES)
+                .map(|wave| wave * WAVE_INTERVAL_MS)
+                .filter(|&start| elapsed_ms >= start)
+                .map(|start| Self::wave_level(elapsed_ms - start, distance))
+                .max()
+                .unwrap_or(0);
+            // All waves fade out over the effect
+            let level = level * RIPPLE_MS.saturating_sub(elapsed_ms) / RIPPLE_MS;
+            let level = level * u32::from(self.strength) / 255;
+            *pixel = fade_color(self.color, level as u8);
+        }
+    }
+
+    fn duration_ms(&self) -> Option<u32> {
+        Some(RIPPLE_MS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    const GOLD: Rgb = (255, 180, 40);
+
+    fn lit(buffer: &[Rgb]) -> Vec<usize> {
+        buffer
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p != (0, 0, 0))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_ripple_runs_down_both_sides() {
+        let mut ripple = Ripple::new(GOLD);
+        let mut buffer = [(0, 0, 0); 12];
+        ripple.render(3 * STEP_MS, &mut buffer);
+        // 3 o'clock and 9 o'clock are three steps from 12
+        assert_eq!(buffer[2], buffer[8]);
+        assert!(buffer[2].0 > buffer[1].0);
+        assert!(buffer[2].0 > buffer[3].0);
+    }
+
+    #[test]
+    fn test_ripple_meets_at_six() {
+        let mut ripple = Ripple::new(GOLD);
+        let mut buffer = [(0, 0, 0); 12];
+        ripple.render(6 * STEP_MS, &mut buffer);
+        assert!(buffer[5].0 > buffer[4].0);
+        assert!(buffer[5].0 > buffer[6].0);
+    }
+
+    #[test]
+    fn test_later_waves_are_fainter() {
+        let mut ripple = Ripple::new(GOLD);
+        let mut first = [(0, 0, 0); 12];
+        let mut last = [(0, 0, 0); 12];
+        ripple.render(0, &mut first);
+        ripple.render(2 * WAVE_INTERVAL_MS, &mut last);
+        assert!(last[11].0 < first[11].0);
+        assert!(!lit(&last).is_empty());
+    }
+
+    #[test]
+    fn test_strength_scales_waves() {
+        let mut ripple = Ripple::new(GOLD).with_strength(128);
+        let mut buffer = [(0, 0, 0); 12];
+        ripple.render(0, &mut buffer);
+        assert_eq!(buffer[11], fade_color(GOLD, 128));
+
+        let mut off = Ripple::new(GOLD).with_strength(0);
+        off.render(0, &mut buffer);
+        assert!(lit(&buffer).is_empty());
+    }
+
+    #[test]
+    fn test_empty_buffer_is_safe() {
+        Ripple::new(GOLD).render(500, &mut []);
+    }
+}
@@ -0,0 +1,223 @@
+use super::Effect;
+use crate::{fade_color, Rgb};
+
+const DEFAULT_PERIOD_MS: u32 = 1200;
+const DEFAULT_TAIL: u8 = 2;
+
+/// A back-and-forth "Knight Rider" scanner sweeping between two ring positions.
+///
+/// The eye travels clockwise from `from` to `to` and back again, leaving a
+/// fading tail behind it. On a circular ring the path wraps past LED 0, so a
+/// sweep from 9 to 1 passes through 10, 11 and 0.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, LarsonScanner};
+///
+/// let mut scanner = LarsonScanner::new((255, 0, 0)).with_range(0, 5).with_tail(0);
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// scanner.render(0, &mut buffer);
+/// assert_eq!(buffer[0], (255, 0, 0));
+/// assert_eq!(buffer.iter().filter(|&&p| p != (0, 0, 0)).count(), 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct LarsonScanner {
+    color: Rgb,
+    from: usize,
+    to: usize,
+    period_ms: u32,
+    tail: u8,
+}
+
+impl LarsonScanner {
+    /// Creates a scanner sweeping the full ring with the given eye color.
+    pub fn new(color: Rgb) -> Self {
+        Self {
+            color,
+            from: 0,
+            to: usize::MAX,
+            period_ms: DEFAULT_PERIOD_MS,
+            tail: DEFAULT_TAIL,
+        }
+    }
+
+    /// Sets the two ring positions the eye sweeps between (clockwise from `from` to `to`).
+    pub fn with_range(mut self, from: usize, to: usize) -> Self {
+        self.from = from;
+        self.to = to;
+        self
+    }
+
+    /// Sets the duration of one full back-and-forth cycle in milliseconds.
+    pub fn with_period_ms(mut self, period_ms: u32) -> Self {
+        self.period_ms = period_ms.max(2);
+        self
+    }
+
+    /// Sets the number of fading LEDs trailing the eye.
+    pub fn with_tail(mut self, tail: u8) -> Self {
+        self.tail = tail;
+        self
+    }
+
+    /// Returns the number of steps between both ends of the sweep on a ring of `len` LEDs.
+    fn span(&self, len: usize) -> usize {
+        let from = self.from % len;
+        let to = self.to.min(len - 1) % len;
+        (to + len - from) % len
+    }
+}
+
+impl Effect for LarsonScanner {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill((0, 0, 0));
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+
+        let span = self.span(len) as u64;
+        let half = u64::from(self.period_ms / 2);
+        let phase = u64::from(elapsed_ms % (self.period_ms / 2 * 2));
+        let forward = phase < half;
+        let step = if forward {
+            phase * span / half
+        } else {
+            span - (phase - half) * span / half
+        } as usize;
+
+        // Draw the tail first so the eye always wins where they overlap.
+        for k in (0..=self.tail as usize).rev() {
+            let offset = if forward {
+                step.checked_sub(k)
+            } else {
+                Some(step + k).filter(|&s| s <= span as usize)
+            };
+            if let Some(offset) = offset {
+                let level = u8::MAX >> k.min(8);
+                buffer[(self.from % len + offset) % len] = fade_color(self.color, level);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    const RED: Rgb = (255, 0, 0);
+
+    fn lit(buffer: &[Rgb]) -> Vec<usize> {
+        buffer
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p != (0, 0, 0))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    #[test]
+    fn test_scanner_starts_at_from() {
+        let mut scanner = LarsonScanner::new(RED).with_range(3, 8).with_tail(0);
+        let mut buffer = [(0, 0, 0); 12];
+        scanner.render(0, &mut buffer);
+        assert_eq!(lit(&buffer), vec![3]);
+    }
+
+    #[test]
+    fn test_scanner_reaches_to_at_half_period() {
+        let mut scanner = LarsonScanner::new(RED)
+            .with_range(3, 8)
+            .with_period_ms(1000)
+            .with_tail(0);
+        let mut buffer = [(0, 0, 0); 12];
+        scanner.render(499, &mut buffer);
+        assert_eq!(lit(&buffer), vec![7]);
+        scanner.render(500, &mut buffer);
+        assert_eq!(lit(&buffer), vec![8]);
+    }
+
+    #[test]
+    fn test_scanner_returns_to_from_after_full_period() {
+        let mut scanner = LarsonScanner::new(RED)
+            .with_range(3, 8)
+            .with_period_ms(1000)
+            .with_tail(0);
+        let mut buffer = [(0, 0, 0); 12];
+        scanner.render(1000, &mut buffer);
+        assert_eq!(lit(&buffer), vec![3]);
+    }
+
+    #[test]
+    fn test_scanner_wraps_around_ring() {
+        // 10 -> 1 passes through 11 and 0
+        let mut scanner = LarsonScanner::new(RED)
+            .with_range(10, 1)
+            .with_period_ms(600)
+            .with_tail(0);
+        let mut buffer = [(0, 0, 0); 12];
+        let positions: Vec<usize> = [0, 100, 200, 300]
+            .iter()
+            .map(|&t| {
+                scanner.render(t, &mut buffer);
+                lit(&buffer)[0]
+            })
+            .collect();
+        assert_eq!(positions, vec![10, 11, 0, 1]);
+    }
+
+    #[test]
+    fn test_scanner_tail_fades_behind_eye() {
+        let mut scanner = LarsonScanner::new(RED)
+            .with_range(0, 10)
+            .with_period_ms(2000)
+            .with_tail(2);
+        let mut buffer = [(0, 0, 0); 12];
+        // 500 ms into the forward sweep: eye at step 5
+        scanner.render(500, &mut buffer);
+        assert_eq!(buffer[5], RED);
+        assert_eq!(buffer[4], fade_color(RED, 127));
+        assert_eq!(buffer[3], fade_color(RED, 63));
+        assert_eq!(lit(&buffer), vec![3, 4, 5]);
+    }
+
+    #[test]
+    fn test_scanner_tail_follows_reverse_direction() {
+        let mut scanner = LarsonScanner::new(RED)
+            .with_range(0, 10)
+            .with_period_ms(2000)
+            .with_tail(1);
+        let mut buffer = [(0, 0, 0); 12];
+        // 1500 ms: on the way back, eye at step 5, tail on the clockwise side
+        scanner.render(1500, &mut buffer);
+        assert_eq!(buffer[5], RED);
+        assert_eq!(lit(&buffer), vec![5, 6]);
+    }
+
+    #[test]
+    fn test_scanner_tail_clipped_at_ends() {
+        let mut scanner = LarsonScanner::new(RED).with_range(2, 6).with_tail(3);
+        let mut buffer = [(0, 0, 0); 12];
+        scanner.render(0, &mut buffer);
+        assert_eq!(lit(&buffer), vec![2]);
+    }
+
+    #[test]
+    fn test_scanner_default_covers_full_ring() {
+        let mut scanner = LarsonScanner::new(RED).with_period_ms(2200).with_tail(0);
+        let mut buffer = [(0, 0, 0); 12];
+        scanner.render(1100, &mut buffer);
+        assert_eq!(lit(&buffer), vec![11]);
+    }
+
+    #[test]
+    fn test_scanner_empty_buffer_is_noop() {
+        let mut scanner = LarsonScanner::new(RED);
+        let mut buffer: [Rgb; 0] = [];
+        scanner.render(123, &mut buffer);
+    }
+}
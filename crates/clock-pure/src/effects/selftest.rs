@@ -0,0 +1,182 @@
+use super::Effect;
+use crate::Rgb;
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// How long each LED shows each test color by default.
+pub const SELF_TEST_STEP_MS: u32 = 250;
+
+/// The colors every LED shows in turn, with the names used in reports.
+pub const SELF_TEST_COLORS: [(&str, Rgb); 4] = [
+    ("red", (255, 0, 0)),
+    ("green", (0, 255, 0)),
+    ("blue", (0, 0, 255)),
+    ("white", (255, 255, 255)),
+];
+
+/// Lights one LED at a time in pure red, green, blue, and white.
+///
+/// The LEDs are tested clockwise, starting with LED 0 at 1 o'clock, so a
+/// dead pixel stays dark during its turn and, on a ring wired in that order,
+/// a break in the data line shows as every LED after it staying dark. An LED showing the wrong color
+/// points to a strip with a different color order than the driver expects.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, SelfTest};
+///
+/// let mut test = SelfTest::new(12);
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// test.render(0, &mut buffer);
+/// assert_eq!(buffer[0], (255, 0, 0));
+/// test.render(250, &mut buffer);
+/// assert_eq!(buffer[0], (0, 255, 0));
+/// assert_eq!(test.step_at(1_000), Some((1, "red")));
+/// assert_eq!(test.duration_ms(), Some(12_000));
+/// ```
+#[derive(Debug, Clone)]
+pub struct SelfTest {
+    leds: usize,
+    step_ms: u32,
+}
+
+impl SelfTest {
+    /// Creates a self-test of `leds` LEDs, showing each color for [`SELF_TEST_STEP_MS`].
+    pub fn new(leds: usize) -> Self {
+        Self {
+            leds,
+            step_ms: SELF_TEST_STEP_MS,
+        }
+    }
+
+    /// Sets how long each LED shows each color in milliseconds.
+    pub fn with_step_ms(mut self, step_ms: u32) -> Self {
+        self.step_ms = step_ms.max(1);
+        self
+    }
+
+    /// Returns the LED index and color shown at `elapsed_ms`, or `None` once
+    /// every LED has been tested.
+    pub fn step_at(&self, elapsed_ms: u32) -> Option<(usize, &'static str)> {
+        self.color_at(elapsed_ms)
+            .map(|(led, (name, _))| (led, name))
+    }
+
+    fn color_at(&self, elapsed_ms: u32) -> Option<(usize, (&'static str, Rgb))> {
+        let step = (elapsed_ms / self.step_ms) as usize;
+        let led = step / SELF_TEST_COLORS.len();
+        (led < self.leds).then(|| (led, SELF_TEST_COLORS[step % SELF_TEST_COLORS.len()]))
+    }
+
+    /// Returns a JSON report of the test, stating whether it ran to the end
+    /// or was interrupted.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::effects::SelfTest;
+    ///
+    /// let report = SelfTest::new(12).report(true);
+    /// assert_eq!(
+    ///     report,
+    ///     r#"{"result":"completed","leds":12,"colors":["red","green","blue","white"],"step_ms":250}"#
+    /// );
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn report(&self, completed: bool) -> String {
+        #[derive(serde::Serialize)]
+        struct Report {
+            result: &'static str,
+            leds: usize,
+            colors: [&'static str; SELF_TEST_COLORS.len()],
+            step_ms: u32,
+        }
+
+        let report = Report {
+            result: if completed {
+                "completed"
+            } else {
+                "interrupted"
+            },
+            leds: self.leds,
+            colors: SELF_TEST_COLORS.map(|(name, _)| name),
+            step_ms: self.step_ms,
+        };
+        serde_json::to_string(&report).unwrap_or_default()
+    }
+}
+
+impl Effect for SelfTest {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill((0, 0, 0));
+        if let Some((led, (_, color))) = self.color_at(elapsed_ms) {
+            if let Some(pixel) = buffer.get_mut(led) {
+                *pixel = color;
+            }
+        }
+    }
+
+    fn duration_ms(&self) -> Option<u32> {
+        let steps = (self.leds * SELF_TEST_COLORS.len()) as u32;
+        Some(steps.saturating_mul(self.step_ms))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    fn lit(buffer: &[Rgb]) -> Vec<(usize, Rgb)> {
+        buffer
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, p)| p != (0, 0, 0))
+            .collect()
+    }
+
+    #[test]
+    fn test_each_led_shows_each_color() {
+        let mut test = SelfTest::new(12).with_step_ms(100);
+        let mut buffer = [(0, 0, 0); 12];
+        for led in 0..12 {
+            for (i, &(_, color)) in SELF_TEST_COLORS.iter().enumerate() {
+                let elapsed_ms = ((led * 4 + i) * 100) as u32;
+                test.render(elapsed_ms, &mut buffer);
+                assert_eq!(lit(&buffer), vec![(led, color)]);
+            }
+        }
+    }
+
+    #[test]
+    fn test_dark_when_done() {
+        let mut test = SelfTest::new(12);
+        let mut buffer = [(0, 0, 0); 12];
+        let end = test.duration_ms().unwrap();
+        test.render(end - 1, &mut buffer);
+        assert_eq!(lit(&buffer), vec![(11, (255, 255, 255))]);
+        test.render(end, &mut buffer);
+        assert!(lit(&buffer).is_empty());
+        assert_eq!(test.step_at(end), None);
+    }
+
+    #[test]
+    fn test_shorter_buffer_is_safe() {
+        let mut test = SelfTest::new(12);
+        let mut buffer = [(0, 0, 0); 4];
+        test.render(11_000, &mut buffer);
+        assert!(lit(&buffer).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_report_interrupted() {
+        let report = SelfTest::new(12).with_step_ms(100).report(false);
+        assert!(report.contains(r#""result":"interrupted""#));
+        assert!(report.contains(r#""step_ms":100"#));
+    }
+}
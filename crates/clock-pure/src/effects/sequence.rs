@@ -0,0 +1,176 @@
+use super::Effect;
+use crate::Rgb;
+use std::boxed::Box;
+use std::vec::Vec;
+
+struct Step {
+    effect: Box<dyn Effect + Send>,
+    duration_ms: u32,
+}
+
+/// Plays a list of effects one after another, each for a fixed duration.
+///
+/// A non-repeating sequence finishes after the last step and renders all LEDs
+/// off from then on; [`Effect::duration_ms`] reports when that happens so the
+/// caller can return to the clock display.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, LarsonScanner, Sequence};
+///
+/// let sequence = Sequence::new()
+///     .then(LarsonScanner::new((255, 0, 0)), 10_000)
+///     .then(LarsonScanner::new((0, 0, 255)), 5_000);
+/// assert_eq!(sequence.duration_ms(), Some(15_000));
+/// assert_eq!(sequence.repeat(true).duration_ms(), None);
+/// ```
+#[derive(Default)]
+pub struct Sequence {
+    steps: Vec<Step>,
+    repeat: bool,
+}
+
+impl Sequence {
+    /// Creates an empty, non-repeating sequence.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends an effect that plays for `duration_ms` milliseconds.
+    pub fn then(mut self, effect: impl Effect + Send + 'static, duration_ms: u32) -> Self {
+        self.push(Box::new(effect), duration_ms);
+        self
+    }
+
+    /// Appends an already boxed effect that plays for `duration_ms` milliseconds.
+    pub fn push(&mut self, effect: Box<dyn Effect + Send>, duration_ms: u32) {
+        self.steps.push(Step {
+            effect,
+            duration_ms,
+        });
+    }
+
+    /// Sets whether the sequence starts over after the last step.
+    pub fn repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Returns the combined duration of all steps in milliseconds.
+    fn total_ms(&self) -> u32 {
+        self.steps
+            .iter()
+            .fold(0u32, |total, step| total.saturating_add(step.duration_ms))
+    }
+}
+
+impl Effect for Sequence {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        let total = self.total_ms();
+        let mut remaining = match total {
+            0 => None,
+            _ if self.repeat => Some(elapsed_ms % total),
+            _ => Some(elapsed_ms).filter(|&t| t < total),
+        };
+
+        for step in &mut self.steps {
+            match remaining {
+                Some(t) if t < step.duration_ms => {
+                    step.effect.render(t, buffer);
+                    return;
+                }
+                Some(t) => remaining = Some(t - step.duration_ms),
+                None => break,
+            }
+        }
+        buffer.fill((0, 0, 0));
+    }
+
+    fn duration_ms(&self) -> Option<u32> {
+        if self.repeat {
+            None
+        } else {
+            Some(self.total_ms())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fills the ring with a fixed color and records the last elapsed time it saw.
+    struct Solid {
+        color: Rgb,
+    }
+
+    impl Effect for Solid {
+        fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+            buffer.fill(self.color);
+            buffer[0] = (0, 0, (elapsed_ms / 100) as u8);
+        }
+    }
+
+    const RED: Rgb = (255, 0, 0);
+    const GREEN: Rgb = (0, 255, 0);
+
+    fn rendered(sequence: &mut Sequence, elapsed_ms: u32) -> [Rgb; 12] {
+        let mut buffer = [(9, 9, 9); 12];
+        sequence.render(elapsed_ms, &mut buffer);
+        buffer
+    }
+
+    fn two_steps() -> Sequence {
+        Sequence::new()
+            .then(Solid { color: RED }, 1000)
+            .then(Solid { color: GREEN }, 500)
+    }
+
+    #[test]
+    fn test_sequence_plays_steps_in_order() {
+        let mut sequence = two_steps();
+        assert_eq!(rendered(&mut sequence, 0)[1], RED);
+        assert_eq!(rendered(&mut sequence, 999)[1], RED);
+        assert_eq!(rendered(&mut sequence, 1000)[1], GREEN);
+        assert_eq!(rendered(&mut sequence, 1499)[1], GREEN);
+    }
+
+    #[test]
+    fn test_sequence_passes_step_local_time() {
+        let mut sequence = two_steps();
+        // 1300 ms into the sequence is 300 ms into the second step
+        assert_eq!(rendered(&mut sequence, 1300)[0], (0, 0, 3));
+    }
+
+    #[test]
+    fn test_sequence_finishes_black() {
+        let mut sequence = two_steps();
+        assert_eq!(sequence.duration_ms(), Some(1500));
+        assert_eq!(rendered(&mut sequence, 1500), [(0, 0, 0); 12]);
+        assert_eq!(rendered(&mut sequence, 99_999), [(0, 0, 0); 12]);
+    }
+
+    #[test]
+    fn test_sequence_repeat_wraps() {
+        let mut sequence = two_steps().repeat(true);
+        assert_eq!(sequence.duration_ms(), None);
+        assert_eq!(rendered(&mut sequence, 1500)[1], RED);
+        assert_eq!(rendered(&mut sequence, 2600)[1], GREEN);
+    }
+
+    #[test]
+    fn test_empty_sequence_is_black() {
+        let mut sequence = Sequence::new().repeat(true);
+        assert_eq!(rendered(&mut sequence, 0), [(0, 0, 0); 12]);
+        assert_eq!(Sequence::new().duration_ms(), Some(0));
+    }
+
+    #[test]
+    fn test_zero_duration_step_is_skipped() {
+        let mut sequence = Sequence::new()
+            .then(Solid { color: RED }, 0)
+            .then(Solid { color: GREEN }, 100);
+        assert_eq!(rendered(&mut sequence, 0)[1], GREEN);
+    }
+}
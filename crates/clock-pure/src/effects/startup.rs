@@ -0,0 +1,589 @@
+use super::Effect;
+use crate::settings::SettingsError;
+use crate::{color_wheel, fade_color, Rgb};
+use core::str::FromStr;
+
+const DEFAULT_SWEEP_STEP_MS: u32 = 80;
+const DEFAULT_SWIRL_PERIOD_MS: u32 = 2000;
+const DEFAULT_SPIRAL_STEP_MS: u32 = 100;
+
+/// The three clock hands sweeping once around the ring, one after another.
+///
+/// A single LED in the hour color travels clockwise from 12 o'clock, followed
+/// by the minute and then the second color, and the cycle repeats.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, HandSweep};
+///
+/// let mut sweep = HandSweep::new((0, 0, 10), (0, 10, 0), (10, 0, 0)).with_step_ms(100);
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// sweep.render(0, &mut buffer);
+/// assert_eq!(buffer[11], (0, 0, 10)); // hour hand starts at 12 o'clock
+/// sweep.render(1200, &mut buffer);
+/// assert_eq!(buffer[11], (0, 10, 0)); // then the minute hand
+/// ```
+#[derive(Debug, Clone)]
+pub struct HandSweep {
+    colors: [Rgb; 3],
+    step_ms: u32,
+}
+
+impl HandSweep {
+    /// Creates a sweep using the given hour, minute, and second hand colors.
+    pub fn new(hour: Rgb, minute: Rgb, second: Rgb) -> Self {
+        Self {
+            colors: [hour, minute, second],
+            step_ms: DEFAULT_SWEEP_STEP_MS,
+        }
+    }
+
+    /// Sets how long each hand stays on one LED.
+    pub fn with_step_ms(mut self, step_ms: u32) -> Self {
+        self.step_ms = step_ms.max(1);
+        self
+    }
+}
+
+impl Effect for HandSweep {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill((0, 0, 0));
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+
+        let step = (elapsed_ms / self.step_ms) as usize % (len * self.colors.len());
+        let hand = step / len;
+        // Start at 12 o'clock, the last LED on the ring.
+        let position = (step % len + len - 1) % len;
+        buffer[position] = self.colors[hand];
+    }
+}
+
+/// A full rainbow rotating clockwise around the ring.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, RainbowSwirl};
+///
+/// let mut swirl = RainbowSwirl::new();
+/// let mut buffer = [(0, 0, 0); 12];
+/// swirl.render(0, &mut buffer);
+/// assert_eq!(buffer[0], (255, 0, 0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RainbowSwirl {
+    period_ms: u32,
+    brightness: u8,
+}
+
+impl RainbowSwirl {
+    /// Creates a full-brightness swirl completing one rotation every two seconds.
+    pub fn new() -> Self {
+        Self {
+            period_ms: DEFAULT_SWIRL_PERIOD_MS,
+            brightness: u8::MAX,
+        }
+    }
+
+    /// Sets the duration of one full rotation in milliseconds.
+    pub fn with_period_ms(mut self, period_ms: u32) -> Self {
+        self.period_ms = period_ms.max(1);
+        self
+    }
+
+    /// Sets the brightness level (0-255) applied to the rainbow.
+    pub fn with_brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+}
+
+impl Default for RainbowSwirl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for RainbowSwirl {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        let len = buffer.len();
+        let offset = (elapsed_ms % self.period_ms) as usize * 256 / self.period_ms as usize;
+        for (idx, pixel) in buffer.iter_mut().enumerate() {
+            // Subtracting the offset moves each hue towards higher indices, i.e. clockwise.
+            let position = (idx * 256 / len + 256 - offset) % 256;
+            *pixel = fade_color(color_wheel(position as u8), self.brightness);
+        }
+    }
+}
+
+/// LEDs lighting up one by one clockwise until the ring is full, then going
+/// dark in the same order, with a new hue on every round.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{Effect, SpiralFill};
+///
+/// let mut spiral = SpiralFill::new().with_step_ms(100);
+/// let mut buffer = [(0, 0, 0); 12];
+/// spiral.render(250, &mut buffer);
+/// assert_eq!(buffer.iter().filter(|&&p| p != (0, 0, 0)).count(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SpiralFill {
+    step_ms: u32,
+    brightness: u8,
+}
+
+impl SpiralFill {
+    /// Creates a full-brightness spiral advancing one LED every 100 ms.
+    pub fn new() -> Self {
+        Self {
+            step_ms: DEFAULT_SPIRAL_STEP_MS,
+            brightness: u8::MAX,
+        }
+    }
+
+    /// Sets how long it takes to light or clear one LED.
+    pub fn with_step_ms(mut self, step_ms: u32) -> Self {
+        self.step_ms = step_ms.max(1);
+        self
+    }
+
+    /// Sets the brightness level (0-255) of the lit LEDs.
+    pub fn with_brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+}
+
+impl Default for SpiralFill {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Effect for SpiralFill {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill((0, 0, 0));
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+
+        let step = (elapsed_ms / self.step_ms) as usize;
+        let round = step / (2 * len);
+        let within = step % (2 * len);
+        let color = fade_color(color_wheel((round * 40) as u8), self.brightness);
+
+        // Start at 12 o'clock, the last LED on the ring.
+        let lit = if within < len {
+            0..within + 1
+        } else {
+            within - len + 1..len
+        };
+        for offset in lit {
+            buffer[(offset + len - 1) % len] = color;
+        }
+    }
+}
+
+/// The built-in startup animations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StartupAnimation {
+    /// See [`HandSweep`].
+    HandSweep,
+    /// See [`RainbowSwirl`].
+    RainbowSwirl,
+    /// See [`SpiralFill`].
+    SpiralFill,
+}
+
+impl StartupAnimation {
+    /// All built-in startup animations.
+    pub const ALL: [StartupAnimation; 3] = [
+        StartupAnimation::HandSweep,
+        StartupAnimation::RainbowSwirl,
+        StartupAnimation::SpiralFill,
+    ];
+
+    /// Picks an animation from a random number, e.g. from the hardware RNG.
+    pub fn from_random(random: u32) -> Self {
+        Self::ALL[random as usize % Self::ALL.len()]
+    }
+
+    /// Returns the configuration name of the animation.
+    pub fn name(self) -> &'static str {
+        match self {
+            StartupAnimation::HandSweep => "hand_sweep",
+            StartupAnimation::RainbowSwirl => "rainbow_swirl",
+            StartupAnimation::SpiralFill => "spiral_fill",
+        }
+    }
+
+    /// Creates the effect for this animation.
+    ///
+    /// `hands` are the hour, minute, and second colors used by the hand sweep;
+    /// `brightness` (0-255) applies to the rainbow and spiral.
+    pub fn effect(self, hands: [Rgb; 3], brightness: u8) -> StartupEffect {
+        match self {
+            StartupAnimation::HandSweep => {
+                StartupEffect::HandSweep(HandSweep::new(hands[0], hands[1], hands[2]))
+            }
+            StartupAnimation::RainbowSwirl => {
+                StartupEffect::RainbowSwirl(RainbowSwirl::new().with_brightness(brightness))
+            }
+            StartupAnimation::SpiralFill => {
+                StartupEffect::SpiralFill(SpiralFill::new().with_brightness(brightness))
+            }
+        }
+    }
+}
+
+/// Error returned when parsing an unknown startup animation name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownAnimation;
+
+impl core::fmt::Display for UnknownAnimation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Unknown startup animation")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UnknownAnimation {}
+
+impl FromStr for StartupAnimation {
+    type Err = UnknownAnimation;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|animation| animation.name() == name)
+            .ok_or(UnknownAnimation)
+    }
+}
+
+/// Which startup animation plays at boot, if any.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::effects::{StartupAnimation, StartupChoice};
+///
+/// let choice = StartupChoice::parse(b"spiral_fill").unwrap();
+/// assert_eq!(choice.pick(7), Some(StartupAnimation::SpiralFill));
+/// assert_eq!(StartupChoice::parse(b"off").unwrap().pick(7), None);
+/// assert_eq!(StartupChoice::from_bytes(&choice.to_bytes()), Ok(choice));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StartupChoice {
+    /// A different built-in animation on every boot
+    #[default]
+    Random,
+    /// Always the same animation
+    Animation(StartupAnimation),
+    /// No animation; the ring stays dark until the first time arrives
+    Off,
+}
+
+impl StartupChoice {
+    /// Size of the binary form used by [`StartupChoice::to_bytes`].
+    pub const BYTES: usize = 1;
+
+    /// Returns the animation to play, using `random` to pick one for [`StartupChoice::Random`].
+    pub fn pick(self, random: u32) -> Option<StartupAnimation> {
+        match self {
+            StartupChoice::Random => Some(StartupAnimation::from_random(random)),
+            StartupChoice::Animation(animation) => Some(animation),
+            StartupChoice::Off => None,
+        }
+    }
+
+    /// Returns the configuration name: `random`, `off`, or the animation name.
+    pub fn name(self) -> &'static str {
+        match self {
+            StartupChoice::Random => "random",
+            StartupChoice::Animation(animation) => animation.name(),
+            StartupChoice::Off => "off",
+        }
+    }
+
+    /// Parses a startup animation command received over MQTT.
+    ///
+    /// The payload is `random`, `off`, or the name of a built-in animation
+    /// such as `rainbow_swirl`.
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        text.parse().map_err(|_| SettingsError::InvalidValue)
+    }
+
+    /// Encodes the choice for persistent storage.
+    pub fn to_bytes(self) -> [u8; Self::BYTES] {
+        let code = match self {
+            StartupChoice::Random => 0,
+            StartupChoice::Off => 1,
+            StartupChoice::Animation(animation) => {
+                let index = StartupAnimation::ALL.iter().position(|&a| a == animation);
+                2 + index.unwrap_or_default() as u8
+            }
+        };
+        [code]
+    }
+
+    /// Decodes a choice stored with [`StartupChoice::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        match *bytes {
+            [0] => Ok(StartupChoice::Random),
+            [1] => Ok(StartupChoice::Off),
+            [code] => StartupAnimation::ALL
+                .get(usize::from(code) - 2)
+                .map(|&animation| StartupChoice::Animation(animation))
+                .ok_or(SettingsError::InvalidValue),
+            _ => Err(SettingsError::InvalidLength(bytes.len())),
+        }
+    }
+}
+
+impl FromStr for StartupChoice {
+    type Err = UnknownAnimation;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "random" => Ok(StartupChoice::Random),
+            "off" => Ok(StartupChoice::Off),
+            name => name.parse().map(StartupChoice::Animation),
+        }
+    }
+}
+
+/// One of the built-in startup animations, ready to render.
+#[derive(Debug, Clone)]
+pub enum StartupEffect {
+    HandSweep(HandSweep),
+    RainbowSwirl(RainbowSwirl),
+    SpiralFill(SpiralFill),
+}
+
+impl Effect for StartupEffect {
+    fn render(&mut self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        match self {
+            StartupEffect::HandSweep(effect) => effect.render(elapsed_ms, buffer),
+            StartupEffect::RainbowSwirl(effect) => effect.render(elapsed_ms, buffer),
+            StartupEffect::SpiralFill(effect) => effect.render(elapsed_ms, buffer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    const HOUR: Rgb = (0, 0, 10);
+    const MINUTE: Rgb = (0, 10, 0);
+    const SECOND: Rgb = (10, 0, 0);
+
+    fn lit(buffer: &[Rgb]) -> Vec<usize> {
+        buffer
+            .iter()
+            .enumerate()
+            .filter(|(_, &p)| p != (0, 0, 0))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    // ===== HandSweep tests =====
+
+    #[test]
+    fn test_hand_sweep_hour_goes_clockwise_from_12() {
+        let mut sweep = HandSweep::new(HOUR, MINUTE, SECOND).with_step_ms(100);
+        let mut buffer = [(0, 0, 0); 12];
+        sweep.render(0, &mut buffer);
+        assert_eq!(lit(&buffer), vec![11]);
+        sweep.render(100, &mut buffer);
+        assert_eq!(lit(&buffer), vec![0]);
+        sweep.render(1100, &mut buffer);
+        assert_eq!(lit(&buffer), vec![10]);
+        assert_eq!(buffer[10], HOUR);
+    }
+
+    #[test]
+    fn test_hand_sweep_hands_follow_each_other() {
+        let mut sweep = HandSweep::new(HOUR, MINUTE, SECOND).with_step_ms(100);
+        let mut buffer = [(0, 0, 0); 12];
+        sweep.render(1200, &mut buffer);
+        assert_eq!(buffer[11], MINUTE);
+        sweep.render(2400, &mut buffer);
+        assert_eq!(buffer[11], SECOND);
+        sweep.render(3600, &mut buffer);
+        assert_eq!(buffer[11], HOUR);
+    }
+
+    // ===== RainbowSwirl tests =====
+
+    #[test]
+    fn test_rainbow_swirl_all_leds_lit() {
+        let mut swirl = RainbowSwirl::new();
+        let mut buffer = [(0, 0, 0); 12];
+        swirl.render(1234, &mut buffer);
+        assert_eq!(lit(&buffer).len(), 12);
+    }
+
+    #[test]
+    fn test_rainbow_swirl_rotates_clockwise() {
+        // 16 LEDs divide the wheel evenly, so hues shift by exactly one LED
+        let mut swirl = RainbowSwirl::new().with_period_ms(1600);
+        let mut start = [(0, 0, 0); 16];
+        let mut later = [(0, 0, 0); 16];
+        swirl.render(0, &mut start);
+        swirl.render(100, &mut later);
+        for (i, &color) in start.iter().enumerate() {
+            assert_eq!(later[(i + 1) % 16], color, "LED {}", i);
+        }
+    }
+
+    #[test]
+    fn test_rainbow_swirl_brightness() {
+        let mut swirl = RainbowSwirl::new().with_brightness(30);
+        let mut buffer = [(0, 0, 0); 12];
+        swirl.render(0, &mut buffer);
+        assert_eq!(buffer[0], (30, 0, 0));
+    }
+
+    // ===== SpiralFill tests =====
+
+    #[test]
+    fn test_spiral_fills_clockwise_from_12() {
+        let mut spiral = SpiralFill::new().with_step_ms(100);
+        let mut buffer = [(0, 0, 0); 12];
+        spiral.render(0, &mut buffer);
+        assert_eq!(lit(&buffer), vec![11]);
+        spiral.render(200, &mut buffer);
+        assert_eq!(lit(&buffer), vec![0, 1, 11]);
+        spiral.render(1100, &mut buffer);
+        assert_eq!(lit(&buffer).len(), 12);
+    }
+
+    #[test]
+    fn test_spiral_empties_in_same_order() {
+        let mut spiral = SpiralFill::new().with_step_ms(100);
+        let mut buffer = [(0, 0, 0); 12];
+        spiral.render(1200, &mut buffer);
+        assert_eq!(lit(&buffer).len(), 11);
+        assert_eq!(buffer[11], (0, 0, 0));
+        spiral.render(2300, &mut buffer);
+        assert!(lit(&buffer).is_empty());
+    }
+
+    #[test]
+    fn test_spiral_changes_hue_each_round() {
+        let mut spiral = SpiralFill::new().with_step_ms(100);
+        let mut first = [(0, 0, 0); 12];
+        let mut second = [(0, 0, 0); 12];
+        spiral.render(0, &mut first);
+        spiral.render(2400, &mut second);
+        assert_ne!(first[11], second[11]);
+    }
+
+    // ===== StartupAnimation tests =====
+
+    #[test]
+    fn test_startup_animation_names_roundtrip() {
+        for animation in StartupAnimation::ALL {
+            assert_eq!(animation.name().parse(), Ok(animation));
+        }
+    }
+
+    #[test]
+    fn test_startup_animation_unknown_name() {
+        assert_eq!(
+            "fireworks".parse::<StartupAnimation>(),
+            Err(UnknownAnimation)
+        );
+    }
+
+    #[test]
+    fn test_startup_animation_from_random_covers_all() {
+        let picked: Vec<_> = (0..3).map(StartupAnimation::from_random).collect();
+        assert_eq!(picked, StartupAnimation::ALL.to_vec());
+        assert_eq!(
+            StartupAnimation::from_random(u32::MAX),
+            StartupAnimation::HandSweep
+        );
+    }
+
+    // ===== StartupChoice tests =====
+
+    #[test]
+    fn test_startup_choice_names_roundtrip() {
+        let choices = StartupAnimation::ALL
+            .map(StartupChoice::Animation)
+            .into_iter()
+            .chain([StartupChoice::Random, StartupChoice::Off]);
+        for choice in choices {
+            assert_eq!(choice.name().parse(), Ok(choice));
+            assert_eq!(StartupChoice::from_bytes(&choice.to_bytes()), Ok(choice));
+        }
+    }
+
+    #[test]
+    fn test_startup_choice_pick() {
+        assert_eq!(
+            StartupChoice::Random.pick(1),
+            Some(StartupAnimation::RainbowSwirl)
+        );
+        assert_eq!(StartupChoice::Off.pick(1), None);
+        assert_eq!(StartupChoice::default(), StartupChoice::Random);
+    }
+
+    #[test]
+    fn test_startup_choice_parse_errors() {
+        assert_eq!(
+            StartupChoice::parse(b" rainbow_swirl\n"),
+            Ok(StartupChoice::Animation(StartupAnimation::RainbowSwirl))
+        );
+        assert_eq!(
+            StartupChoice::parse(b"fireworks"),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            StartupChoice::parse(&[0xff]),
+            Err(SettingsError::InvalidUtf8)
+        );
+    }
+
+    #[test]
+    fn test_startup_choice_from_bytes_errors() {
+        assert_eq!(
+            StartupChoice::from_bytes(&[5]),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            StartupChoice::from_bytes(&[]),
+            Err(SettingsError::InvalidLength(0))
+        );
+    }
+
+    #[test]
+    fn test_startup_effect_dispatches() {
+        let hands = [HOUR, MINUTE, SECOND];
+        let mut buffer = [(0, 0, 0); 12];
+        StartupAnimation::HandSweep
+            .effect(hands, 30)
+            .render(0, &mut buffer);
+        assert_eq!(lit(&buffer), vec![11]);
+        StartupAnimation::RainbowSwirl
+            .effect(hands, 30)
+            .render(0, &mut buffer);
+        assert_eq!(buffer[0], (30, 0, 0));
+    }
+}
@@ -0,0 +1,202 @@
+//! The ring of the clock face itself, of 12, 24, or 60 LEDs.
+
+use crate::settings::SettingsError;
+use crate::{Frame, HandColors, LocalTime, OverlapPolicy, Rgb, RingLayout, SecondHand, LED_COUNT};
+
+/// Most LEDs the clock face may have.
+pub const MAX_FACE_LEDS: usize = 60;
+
+/// The pixel colors of a clock face; a smaller face uses the first LEDs.
+pub type FaceFrame = [Rgb; MAX_FACE_LEDS];
+
+/// The size of the clock face ring.
+///
+/// The clock hands are drawn at the ring's own resolution, so a 60-LED face
+/// shows every minute and second. Everything else, from effects to
+/// notifications, draws the twelve hour positions of a [`Frame`], which
+/// [`ClockFace::scale`] spreads over the ring. Like a 12-LED face, the ring
+/// starts one step past 12 o'clock and runs clockwise.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{ClockFace, HandColors, LocalTime, OverlapPolicy, SecondHand};
+///
+/// let face: ClockFace = "60".parse().unwrap();
+/// let mut pixels = [(0, 0, 0); 60];
+/// let time = LocalTime::new(3, 30, 15).unwrap();
+/// let colors = HandColors::default();
+/// face.render(time, colors, SecondHand::Step, OverlapPolicy::Additive, 0, &mut pixels);
+/// assert_eq!(pixels[29], colors.minute);
+/// assert_eq!(pixels[14], colors.second);
+/// assert_eq!(pixels[16], colors.hour);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockFace {
+    ring: RingLayout,
+}
+
+impl Default for ClockFace {
+    /// A 12-LED face, one LED per hour.
+    fn default() -> Self {
+        Self {
+            ring: RingLayout::all_hands(LED_COUNT as u8),
+        }
+    }
+}
+
+impl ClockFace {
+    /// Creates a face of `leds` LEDs; 12, 24, and 60 are supported.
+    pub fn new(leds: usize) -> Result<Self, SettingsError> {
+        if !matches!(leds, 12 | 24 | 60) {
+            return Err(SettingsError::OutOfRange {
+                field: "leds",
+                value: leds as u32,
+            });
+        }
+        Ok(Self {
+            ring: RingLayout::all_hands(leds as u8),
+        })
+    }
+
+    /// Returns the number of LEDs on the face.
+    pub const fn leds(&self) -> usize {
+        self.ring.leds()
+    }
+
+    /// Returns the LED of an hour position (0-11, LED 0 of a [`Frame`]
+    /// being 1 o'clock) on the face.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::ClockFace;
+    ///
+    /// let face = ClockFace::new(24).unwrap();
+    /// assert_eq!(face.hour_led(0), 1);
+    /// assert_eq!(face.hour_led(11), 23);
+    /// ```
+    pub const fn hour_led(&self, index: usize) -> usize {
+        let per_hour = self.leds() / LED_COUNT;
+        (index % LED_COUNT + 1) * per_hour - 1
+    }
+
+    /// Draws the hands for `time` into `pixels` at the face's resolution.
+    ///
+    /// Hands on the same LED are combined by `policy`. Only the first
+    /// [`ClockFace::leds`] pixels are drawn.
+    pub fn render(
+        &self,
+        time: LocalTime,
+        colors: HandColors,
+        second: SecondHand,
+        policy: OverlapPolicy,
+        elapsed_ms: u32,
+        pixels: &mut [Rgb],
+    ) {
+        self.ring
+            .render(time, colors, second, policy, elapsed_ms, pixels);
+    }
+
+    /// Spreads the twelve hour positions of `frame` over the face.
+    ///
+    /// Each hour position lights the LEDs from the previous hour up to its
+    /// own, so fills and sweeps keep their shape on a larger ring.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{ClockFace, Frame};
+    ///
+    /// let mut frame = Frame::new();
+    /// frame[0] = (0, 0, 255);
+    /// let mut pixels = [(0, 0, 0); 60];
+    /// ClockFace::new(60).unwrap().scale(&frame, &mut pixels);
+    /// assert_eq!(pixels[..6], [(0, 0, 255), (0, 0, 255), (0, 0, 255), (0, 0, 255), (0, 0, 255), (0, 0, 0)]);
+    /// ```
+    pub fn scale(&self, frame: &Frame, pixels: &mut [Rgb]) {
+        let per_hour = self.leds() / LED_COUNT;
+        let leds = self.leds().min(pixels.len());
+        for (span, &color) in pixels[..leds].chunks_mut(per_hour).zip(frame.pixels()) {
+            span.fill(color);
+        }
+    }
+}
+
+impl core::str::FromStr for ClockFace {
+    type Err = SettingsError;
+
+    /// Parses the number of LEDs on the face: `12`, `24`, or `60`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let leds = text
+            .trim()
+            .parse()
+            .map_err(|_| SettingsError::InvalidValue)?;
+        Self::new(leds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_sizes() {
+        for leds in [12, 24, 60] {
+            assert_eq!(ClockFace::new(leds).map(|face| face.leds()), Ok(leds));
+        }
+        assert_eq!(
+            ClockFace::new(16),
+            Err(SettingsError::OutOfRange {
+                field: "leds",
+                value: 16
+            })
+        );
+        assert_eq!(" 24 ".parse::<ClockFace>().map(|face| face.leds()), Ok(24));
+        assert_eq!(
+            "many".parse::<ClockFace>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(ClockFace::default().leds(), LED_COUNT);
+    }
+
+    #[test]
+    fn test_twelve_leds_match_frame() {
+        let face = ClockFace::default();
+        let frame = Frame::from_pixels(core::array::from_fn(|i| (i as u8, 0, 0)));
+        let mut pixels = [(9, 9, 9); LED_COUNT];
+        face.scale(&frame, &mut pixels);
+        assert_eq!(&pixels, frame.pixels());
+        for index in 0..LED_COUNT {
+            assert_eq!(face.hour_led(index), index);
+        }
+    }
+
+    #[test]
+    fn test_hands_on_24_leds() {
+        let face = ClockFace::new(24).unwrap();
+        let colors = HandColors::default();
+        let mut pixels = [(0, 0, 0); 24];
+        let time = LocalTime::new(12, 45, 0).unwrap();
+        face.render(
+            time,
+            colors,
+            SecondHand::Hidden,
+            OverlapPolicy::Additive,
+            0,
+            &mut pixels,
+        );
+        // Three quarters past twelve: the hour hand half a step on, the minute hand at nine
+        assert_eq!(pixels[0], colors.hour);
+        assert_eq!(pixels[17], colors.minute);
+        assert_eq!(pixels.iter().filter(|&&p| p != (0, 0, 0)).count(), 2);
+    }
+
+    #[test]
+    fn test_scale_ignores_short_buffer() {
+        let frame = Frame::from_pixels([(1, 1, 1); LED_COUNT]);
+        let mut pixels = [(0, 0, 0); 30];
+        ClockFace::new(60).unwrap().scale(&frame, &mut pixels);
+        assert!(pixels.iter().all(|&p| p == (1, 1, 1)));
+    }
+}
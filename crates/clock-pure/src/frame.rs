@@ -0,0 +1,277 @@
+//! A complete set of pixel colors for the clock face.
+
+use crate::effects::Effect;
+use crate::{Rgb, LED_COUNT};
+use core::ops::{Index, IndexMut};
+
+/// Size of a frame in the compact binary encoding: one RGB triple per LED.
+pub const FRAME_BYTES: usize = LED_COUNT * 3;
+
+/// Error type for binary frame decoding failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FrameError {
+    /// The data is not exactly [`FRAME_BYTES`] long
+    InvalidLength(usize),
+}
+
+impl core::fmt::Display for FrameError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FrameError::InvalidLength(len) => {
+                write!(f, "Frame data must be {} bytes, got {}", FRAME_BYTES, len)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FrameError {}
+
+/// One frame of pixel colors, indexed by LED position (LED 0 is at 1 o'clock).
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::Frame;
+///
+/// let mut frame = Frame::new();
+/// frame[11] = (0, 0, 1);
+/// assert_eq!(frame[11], (0, 0, 1));
+/// assert_eq!(frame[0], (0, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Frame {
+    pixels: [Rgb; LED_COUNT],
+}
+
+impl Frame {
+    /// Creates a frame with all LEDs off.
+    pub const fn new() -> Self {
+        Self {
+            pixels: [(0, 0, 0); LED_COUNT],
+        }
+    }
+
+    /// Creates a frame from explicit pixel colors.
+    ///
+    /// This is a `const fn`, so fixed frames can be built at compile time.
+    pub const fn from_pixels(pixels: [Rgb; LED_COUNT]) -> Self {
+        Self { pixels }
+    }
+
+    /// Returns the pixel colors.
+    pub const fn pixels(&self) -> &[Rgb; LED_COUNT] {
+        &self.pixels
+    }
+
+    /// Returns the pixel colors for in-place modification, e.g. by an effect.
+    pub fn pixels_mut(&mut self) -> &mut [Rgb; LED_COUNT] {
+        &mut self.pixels
+    }
+
+    /// Returns a new frame with `f` applied to every pixel.
+    pub fn map(&self, f: impl FnMut(Rgb) -> Rgb) -> Self {
+        Self {
+            pixels: self.pixels.map(f),
+        }
+    }
+
+    /// Returns the pixels of this frame that differ from `other`.
+    ///
+    /// Each item is the LED index and the color it has in `self`, so diffing
+    /// the next frame against the one currently displayed yields exactly the
+    /// LEDs that need to change.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::Frame;
+    ///
+    /// let shown = Frame::new();
+    /// let mut next = shown;
+    /// next[4] = (1, 0, 0);
+    ///
+    /// let changed: Vec<_> = next.diff(&shown).collect();
+    /// assert_eq!(changed, vec![(4, (1, 0, 0))]);
+    /// assert_eq!(shown.diff(&shown).count(), 0);
+    /// ```
+    pub fn diff<'a>(&'a self, other: &'a Frame) -> impl Iterator<Item = (usize, Rgb)> + 'a {
+        self.pixels
+            .iter()
+            .zip(other.pixels.iter())
+            .enumerate()
+            .filter(|(_, (a, b))| a != b)
+            .map(|(idx, (&color, _))| (idx, color))
+    }
+}
+
+impl Frame {
+    /// Encodes the frame as [`FRAME_BYTES`] bytes: `r, g, b` for LED 0, then LED 1, and so on.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{Frame, FRAME_BYTES};
+    ///
+    /// let mut frame = Frame::new();
+    /// frame[1] = (10, 20, 30);
+    ///
+    /// let bytes = frame.to_bytes();
+    /// assert_eq!(bytes.len(), FRAME_BYTES);
+    /// assert_eq!(&bytes[3..6], &[10, 20, 30]);
+    /// assert_eq!(Frame::try_from(&bytes[..]), Ok(frame));
+    /// ```
+    pub fn to_bytes(&self) -> [u8; FRAME_BYTES] {
+        let mut bytes = [0; FRAME_BYTES];
+        for (chunk, &(r, g, b)) in bytes.chunks_exact_mut(3).zip(self.pixels.iter()) {
+            chunk.copy_from_slice(&[r, g, b]);
+        }
+        bytes
+    }
+}
+
+impl TryFrom<&[u8]> for Frame {
+    type Error = FrameError;
+
+    /// Decodes a frame from the compact binary encoding produced by [`Frame::to_bytes`].
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() != FRAME_BYTES {
+            return Err(FrameError::InvalidLength(data.len()));
+        }
+        let mut frame = Frame::new();
+        for (pixel, chunk) in frame.pixels.iter_mut().zip(data.chunks_exact(3)) {
+            *pixel = (chunk[0], chunk[1], chunk[2]);
+        }
+        Ok(frame)
+    }
+}
+
+/// A frame is an effect that shows it as it is, e.g. a frame pushed over MQTT.
+///
+/// LEDs beyond the frame's twelve stay dark.
+impl Effect for Frame {
+    fn render(&mut self, _elapsed_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill((0, 0, 0));
+        for (pixel, &color) in buffer.iter_mut().zip(self.pixels.iter()) {
+            *pixel = color;
+        }
+    }
+}
+
+impl Index<usize> for Frame {
+    type Output = Rgb;
+
+    fn index(&self, idx: usize) -> &Rgb {
+        &self.pixels[idx]
+    }
+}
+
+impl IndexMut<usize> for Frame {
+    fn index_mut(&mut self, idx: usize) -> &mut Rgb {
+        &mut self.pixels[idx]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec;
+    use std::vec::Vec;
+
+    #[test]
+    fn test_frame_renders_itself() {
+        let mut frame = Frame::from_pixels([(1, 2, 3); LED_COUNT]);
+        let mut buffer = [(9, 9, 9); LED_COUNT + 1];
+        frame.render(500, &mut buffer);
+        assert_eq!(&buffer[..LED_COUNT], frame.pixels());
+        assert_eq!(buffer[LED_COUNT], (0, 0, 0));
+    }
+
+    #[test]
+    fn test_new_frame_is_black() {
+        assert!(Frame::new().pixels().iter().all(|&p| p == (0, 0, 0)));
+    }
+
+    #[test]
+    fn test_diff_identical_frames_is_empty() {
+        let frame = Frame::from_pixels([(1, 2, 3); LED_COUNT]);
+        assert_eq!(frame.diff(&frame).count(), 0);
+    }
+
+    #[test]
+    fn test_diff_reports_changed_pixels_with_new_color() {
+        let shown = Frame::new();
+        let mut next = shown;
+        next[0] = (1, 0, 0);
+        next[11] = (0, 0, 1);
+        let changed: Vec<_> = next.diff(&shown).collect();
+        assert_eq!(changed, vec![(0, (1, 0, 0)), (11, (0, 0, 1))]);
+    }
+
+    #[test]
+    fn test_diff_second_hand_step() {
+        // 12:00:04 -> 12:00:05: the second hand leaves LED 11 and lands on LED 0
+        let mut before = Frame::new();
+        before[11] = (1, 1, 1);
+        let mut after = Frame::new();
+        after[11] = (0, 1, 1);
+        after[0] = (1, 0, 0);
+        let changed: Vec<_> = after.diff(&before).collect();
+        assert_eq!(changed, vec![(0, (1, 0, 0)), (11, (0, 1, 1))]);
+    }
+
+    #[test]
+    fn test_diff_is_symmetric_in_indices() {
+        let a = Frame::from_pixels([(5, 5, 5); LED_COUNT]);
+        let mut b = a;
+        b[3] = (0, 0, 0);
+        b[7] = (9, 9, 9);
+        let ab: Vec<usize> = a.diff(&b).map(|(i, _)| i).collect();
+        let ba: Vec<usize> = b.diff(&a).map(|(i, _)| i).collect();
+        assert_eq!(ab, ba);
+    }
+
+    #[test]
+    fn test_to_bytes_layout() {
+        let mut frame = Frame::new();
+        frame[0] = (1, 2, 3);
+        frame[11] = (4, 5, 6);
+        let bytes = frame.to_bytes();
+        assert_eq!(&bytes[..3], &[1, 2, 3]);
+        assert_eq!(&bytes[33..], &[4, 5, 6]);
+        assert!(bytes[3..33].iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut frame = Frame::new();
+        for (i, pixel) in frame.pixels_mut().iter_mut().enumerate() {
+            let v = i as u8 * 20;
+            *pixel = (v, v + 1, v + 2);
+        }
+        assert_eq!(Frame::try_from(&frame.to_bytes()[..]), Ok(frame));
+    }
+
+    #[test]
+    fn test_decode_rejects_short_data() {
+        assert_eq!(
+            Frame::try_from(&[0u8; 35][..]),
+            Err(FrameError::InvalidLength(35))
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_long_data() {
+        assert_eq!(
+            Frame::try_from(&[0u8; 37][..]),
+            Err(FrameError::InvalidLength(37))
+        );
+    }
+
+    #[test]
+    fn test_map_applies_to_every_pixel() {
+        let frame = Frame::from_pixels([(1, 2, 3); LED_COUNT]);
+        let scaled = frame.map(|c| crate::scale_color(c, 10));
+        assert!(scaled.pixels().iter().all(|&p| p == (10, 20, 30)));
+    }
+}
@@ -0,0 +1,163 @@
+//! Temperatures shown as an arc colored from blue (cold) to red (hot).
+
+use crate::mode::render_progress;
+use crate::settings::SettingsError;
+use crate::{color_wheel, fade_color, Rgb};
+
+/// Color wheel positions at the cold and hot end of the gauge.
+const COLD_HUE: u8 = 170;
+const HOT_HUE: u8 = 0;
+
+/// Temperatures accepted by [`parse_temperature`], in °C.
+const TEMPERATURE_RANGE: core::ops::RangeInclusive<f32> = -100.0..=100.0;
+
+/// Maps a temperature onto the ring as an arc from 12 o'clock.
+///
+/// The arc is empty at `min` and fills the ring at `max` (both in °C). Each
+/// LED keeps its place on a blue → green → red gradient, so the color at the
+/// tip of the arc shows how warm it is at a glance.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::TemperatureGauge;
+///
+/// let gauge = TemperatureGauge::new(10, 30);
+/// let mut buffer = [(0, 0, 0); 12];
+/// gauge.render(20.0, &mut buffer);
+/// assert_eq!(buffer[11], (0, 0, 255)); // 12 o'clock is the cold end
+/// assert_eq!(buffer[6], (0, 0, 0)); // 7 o'clock and beyond stay dark
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TemperatureGauge {
+    pub min: i8,
+    pub max: i8,
+}
+
+impl TemperatureGauge {
+    /// Creates a gauge for temperatures from `min` to `max` °C.
+    ///
+    /// A gauge whose `max` is not above its `min` stays dark.
+    pub const fn new(min: i8, max: i8) -> Self {
+        Self { min, max }
+    }
+
+    /// Renders `temperature` (°C) into `buffer`, overwriting every pixel.
+    ///
+    /// Temperatures outside the gauge's range show an empty or full ring.
+    pub fn render(&self, temperature: f32, buffer: &mut [Rgb]) {
+        // Tenths of a degree, so the edge of the arc moves smoothly
+        let total = (i32::from(self.max) - i32::from(self.min)).max(0) as u32 * 10;
+        let done = ((temperature - f32::from(self.min)) * 10.0).clamp(0.0, total as f32) as u32;
+        render_progress(done, total, (255, 255, 255), buffer);
+
+        let len = buffer.len();
+        for position in 0..len {
+            // Position 0 is 12 o'clock, the last LED on the ring.
+            let idx = (position + len - 1) % len;
+            let level = buffer[idx].0;
+            buffer[idx] = fade_color(gradient(position, len), level);
+        }
+    }
+}
+
+impl Default for TemperatureGauge {
+    /// A range for room temperatures, 10 °C to 30 °C.
+    fn default() -> Self {
+        Self::new(10, 30)
+    }
+}
+
+/// Returns the gradient color of the LED at `position` of `len`, counted from 12 o'clock.
+fn gradient(position: usize, len: usize) -> Rgb {
+    let span = usize::from(COLD_HUE - HOT_HUE);
+    let step = span * position / len.saturating_sub(1).max(1);
+    color_wheel(COLD_HUE - step as u8)
+}
+
+/// Parses a temperature payload: a decimal number of degrees Celsius.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::parse_temperature;
+///
+/// assert_eq!(parse_temperature(b"21.5\n"), Ok(21.5));
+/// assert!(parse_temperature(b"warm").is_err());
+/// ```
+pub fn parse_temperature(payload: &[u8]) -> Result<f32, SettingsError> {
+    let text = core::str::from_utf8(payload)
+        .map_err(|_| SettingsError::InvalidUtf8)?
+        .trim();
+    let temperature: f32 = text.parse().map_err(|_| SettingsError::InvalidValue)?;
+    if !TEMPERATURE_RANGE.contains(&temperature) {
+        return Err(SettingsError::InvalidValue);
+    }
+    Ok(temperature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLUE: Rgb = (0, 0, 255);
+    const RED: Rgb = (255, 0, 0);
+
+    fn lit(buffer: &[Rgb]) -> usize {
+        buffer.iter().filter(|&&p| p != (0, 0, 0)).count()
+    }
+
+    #[test]
+    fn test_full_ring_runs_blue_to_red() {
+        let mut buffer = [(0, 0, 0); 12];
+        TemperatureGauge::new(0, 10).render(10.0, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+        assert_eq!(buffer[11], BLUE);
+        assert_eq!(buffer[10], RED);
+        // Green halfway round
+        assert!(buffer[5].1 > buffer[5].0 && buffer[5].1 > buffer[5].2);
+    }
+
+    #[test]
+    fn test_out_of_range_clamps() {
+        let gauge = TemperatureGauge::default();
+        let mut buffer = [(9, 9, 9); 12];
+        gauge.render(-5.0, &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+        gauge.render(45.0, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+    }
+
+    #[test]
+    fn test_fraction_dims_the_edge() {
+        let mut buffer = [(0, 0, 0); 12];
+        // 1.5 of 12 degrees: one and a half LEDs
+        TemperatureGauge::new(0, 12).render(1.5, &mut buffer);
+        assert_eq!(buffer[11], BLUE);
+        assert_eq!(buffer[0], fade_color(gradient(1, 12), 128));
+        assert_eq!(lit(&buffer), 2);
+    }
+
+    #[test]
+    fn test_empty_range_stays_dark() {
+        let mut buffer = [(9, 9, 9); 12];
+        TemperatureGauge::new(20, 20).render(20.0, &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+        TemperatureGauge::new(30, 10).render(f32::NAN, &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+    }
+
+    #[test]
+    fn test_parse_temperature() {
+        assert_eq!(parse_temperature(b"-3"), Ok(-3.0));
+        assert_eq!(parse_temperature(b" 19.25 "), Ok(19.25));
+        for payload in [&b""[..], b"NaN", b"inf", b"150", b"20 C"] {
+            assert_eq!(
+                parse_temperature(payload),
+                Err(SettingsError::InvalidValue),
+                "{payload:?}"
+            );
+        }
+        assert_eq!(parse_temperature(&[0xff]), Err(SettingsError::InvalidUtf8));
+    }
+}
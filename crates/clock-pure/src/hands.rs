@@ -0,0 +1,371 @@
+//! Drawing the clock hands, including how hands on the same LED combine.
+
+use crate::settings::SettingsError;
+use crate::{add_colors, fade_color, Rgb};
+
+/// Default level of [`HourMarkers`], a quarter of the hands' intensity.
+const DEFAULT_MARKER_LEVEL: u8 = 64;
+
+/// How long [`OverlapPolicy::Alternate`] shows each hand on a shared LED.
+pub const ALTERNATE_INTERVAL_MS: u32 = 500;
+
+/// How hands that point at the same LED are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlapPolicy {
+    /// Mix the colors by adding them, e.g. blue and green give cyan
+    #[default]
+    Additive,
+    /// Show only the first hand, so the hour wins over the minute and second
+    Priority,
+    /// Show each hand in turn for [`ALTERNATE_INTERVAL_MS`]
+    Alternate,
+}
+
+impl OverlapPolicy {
+    /// Returns a short lowercase name for logging.
+    pub const fn name(self) -> &'static str {
+        match self {
+            OverlapPolicy::Additive => "additive",
+            OverlapPolicy::Priority => "priority",
+            OverlapPolicy::Alternate => "alternate",
+        }
+    }
+}
+
+impl core::str::FromStr for OverlapPolicy {
+    type Err = SettingsError;
+
+    /// Parses `additive`, `priority`, or `alternate` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        [
+            OverlapPolicy::Additive,
+            OverlapPolicy::Priority,
+            OverlapPolicy::Alternate,
+        ]
+        .into_iter()
+        .find(|policy| policy.name().eq_ignore_ascii_case(text.trim()))
+        .ok_or(SettingsError::InvalidValue)
+    }
+}
+
+/// Draws clock hands into `buffer`, combining hands on the same LED by `policy`.
+///
+/// `hands` lists each hand's LED index and color in priority order, usually
+/// hour, minute, second. `elapsed_ms` drives [`OverlapPolicy::Alternate`].
+/// Every pixel is overwritten; indices outside the buffer are ignored.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{render_hands, OverlapPolicy};
+///
+/// let hands = [(11, (0, 0, 255)), (11, (0, 255, 0))];
+/// let mut buffer = [(0, 0, 0); 12];
+///
+/// render_hands(&hands, OverlapPolicy::Additive, 0, &mut buffer);
+/// assert_eq!(buffer[11], (0, 255, 255));
+///
+/// render_hands(&hands, OverlapPolicy::Priority, 0, &mut buffer);
+/// assert_eq!(buffer[11], (0, 0, 255));
+///
+/// render_hands(&hands, OverlapPolicy::Alternate, 500, &mut buffer);
+/// assert_eq!(buffer[11], (0, 255, 0));
+/// ```
+pub fn render_hands(
+    hands: &[(usize, Rgb)],
+    policy: OverlapPolicy,
+    elapsed_ms: u32,
+    buffer: &mut [Rgb],
+) {
+    buffer.fill((0, 0, 0));
+    let turn = (elapsed_ms / ALTERNATE_INTERVAL_MS) as usize;
+
+    for (i, &(idx, color)) in hands.iter().enumerate() {
+        if idx >= buffer.len() {
+            continue;
+        }
+        let sharing = hands.iter().filter(|&&(other, _)| other == idx).count();
+        let earlier = hands[..i]
+            .iter()
+            .filter(|&&(other, _)| other == idx)
+            .count();
+        let visible = match policy {
+            OverlapPolicy::Additive => true,
+            OverlapPolicy::Priority => earlier == 0,
+            OverlapPolicy::Alternate => turn % sharing == earlier,
+        };
+        if visible {
+            buffer[idx] = add_colors(buffer[idx], color);
+        }
+    }
+}
+
+/// Adds a hand at a fractional position on the ring, anti-aliased across two LEDs.
+///
+/// The hand sits `progress` out of `period` of the way around the ring
+/// clockwise from 12 o'clock, so passing the milliseconds into the current
+/// minute and 60 000 gives a continuously sweeping second hand. Between two
+/// LEDs the color is split in proportion to the distance from each. The
+/// color is added to what is already in `buffer`.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::render_sweep;
+///
+/// let mut buffer = [(0, 0, 0); 12];
+/// // Halfway between 12 and 1 o'clock, 2.5 seconds into the minute
+/// render_sweep(2_500, 60_000, (255, 0, 0), &mut buffer);
+/// assert_eq!(buffer[11], (127, 0, 0));
+/// assert_eq!(buffer[0], (128, 0, 0));
+/// ```
+pub fn render_sweep(progress: u32, period: u32, color: Rgb, buffer: &mut [Rgb]) {
+    let len = buffer.len() as u64;
+    if len == 0 || period == 0 {
+        return;
+    }
+
+    // Position in 1/256 steps of one LED
+    let steps = u64::from(progress % period) * len * 256 / u64::from(period);
+    let position = steps / 256;
+    let fraction = (steps % 256) as u8;
+
+    // Position 0 is 12 o'clock, the last LED on the ring.
+    let first = ((position + len - 1) % len) as usize;
+    let second = (position % len) as usize;
+    buffer[first] = add_colors(buffer[first], fade_color(color, 255 - fraction));
+    if fraction > 0 {
+        buffer[second] = add_colors(buffer[second], fade_color(color, fraction));
+    }
+}
+
+/// Faint background LEDs at 12, 3, 6, and 9 o'clock for orientation in the dark.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{render_hands, HourMarkers, OverlapPolicy};
+///
+/// let mut buffer = [(0, 0, 0); 12];
+/// render_hands(&[(2, (0, 0, 255))], OverlapPolicy::Additive, 0, &mut buffer);
+/// HourMarkers::new((255, 255, 255)).with_level(51).render(&mut buffer);
+///
+/// assert_eq!(buffer[11], (51, 51, 51)); // 12 o'clock marker
+/// assert_eq!(buffer[2], (0, 0, 255)); // the hand covers the 3 o'clock marker
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HourMarkers {
+    color: Rgb,
+    level: u8,
+}
+
+impl HourMarkers {
+    /// Creates markers in `color`, dimmed to a quarter of its intensity.
+    pub const fn new(color: Rgb) -> Self {
+        Self {
+            color,
+            level: DEFAULT_MARKER_LEVEL,
+        }
+    }
+
+    /// Sets the marker level (0-255) relative to the full `color`.
+    pub const fn with_level(mut self, level: u8) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Returns the marker color after applying the level.
+    pub const fn dimmed_color(&self) -> Rgb {
+        fade_color(self.color, self.level)
+    }
+
+    /// Draws the markers beneath whatever is already in `buffer`.
+    ///
+    /// Only dark LEDs are filled, so the hands always cover the markers. The
+    /// markers sit at the quarter positions clockwise from 12 o'clock, the
+    /// last LED on the ring.
+    pub fn render(&self, buffer: &mut [Rgb]) {
+        let len = buffer.len();
+        if len == 0 {
+            return;
+        }
+        let color = self.dimmed_color();
+        for quarter in 0..4 {
+            let idx = (quarter * len / 4 + len - 1) % len;
+            if buffer[idx] == (0, 0, 0) {
+                buffer[idx] = color;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    const HOUR: Rgb = (0, 0, 255);
+    const MINUTE: Rgb = (0, 255, 0);
+    const SECOND: Rgb = (255, 0, 0);
+
+    fn render(hands: &[(usize, Rgb)], policy: OverlapPolicy, elapsed_ms: u32) -> [Rgb; 12] {
+        let mut buffer = [(9, 9, 9); 12];
+        render_hands(hands, policy, elapsed_ms, &mut buffer);
+        buffer
+    }
+
+    #[test]
+    fn test_separate_hands_are_unaffected_by_policy() {
+        let hands = [(2, HOUR), (5, MINUTE), (0, SECOND)];
+        for policy in [
+            OverlapPolicy::Additive,
+            OverlapPolicy::Priority,
+            OverlapPolicy::Alternate,
+        ] {
+            let buffer = render(&hands, policy, 700);
+            assert_eq!((buffer[2], buffer[5], buffer[0]), (HOUR, MINUTE, SECOND));
+            assert_eq!(buffer.iter().filter(|&&p| p != (0, 0, 0)).count(), 3);
+        }
+    }
+
+    #[test]
+    fn test_additive_mixes_all_hands() {
+        let buffer = render(
+            &[(11, HOUR), (11, MINUTE), (11, SECOND)],
+            OverlapPolicy::Additive,
+            0,
+        );
+        assert_eq!(buffer[11], (255, 255, 255));
+    }
+
+    #[test]
+    fn test_priority_prefers_earlier_hands() {
+        let hands = [(2, HOUR), (5, MINUTE), (5, SECOND)];
+        let buffer = render(&hands, OverlapPolicy::Priority, 0);
+        assert_eq!((buffer[2], buffer[5]), (HOUR, MINUTE));
+
+        let buffer = render(
+            &[(11, HOUR), (11, MINUTE), (11, SECOND)],
+            OverlapPolicy::Priority,
+            0,
+        );
+        assert_eq!(buffer[11], HOUR);
+    }
+
+    #[test]
+    fn test_alternate_takes_turns() {
+        let hands = [(11, HOUR), (11, MINUTE), (11, SECOND)];
+        let shown: Vec<Rgb> = (0..4)
+            .map(|turn| {
+                render(
+                    &hands,
+                    OverlapPolicy::Alternate,
+                    turn * ALTERNATE_INTERVAL_MS,
+                )[11]
+            })
+            .collect();
+        assert_eq!(shown, [HOUR, MINUTE, SECOND, HOUR]);
+    }
+
+    #[test]
+    fn test_alternate_only_affects_shared_leds() {
+        // Hour alone at LED 2, minute and second share LED 5
+        let hands = [(2, HOUR), (5, MINUTE), (5, SECOND)];
+        let buffer = render(&hands, OverlapPolicy::Alternate, ALTERNATE_INTERVAL_MS);
+        assert_eq!((buffer[2], buffer[5]), (HOUR, SECOND));
+    }
+
+    #[test]
+    fn test_out_of_range_index_is_ignored() {
+        let buffer = render(&[(12, HOUR), (0, MINUTE)], OverlapPolicy::Additive, 0);
+        assert_eq!(buffer[0], MINUTE);
+    }
+
+    #[test]
+    fn test_parse_policy() {
+        assert_eq!("Priority\n".parse(), Ok(OverlapPolicy::Priority));
+        assert_eq!("alternate".parse(), Ok(OverlapPolicy::Alternate));
+        assert_eq!(
+            "blend".parse::<OverlapPolicy>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(OverlapPolicy::default(), OverlapPolicy::Additive);
+    }
+
+    // ===== Sweep tests =====
+
+    #[test]
+    fn test_sweep_on_led() {
+        let mut buffer = [(0, 0, 0); 12];
+        render_sweep(0, 60_000, SECOND, &mut buffer);
+        assert_eq!(buffer[11], SECOND);
+        assert_eq!(buffer.iter().filter(|&&p| p != (0, 0, 0)).count(), 1);
+
+        let mut buffer = [(0, 0, 0); 12];
+        render_sweep(30_000, 60_000, SECOND, &mut buffer);
+        assert_eq!(buffer[5], SECOND);
+    }
+
+    #[test]
+    fn test_sweep_blends_between_leds() {
+        let mut buffer = [(0, 0, 0); 12];
+        // A quarter of the way from 1 to 2 o'clock
+        render_sweep(6_250, 60_000, SECOND, &mut buffer);
+        assert_eq!(buffer[0], (191, 0, 0));
+        assert_eq!(buffer[1], (64, 0, 0));
+    }
+
+    #[test]
+    fn test_sweep_wraps_from_11_to_12() {
+        let mut buffer = [(0, 0, 0); 12];
+        render_sweep(57_500, 60_000, SECOND, &mut buffer);
+        assert_eq!(buffer[10], (127, 0, 0));
+        assert_eq!(buffer[11], (128, 0, 0));
+
+        // Progress beyond the period wraps around
+        let mut wrapped = [(0, 0, 0); 12];
+        render_sweep(117_500, 60_000, SECOND, &mut wrapped);
+        assert_eq!(wrapped, buffer);
+    }
+
+    #[test]
+    fn test_sweep_adds_to_buffer() {
+        let mut buffer = [(0, 0, 0); 12];
+        buffer[11] = HOUR;
+        render_sweep(0, 60_000, SECOND, &mut buffer);
+        assert_eq!(buffer[11], (255, 0, 255));
+        render_sweep(0, 0, SECOND, &mut buffer);
+        render_sweep(0, 60_000, SECOND, &mut []);
+    }
+
+    // ===== HourMarkers tests =====
+
+    #[test]
+    fn test_markers_at_quarter_hours() {
+        let mut buffer = [(0, 0, 0); 12];
+        HourMarkers::new((200, 100, 0))
+            .with_level(255)
+            .render(&mut buffer);
+        let lit: Vec<usize> = (0..12).filter(|&i| buffer[i] != (0, 0, 0)).collect();
+        assert_eq!(lit, [2, 5, 8, 11]);
+        assert_eq!(buffer[5], (200, 100, 0));
+    }
+
+    #[test]
+    fn test_markers_stay_beneath_hands() {
+        let mut buffer = render(&[(11, HOUR), (5, MINUTE)], OverlapPolicy::Additive, 0);
+        HourMarkers::new((255, 255, 255)).render(&mut buffer);
+        assert_eq!((buffer[11], buffer[5]), (HOUR, MINUTE));
+        assert_eq!(buffer[2], (64, 64, 64));
+        assert_eq!(buffer[8], (64, 64, 64));
+    }
+
+    #[test]
+    fn test_markers_on_other_ring_sizes() {
+        let mut buffer = [(0, 0, 0); 60];
+        HourMarkers::new((255, 255, 255)).render(&mut buffer);
+        let lit: Vec<usize> = (0..60).filter(|&i| buffer[i] != (0, 0, 0)).collect();
+        assert_eq!(lit, [14, 29, 44, 59]);
+        HourMarkers::new((255, 255, 255)).render(&mut []);
+    }
+}
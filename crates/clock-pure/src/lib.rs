@@ -3,12 +3,141 @@
 //! This crate provides hardware-independent time-to-LED-index mapping
 //! and color manipulation utilities. It has no ESP or embedded dependencies,
 //! making it fully testable on any platform.
+//!
+//! The crate is `no_std`. The default `std` feature adds serde support and
+//! parsing of MQTT time payloads; disable default features to use the crate
+//! in bare-metal projects (esp-hal, RP2040, ...).
+
+#![no_std]
+
+#[cfg(any(test, feature = "std"))]
+extern crate std;
+
+mod alarm;
+pub mod animation;
+mod apa102;
+mod backoff;
+mod button;
+mod calibration;
+mod date;
+mod dead_pixels;
+mod dnd;
+mod ds3231;
+mod easing;
+pub mod effects;
+mod face;
+mod frame;
+mod gauge;
+mod hands;
+pub mod lut;
+#[cfg(feature = "std")]
+mod mdns;
+mod melody;
+mod mode;
+#[cfg(feature = "std")]
+mod network;
+mod night;
+mod notify;
+mod outer;
+mod pomodoro;
+mod power;
+mod push;
+mod qos;
+#[cfg(feature = "std")]
+mod remote;
+#[cfg(feature = "std")]
+mod report;
+mod retained;
+mod rgbw;
+mod scene;
+mod schedule;
+mod screensaver;
+mod settings;
+mod status;
+mod stopwatch;
+#[cfg(feature = "std")]
+mod telemetry;
+mod theme;
+mod tick;
+mod time;
+mod time_source;
+mod timekeeper;
+mod topic;
+mod transition;
+mod tz;
+mod weather;
+mod wiring;
+mod ws2812;
+
+pub use alarm::{Alarm, AlarmDays, AlarmStyle, Alarms, ALARM_RAMP_MS, MAX_ALARMS};
+pub use apa102::{encode_apa102, APA102_FRAME_BYTES, APA102_MAX_BRIGHTNESS};
+pub use backoff::{Backoff, BACKOFF_INITIAL_MS, BACKOFF_MAX_MS};
+pub use button::{Button, ButtonPress, DEBOUNCE_MS, LONG_PRESS_MS};
+pub use calibration::Calibration;
+pub use date::{Date, LocalDateTime, Weekday};
+pub use dead_pixels::{DeadPixelPolicy, DeadPixels};
+pub use dnd::DoNotDisturb;
+pub use ds3231::{
+    decode_ds3231, encode_ds3231, DS3231_ADDRESS, DS3231_OSF, DS3231_STATUS_REGISTER,
+    DS3231_TIME_REGISTER,
+};
+pub use easing::{ease_in_out, Ramp};
+pub use face::{ClockFace, FaceFrame, MAX_FACE_LEDS};
+pub use frame::{Frame, FrameError, FRAME_BYTES};
+pub use gauge::{parse_temperature, TemperatureGauge};
+pub use hands::{render_hands, render_sweep, HourMarkers, OverlapPolicy, ALTERNATE_INTERVAL_MS};
+#[cfg(feature = "std")]
+pub use mdns::mdns_hostname;
+pub use melody::{Melody, Note, DEFAULT_NOTE_MS, MAX_MELODY_NOTES};
+pub use mode::{render_progress, DisplayMode};
+#[cfg(feature = "std")]
+pub use network::{NetworkSettings, MAX_HOST, MAX_PASSWORD, MAX_SSID};
+pub use night::{NightMode, NightSwitch, TimeWindow};
+pub use notify::{Notification, NotifyPattern, DEFAULT_NOTIFY_MS};
+pub use outer::{outer_index, OuterFrame, RingHands, RingLayout, SecondHand, OUTER_LED_COUNT};
+pub use pomodoro::{Pomodoro, PomodoroCommand, PomodoroPhase, POMODORO_FLASH_MS};
+pub use power::{PowerBudget, PowerGate, LED_CHANNEL_MA, LED_IDLE_MA, POWER_GATE_HOLD_MS};
+pub use push::{PushedFrame, DEFAULT_PUSH_MS};
+pub use qos::{QosLevels, DEFAULT_QOS, MAX_QOS_TOPICS};
+#[cfg(feature = "std")]
+pub use remote::RemoteCommand;
+#[cfg(feature = "std")]
+pub use report::StateReport;
+pub use retained::{RetainedTime, MAX_RETAINED_AGE_US};
+pub use rgbw::{encode_sk6812, Rgbw, WhitePolicy, SK6812_FRAME_BYTES};
+pub use scene::{Scene, SceneName, Scenes, MAX_SCENES, MAX_SCENE_NAME};
+pub use schedule::{Schedule, ScheduleEntry, ScheduledAction, MAX_SCHEDULE_ENTRIES};
+pub use screensaver::{parse_presence, Screensaver, SCREENSAVER_CYCLE_MS};
+pub use settings::{parse_brightness, parse_color, parse_on_off, HandColors, SettingsError};
+pub use status::{Fault, Status};
+pub use stopwatch::{Stopwatch, StopwatchCommand};
+#[cfg(feature = "std")]
+pub use telemetry::Telemetry;
+pub use theme::{DayRange, Theme, ThemeCalendar, MAX_THEMES};
+pub use tick::{parse_tick, TickFormat};
+pub use time::{ConvertError, LocalTime, BINARY_TICK_BYTES, SECONDS_PER_DAY};
+pub use time_source::{TimeSource, SNTP_STALE_MS, TICK_STALE_MS};
+pub use timekeeper::{TimeKeeper, MAX_SLEW_MS};
+pub use topic::Topic;
+pub use transition::Transition;
+pub use tz::{TimeZone, TimeZoneError};
+pub use weather::{Weather, WeatherCondition, WEATHER_CONDITION_MS, WEATHER_CYCLE_MS};
+pub use wiring::{ChannelOrder, WiringOrder};
+pub use ws2812::{
+    encode_ws2812_spi, ws2812_spi_len, WS2812_SPI_HZ, WS2812_SPI_LED_BYTES, WS2812_SPI_RESET_BYTES,
+};
 
 /// RGB color representation as (r, g, b) tuple.
 pub type Rgb = (u8, u8, u8);
 
+/// Number of LEDs on the clock face.
+pub const LED_COUNT: usize = 12;
+
 /// Maps an hour value (0-23) to the corresponding LED index (0-11).
 ///
+/// All index mappings are `const fn`, so LED positions for fixed times can be
+/// computed at compile time.
+///
 /// The clock has 12 LEDs arranged in a circle. LED 0 is at the 1 o'clock
 /// position, LED 11 is at the 12 o'clock position.
 ///
@@ -27,7 +156,7 @@ pub type Rgb = (u8, u8, u8);
 /// assert_eq!(hour_to_index(1), 0);   // 1 o'clock -> LED 0
 /// assert_eq!(hour_to_index(6), 5);   // 6 o'clock -> LED 5
 /// ```
-pub fn hour_to_index(hour: u8) -> usize {
+pub const fn hour_to_index(hour: u8) -> usize {
     (hour as usize + 11) % 12
 }
 
@@ -51,7 +180,7 @@ pub fn hour_to_index(hour: u8) -> usize {
 /// assert_eq!(minute_to_index(5), 0);   // :05 -> 1 o'clock
 /// assert_eq!(minute_to_index(30), 5);  // :30 -> 6 o'clock
 /// ```
-pub fn minute_to_index(minute: u8) -> usize {
+pub const fn minute_to_index(minute: u8) -> usize {
     (minute as usize + 55) % 60 / 5
 }
 
@@ -73,7 +202,7 @@ pub fn minute_to_index(minute: u8) -> usize {
 /// assert_eq!(second_to_index(0), 11);  // :00 -> 12 o'clock
 /// assert_eq!(second_to_index(59), 10); // :59 -> 11 o'clock
 /// ```
-pub fn second_to_index(second: u8) -> usize {
+pub const fn second_to_index(second: u8) -> usize {
     (second as usize + 55) % 60 / 5
 }
 
@@ -92,7 +221,7 @@ pub fn second_to_index(second: u8) -> usize {
 /// let bright = scale_color((100, 100, 100), 10);
 /// assert_eq!(bright, (255, 255, 255));
 /// ```
-pub fn scale_color(color: Rgb, factor: u8) -> Rgb {
+pub const fn scale_color(color: Rgb, factor: u8) -> Rgb {
     (
         color.0.saturating_mul(factor),
         color.1.saturating_mul(factor),
@@ -116,7 +245,7 @@ pub fn scale_color(color: Rgb, factor: u8) -> Rgb {
 /// let d = (100, 100, 100);
 /// assert_eq!(add_colors(c, d), (255, 255, 255));
 /// ```
-pub fn add_colors(a: Rgb, b: Rgb) -> Rgb {
+pub const fn add_colors(a: Rgb, b: Rgb) -> Rgb {
     (
         a.0.saturating_add(b.0),
         a.1.saturating_add(b.1),
@@ -124,6 +253,79 @@ pub fn add_colors(a: Rgb, b: Rgb) -> Rgb {
     )
 }
 
+/// Dims an RGB color to `level`/255 of its intensity.
+///
+/// Unlike [`scale_color`], which multiplies by an integer brightness factor,
+/// this scales down proportionally and never saturates.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::fade_color;
+///
+/// assert_eq!(fade_color((200, 100, 50), 255), (200, 100, 50));
+/// assert_eq!(fade_color((200, 100, 50), 0), (0, 0, 0));
+/// assert_eq!(fade_color((200, 100, 50), 127), (99, 49, 24));
+/// ```
+pub const fn fade_color(color: Rgb, level: u8) -> Rgb {
+    (
+        (color.0 as u16 * level as u16 / 255) as u8,
+        (color.1 as u16 * level as u16 / 255) as u8,
+        (color.2 as u16 * level as u16 / 255) as u8,
+    )
+}
+
+/// Blends from color `a` towards color `b` by `amount`/255.
+///
+/// 0 returns `a` unchanged and 255 returns `b`, so an overlay can tint the
+/// face without hiding it.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::mix_colors;
+///
+/// assert_eq!(mix_colors((0, 0, 200), (255, 0, 0), 0), (0, 0, 200));
+/// assert_eq!(mix_colors((0, 0, 200), (255, 0, 0), 255), (255, 0, 0));
+/// assert_eq!(mix_colors((0, 0, 200), (255, 0, 0), 128), (128, 0, 99));
+/// ```
+pub const fn mix_colors(a: Rgb, b: Rgb, amount: u8) -> Rgb {
+    const fn mix(a: u8, b: u8, amount: u8) -> u8 {
+        ((a as u16 * (255 - amount as u16) + b as u16 * amount as u16) / 255) as u8
+    }
+    (
+        mix(a.0, b.0, amount),
+        mix(a.1, b.1, amount),
+        mix(a.2, b.2, amount),
+    )
+}
+
+/// Maps a position on a 256-step color wheel to a fully saturated hue.
+///
+/// The wheel runs red → green → blue → red; position 0 is pure red.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::color_wheel;
+///
+/// assert_eq!(color_wheel(0), (255, 0, 0));
+/// assert_eq!(color_wheel(85), (0, 255, 0));
+/// assert_eq!(color_wheel(170), (0, 0, 255));
+/// ```
+pub const fn color_wheel(position: u8) -> Rgb {
+    let pos = 255 - position;
+    if pos < 85 {
+        (255 - pos * 3, 0, pos * 3)
+    } else if pos < 170 {
+        let pos = pos - 85;
+        (0, pos * 3, 255 - pos * 3)
+    } else {
+        let pos = pos - 170;
+        (pos * 3, 255 - pos * 3, 0)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -297,6 +499,61 @@ mod tests {
         assert_eq!(add_colors(a, b), add_colors(b, a));
     }
 
+    // ===== fade_color tests =====
+
+    #[test]
+    fn test_fade_color_full_level_is_identity() {
+        assert_eq!(fade_color((255, 128, 1), 255), (255, 128, 1));
+    }
+
+    #[test]
+    fn test_fade_color_zero_level_is_black() {
+        assert_eq!(fade_color((255, 255, 255), 0), (0, 0, 0));
+    }
+
+    #[test]
+    fn test_fade_color_half_level() {
+        assert_eq!(fade_color((255, 100, 0), 128), (128, 50, 0));
+    }
+
+    // ===== mix_colors tests =====
+
+    #[test]
+    fn test_mix_colors_endpoints_and_midpoint() {
+        let a = (10, 200, 255);
+        let b = (250, 0, 5);
+        assert_eq!(mix_colors(a, b, 0), a);
+        assert_eq!(mix_colors(a, b, 255), b);
+        assert_eq!(mix_colors((0, 0, 0), (255, 255, 255), 64), (64, 64, 64));
+    }
+
+    // ===== color_wheel tests =====
+
+    #[test]
+    fn test_color_wheel_primaries() {
+        assert_eq!(color_wheel(0), (255, 0, 0));
+        assert_eq!(color_wheel(85), (0, 255, 0));
+        assert_eq!(color_wheel(170), (0, 0, 255));
+    }
+
+    #[test]
+    fn test_color_wheel_channels_sum_to_full() {
+        for p in 0..=255u8 {
+            let (r, g, b) = color_wheel(p);
+            assert_eq!(r as u16 + g as u16 + b as u16, 255, "position {}", p);
+        }
+    }
+
+    // ===== const evaluation =====
+
+    #[test]
+    fn test_mappings_usable_in_const() {
+        const NOON: [usize; 3] = [hour_to_index(12), minute_to_index(0), second_to_index(0)];
+        const CYAN: Rgb = scale_color(add_colors(HOUR_COLOR, MINUTE_COLOR), 10);
+        assert_eq!(NOON, [11, 11, 11]);
+        assert_eq!(CYAN, (0, 10, 10));
+    }
+
     // ===== hour_to_index edge cases =====
 
     #[test]
@@ -545,6 +802,23 @@ mod proptests {
             prop_assert!(result.2 >= b1);
         }
 
+        #[test]
+        fn fade_color_never_increases(
+            r in 0..=255u8, g in 0..=255u8, b in 0..=255u8,
+            level in 0..=255u8,
+        ) {
+            let faded = fade_color((r, g, b), level);
+            prop_assert!(faded.0 <= r);
+            prop_assert!(faded.1 <= g);
+            prop_assert!(faded.2 <= b);
+        }
+
+        #[test]
+        fn frame_bytes_roundtrip(bytes in proptest::collection::vec(0..=255u8, FRAME_BYTES)) {
+            let frame = Frame::try_from(&bytes[..]).unwrap();
+            prop_assert_eq!(&frame.to_bytes()[..], &bytes[..]);
+        }
+
         #[test]
         fn hour_to_index_always_valid(hour in 0..=255u8) {
             prop_assert!(hour_to_index(hour) < 12);
@@ -559,5 +833,22 @@ mod proptests {
         fn hour_to_index_am_pm_equivalent(hour in 0..=11u8) {
             prop_assert_eq!(hour_to_index(hour), hour_to_index(hour + 12));
         }
+
+        #[test]
+        fn local_time_add_then_sub_roundtrip(
+            hour in 0..=23u8, minute in 0..=59u8, second in 0..=59u8, seconds in any::<u32>()
+        ) {
+            let time = LocalTime::new(hour, minute, second).unwrap();
+            let later = time.add_seconds(seconds);
+            prop_assert_eq!(later.sub_seconds(seconds), time);
+            prop_assert_eq!(later.diff(time), seconds % SECONDS_PER_DAY);
+        }
+
+        #[test]
+        fn local_time_seconds_roundtrip(seconds in 0..SECONDS_PER_DAY) {
+            let time = LocalTime::from_seconds(seconds);
+            prop_assert!(LocalTime::new(time.hour, time.minute, time.second).is_ok());
+            prop_assert_eq!(time.to_seconds(), seconds);
+        }
     }
 }
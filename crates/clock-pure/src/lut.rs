@@ -0,0 +1,287 @@
+//! Compile-time lookup tables for color correction, hue selection, and waveforms.
+//!
+//! All tables are `const`, so they live in flash and can be used to build
+//! frames for static animations at compile time.
+
+use crate::{color_wheel, Rgb, LED_COUNT};
+
+/// Gamma correction table (gamma 2.8) mapping linear 8-bit intensity to LED PWM level.
+///
+/// WS2812 output is linear in PWM duty cycle, while perceived brightness is
+/// not; running colors through this table makes fades and dim themes look even.
+#[rustfmt::skip]
+pub const GAMMA8: [u8; 256] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2,
+    2, 3, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 5, 5, 5,
+    5, 6, 6, 6, 6, 7, 7, 7, 7, 8, 8, 8, 9, 9, 9, 10,
+    10, 10, 11, 11, 11, 12, 12, 13, 13, 13, 14, 14, 15, 15, 16, 16,
+    17, 17, 18, 18, 19, 19, 20, 20, 21, 21, 22, 22, 23, 24, 24, 25,
+    25, 26, 27, 27, 28, 29, 29, 30, 31, 32, 32, 33, 34, 35, 35, 36,
+    37, 38, 39, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 50,
+    51, 52, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 66, 67, 68,
+    69, 70, 72, 73, 74, 75, 77, 78, 79, 81, 82, 83, 85, 86, 87, 89,
+    90, 92, 93, 95, 96, 98, 99, 101, 102, 104, 105, 107, 109, 110, 112, 114,
+    115, 117, 119, 120, 122, 124, 126, 127, 129, 131, 133, 135, 137, 138, 140, 142,
+    144, 146, 148, 150, 152, 154, 156, 158, 160, 162, 164, 167, 169, 171, 173, 175,
+    177, 180, 182, 184, 186, 189, 191, 193, 196, 198, 200, 203, 205, 208, 210, 213,
+    215, 218, 220, 223, 225, 228, 231, 233, 236, 239, 241, 244, 247, 249, 252, 255,
+];
+
+/// Twelve evenly spaced hues, one per LED position, starting with red at LED 0.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::lut::HUE_WHEEL;
+/// use clock_pure::Frame;
+///
+/// const RAINBOW: Frame = Frame::from_pixels(HUE_WHEEL);
+/// assert_eq!(RAINBOW[0], (255, 0, 0));
+/// ```
+pub const HUE_WHEEL: [Rgb; LED_COUNT] = {
+    let mut table = [(0, 0, 0); LED_COUNT];
+    let mut idx = 0;
+    while idx < LED_COUNT {
+        table[idx] = color_wheel((idx * 256 / LED_COUNT) as u8);
+        idx += 1;
+    }
+    table
+};
+
+/// One period of a sine wave sampled at 256 phases, scaled to `0..=255`.
+///
+/// Phase 0 is the midpoint (128), phase 64 the peak, and phase 192 the trough.
+/// Shared by breathing, wave, and sweep effects so none of them needs floating point.
+#[rustfmt::skip]
+pub const SINE8: [u8; 256] = [
+    128, 131, 134, 137, 140, 143, 146, 149, 152, 155, 158, 162, 165, 167, 170, 173,
+    176, 179, 182, 185, 188, 190, 193, 196, 198, 201, 203, 206, 208, 211, 213, 215,
+    218, 220, 222, 224, 226, 228, 230, 232, 234, 235, 237, 238, 240, 241, 243, 244,
+    245, 246, 248, 249, 250, 250, 251, 252, 253, 253, 254, 254, 254, 255, 255, 255,
+    255, 255, 255, 255, 254, 254, 254, 253, 253, 252, 251, 250, 250, 249, 248, 246,
+    245, 244, 243, 241, 240, 238, 237, 235, 234, 232, 230, 228, 226, 224, 222, 220,
+    218, 215, 213, 211, 208, 206, 203, 201, 198, 196, 193, 190, 188, 185, 182, 179,
+    176, 173, 170, 167, 165, 162, 158, 155, 152, 149, 146, 143, 140, 137, 134, 131,
+    128, 124, 121, 118, 115, 112, 109, 106, 103, 100, 97, 93, 90, 88, 85, 82,
+    79, 76, 73, 70, 67, 65, 62, 59, 57, 54, 52, 49, 47, 44, 42, 40,
+    37, 35, 33, 31, 29, 27, 25, 23, 21, 20, 18, 17, 15, 14, 12, 11,
+    10, 9, 7, 6, 5, 5, 4, 3, 2, 2, 1, 1, 1, 0, 0, 0,
+    0, 0, 0, 0, 1, 1, 1, 2, 2, 3, 4, 5, 5, 6, 7, 9,
+    10, 11, 12, 14, 15, 17, 18, 20, 21, 23, 25, 27, 29, 31, 33, 35,
+    37, 40, 42, 44, 47, 49, 52, 54, 57, 59, 62, 65, 67, 70, 73, 76,
+    79, 82, 85, 88, 90, 93, 97, 100, 103, 106, 109, 112, 115, 118, 121, 124,
+];
+
+/// One period of a triangle wave sampled at 256 phases, scaled to `0..=255`.
+///
+/// Starts at 0, peaks at phase 128, and falls back towards 0.
+pub const TRIANGLE8: [u8; 256] = {
+    let mut table = [0; 256];
+    let mut phase = 0;
+    while phase < 256 {
+        table[phase] = triangle8(phase as u8);
+        phase += 1;
+    }
+    table
+};
+
+/// Returns the sine wave value at `phase` (see [`SINE8`]).
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::lut::sine8;
+///
+/// assert_eq!(sine8(64), 255);
+/// assert_eq!(sine8(192), 0);
+/// ```
+pub const fn sine8(phase: u8) -> u8 {
+    SINE8[phase as usize]
+}
+
+/// Returns the triangle wave value at `phase` (see [`TRIANGLE8`]).
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::lut::triangle8;
+///
+/// assert_eq!(triangle8(0), 0);
+/// assert_eq!(triangle8(64), 128);
+/// assert_eq!(triangle8(128), 255);
+/// ```
+pub const fn triangle8(phase: u8) -> u8 {
+    if phase < 128 {
+        phase * 2
+    } else if phase == 128 {
+        255
+    } else {
+        (255 - phase) * 2 + 1
+    }
+}
+
+/// Converts a position within a repeating period into a wave phase.
+///
+/// Useful for driving [`sine8`] or [`triangle8`] from an effect's elapsed time.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::lut::phase_of;
+///
+/// assert_eq!(phase_of(0, 1000), 0);
+/// assert_eq!(phase_of(500, 1000), 128);
+/// assert_eq!(phase_of(1250, 1000), 64);
+/// ```
+pub const fn phase_of(elapsed_ms: u32, period_ms: u32) -> u8 {
+    if period_ms == 0 {
+        return 0;
+    }
+    ((elapsed_ms % period_ms) as u64 * 256 / period_ms as u64) as u8
+}
+
+/// Applies [`GAMMA8`] to each channel of a color.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::lut::gamma_correct;
+///
+/// assert_eq!(gamma_correct((255, 0, 128)), (255, 0, 37));
+/// ```
+pub const fn gamma_correct(color: Rgb) -> Rgb {
+    (
+        GAMMA8[color.0 as usize],
+        GAMMA8[color.1 as usize],
+        GAMMA8[color.2 as usize],
+    )
+}
+
+/// Applies [`GAMMA8`] like [`gamma_correct`], but keeps every lit channel at
+/// level 1 or above.
+///
+/// The table maps levels up to 27 to 0, so a clock dimmed for the night
+/// would go dark; this keeps its hands faintly visible instead.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::lut::gamma_correct_lit;
+///
+/// assert_eq!(gamma_correct_lit((10, 0, 128)), (1, 0, 37));
+/// ```
+pub const fn gamma_correct_lit(color: Rgb) -> Rgb {
+    const fn channel(level: u8) -> u8 {
+        match GAMMA8[level as usize] {
+            0 if level > 0 => 1,
+            corrected => corrected,
+        }
+    }
+    (channel(color.0), channel(color.1), channel(color.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gamma_endpoints() {
+        assert_eq!(GAMMA8[0], 0);
+        assert_eq!(GAMMA8[255], 255);
+    }
+
+    #[test]
+    fn test_gamma_is_monotonic() {
+        for i in 1..256 {
+            assert!(GAMMA8[i] >= GAMMA8[i - 1], "GAMMA8 decreases at {}", i);
+        }
+    }
+
+    #[test]
+    fn test_gamma_darkens_midtones() {
+        assert!(GAMMA8[128] < 128);
+    }
+
+    #[test]
+    fn test_hue_wheel_starts_red() {
+        assert_eq!(HUE_WHEEL[0], (255, 0, 0));
+    }
+
+    #[test]
+    fn test_hue_wheel_entries_are_distinct() {
+        for (i, a) in HUE_WHEEL.iter().enumerate() {
+            for (j, b) in HUE_WHEEL.iter().enumerate().skip(i + 1) {
+                assert_ne!(a, b, "hues {} and {} collide", i, j);
+            }
+        }
+    }
+
+    #[test]
+    fn test_sine_extremes() {
+        assert_eq!(SINE8[64], 255);
+        assert_eq!(SINE8[192], 0);
+        assert_eq!(SINE8[0], 128);
+        assert_eq!(*SINE8.iter().max().unwrap(), 255);
+        assert_eq!(*SINE8.iter().min().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_sine_is_symmetric_around_peak() {
+        for offset in 1..64u8 {
+            let rising = SINE8[64 - offset as usize] as i16;
+            let falling = SINE8[64 + offset as usize] as i16;
+            assert!(
+                (rising - falling).abs() <= 1,
+                "asymmetry at offset {}",
+                offset
+            );
+        }
+    }
+
+    #[test]
+    fn test_triangle_shape() {
+        assert_eq!(TRIANGLE8[0], 0);
+        assert_eq!(TRIANGLE8[128], 255);
+        assert_eq!(TRIANGLE8[255], 1);
+        for phase in 1..=128 {
+            assert!(
+                TRIANGLE8[phase] > TRIANGLE8[phase - 1],
+                "not rising at {}",
+                phase
+            );
+        }
+        for phase in 129..256 {
+            assert!(
+                TRIANGLE8[phase] < TRIANGLE8[phase - 1],
+                "not falling at {}",
+                phase
+            );
+        }
+    }
+
+    #[test]
+    fn test_phase_of_wraps_period() {
+        assert_eq!(phase_of(0, 1000), 0);
+        assert_eq!(phase_of(999, 1000), 255);
+        assert_eq!(phase_of(1000, 1000), 0);
+        assert_eq!(phase_of(123, 0), 0);
+    }
+
+    #[test]
+    fn test_gamma_correct_lit_keeps_dim_channels() {
+        assert_eq!(gamma_correct_lit((0, 0, 0)), (0, 0, 0));
+        assert_eq!(gamma_correct_lit((1, 27, 28)), (1, 1, 1));
+        assert_eq!(
+            gamma_correct_lit((255, 200, 29)),
+            gamma_correct((255, 200, 29))
+        );
+    }
+
+    #[test]
+    fn test_gamma_correct_usable_in_const() {
+        const CORRECTED: Rgb = gamma_correct((128, 128, 128));
+        assert_eq!(CORRECTED, (GAMMA8[128], GAMMA8[128], GAMMA8[128]));
+    }
+}
@@ -0,0 +1,55 @@
+//! The name the clock is found by on the local network.
+
+use std::string::String;
+
+/// Longest DNS label, and so the longest host name in `.local`.
+const MAX_LABEL: usize = 63;
+
+/// Returns the mDNS host name of the clock `client_id`, so it is reachable
+/// as `<name>.local`.
+///
+/// The name is `rgbclock-` followed by the client ID in lowercase, with
+/// anything but letters and digits turned into single dashes, cut to the 63
+/// characters of a DNS label.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::mdns_hostname;
+///
+/// assert_eq!(mdns_hostname("kitchen"), "rgbclock-kitchen");
+/// assert_eq!(mdns_hostname("Living Room #2"), "rgbclock-living-room-2");
+/// ```
+pub fn mdns_hostname(client_id: &str) -> String {
+    let mut name = String::from("rgbclock-");
+    for c in client_id.chars() {
+        if c.is_ascii_alphanumeric() {
+            name.push(c.to_ascii_lowercase());
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    name.truncate(MAX_LABEL);
+    name.truncate(name.trim_end_matches('-').len());
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_hostname() {
+        assert_eq!(mdns_hostname("rgb-clock"), "rgbclock-rgb-clock");
+        assert_eq!(mdns_hostname("--hall__clock--"), "rgbclock-hall-clock");
+        assert_eq!(mdns_hostname("küche"), "rgbclock-k-che");
+        for empty in ["", "-", "ü"] {
+            assert_eq!(mdns_hostname(empty), "rgbclock", "{empty}");
+        }
+        let long = mdns_hostname(&"a".repeat(100));
+        assert_eq!(long.len(), MAX_LABEL);
+        let cut = mdns_hostname(&format!("{}-b", "a".repeat(53)));
+        assert_eq!(cut, format!("rgbclock-{}", "a".repeat(53)));
+    }
+}
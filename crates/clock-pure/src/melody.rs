@@ -0,0 +1,271 @@
+//! Short melodies for a piezo buzzer, written as a line of notes.
+
+use crate::settings::SettingsError;
+
+/// Most notes a melody may have.
+pub const MAX_MELODY_NOTES: usize = 32;
+
+/// Length of a note written without one.
+pub const DEFAULT_NOTE_MS: u16 = 200;
+
+/// Longest note, in milliseconds.
+const MAX_NOTE_MS: u16 = 10_000;
+
+/// Frequencies of the twelve notes from C8 to B8 in Hz; lower octaves halve them.
+const OCTAVE_8_HZ: [u16; 12] = [
+    4186, 4435, 4699, 4978, 5274, 5588, 5920, 6272, 6645, 7040, 7459, 7902,
+];
+
+/// A tone, or a rest, of a melody.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// Frequency of the tone in Hz, 0 for a rest
+    pub frequency_hz: u16,
+    pub duration_ms: u16,
+}
+
+impl Note {
+    /// A rest of `duration_ms`.
+    pub const fn rest(duration_ms: u16) -> Self {
+        Self {
+            frequency_hz: 0,
+            duration_ms,
+        }
+    }
+
+    /// Returns true if the note is a rest.
+    pub const fn is_rest(&self) -> bool {
+        self.frequency_hz == 0
+    }
+}
+
+/// A melody of up to [`MAX_MELODY_NOTES`] notes.
+///
+/// Melodies are written as notes separated by spaces or commas. A note is
+/// a letter from `a` to `g`, optionally `#` (sharp) or `b` (flat), and an
+/// octave from 0 to 8, so `a4` is the 440 Hz concert pitch; `-` is a rest.
+/// A length in milliseconds may follow after a colon, otherwise the note
+/// lasts [`DEFAULT_NOTE_MS`].
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Melody, Note};
+///
+/// let melody: Melody = "a4:100 - c#5:300".parse().unwrap();
+/// assert_eq!(melody.notes()[0], Note { frequency_hz: 440, duration_ms: 100 });
+/// assert!(melody.notes()[1].is_rest());
+/// assert_eq!(melody.notes()[2].frequency_hz, 554);
+/// assert_eq!(melody.duration_ms(), 600);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Melody {
+    notes: [Note; MAX_MELODY_NOTES],
+    len: usize,
+}
+
+impl Melody {
+    /// Three short beeps and a pause, played over and over while an alarm rings.
+    pub const ALARM: Self = Self::from_notes(&[
+        Note {
+            frequency_hz: 1760,
+            duration_ms: 100,
+        },
+        Note::rest(80),
+        Note {
+            frequency_hz: 1760,
+            duration_ms: 100,
+        },
+        Note::rest(80),
+        Note {
+            frequency_hz: 1760,
+            duration_ms: 100,
+        },
+        Note::rest(500),
+    ]);
+
+    /// A falling two-tone chime for the full hour.
+    pub const CHIME: Self = Self::from_notes(&[
+        Note {
+            frequency_hz: 659,
+            duration_ms: 300,
+        },
+        Note {
+            frequency_hz: 523,
+            duration_ms: 600,
+        },
+    ]);
+
+    /// Creates a melody of the first [`MAX_MELODY_NOTES`] of `notes`.
+    pub const fn from_notes(notes: &[Note]) -> Self {
+        let mut melody = Self {
+            notes: [Note::rest(0); MAX_MELODY_NOTES],
+            len: 0,
+        };
+        while melody.len < notes.len() && melody.len < MAX_MELODY_NOTES {
+            melody.notes[melody.len] = notes[melody.len];
+            melody.len += 1;
+        }
+        melody
+    }
+
+    /// Returns the notes in the order they play.
+    pub fn notes(&self) -> &[Note] {
+        &self.notes[..self.len]
+    }
+
+    /// Returns how long the melody plays, in milliseconds.
+    pub fn duration_ms(&self) -> u32 {
+        self.notes()
+            .iter()
+            .map(|note| u32::from(note.duration_ms))
+            .sum()
+    }
+}
+
+impl core::str::FromStr for Melody {
+    type Err = SettingsError;
+
+    /// Parses notes such as `c5:150 e5 g5:400`, see [`Melody`].
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut melody = Self::from_notes(&[]);
+        for token in text
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            if melody.len == MAX_MELODY_NOTES {
+                return Err(SettingsError::OutOfRange {
+                    field: "notes",
+                    value: MAX_MELODY_NOTES as u32 + 1,
+                });
+            }
+            melody.notes[melody.len] = parse_note(token)?;
+            melody.len += 1;
+        }
+        if melody.len == 0 {
+            return Err(SettingsError::InvalidValue);
+        }
+        Ok(melody)
+    }
+}
+
+/// Parses one note, e.g. `c#5:150` or `-`.
+fn parse_note(token: &str) -> Result<Note, SettingsError> {
+    let (pitch, duration_ms) = match token.split_once(':') {
+        Some((pitch, duration)) => {
+            let duration: u16 = duration.parse().map_err(|_| SettingsError::InvalidValue)?;
+            if duration == 0 || duration > MAX_NOTE_MS {
+                return Err(SettingsError::OutOfRange {
+                    field: "duration",
+                    value: duration.into(),
+                });
+            }
+            (pitch, duration)
+        }
+        None => (token, DEFAULT_NOTE_MS),
+    };
+    if pitch == "-" {
+        return Ok(Note::rest(duration_ms));
+    }
+
+    let mut chars = pitch.chars();
+    let semitone: i32 = match chars.next().map(|c| c.to_ascii_lowercase()) {
+        Some('c') => 0,
+        Some('d') => 2,
+        Some('e') => 4,
+        Some('f') => 5,
+        Some('g') => 7,
+        Some('a') => 9,
+        Some('b') => 11,
+        _ => return Err(SettingsError::InvalidValue),
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = match rest.as_bytes().first() {
+        Some(b'#') => (1, &rest[1..]),
+        Some(b'b') => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let octave: i32 = match octave.as_bytes() {
+        [digit @ b'0'..=b'8'] => i32::from(digit - b'0'),
+        _ => return Err(SettingsError::InvalidValue),
+    };
+    // Sharps and flats may cross into the next or previous octave, e.g. b#4 or cb5
+    let key = octave * 12 + semitone + accidental;
+    if !(0..9 * 12).contains(&key) {
+        return Err(SettingsError::InvalidValue);
+    }
+    let shift = 8 - key / 12;
+    let hz = u32::from(OCTAVE_8_HZ[(key % 12) as usize]);
+    let frequency_hz = (hz + (1 << shift >> 1)) >> shift;
+    Ok(Note {
+        frequency_hz: frequency_hz as u16,
+        duration_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    fn frequency(note: &str) -> Result<u16, SettingsError> {
+        parse_note(note).map(|note| note.frequency_hz)
+    }
+
+    #[test]
+    fn test_pitches() {
+        assert_eq!(frequency("a4"), Ok(440));
+        assert_eq!(frequency("A4"), Ok(440));
+        assert_eq!(frequency("c4"), Ok(262));
+        assert_eq!(frequency("c8"), Ok(4186));
+        assert_eq!(frequency("c0"), Ok(16));
+        assert_eq!(frequency("bb4"), frequency("a#4"));
+        assert_eq!(frequency("b#4"), frequency("c5"));
+        assert_eq!(frequency("cb5"), frequency("b4"));
+        for invalid in ["h4", "a9", "a", "cb0", "b#8", "a#", "a44", ""] {
+            assert_eq!(
+                frequency(invalid),
+                Err(SettingsError::InvalidValue),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_durations() {
+        assert_eq!(parse_note("-:50"), Ok(Note::rest(50)));
+        assert_eq!(parse_note("e5").map(|n| n.duration_ms), Ok(DEFAULT_NOTE_MS));
+        assert_eq!(
+            parse_note("e5:0"),
+            Err(SettingsError::OutOfRange {
+                field: "duration",
+                value: 0
+            })
+        );
+        assert_eq!(parse_note("e5:x"), Err(SettingsError::InvalidValue));
+    }
+
+    #[test]
+    fn test_melody_length() {
+        let melody: Melody = " c5, e5,,g5 ".parse().unwrap();
+        assert_eq!(melody.notes().len(), 3);
+        assert_eq!("".parse::<Melody>(), Err(SettingsError::InvalidValue));
+
+        let full = ["c5"; MAX_MELODY_NOTES].join(" ");
+        assert!(full.parse::<Melody>().is_ok());
+        assert_eq!(
+            format!("{} c5", full).parse::<Melody>(),
+            Err(SettingsError::OutOfRange {
+                field: "notes",
+                value: MAX_MELODY_NOTES as u32 + 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_built_in_melodies() {
+        assert_eq!(Melody::ALARM.duration_ms(), 960);
+        assert_eq!(Melody::CHIME.notes()[1], parse_note("c5:600").unwrap());
+        assert_eq!(Melody::CHIME.notes()[0], parse_note("e5:300").unwrap());
+    }
+}
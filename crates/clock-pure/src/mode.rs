@@ -0,0 +1,443 @@
+//! What the clock face shows: the time, a countdown, progress, or ambient light.
+
+use crate::settings::SettingsError;
+use crate::{fade_color, Rgb};
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// Default color of the ambient light.
+const DEFAULT_AMBIENT_COLOR: Rgb = (255, 160, 64);
+
+/// What the ring displays.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::DisplayMode;
+///
+/// let mode = DisplayMode::parse(br#"{"mode":"countdown","seconds":300}"#).unwrap();
+/// assert_eq!(mode, DisplayMode::Countdown { duration_ms: 300_000 });
+/// assert_eq!(DisplayMode::parse(b"off"), Ok(DisplayMode::Off));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisplayMode {
+    /// The clock hands
+    Clock,
+    /// A ring that empties over `duration_ms`
+    Countdown { duration_ms: u32 },
+    /// A ring filled to `percent`
+    Progress { percent: u8 },
+    /// All LEDs in one color
+    Ambient { color: Rgb },
+    /// All LEDs dark
+    Off,
+    /// The built-in effects
+    Demo,
+    /// The Pomodoro timer
+    Pomodoro,
+    /// The stopwatch
+    Stopwatch,
+    /// The latest weather report
+    Weather,
+    /// The latest temperature reading on a gauge
+    Temperature,
+}
+
+impl DisplayMode {
+    /// Size of the binary form used by [`DisplayMode::to_bytes`].
+    pub const BYTES: usize = 5;
+
+    /// Modes a button cycles through with [`DisplayMode::next`].
+    pub const CYCLE: [DisplayMode; 4] = [
+        DisplayMode::Clock,
+        DisplayMode::Ambient {
+            color: DEFAULT_AMBIENT_COLOR,
+        },
+        DisplayMode::Demo,
+        DisplayMode::Off,
+    ];
+
+    /// Returns a short lowercase name for logging.
+    pub const fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Clock => "clock",
+            DisplayMode::Countdown { .. } => "countdown",
+            DisplayMode::Progress { .. } => "progress",
+            DisplayMode::Ambient { .. } => "ambient",
+            DisplayMode::Off => "off",
+            DisplayMode::Demo => "demo",
+            DisplayMode::Pomodoro => "pomodoro",
+            DisplayMode::Stopwatch => "stopwatch",
+            DisplayMode::Weather => "weather",
+            DisplayMode::Temperature => "temperature",
+        }
+    }
+
+    /// Returns the mode after this one in [`DisplayMode::CYCLE`].
+    ///
+    /// Modes outside the cycle, such as a countdown, return to the clock.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::DisplayMode;
+    ///
+    /// assert_eq!(DisplayMode::Demo.next(), DisplayMode::Off);
+    /// assert_eq!(DisplayMode::Off.next(), DisplayMode::Clock);
+    /// ```
+    pub fn next(&self) -> DisplayMode {
+        let position = Self::CYCLE.iter().position(|m| m.name() == self.name());
+        match position {
+            Some(i) => Self::CYCLE[(i + 1) % Self::CYCLE.len()],
+            None => DisplayMode::Clock,
+        }
+    }
+
+    /// Encodes the mode for persistent storage: a tag byte followed by the
+    /// mode's parameters, zero-padded.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::DisplayMode;
+    ///
+    /// let mode = DisplayMode::Countdown { duration_ms: 90_000 };
+    /// assert_eq!(DisplayMode::from_bytes(&mode.to_bytes()), Ok(mode));
+    /// ```
+    pub const fn to_bytes(&self) -> [u8; Self::BYTES] {
+        match *self {
+            DisplayMode::Clock => [0; Self::BYTES],
+            DisplayMode::Countdown { duration_ms } => {
+                let [a, b, c, d] = duration_ms.to_le_bytes();
+                [1, a, b, c, d]
+            }
+            DisplayMode::Progress { percent } => [2, percent, 0, 0, 0],
+            DisplayMode::Ambient { color: (r, g, b) } => [3, r, g, b, 0],
+            DisplayMode::Off => [4, 0, 0, 0, 0],
+            DisplayMode::Demo => [5, 0, 0, 0, 0],
+            DisplayMode::Pomodoro => [6, 0, 0, 0, 0],
+            DisplayMode::Stopwatch => [7, 0, 0, 0, 0],
+            DisplayMode::Weather => [8, 0, 0, 0, 0],
+            DisplayMode::Temperature => [9, 0, 0, 0, 0],
+        }
+    }
+
+    /// Decodes a mode stored with [`DisplayMode::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        let &[tag, a, b, c, d] = bytes else {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        };
+        match tag {
+            0 => Ok(DisplayMode::Clock),
+            1 => Ok(DisplayMode::Countdown {
+                duration_ms: u32::from_le_bytes([a, b, c, d]),
+            }),
+            2 if a <= 100 => Ok(DisplayMode::Progress { percent: a }),
+            3 => Ok(DisplayMode::Ambient { color: (a, b, c) }),
+            4 => Ok(DisplayMode::Off),
+            5 => Ok(DisplayMode::Demo),
+            6 => Ok(DisplayMode::Pomodoro),
+            7 => Ok(DisplayMode::Stopwatch),
+            8 => Ok(DisplayMode::Weather),
+            9 => Ok(DisplayMode::Temperature),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+
+    /// Parses a mode command received over MQTT.
+    ///
+    /// The payload is a mode name (`clock`, `ambient`, `off`, `demo`,
+    /// `pomodoro`, `stopwatch`, `weather`, `temperature`), or a
+    /// JSON object naming the `mode` with its parameters:
+    ///
+    /// - `{"mode":"countdown","seconds":300}`
+    /// - `{"mode":"progress","percent":40}`
+    /// - `{"mode":"ambient","color":"#ff8000"}` (the color is optional)
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.starts_with('{') {
+            return parse_json(payload);
+        }
+        match text {
+            t if t.eq_ignore_ascii_case("clock") => Ok(DisplayMode::Clock),
+            t if t.eq_ignore_ascii_case("ambient") => Ok(DisplayMode::Ambient {
+                color: DEFAULT_AMBIENT_COLOR,
+            }),
+            t if t.eq_ignore_ascii_case("off") => Ok(DisplayMode::Off),
+            t if t.eq_ignore_ascii_case("demo") => Ok(DisplayMode::Demo),
+            t if t.eq_ignore_ascii_case("pomodoro") => Ok(DisplayMode::Pomodoro),
+            t if t.eq_ignore_ascii_case("stopwatch") => Ok(DisplayMode::Stopwatch),
+            t if t.eq_ignore_ascii_case("weather") => Ok(DisplayMode::Weather),
+            t if t.eq_ignore_ascii_case("temperature") => Ok(DisplayMode::Temperature),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_json(payload: &[u8]) -> Result<DisplayMode, SettingsError> {
+    #[derive(serde::Deserialize)]
+    #[serde(tag = "mode", rename_all = "lowercase", deny_unknown_fields)]
+    enum Command {
+        Clock,
+        Countdown { seconds: u32 },
+        Progress { percent: u32 },
+        Ambient { color: Option<String> },
+        Off,
+        Demo,
+        Pomodoro,
+        Stopwatch,
+        Weather,
+        Temperature,
+    }
+
+    let command: Command =
+        serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+    Ok(match command {
+        Command::Clock => DisplayMode::Clock,
+        Command::Countdown { seconds } => DisplayMode::Countdown {
+            duration_ms: seconds.checked_mul(1000).ok_or(SettingsError::OutOfRange {
+                field: "seconds",
+                value: seconds,
+            })?,
+        },
+        Command::Progress { percent } => DisplayMode::Progress {
+            percent: u8::try_from(percent).ok().filter(|&p| p <= 100).ok_or(
+                SettingsError::OutOfRange {
+                    field: "percent",
+                    value: percent,
+                },
+            )?,
+        },
+        Command::Ambient { color } => DisplayMode::Ambient {
+            color: match color {
+                Some(text) => crate::parse_color(&text)?,
+                None => DEFAULT_AMBIENT_COLOR,
+            },
+        },
+        Command::Off => DisplayMode::Off,
+        Command::Demo => DisplayMode::Demo,
+        Command::Pomodoro => DisplayMode::Pomodoro,
+        Command::Stopwatch => DisplayMode::Stopwatch,
+        Command::Weather => DisplayMode::Weather,
+        Command::Temperature => DisplayMode::Temperature,
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn parse_json(_payload: &[u8]) -> Result<DisplayMode, SettingsError> {
+    Err(SettingsError::InvalidJson)
+}
+
+/// Fills the ring clockwise from 12 o'clock to show `done` out of `total`.
+///
+/// Each LED stands for an equal share; the LED at the edge of the filled part
+/// is dimmed in proportion to how much of its share is reached. Every pixel
+/// is overwritten.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::render_progress;
+///
+/// let mut buffer = [(0, 0, 0); 12];
+/// render_progress(1, 4, (0, 255, 0), &mut buffer);
+/// assert_eq!(buffer[11], (0, 255, 0)); // 12 o'clock
+/// assert_eq!(buffer[1], (0, 255, 0)); // 2 o'clock
+/// assert_eq!(buffer[2], (0, 0, 0)); // 3 o'clock stays dark
+/// ```
+pub fn render_progress(done: u32, total: u32, color: Rgb, buffer: &mut [Rgb]) {
+    buffer.fill((0, 0, 0));
+    let len = buffer.len() as u64;
+    if len == 0 || total == 0 {
+        return;
+    }
+
+    // Progress in 1/256 steps of one LED
+    let steps = u64::from(done.min(total)) * len * 256 / u64::from(total);
+    for position in 0..len {
+        let level = steps.saturating_sub(position * 256).min(255) as u8;
+        if level > 0 {
+            // Position 0 is 12 o'clock, the last LED on the ring.
+            let idx = ((position + len - 1) % len) as usize;
+            buffer[idx] = fade_color(color, level);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(buffer: &[Rgb]) -> usize {
+        buffer.iter().filter(|&&p| p != (0, 0, 0)).count()
+    }
+
+    // ===== DisplayMode tests =====
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let modes = [
+            DisplayMode::Clock,
+            DisplayMode::Countdown {
+                duration_ms: u32::MAX,
+            },
+            DisplayMode::Progress { percent: 100 },
+            DisplayMode::Ambient { color: (1, 2, 3) },
+            DisplayMode::Off,
+            DisplayMode::Demo,
+            DisplayMode::Pomodoro,
+            DisplayMode::Stopwatch,
+            DisplayMode::Weather,
+            DisplayMode::Temperature,
+        ];
+        for mode in modes {
+            assert_eq!(DisplayMode::from_bytes(&mode.to_bytes()), Ok(mode));
+        }
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        assert_eq!(
+            DisplayMode::from_bytes(&[0; 4]),
+            Err(SettingsError::InvalidLength(4))
+        );
+        assert_eq!(
+            DisplayMode::from_bytes(&[2, 101, 0, 0, 0]),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            DisplayMode::from_bytes(&[10, 0, 0, 0, 0]),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_parse_names() {
+        assert_eq!(DisplayMode::parse(b"clock\n"), Ok(DisplayMode::Clock));
+        assert_eq!(DisplayMode::parse(b"DEMO"), Ok(DisplayMode::Demo));
+        assert_eq!(DisplayMode::parse(b"pomodoro"), Ok(DisplayMode::Pomodoro));
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"stopwatch"}"#),
+            Ok(DisplayMode::Stopwatch)
+        );
+        assert_eq!(DisplayMode::parse(b"weather"), Ok(DisplayMode::Weather));
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"temperature"}"#),
+            Ok(DisplayMode::Temperature)
+        );
+        assert_eq!(
+            DisplayMode::parse(b"ambient"),
+            Ok(DisplayMode::Ambient {
+                color: DEFAULT_AMBIENT_COLOR
+            })
+        );
+        assert_eq!(
+            DisplayMode::parse(b"countdown"),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_parse_json() {
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"progress","percent":100}"#),
+            Ok(DisplayMode::Progress { percent: 100 })
+        );
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"ambient","color":"blue"}"#),
+            Ok(DisplayMode::Ambient { color: (0, 0, 255) })
+        );
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"off"}"#),
+            Ok(DisplayMode::Off)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_parse_json_errors() {
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"progress","percent":101}"#),
+            Err(SettingsError::OutOfRange {
+                field: "percent",
+                value: 101
+            })
+        );
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"countdown","seconds":5000000}"#),
+            Err(SettingsError::OutOfRange {
+                field: "seconds",
+                value: 5_000_000
+            })
+        );
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"countdown"}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"disco"}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(
+            DisplayMode::parse(br#"{"mode":"ambient","color":"nope"}"#),
+            Err(SettingsError::UnknownColor)
+        );
+    }
+
+    #[test]
+    fn test_next_cycles() {
+        let mut mode = DisplayMode::Clock;
+        for expected in DisplayMode::CYCLE.iter().cycle().skip(1).take(8) {
+            mode = mode.next();
+            assert_eq!(mode, *expected);
+        }
+    }
+
+    #[test]
+    fn test_next_leaves_timed_modes() {
+        assert_eq!(
+            DisplayMode::Countdown { duration_ms: 1000 }.next(),
+            DisplayMode::Clock
+        );
+        assert_eq!(DisplayMode::Pomodoro.next(), DisplayMode::Clock);
+        assert_eq!(
+            DisplayMode::Ambient { color: (1, 2, 3) }.next(),
+            DisplayMode::Demo
+        );
+    }
+
+    // ===== Progress tests =====
+
+    #[test]
+    fn test_progress_empty_and_full() {
+        let mut buffer = [(9, 9, 9); 12];
+        render_progress(0, 10, (255, 0, 0), &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+        render_progress(10, 10, (255, 0, 0), &mut buffer);
+        assert!(buffer.iter().all(|&p| p == (255, 0, 0)));
+        render_progress(20, 10, (255, 0, 0), &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+    }
+
+    #[test]
+    fn test_progress_partial_led_is_dimmed() {
+        let mut buffer = [(0, 0, 0); 12];
+        // 1.5 LEDs
+        render_progress(1, 8, (0, 0, 200), &mut buffer);
+        assert_eq!(buffer[11], (0, 0, 200));
+        assert_eq!(buffer[0], (0, 0, 100));
+        assert_eq!(lit(&buffer), 2);
+    }
+
+    #[test]
+    fn test_progress_zero_total() {
+        let mut buffer = [(9, 9, 9); 12];
+        render_progress(0, 0, (255, 0, 0), &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+        render_progress(1, 1, (255, 0, 0), &mut []);
+    }
+}
@@ -0,0 +1,272 @@
+//! The WiFi network and MQTT broker the clock connects to.
+
+use crate::settings::SettingsError;
+use serde_json::json;
+use std::string::{String, ToString};
+
+/// Maximum length of a WiFi SSID in bytes.
+pub const MAX_SSID: usize = 32;
+
+/// Maximum length of a WiFi password in bytes; WPA2 needs at least 8.
+pub const MAX_PASSWORD: usize = 64;
+
+/// Maximum length of an MQTT host name in bytes.
+pub const MAX_HOST: usize = 64;
+
+/// The network settings, built from `.env` and changed from the web interface.
+///
+/// Changes take effect after a restart.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::NetworkSettings;
+///
+/// let network = NetworkSettings::new("home", "secret-password")
+///     .with_update(br#"{"mqtt_host":"192.168.1.5","mqtt_port":1883}"#)
+///     .unwrap();
+/// assert_eq!(network.mqtt_host.as_deref(), Some("192.168.1.5"));
+/// assert_eq!(network.mqtt_port, Some(1883));
+/// assert!(network.with_update(br#"{"wifi_pass":"short"}"#).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkSettings {
+    pub wifi_ssid: String,
+    /// Empty for an open network
+    pub wifi_pass: String,
+    pub mqtt_host: Option<String>,
+    pub mqtt_port: Option<u16>,
+}
+
+impl NetworkSettings {
+    /// Size of the binary form used by [`NetworkSettings::to_bytes`].
+    pub const BYTES: usize = 1 + MAX_SSID + 1 + MAX_PASSWORD + 1 + MAX_HOST + 2;
+
+    /// Creates the settings of the WiFi network `wifi_ssid`, without a broker.
+    pub fn new(wifi_ssid: &str, wifi_pass: &str) -> Self {
+        Self {
+            wifi_ssid: wifi_ssid.into(),
+            wifi_pass: wifi_pass.into(),
+            mqtt_host: None,
+            mqtt_port: None,
+        }
+    }
+
+    /// Encodes the settings for persistent storage: the SSID, password, and
+    /// host, each as its length followed by the zero-padded text, then the
+    /// port, with 0 for none.
+    ///
+    /// The settings must be valid, as checked by [`NetworkSettings::with_update`].
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        let (ssid, rest) = bytes.split_at_mut(1 + MAX_SSID);
+        let (pass, rest) = rest.split_at_mut(1 + MAX_PASSWORD);
+        let (host, port) = rest.split_at_mut(1 + MAX_HOST);
+        put_text(ssid, &self.wifi_ssid);
+        put_text(pass, &self.wifi_pass);
+        put_text(host, self.mqtt_host.as_deref().unwrap_or_default());
+        port.copy_from_slice(&self.mqtt_port.unwrap_or(0).to_le_bytes());
+        bytes
+    }
+
+    /// Decodes settings stored with [`NetworkSettings::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        if bytes.len() != Self::BYTES {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        }
+        let (ssid, rest) = bytes.split_at(1 + MAX_SSID);
+        let (pass, rest) = rest.split_at(1 + MAX_PASSWORD);
+        let (host, port) = rest.split_at(1 + MAX_HOST);
+        let host = take_text(host)?;
+        let port = u16::from_le_bytes([port[0], port[1]]);
+        let network = Self {
+            wifi_ssid: take_text(ssid)?,
+            wifi_pass: take_text(pass)?,
+            mqtt_host: (!host.is_empty()).then_some(host),
+            mqtt_port: (port != 0).then_some(port),
+        };
+        network.check()?;
+        Ok(network)
+    }
+
+    /// Returns the settings with an update from the web interface applied.
+    ///
+    /// The payload is a JSON object with any of `wifi_ssid`, `wifi_pass`,
+    /// `mqtt_host`, and `mqtt_port`; an empty `mqtt_host` removes the broker.
+    /// Nothing changes on error.
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            wifi_ssid: Option<String>,
+            wifi_pass: Option<String>,
+            mqtt_host: Option<String>,
+            mqtt_port: Option<u16>,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let host = update.mqtt_host.map(|host| host.trim().to_string());
+        let network = Self {
+            wifi_ssid: update.wifi_ssid.unwrap_or(self.wifi_ssid),
+            wifi_pass: update.wifi_pass.unwrap_or(self.wifi_pass),
+            mqtt_host: match host {
+                Some(host) => (!host.is_empty()).then_some(host),
+                None => self.mqtt_host,
+            },
+            mqtt_port: update.mqtt_port.or(self.mqtt_port),
+        };
+        network.check()?;
+        Ok(network)
+    }
+
+    /// Returns the settings as JSON, leaving out the password, e.g.
+    /// `{"mqtt_host":"192.168.1.5","mqtt_port":1883,"wifi_pass_set":true,"wifi_ssid":"home"}`.
+    pub fn to_json(&self) -> String {
+        json!({
+            "wifi_ssid": self.wifi_ssid,
+            "wifi_pass_set": !self.wifi_pass.is_empty(),
+            "mqtt_host": self.mqtt_host,
+            "mqtt_port": self.mqtt_port,
+        })
+        .to_string()
+    }
+
+    fn check(&self) -> Result<(), SettingsError> {
+        let length = |field, text: &str, range: core::ops::RangeInclusive<usize>| {
+            if range.contains(&text.len()) {
+                Ok(())
+            } else {
+                Err(SettingsError::OutOfRange {
+                    field,
+                    value: text.len() as u32,
+                })
+            }
+        };
+        length("wifi_ssid", &self.wifi_ssid, 1..=MAX_SSID)?;
+        if !self.wifi_pass.is_empty() {
+            length("wifi_pass", &self.wifi_pass, 8..=MAX_PASSWORD)?;
+        }
+        if let Some(host) = &self.mqtt_host {
+            length("mqtt_host", host, 1..=MAX_HOST)?;
+            if host.contains(char::is_whitespace) {
+                return Err(SettingsError::InvalidValue);
+            }
+        }
+        if self.mqtt_port == Some(0) {
+            return Err(SettingsError::OutOfRange {
+                field: "mqtt_port",
+                value: 0,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Writes `text`, which fits, as its length followed by its bytes.
+fn put_text(field: &mut [u8], text: &str) {
+    field[0] = text.len() as u8;
+    field[1..=text.len()].copy_from_slice(text.as_bytes());
+}
+
+/// Reads text written by [`put_text`].
+fn take_text(field: &[u8]) -> Result<String, SettingsError> {
+    let len = usize::from(field[0]);
+    let text = field
+        .get(1..=len)
+        .ok_or(SettingsError::InvalidLength(len))?;
+    core::str::from_utf8(text)
+        .map(Into::into)
+        .map_err(|_| SettingsError::InvalidUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    fn home() -> NetworkSettings {
+        NetworkSettings::new("home", "secret-password")
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let open = NetworkSettings::new("café", "");
+        let broker = NetworkSettings {
+            mqtt_host: Some("broker.example.com".into()),
+            mqtt_port: Some(8883),
+            ..home()
+        };
+        let longest = NetworkSettings {
+            wifi_ssid: "s".repeat(MAX_SSID),
+            wifi_pass: "p".repeat(MAX_PASSWORD),
+            mqtt_host: Some("h".repeat(MAX_HOST)),
+            mqtt_port: Some(u16::MAX),
+        };
+        for network in [open, broker, longest] {
+            assert_eq!(
+                NetworkSettings::from_bytes(&network.to_bytes()),
+                Ok(network)
+            );
+        }
+        assert_eq!(
+            NetworkSettings::from_bytes(&[0; 3]),
+            Err(SettingsError::InvalidLength(3))
+        );
+        // Never stored: an empty SSID
+        assert!(NetworkSettings::from_bytes(&[0; NetworkSettings::BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_update() {
+        let network = home()
+            .with_update(br#"{"mqtt_host":" broker ","mqtt_port":1883}"#)
+            .unwrap();
+        assert_eq!(network.mqtt_host.as_deref(), Some("broker"));
+        assert_eq!(network.wifi_pass, "secret-password");
+        let network = network
+            .with_update(br#"{"wifi_ssid":"attic","wifi_pass":"","mqtt_host":""}"#)
+            .unwrap();
+        assert_eq!(network.wifi_ssid, "attic");
+        assert_eq!(network.wifi_pass, "");
+        assert_eq!(network.mqtt_host, None);
+        assert_eq!(network.mqtt_port, Some(1883));
+    }
+
+    #[test]
+    fn test_invalid_updates() {
+        let long_ssid = format!(r#"{{"wifi_ssid":"{}"}}"#, "s".repeat(MAX_SSID + 1));
+        for (payload, field, value) in [
+            (r#"{"wifi_ssid":""}"#, "wifi_ssid", 0),
+            (long_ssid.as_str(), "wifi_ssid", 33),
+            (r#"{"wifi_pass":"1234567"}"#, "wifi_pass", 7),
+            (r#"{"mqtt_port":0}"#, "mqtt_port", 0),
+        ] {
+            assert_eq!(
+                home().with_update(payload.as_bytes()),
+                Err(SettingsError::OutOfRange { field, value }),
+                "{payload}"
+            );
+        }
+        assert_eq!(
+            home().with_update(br#"{"mqtt_host":"my broker"}"#),
+            Err(SettingsError::InvalidValue)
+        );
+        for invalid in [r#"{"mqtt_port":70000}"#, r#"{"client_id":"x"}"#, "{"] {
+            assert_eq!(
+                home().with_update(invalid.as_bytes()),
+                Err(SettingsError::InvalidJson),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_json_hides_password() {
+        let json: serde_json::Value = serde_json::from_str(&home().to_json()).unwrap();
+        assert_eq!(
+            json,
+            json!({"wifi_ssid": "home", "wifi_pass_set": true, "mqtt_host": null, "mqtt_port": null})
+        );
+    }
+}
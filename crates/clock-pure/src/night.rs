@@ -0,0 +1,333 @@
+//! Automatic dimming of the clock face at night.
+
+use crate::settings::SettingsError;
+use crate::time::LocalTime;
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// A daily time window from `start` (inclusive) to `end` (exclusive).
+///
+/// Windows whose end is before their start run across midnight, so
+/// `22:00-07:00` covers the night. A window with equal start and end is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub start: LocalTime,
+    pub end: LocalTime,
+}
+
+impl TimeWindow {
+    /// Creates a window from `start` to `end`.
+    pub const fn new(start: LocalTime, end: LocalTime) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns true if `time` falls within the window.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{LocalTime, TimeWindow};
+    ///
+    /// let night: TimeWindow = "22:00-07:00".parse().unwrap();
+    /// assert!(night.contains(LocalTime::new(23, 30, 0).unwrap()));
+    /// assert!(night.contains(LocalTime::new(6, 59, 59).unwrap()));
+    /// assert!(!night.contains(LocalTime::new(7, 0, 0).unwrap()));
+    /// ```
+    pub const fn contains(&self, time: LocalTime) -> bool {
+        let (start, end, now) = (
+            self.start.to_seconds(),
+            self.end.to_seconds(),
+            time.to_seconds(),
+        );
+        if start <= end {
+            start <= now && now < end
+        } else {
+            now >= start || now < end
+        }
+    }
+}
+
+impl core::str::FromStr for TimeWindow {
+    type Err = SettingsError;
+
+    /// Parses a window written as `HH:MM-HH:MM` (seconds are optional).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (start, end) = text
+            .trim()
+            .split_once('-')
+            .ok_or(SettingsError::InvalidValue)?;
+        let parse = |t: &str| t.parse().map_err(|_| SettingsError::InvalidValue);
+        Ok(Self::new(parse(start)?, parse(end)?))
+    }
+}
+
+/// Whether night mode follows its schedule or is forced on or off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NightSwitch {
+    /// Dim during the scheduled window
+    Auto,
+    /// Always dim
+    On,
+    /// Never dim
+    Off,
+}
+
+impl core::str::FromStr for NightSwitch {
+    type Err = SettingsError;
+
+    /// Parses `auto`, `on`, or `off` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("auto") => Ok(NightSwitch::Auto),
+            t if t.eq_ignore_ascii_case("on") => Ok(NightSwitch::On),
+            t if t.eq_ignore_ascii_case("off") => Ok(NightSwitch::Off),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// Dims the clock face to a lower brightness during a nightly time window.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{LocalTime, NightMode, NightSwitch};
+///
+/// let night = NightMode::new("22:00-07:00".parse().unwrap(), 2);
+/// let late = LocalTime::new(23, 0, 0).unwrap();
+/// assert_eq!(night.brightness_at(late, 40), 2);
+///
+/// let day = night.with_switch(NightSwitch::Off);
+/// assert_eq!(day.brightness_at(late, 40), 40);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NightMode {
+    pub window: TimeWindow,
+    /// Brightness level (0-255) while night mode is active
+    pub brightness: u8,
+    pub switch: NightSwitch,
+}
+
+impl NightMode {
+    /// Creates a night mode following `window`, dimming to `brightness`.
+    pub const fn new(window: TimeWindow, brightness: u8) -> Self {
+        Self {
+            window,
+            brightness,
+            switch: NightSwitch::Auto,
+        }
+    }
+
+    /// Sets whether the schedule applies or night mode is forced on or off.
+    pub const fn with_switch(mut self, switch: NightSwitch) -> Self {
+        self.switch = switch;
+        self
+    }
+
+    /// Returns true if the face should be dimmed at `time`.
+    pub const fn is_active(&self, time: LocalTime) -> bool {
+        match self.switch {
+            NightSwitch::Auto => self.window.contains(time),
+            NightSwitch::On => true,
+            NightSwitch::Off => false,
+        }
+    }
+
+    /// Returns the brightness to use at `time`, given the daytime brightness.
+    ///
+    /// Night mode only ever dims: if the daytime brightness is already lower,
+    /// it is kept.
+    pub fn brightness_at(&self, time: LocalTime, day_brightness: u8) -> u8 {
+        if self.is_active(time) {
+            day_brightness.min(self.brightness)
+        } else {
+            day_brightness
+        }
+    }
+
+    /// Applies a night mode command received over MQTT.
+    ///
+    /// The payload is `auto`, `on`, or `off`, or a JSON object with any of
+    /// `start`, `end` (`HH:MM`), `brightness`, and `mode` (`auto`, `on`,
+    /// `off`). Fields left out keep their current values. Nothing changes on
+    /// error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{NightMode, NightSwitch};
+    ///
+    /// let night = NightMode::new("22:00-07:00".parse().unwrap(), 2);
+    /// assert_eq!(night.with_update(b"on").unwrap().switch, NightSwitch::On);
+    ///
+    /// let later = night.with_update(br#"{"start":"23:30","brightness":1}"#).unwrap();
+    /// assert_eq!(later.window, "23:30-07:00".parse().unwrap());
+    /// assert_eq!(later.brightness, 1);
+    /// ```
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.starts_with('{') {
+            self.with_json_update(payload)
+        } else {
+            text.parse().map(|switch| self.with_switch(switch))
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn with_json_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            start: Option<String>,
+            end: Option<String>,
+            brightness: Option<u8>,
+            mode: Option<String>,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let time = |text: Option<String>, current: LocalTime| match text {
+            Some(text) => text.parse().map_err(|_| SettingsError::InvalidValue),
+            None => Ok(current),
+        };
+        Ok(Self {
+            window: TimeWindow::new(
+                time(update.start, self.window.start)?,
+                time(update.end, self.window.end)?,
+            ),
+            brightness: update.brightness.unwrap_or(self.brightness),
+            switch: match update.mode {
+                Some(mode) => mode.parse()?,
+                None => self.switch,
+            },
+        })
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn with_json_update(self, _payload: &[u8]) -> Result<Self, SettingsError> {
+        Err(SettingsError::InvalidJson)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u8, minute: u8) -> LocalTime {
+        LocalTime::new(hour, minute, 0).unwrap()
+    }
+
+    fn window(text: &str) -> TimeWindow {
+        text.parse().unwrap()
+    }
+
+    // ===== TimeWindow tests =====
+
+    #[test]
+    fn test_window_within_day() {
+        let w = window("13:00-14:30");
+        assert!(!w.contains(time(12, 59)));
+        assert!(w.contains(time(13, 0)));
+        assert!(w.contains(time(14, 29)));
+        assert!(!w.contains(time(14, 30)));
+    }
+
+    #[test]
+    fn test_window_across_midnight() {
+        let w = window("22:00-07:00");
+        assert!(w.contains(time(22, 0)));
+        assert!(w.contains(time(0, 0)));
+        assert!(!w.contains(time(7, 0)));
+        assert!(!w.contains(time(12, 0)));
+    }
+
+    #[test]
+    fn test_empty_window() {
+        let w = window("08:00-08:00");
+        assert!(!w.contains(time(8, 0)));
+        assert!(!w.contains(time(20, 0)));
+    }
+
+    #[test]
+    fn test_window_parse_errors() {
+        for text in ["", "22:00", "22:00-", "25:00-07:00", "22:00-07:00-08:00"] {
+            assert_eq!(
+                text.parse::<TimeWindow>(),
+                Err(SettingsError::InvalidValue),
+                "{text}"
+            );
+        }
+        assert_eq!(
+            window(" 22:15:30-06:45 "),
+            TimeWindow::new(LocalTime::new(22, 15, 30).unwrap(), time(6, 45))
+        );
+    }
+
+    // ===== NightMode tests =====
+
+    #[test]
+    fn test_switch_overrides_schedule() {
+        let night = NightMode::new(window("22:00-07:00"), 2);
+        assert!(night.is_active(time(23, 0)));
+        assert!(!night.is_active(time(12, 0)));
+        assert!(night.with_switch(NightSwitch::On).is_active(time(12, 0)));
+        assert!(!night.with_switch(NightSwitch::Off).is_active(time(23, 0)));
+    }
+
+    #[test]
+    fn test_night_never_brightens() {
+        let night = NightMode::new(window("22:00-07:00"), 20);
+        assert_eq!(night.brightness_at(time(23, 0), 40), 20);
+        assert_eq!(night.brightness_at(time(23, 0), 5), 5);
+        assert_eq!(night.brightness_at(time(12, 0), 40), 40);
+    }
+
+    #[test]
+    fn test_update_switch() {
+        let night = NightMode::new(window("22:00-07:00"), 2);
+        assert_eq!(
+            night.with_update(b" OFF\n").unwrap().switch,
+            NightSwitch::Off
+        );
+        assert_eq!(night.with_update(b"dim"), Err(SettingsError::InvalidValue));
+        assert_eq!(night.with_update(&[0xff]), Err(SettingsError::InvalidUtf8));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_json() {
+        let night = NightMode::new(window("22:00-07:00"), 2);
+        let updated = night
+            .with_update(br#"{"start":"21:00","end":"06:30","brightness":4,"mode":"on"}"#)
+            .unwrap();
+        assert_eq!(
+            updated,
+            NightMode::new(window("21:00-06:30"), 4).with_switch(NightSwitch::On)
+        );
+        assert_eq!(night.with_update(b"{}"), Ok(night));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_json_errors() {
+        let night = NightMode::new(window("22:00-07:00"), 2);
+        assert_eq!(
+            night.with_update(br#"{"start":"late"}"#),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            night.with_update(br#"{"mode":"sometimes"}"#),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            night.with_update(br#"{"brightness":300}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(
+            night.with_update(br#"{"window":"22:00-07:00"}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Brief notifications drawn over whatever the ring shows.
+
+use crate::lut::{phase_of, sine8};
+use crate::settings::SettingsError;
+use crate::{mix_colors, Rgb};
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// How long a notification lasts unless told otherwise.
+pub const DEFAULT_NOTIFY_MS: u32 = 3000;
+
+const FLASH_INTERVAL_MS: u32 = 250;
+const PULSE_PERIOD_MS: u32 = 1000;
+
+/// Strongest tint of the face, leaving the hands faintly visible beneath it.
+const MAX_MIX: u8 = 192;
+
+/// How a notification catches the eye.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NotifyPattern {
+    /// Blinks on and off
+    #[default]
+    Flash,
+    /// Swells and fades smoothly
+    Pulse,
+}
+
+impl core::str::FromStr for NotifyPattern {
+    type Err = SettingsError;
+
+    /// Parses `flash` or `pulse` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("flash") => Ok(NotifyPattern::Flash),
+            t if t.eq_ignore_ascii_case("pulse") => Ok(NotifyPattern::Pulse),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// A short alert, such as a doorbell or a failed build, tinting the ring
+/// for a while without replacing the time display.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Notification, NotifyPattern};
+///
+/// let doorbell = Notification::parse(br#"{"color":"blue","pattern":"pulse","seconds":5}"#).unwrap();
+/// assert_eq!(doorbell.pattern, NotifyPattern::Pulse);
+/// assert!(!doorbell.is_finished(4_999));
+/// assert!(doorbell.is_finished(5_000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Notification {
+    pub color: Rgb,
+    pub pattern: NotifyPattern,
+    pub duration_ms: u32,
+}
+
+impl Notification {
+    /// Creates a flashing notification in `color` lasting [`DEFAULT_NOTIFY_MS`].
+    pub const fn new(color: Rgb) -> Self {
+        Self {
+            color,
+            pattern: NotifyPattern::Flash,
+            duration_ms: DEFAULT_NOTIFY_MS,
+        }
+    }
+
+    /// Sets how the notification catches the eye.
+    pub const fn with_pattern(mut self, pattern: NotifyPattern) -> Self {
+        self.pattern = pattern;
+        self
+    }
+
+    /// Sets how long the notification lasts.
+    pub const fn with_duration_ms(mut self, duration_ms: u32) -> Self {
+        self.duration_ms = duration_ms;
+        self
+    }
+
+    /// Returns true once the notification has run for its duration.
+    pub const fn is_finished(&self, elapsed_ms: u32) -> bool {
+        elapsed_ms >= self.duration_ms
+    }
+
+    /// Returns how strongly the face is tinted `elapsed_ms` into the notification.
+    pub fn level(&self, elapsed_ms: u32) -> u8 {
+        if self.is_finished(elapsed_ms) {
+            return 0;
+        }
+        match self.pattern {
+            NotifyPattern::Flash if (elapsed_ms / FLASH_INTERVAL_MS) % 2 == 1 => 0,
+            NotifyPattern::Flash => MAX_MIX,
+            // Start dark, at the bottom of the wave
+            NotifyPattern::Pulse => {
+                let wave = sine8(phase_of(elapsed_ms, PULSE_PERIOD_MS).wrapping_sub(64));
+                (u16::from(wave) * u16::from(MAX_MIX) / 255) as u8
+            }
+        }
+    }
+
+    /// Tints the pixels in `buffer` with the notification color at `elapsed_ms`.
+    ///
+    /// The existing frame shows through, so the time stays readable.
+    pub fn overlay(&self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        let level = self.level(elapsed_ms);
+        if level == 0 {
+            return;
+        }
+        for pixel in buffer.iter_mut() {
+            *pixel = mix_colors(*pixel, self.color, level);
+        }
+    }
+
+    /// Parses a notification received over MQTT.
+    ///
+    /// The payload is a color (hex or name), or a JSON object with a `color`
+    /// and optionally a `pattern` (`flash` or `pulse`) and a duration in
+    /// `seconds`.
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.starts_with('{') {
+            return parse_json(payload);
+        }
+        crate::parse_color(text).map(Self::new)
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_json(payload: &[u8]) -> Result<Notification, SettingsError> {
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Command {
+        color: String,
+        pattern: Option<String>,
+        seconds: Option<u32>,
+    }
+
+    let command: Command =
+        serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+    let mut notification = Notification::new(crate::parse_color(&command.color)?);
+    if let Some(pattern) = command.pattern {
+        notification = notification.with_pattern(pattern.parse()?);
+    }
+    if let Some(seconds) = command.seconds {
+        let duration_ms =
+            seconds
+                .checked_mul(1000)
+                .filter(|&ms| ms > 0)
+                .ok_or(SettingsError::OutOfRange {
+                    field: "seconds",
+                    value: seconds,
+                })?;
+        notification = notification.with_duration_ms(duration_ms);
+    }
+    Ok(notification)
+}
+
+#[cfg(not(feature = "std"))]
+fn parse_json(_payload: &[u8]) -> Result<Notification, SettingsError> {
+    Err(SettingsError::InvalidJson)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: Rgb = (255, 0, 0);
+
+    #[test]
+    fn test_flash_blinks_until_finished() {
+        let notification = Notification::new(RED);
+        assert_eq!(notification.level(0), MAX_MIX);
+        assert_eq!(notification.level(FLASH_INTERVAL_MS), 0);
+        assert_eq!(notification.level(2 * FLASH_INTERVAL_MS), MAX_MIX);
+        assert_eq!(notification.level(DEFAULT_NOTIFY_MS), 0);
+    }
+
+    #[test]
+    fn test_pulse_swells_and_fades() {
+        let notification = Notification::new(RED).with_pattern(NotifyPattern::Pulse);
+        assert_eq!(notification.level(0), 0);
+        assert_eq!(notification.level(PULSE_PERIOD_MS / 2), MAX_MIX);
+        assert!(notification.level(PULSE_PERIOD_MS / 4) < MAX_MIX);
+        assert_eq!(notification.level(PULSE_PERIOD_MS), 0);
+    }
+
+    #[test]
+    fn test_overlay_keeps_face_visible() {
+        let notification = Notification::new(RED);
+        let mut buffer = [(0, 0, 0); 12];
+        buffer[3] = (0, 0, 255);
+        notification.overlay(0, &mut buffer);
+        assert_eq!(buffer[0], mix_colors((0, 0, 0), RED, MAX_MIX));
+        assert_ne!(buffer[3], buffer[0]);
+        assert!(buffer[3].2 > 0);
+
+        let mut untouched = [(0, 0, 255); 12];
+        notification.overlay(DEFAULT_NOTIFY_MS, &mut untouched);
+        assert_eq!(untouched, [(0, 0, 255); 12]);
+    }
+
+    #[test]
+    fn test_parse_color_only() {
+        assert_eq!(Notification::parse(b"red\n"), Ok(Notification::new(RED)));
+        assert_eq!(
+            Notification::parse(b"#00ff00"),
+            Ok(Notification::new((0, 255, 0)))
+        );
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let notification = Notification::parse(br#"{"color":"red","seconds":10}"#).unwrap();
+        assert_eq!(
+            notification,
+            Notification::new(RED).with_duration_ms(10_000)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Notification::parse(b"chartreuse"),
+            Err(SettingsError::UnknownColor)
+        );
+        assert_eq!(
+            Notification::parse(br#"{"color":"red","pattern":"strobe"}"#),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            Notification::parse(br#"{"pattern":"pulse"}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(
+            Notification::parse(br#"{"color":"red","seconds":0}"#),
+            Err(SettingsError::OutOfRange {
+                field: "seconds",
+                value: 0
+            })
+        );
+    }
+}
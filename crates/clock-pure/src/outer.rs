@@ -0,0 +1,418 @@
+//! An optional outer ring around the clock face, e.g. of 24 or 60 LEDs.
+
+use crate::settings::SettingsError;
+use crate::{parse_color, render_hands, render_sweep, HandColors, LocalTime, OverlapPolicy, Rgb};
+
+/// Most LEDs an outer ring may have.
+pub const OUTER_LED_COUNT: usize = 60;
+
+/// The pixel colors of an outer ring; a smaller ring uses the first LEDs.
+pub type OuterFrame = [Rgb; OUTER_LED_COUNT];
+
+/// Returns the LED of a minute or second (0-59) on a 60-LED ring.
+///
+/// Like the clock face, the ring starts one step past 12 o'clock: LED 0
+/// shows second 1 and LED 59 second 0, at 12 o'clock. The sweep of
+/// [`render_sweep`](crate::render_sweep) over the ring matches it.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::outer_index;
+///
+/// assert_eq!(outer_index(0), 59);
+/// assert_eq!(outer_index(1), 0);
+/// assert_eq!(outer_index(59), 58);
+/// ```
+pub const fn outer_index(value: u8) -> usize {
+    (value as usize + OUTER_LED_COUNT - 1) % OUTER_LED_COUNT
+}
+
+/// How a ring shows its second hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecondHand {
+    /// Left out, e.g. during the off phase of the stale blink
+    Hidden,
+    /// Stepping from LED to LED
+    Step,
+    /// Sweeping smoothly, the given milliseconds into the current minute
+    Sweep(u32),
+}
+
+/// Which hands a ring shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingHands {
+    pub hour: bool,
+    pub minute: bool,
+    pub second: bool,
+}
+
+/// The size, wiring, hands, and colors of an outer ring.
+///
+/// The ring is drawn like the clock face, one step past 12 o'clock first
+/// and clockwise, and then mapped to the strip: `start` is the strip index
+/// of that first LED, and a reversed ring is wired counterclockwise. Hands
+/// take the colors of the clock face, holiday themes included, unless the
+/// ring has colors of its own.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{HandColors, LocalTime, OverlapPolicy, RingLayout, SecondHand};
+///
+/// let ring: RingLayout = "24,hands=hm,minute=white".parse().unwrap();
+/// let mut pixels = [(0, 0, 0); 24];
+/// let time = LocalTime::new(3, 30, 0).unwrap();
+/// let colors = HandColors::default();
+/// ring.render(time, colors, SecondHand::Step, OverlapPolicy::Additive, 0, &mut pixels);
+/// assert_eq!(pixels[11], (255, 255, 255));
+/// assert_eq!(pixels[6], HandColors::default().hour);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RingLayout {
+    leds: u8,
+    start: u8,
+    reversed: bool,
+    pub hands: RingHands,
+    /// Colors of the ring's hands, `None` to follow the clock face
+    pub colors: Option<HandColors>,
+}
+
+impl Default for RingLayout {
+    /// A 60-LED ring showing minutes and seconds.
+    fn default() -> Self {
+        Self {
+            leds: OUTER_LED_COUNT as u8,
+            start: 0,
+            reversed: false,
+            hands: RingHands {
+                hour: false,
+                minute: true,
+                second: true,
+            },
+            colors: None,
+        }
+    }
+}
+
+impl RingLayout {
+    /// Creates a layout for a ring of `leds` LEDs (1-60) showing minutes and seconds.
+    pub fn new(leds: usize) -> Result<Self, SettingsError> {
+        if !(1..=OUTER_LED_COUNT).contains(&leds) {
+            return Err(SettingsError::OutOfRange {
+                field: "leds",
+                value: leds as u32,
+            });
+        }
+        Ok(Self {
+            leds: leds as u8,
+            ..Self::default()
+        })
+    }
+
+    /// Creates a layout for a ring of `leds` LEDs showing all three hands,
+    /// e.g. the clock face itself; `leds` must be 1-60.
+    pub(crate) const fn all_hands(leds: u8) -> Self {
+        Self {
+            leds,
+            start: 0,
+            reversed: false,
+            hands: RingHands {
+                hour: true,
+                minute: true,
+                second: true,
+            },
+            colors: None,
+        }
+    }
+
+    /// Sets the strip index of the LED one step past 12 o'clock.
+    pub fn with_start(mut self, start: usize) -> Result<Self, SettingsError> {
+        if start >= self.leds() {
+            return Err(SettingsError::OutOfRange {
+                field: "start",
+                value: start as u32,
+            });
+        }
+        self.start = start as u8;
+        Ok(self)
+    }
+
+    /// Sets whether the ring is wired counterclockwise.
+    pub const fn with_reversed(mut self, reversed: bool) -> Self {
+        self.reversed = reversed;
+        self
+    }
+
+    /// Returns the number of LEDs on the ring.
+    pub const fn leds(&self) -> usize {
+        self.leds as usize
+    }
+
+    /// Returns the strip index of the LED at `position` on the ring.
+    pub const fn strip_index(&self, position: usize) -> usize {
+        let leds = self.leds();
+        let offset = if self.reversed {
+            leds - position % leds
+        } else {
+            position % leds
+        };
+        (self.start as usize + offset) % leds
+    }
+
+    /// Draws the hands for `time` into `pixels`, in strip order.
+    ///
+    /// Hands on the same LED are combined by `policy`, as on the clock face.
+    /// Only the first [`RingLayout::leds`] pixels are drawn.
+    pub fn render(
+        &self,
+        time: LocalTime,
+        face_colors: HandColors,
+        second: SecondHand,
+        policy: OverlapPolicy,
+        elapsed_ms: u32,
+        pixels: &mut [Rgb],
+    ) {
+        let colors = self.colors.unwrap_or(face_colors);
+        let hour_minutes = u32::from(time.hour % 12) * 60 + u32::from(time.minute);
+        let candidates = [
+            (
+                self.hands.hour,
+                self.position(hour_minutes, 720),
+                colors.hour,
+            ),
+            (
+                self.hands.minute,
+                self.position(time.minute.into(), 60),
+                colors.minute,
+            ),
+            (
+                self.hands.second && second == SecondHand::Step,
+                self.position(time.second.into(), 60),
+                colors.second,
+            ),
+        ];
+        let mut hands = [(0, (0, 0, 0)); 3];
+        let mut count = 0;
+        for (shown, position, color) in candidates {
+            if shown {
+                hands[count] = (position, color);
+                count += 1;
+            }
+        }
+
+        let mut ring = [(0, 0, 0); OUTER_LED_COUNT];
+        let ring = &mut ring[..self.leds()];
+        render_hands(&hands[..count], policy, elapsed_ms, ring);
+        if let (true, SecondHand::Sweep(minute_ms)) = (self.hands.second, second) {
+            render_sweep(minute_ms % 60_000, 60_000, colors.second, ring);
+        }
+        for (position, &color) in ring.iter().enumerate() {
+            if let Some(pixel) = pixels.get_mut(self.strip_index(position)) {
+                *pixel = color;
+            }
+        }
+    }
+
+    /// Returns the ring's own colors, starting from the default colors.
+    fn own_colors(&mut self) -> &mut HandColors {
+        self.colors.get_or_insert_with(HandColors::default)
+    }
+
+    /// Returns the ring position of `value` out of `period`, one step past
+    /// 12 o'clock being position 0.
+    fn position(&self, value: u32, period: u32) -> usize {
+        let leds = self.leds();
+        (value as usize * leds / period as usize + leds - 1) % leds
+    }
+}
+
+impl core::str::FromStr for RingLayout {
+    type Err = SettingsError;
+
+    /// Parses a layout like `24,hands=hm,start=6,reverse,second=red`.
+    ///
+    /// The number of LEDs comes first, followed by any of `hands` (the
+    /// letters `h`, `m`, and `s`, default `ms`), `start` (the strip index
+    /// of the LED one step past 12 o'clock, default 0), `reverse` for a ring
+    /// wired counterclockwise, and `hour`, `minute`, and `second` colors,
+    /// which give the ring colors of its own. Unset colors are taken from
+    /// the clock face's default colors.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.split(',').map(str::trim);
+        let leds = parts
+            .next()
+            .and_then(|leds| leds.parse().ok())
+            .ok_or(SettingsError::InvalidValue)?;
+        let mut layout = Self::new(leds)?;
+        for part in parts {
+            if part.eq_ignore_ascii_case("reverse") {
+                layout.reversed = true;
+                continue;
+            }
+            let (key, value) = part.split_once('=').ok_or(SettingsError::InvalidValue)?;
+            match key.trim() {
+                "hands" => {
+                    let value = value.trim();
+                    if value.is_empty() || !value.chars().all(|c| "hms".contains(c)) {
+                        return Err(SettingsError::InvalidValue);
+                    }
+                    layout.hands = RingHands {
+                        hour: value.contains('h'),
+                        minute: value.contains('m'),
+                        second: value.contains('s'),
+                    };
+                }
+                "start" => {
+                    let start = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| SettingsError::InvalidValue)?;
+                    layout = layout.with_start(start)?;
+                }
+                "hour" => layout.own_colors().hour = parse_color(value)?,
+                "minute" => layout.own_colors().minute = parse_color(value)?,
+                "second" => layout.own_colors().second = parse_color(value)?,
+                _ => return Err(SettingsError::InvalidValue),
+            }
+        }
+        Ok(layout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(layout: &RingLayout, time: LocalTime) -> OuterFrame {
+        let mut pixels = [(0, 0, 0); OUTER_LED_COUNT];
+        let colors = HandColors::default();
+        layout.render(
+            time,
+            colors,
+            SecondHand::Step,
+            OverlapPolicy::Priority,
+            0,
+            &mut pixels,
+        );
+        pixels
+    }
+
+    #[test]
+    fn test_outer_index_wraps() {
+        assert_eq!(outer_index(30), 29);
+        assert_eq!(outer_index(60), 59);
+    }
+
+    #[test]
+    fn test_sweep_matches_steps() {
+        for second in 0..60u8 {
+            let mut swept: OuterFrame = [(0, 0, 0); OUTER_LED_COUNT];
+            render_sweep(u32::from(second) * 1000, 60_000, (255, 0, 0), &mut swept);
+            let mut stepped: OuterFrame = [(0, 0, 0); OUTER_LED_COUNT];
+            let hands = [(outer_index(second), (255, 0, 0))];
+            render_hands(&hands, OverlapPolicy::Priority, 0, &mut stepped);
+            assert_eq!(swept, stepped, "second {}", second);
+        }
+    }
+
+    #[test]
+    fn test_default_ring_matches_outer_index() {
+        let time = LocalTime::new(7, 20, 45).unwrap();
+        let pixels = render(&RingLayout::default(), time);
+        let colors = HandColors::default();
+        assert_eq!(pixels[outer_index(20)], colors.minute);
+        assert_eq!(pixels[outer_index(45)], colors.second);
+        assert_eq!(pixels.iter().filter(|&&p| p != (0, 0, 0)).count(), 2);
+    }
+
+    #[test]
+    fn test_start_and_reverse() {
+        let time = LocalTime::new(0, 1, 0).unwrap();
+        let ring = RingLayout::new(12).unwrap().with_start(3).unwrap();
+        let minute = HandColors::default().minute;
+        // 12 o'clock is the last position, one before the start
+        assert_eq!(render(&ring, time)[2], minute);
+
+        let reversed = ring.with_reversed(true);
+        assert_eq!(reversed.strip_index(0), 3);
+        assert_eq!(reversed.strip_index(1), 2);
+        assert_eq!(render(&reversed, time)[4], minute);
+    }
+
+    #[test]
+    fn test_smaller_ring_leaves_rest_alone() {
+        let ring = RingLayout::new(24).unwrap();
+        let mut pixels = [(9, 9, 9); OUTER_LED_COUNT];
+        let time = LocalTime::new(0, 0, 0).unwrap();
+        let colors = HandColors::default();
+        ring.render(
+            time,
+            colors,
+            SecondHand::Hidden,
+            OverlapPolicy::Priority,
+            0,
+            &mut pixels,
+        );
+        assert_eq!(pixels[23], HandColors::default().minute);
+        assert_eq!(pixels[0], (0, 0, 0));
+        assert_eq!(pixels[24], (9, 9, 9));
+    }
+
+    #[test]
+    fn test_sweep_on_smaller_ring() {
+        let ring: RingLayout = "12,hands=s".parse().unwrap();
+        let mut pixels = [(0, 0, 0); OUTER_LED_COUNT];
+        let time = LocalTime::new(0, 0, 2).unwrap();
+        let colors = HandColors::default();
+        ring.render(
+            time,
+            colors,
+            SecondHand::Sweep(2_500),
+            OverlapPolicy::Priority,
+            0,
+            &mut pixels,
+        );
+        assert_eq!(pixels[11], fade_half(colors.second));
+        assert_ne!(pixels[0], (0, 0, 0));
+    }
+
+    fn fade_half(color: Rgb) -> Rgb {
+        crate::fade_color(color, 127)
+    }
+
+    #[test]
+    fn test_parse() {
+        let ring: RingLayout = "24, hands=h, start=23, reverse, hour=#ff0000"
+            .parse()
+            .unwrap();
+        assert_eq!(ring.leds(), 24);
+        assert!(ring.hands.hour && !ring.hands.minute && !ring.hands.second);
+        assert_eq!(ring.strip_index(0), 23);
+        assert_eq!(ring.colors.unwrap().hour, (255, 0, 0));
+        assert_eq!("60".parse(), Ok(RingLayout::default()));
+
+        assert_eq!(
+            "61".parse::<RingLayout>(),
+            Err(SettingsError::OutOfRange {
+                field: "leds",
+                value: 61
+            })
+        );
+        assert_eq!(
+            "12,start=12".parse::<RingLayout>(),
+            Err(SettingsError::OutOfRange {
+                field: "start",
+                value: 12
+            })
+        );
+        assert_eq!(
+            "12,hands=x".parse::<RingLayout>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            "12,size=3".parse::<RingLayout>(),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+}
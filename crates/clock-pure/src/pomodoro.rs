@@ -0,0 +1,402 @@
+//! Pomodoro timer: alternating work and break phases shown as an emptying ring.
+
+use crate::mode::render_progress;
+use crate::settings::SettingsError;
+use crate::{fade_color, Rgb};
+
+const DEFAULT_WORK_MS: u32 = 25 * 60 * 1000;
+const DEFAULT_BREAK_MS: u32 = 5 * 60 * 1000;
+const DEFAULT_WORK_COLOR: Rgb = (255, 32, 0);
+const DEFAULT_BREAK_COLOR: Rgb = (0, 255, 64);
+
+/// How long the ring flashes after a phase ends.
+pub const POMODORO_FLASH_MS: u32 = 3000;
+const FLASH_INTERVAL_MS: u32 = 250;
+
+/// Level of the full ring shown while the timer is stopped.
+const STOPPED_LEVEL: u8 = 64;
+
+/// A phase of the Pomodoro cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroPhase {
+    Work,
+    Break,
+}
+
+impl PomodoroPhase {
+    /// Returns the phase that follows this one.
+    pub const fn next(self) -> Self {
+        match self {
+            PomodoroPhase::Work => PomodoroPhase::Break,
+            PomodoroPhase::Break => PomodoroPhase::Work,
+        }
+    }
+
+    /// Returns a short lowercase name for logging.
+    pub const fn name(self) -> &'static str {
+        match self {
+            PomodoroPhase::Work => "work",
+            PomodoroPhase::Break => "break",
+        }
+    }
+}
+
+/// A command for the Pomodoro timer, received over MQTT or from a button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PomodoroCommand {
+    /// Start a work phase, or resume cycling after a stop
+    Start,
+    /// Stop the timer
+    Stop,
+    /// End the current phase early and move on to the next
+    Skip,
+}
+
+impl core::str::FromStr for PomodoroCommand {
+    type Err = SettingsError;
+
+    /// Parses `start`, `stop`, or `skip` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("start") => Ok(PomodoroCommand::Start),
+            t if t.eq_ignore_ascii_case("stop") => Ok(PomodoroCommand::Stop),
+            t if t.eq_ignore_ascii_case("skip") => Ok(PomodoroCommand::Skip),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// A Pomodoro timer cycling between work and break phases.
+///
+/// The timer is driven by a millisecond counter passed to every method, like
+/// [`crate::TimeKeeper`], so it runs on any clock source and is easy to test.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Pomodoro, PomodoroPhase};
+///
+/// let mut timer = Pomodoro::new().with_durations(1000, 500);
+/// timer.start(0);
+/// assert_eq!(timer.update(999), None);
+/// assert_eq!(timer.update(1000), Some(PomodoroPhase::Break));
+/// assert_eq!(timer.remaining_ms(1200), Some(300));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pomodoro {
+    work_ms: u32,
+    break_ms: u32,
+    work_color: Rgb,
+    break_color: Rgb,
+    phase: PomodoroPhase,
+    /// Start of the current phase, `None` while stopped.
+    phase_started_ms: Option<u32>,
+    /// Whether the current phase began by completing the previous one.
+    flash: bool,
+    completed_work: u32,
+}
+
+impl Pomodoro {
+    /// Creates a stopped timer with 25 minute work and 5 minute break phases.
+    pub const fn new() -> Self {
+        Self {
+            work_ms: DEFAULT_WORK_MS,
+            break_ms: DEFAULT_BREAK_MS,
+            work_color: DEFAULT_WORK_COLOR,
+            break_color: DEFAULT_BREAK_COLOR,
+            phase: PomodoroPhase::Work,
+            phase_started_ms: None,
+            flash: false,
+            completed_work: 0,
+        }
+    }
+
+    /// Sets the work and break durations (at least 1 ms each).
+    pub const fn with_durations(mut self, work_ms: u32, break_ms: u32) -> Self {
+        self.work_ms = if work_ms > 0 { work_ms } else { 1 };
+        self.break_ms = if break_ms > 0 { break_ms } else { 1 };
+        self
+    }
+
+    /// Sets the ring colors for work and break phases.
+    pub const fn with_colors(mut self, work: Rgb, brk: Rgb) -> Self {
+        self.work_color = work;
+        self.break_color = brk;
+        self
+    }
+
+    /// Returns the current phase.
+    pub const fn phase(&self) -> PomodoroPhase {
+        self.phase
+    }
+
+    /// Returns true while the timer is running.
+    pub const fn is_running(&self) -> bool {
+        self.phase_started_ms.is_some()
+    }
+
+    /// Returns how many work phases were completed since the timer was created.
+    pub const fn completed_work(&self) -> u32 {
+        self.completed_work
+    }
+
+    /// Returns the length of `phase`.
+    pub const fn duration_ms(&self, phase: PomodoroPhase) -> u32 {
+        match phase {
+            PomodoroPhase::Work => self.work_ms,
+            PomodoroPhase::Break => self.break_ms,
+        }
+    }
+
+    /// Applies a command at `now_ms`.
+    pub fn apply(&mut self, command: PomodoroCommand, now_ms: u32) {
+        match command {
+            PomodoroCommand::Start => self.start(now_ms),
+            PomodoroCommand::Stop => self.stop(),
+            PomodoroCommand::Skip => self.skip(now_ms),
+        }
+    }
+
+    /// Starts a fresh work phase at `now_ms`; does nothing if already running.
+    pub fn start(&mut self, now_ms: u32) {
+        if !self.is_running() {
+            self.enter(PomodoroPhase::Work, now_ms, false);
+        }
+    }
+
+    /// Stops the timer; the next start begins a fresh work phase.
+    pub fn stop(&mut self) {
+        self.phase = PomodoroPhase::Work;
+        self.phase_started_ms = None;
+        self.flash = false;
+    }
+
+    /// Ends the current phase early and starts the next one at `now_ms`.
+    ///
+    /// A skipped work phase does not count as completed. Starts the timer if
+    /// it is stopped.
+    pub fn skip(&mut self, now_ms: u32) {
+        if self.is_running() {
+            self.enter(self.phase.next(), now_ms, false);
+        } else {
+            self.start(now_ms);
+        }
+    }
+
+    /// Advances past phases that ended by `now_ms`.
+    ///
+    /// Returns the phase that was entered if at least one phase ended, so the
+    /// caller can announce it.
+    pub fn update(&mut self, now_ms: u32) -> Option<PomodoroPhase> {
+        let mut entered = None;
+        while let Some(started) = self.phase_started_ms {
+            let duration = self.duration_ms(self.phase);
+            if now_ms.wrapping_sub(started) < duration {
+                break;
+            }
+            if self.phase == PomodoroPhase::Work {
+                self.completed_work += 1;
+            }
+            self.enter(self.phase.next(), started.wrapping_add(duration), true);
+            entered = Some(self.phase);
+        }
+        entered
+    }
+
+    /// Returns the time left in the current phase, or `None` while stopped.
+    ///
+    /// Call [`Pomodoro::update`] first so finished phases are advanced.
+    pub fn remaining_ms(&self, now_ms: u32) -> Option<u32> {
+        let started = self.phase_started_ms?;
+        Some(
+            self.duration_ms(self.phase)
+                .saturating_sub(now_ms.wrapping_sub(started)),
+        )
+    }
+
+    /// Renders the timer at `now_ms` into `buffer`.
+    ///
+    /// A running phase shows an emptying ring in the phase color; for
+    /// [`POMODORO_FLASH_MS`] after a phase completes the whole ring flashes
+    /// instead. A stopped timer shows a dim full ring in the work color.
+    /// Call [`Pomodoro::update`] first so finished phases are advanced.
+    pub fn render(&self, now_ms: u32, buffer: &mut [Rgb]) {
+        let color = match self.phase {
+            PomodoroPhase::Work => self.work_color,
+            PomodoroPhase::Break => self.break_color,
+        };
+        let Some(started) = self.phase_started_ms else {
+            buffer.fill(fade_color(self.work_color, STOPPED_LEVEL));
+            return;
+        };
+
+        let elapsed = now_ms.wrapping_sub(started);
+        if self.flash && elapsed < POMODORO_FLASH_MS {
+            let off = (elapsed / FLASH_INTERVAL_MS) % 2 == 1;
+            buffer.fill(if off { (0, 0, 0) } else { color });
+            return;
+        }
+        let duration = self.duration_ms(self.phase);
+        render_progress(duration.saturating_sub(elapsed), duration, color, buffer);
+    }
+
+    fn enter(&mut self, phase: PomodoroPhase, now_ms: u32, flash: bool) {
+        self.phase = phase;
+        self.phase_started_ms = Some(now_ms);
+        self.flash = flash;
+    }
+}
+
+impl Default for Pomodoro {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORK: Rgb = (255, 0, 0);
+    const BREAK: Rgb = (0, 255, 0);
+
+    fn timer() -> Pomodoro {
+        Pomodoro::new()
+            .with_durations(12_000, 6_000)
+            .with_colors(WORK, BREAK)
+    }
+
+    fn lit(buffer: &[Rgb]) -> usize {
+        buffer.iter().filter(|&&p| p != (0, 0, 0)).count()
+    }
+
+    #[test]
+    fn test_defaults() {
+        let timer = Pomodoro::default();
+        assert!(!timer.is_running());
+        assert_eq!(timer.duration_ms(PomodoroPhase::Work), 25 * 60 * 1000);
+        assert_eq!(timer.duration_ms(PomodoroPhase::Break), 5 * 60 * 1000);
+        assert_eq!(timer.remaining_ms(0), None);
+    }
+
+    #[test]
+    fn test_cycles_between_phases() {
+        let mut timer = timer();
+        timer.start(1000);
+        assert_eq!(timer.update(12_999), None);
+        assert_eq!(timer.update(13_000), Some(PomodoroPhase::Break));
+        assert_eq!(timer.completed_work(), 1);
+        assert_eq!(timer.update(19_000), Some(PomodoroPhase::Work));
+        assert_eq!(timer.completed_work(), 1);
+        assert_eq!(timer.remaining_ms(19_000), Some(12_000));
+    }
+
+    #[test]
+    fn test_update_catches_up_on_missed_phases() {
+        let mut timer = timer();
+        timer.start(0);
+        // Work ends at 12 s, break at 18 s, the next work at 30 s
+        assert_eq!(timer.update(31_000), Some(PomodoroPhase::Break));
+        assert_eq!(timer.completed_work(), 2);
+        assert_eq!(timer.remaining_ms(31_000), Some(5_000));
+    }
+
+    #[test]
+    fn test_start_while_running_is_ignored() {
+        let mut timer = timer();
+        timer.start(0);
+        timer.start(5_000);
+        assert_eq!(timer.remaining_ms(5_000), Some(7_000));
+    }
+
+    #[test]
+    fn test_stop_and_restart() {
+        let mut timer = timer();
+        timer.start(0);
+        timer.update(13_000);
+        timer.stop();
+        assert!(!timer.is_running());
+        assert_eq!(timer.update(100_000), None);
+
+        timer.apply(PomodoroCommand::Start, 100_000);
+        assert_eq!(timer.phase(), PomodoroPhase::Work);
+        assert_eq!(timer.remaining_ms(100_000), Some(12_000));
+    }
+
+    #[test]
+    fn test_skip() {
+        let mut timer = timer();
+        timer.skip(0);
+        assert_eq!(timer.phase(), PomodoroPhase::Work);
+        assert!(timer.is_running());
+
+        timer.apply(PomodoroCommand::Skip, 2_000);
+        assert_eq!(timer.phase(), PomodoroPhase::Break);
+        assert_eq!(timer.completed_work(), 0);
+        assert_eq!(timer.remaining_ms(2_000), Some(6_000));
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let mut timer = timer();
+        timer.start(u32::MAX - 1_000);
+        assert_eq!(timer.update(10_000), None);
+        assert_eq!(timer.remaining_ms(10_000), Some(999));
+        assert_eq!(timer.update(11_000), Some(PomodoroPhase::Break));
+    }
+
+    #[test]
+    fn test_render_running_ring_empties() {
+        let mut timer = timer();
+        let mut buffer = [(0, 0, 0); 12];
+        timer.start(0);
+        timer.render(0, &mut buffer);
+        assert_eq!(buffer, [WORK; 12]);
+        timer.render(6_000, &mut buffer);
+        assert_eq!(lit(&buffer), 6);
+    }
+
+    #[test]
+    fn test_render_flashes_after_completion() {
+        let mut timer = timer();
+        let mut buffer = [(0, 0, 0); 12];
+        timer.start(0);
+        timer.update(12_000);
+        timer.render(12_000, &mut buffer);
+        assert_eq!(buffer, [BREAK; 12]);
+        timer.render(12_000 + FLASH_INTERVAL_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+        // After the flash, half of the 6 s break is left
+        timer.render(12_000 + POMODORO_FLASH_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 6);
+    }
+
+    #[test]
+    fn test_render_skip_does_not_flash() {
+        let mut timer = timer();
+        let mut buffer = [(0, 0, 0); 12];
+        timer.start(0);
+        timer.skip(1_000);
+        timer.render(1_000 + FLASH_INTERVAL_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+        assert_eq!(buffer[11], BREAK);
+    }
+
+    #[test]
+    fn test_render_stopped() {
+        let mut buffer = [(0, 0, 0); 12];
+        timer().render(0, &mut buffer);
+        assert_eq!(buffer, [fade_color(WORK, STOPPED_LEVEL); 12]);
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!("start".parse(), Ok(PomodoroCommand::Start));
+        assert_eq!(" STOP\n".parse(), Ok(PomodoroCommand::Stop));
+        assert_eq!("skip".parse(), Ok(PomodoroCommand::Skip));
+        assert_eq!(
+            "pause".parse::<PomodoroCommand>(),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+}
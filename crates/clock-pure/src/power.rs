@@ -0,0 +1,237 @@
+//! Keeps the ring's current draw within what the power supply can deliver.
+
+use crate::{fade_color, Rgb};
+
+/// Current (mA) one color channel of a WS2812 LED draws at full level.
+pub const LED_CHANNEL_MA: u32 = 20;
+
+/// Current (mA) one WS2812 LED draws while dark.
+pub const LED_IDLE_MA: u32 = 1;
+
+/// How long the ring stays dark before a [`PowerGate`] cuts its power.
+pub const POWER_GATE_HOLD_MS: u32 = 2_000;
+
+/// A current budget for all LEDs on the strip.
+///
+/// The current a frame draws is estimated from its pixel values; a frame
+/// that would exceed the budget is dimmed as a whole, so colors and the
+/// picture stay the same, just darker. This protects small USB supplies
+/// when bright white effects run at high brightness.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Frame, PowerBudget};
+///
+/// let mut white = Frame::from_pixels([(255, 255, 255); 12]);
+/// assert_eq!(PowerBudget::estimate_ma(white.pixels()), 732);
+///
+/// let budget = PowerBudget::new(300);
+/// assert!(budget.apply(white.pixels_mut()));
+/// assert!(PowerBudget::estimate_ma(white.pixels()) <= 300);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerBudget {
+    max_ma: u32,
+}
+
+impl PowerBudget {
+    /// Creates a budget of `max_ma` milliamperes for the strip.
+    pub const fn new(max_ma: u32) -> Self {
+        Self { max_ma }
+    }
+
+    /// Returns the budget in milliamperes.
+    pub const fn max_ma(&self) -> u32 {
+        self.max_ma
+    }
+
+    /// Estimates the current in milliamperes the LEDs draw showing `pixels`.
+    pub fn estimate_ma(pixels: &[Rgb]) -> u32 {
+        Self::channel_sum(pixels) * LED_CHANNEL_MA / 255 + Self::idle_ma(pixels)
+    }
+
+    /// Returns the level (0-255) `pixels` must be dimmed to for staying
+    /// within the budget; 255 if they already do.
+    pub fn level(&self, pixels: &[Rgb]) -> u8 {
+        let sum = u64::from(Self::channel_sum(pixels));
+        if sum == 0 {
+            return u8::MAX;
+        }
+        let available_ma = u64::from(self.max_ma.saturating_sub(Self::idle_ma(pixels)));
+        // Dimming by `level` scales every channel by at most level/255
+        let level = available_ma * 255 * 255 / (sum * u64::from(LED_CHANNEL_MA));
+        level.min(u64::from(u8::MAX)) as u8
+    }
+
+    /// Dims `pixels` as a whole if they would draw more than the budget.
+    ///
+    /// Returns true if they were dimmed.
+    pub fn apply(&self, pixels: &mut [Rgb]) -> bool {
+        let level = self.level(pixels);
+        if level == u8::MAX {
+            return false;
+        }
+        for pixel in pixels {
+            *pixel = fade_color(*pixel, level);
+        }
+        true
+    }
+
+    fn idle_ma(pixels: &[Rgb]) -> u32 {
+        LED_IDLE_MA * pixels.len() as u32
+    }
+
+    fn channel_sum(pixels: &[Rgb]) -> u32 {
+        pixels
+            .iter()
+            .map(|&(r, g, b)| u32::from(r) + u32::from(g) + u32::from(b))
+            .sum()
+    }
+}
+
+/// Decides when to cut the power of a dark ring.
+///
+/// Even dark, every WS2812 LED draws [`LED_IDLE_MA`], so a ring behind a
+/// switch, such as a MOSFET on a GPIO, can be switched off while the
+/// display is. The gate waits [`POWER_GATE_HOLD_MS`] before switching off,
+/// so a short dark moment like a blink does not cycle the power, and
+/// switches on for the first lit frame. LEDs forget their color without
+/// power, so the whole frame must be written again after switching on.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{PowerGate, POWER_GATE_HOLD_MS};
+///
+/// let mut gate = PowerGate::new();
+/// assert_eq!(gate.update(true, 1_000), None);
+/// assert_eq!(gate.update(true, 1_000 + POWER_GATE_HOLD_MS), Some(false));
+/// assert!(!gate.is_on());
+/// assert_eq!(gate.update(false, 9_000), Some(true));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PowerGate {
+    on: bool,
+    /// When the ring went dark while powered
+    dark_since_ms: Option<u32>,
+}
+
+impl PowerGate {
+    /// Creates a gate with the ring switched on.
+    pub const fn new() -> Self {
+        Self {
+            on: true,
+            dark_since_ms: None,
+        }
+    }
+
+    /// Returns true if the ring should be powered.
+    pub fn is_on(&self) -> bool {
+        self.on
+    }
+
+    /// Updates the gate with whether the ring is `dark` at `now_ms`.
+    ///
+    /// Returns the new power state when it changes.
+    pub fn update(&mut self, dark: bool, now_ms: u32) -> Option<bool> {
+        if !dark {
+            self.dark_since_ms = None;
+            return (!self.on).then(|| {
+                self.on = true;
+                true
+            });
+        }
+        if !self.on {
+            return None;
+        }
+        let since = *self.dark_since_ms.get_or_insert(now_ms);
+        if now_ms.wrapping_sub(since) < POWER_GATE_HOLD_MS {
+            return None;
+        }
+        self.on = false;
+        self.dark_since_ms = None;
+        Some(false)
+    }
+}
+
+impl Default for PowerGate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Frame, LED_COUNT, OUTER_LED_COUNT};
+
+    #[test]
+    fn test_dark_frame_draws_idle_current() {
+        assert_eq!(PowerBudget::estimate_ma(Frame::new().pixels()), 12);
+        let mut frame = Frame::new();
+        assert!(!PowerBudget::new(0).apply(frame.pixels_mut()));
+    }
+
+    #[test]
+    fn test_within_budget_is_unchanged() {
+        let mut frame = Frame::new();
+        frame[0] = (255, 0, 0);
+        frame[5] = (0, 255, 255);
+        let before = frame;
+        assert!(!PowerBudget::new(500).apply(frame.pixels_mut()));
+        assert_eq!(frame, before);
+    }
+
+    #[test]
+    fn test_over_budget_dims_evenly() {
+        let mut frame = Frame::from_pixels([(200, 100, 0); LED_COUNT]);
+        let budget = PowerBudget::new(200);
+        assert!(budget.apply(frame.pixels_mut()));
+        assert!(PowerBudget::estimate_ma(frame.pixels()) <= 200);
+        // Still the same hue, just darker
+        let (r, g, b) = frame[3];
+        assert_eq!(b, 0);
+        assert!(r > g && g > 0);
+    }
+
+    #[test]
+    fn test_budget_below_idle_current() {
+        let mut frame = Frame::from_pixels([(255, 255, 255); LED_COUNT]);
+        assert!(PowerBudget::new(5).apply(frame.pixels_mut()));
+        assert_eq!(frame, Frame::new());
+    }
+
+    #[test]
+    fn test_chained_rings_share_the_budget() {
+        let mut chain = [(255, 255, 255); LED_COUNT + OUTER_LED_COUNT];
+        assert_eq!(PowerBudget::estimate_ma(&chain), 4392);
+        assert!(PowerBudget::new(1000).apply(&mut chain));
+        assert!(PowerBudget::estimate_ma(&chain) <= 1000);
+    }
+
+    #[test]
+    fn test_gate_holds_before_switching_off() {
+        let mut gate = PowerGate::new();
+        assert_eq!(gate.update(false, 0), None);
+        assert_eq!(gate.update(true, 100), None);
+        // Lit again within the hold time, so the countdown starts over
+        assert_eq!(gate.update(false, 1_000), None);
+        assert_eq!(gate.update(true, 1_500), None);
+        assert_eq!(gate.update(true, 1_499 + POWER_GATE_HOLD_MS), None);
+        assert_eq!(gate.update(true, 1_500 + POWER_GATE_HOLD_MS), Some(false));
+        assert_eq!(gate.update(true, 60_000), None);
+        assert!(!gate.is_on());
+    }
+
+    #[test]
+    fn test_gate_switches_on_at_once() {
+        let mut gate = PowerGate::new();
+        gate.update(true, u32::MAX - 100);
+        // The uptime wraps while dark
+        assert_eq!(gate.update(true, POWER_GATE_HOLD_MS), Some(false));
+        assert_eq!(gate.update(false, POWER_GATE_HOLD_MS + 1), Some(true));
+        assert!(gate.is_on());
+        assert_eq!(gate.update(false, POWER_GATE_HOLD_MS + 2), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Frames pushed by another controller, shown in place of the clock for a while.
+
+use crate::frame::{Frame, FRAME_BYTES};
+use crate::settings::SettingsError;
+#[cfg(feature = "std")]
+use std::string::String;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+/// How long a pushed frame shows when the payload gives no duration.
+pub const DEFAULT_PUSH_MS: u32 = 10_000;
+
+/// Longest a pushed frame may show, in seconds.
+const MAX_PUSH_SECONDS: u32 = 60 * 60;
+
+/// A complete frame to show for a while, e.g. from a home automation
+/// controller using the clock as a notification ring.
+///
+/// The payload is either JSON with the twelve `pixels` as colors (hex or
+/// name, LED 0 first) and optionally a duration in `seconds`, or the
+/// compact [`FRAME_BYTES`]-byte binary encoding of [`Frame::to_bytes`],
+/// optionally followed by the duration in seconds as a big-endian `u16`.
+/// Without a duration, the frame shows for [`DEFAULT_PUSH_MS`].
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Frame, PushedFrame, DEFAULT_PUSH_MS};
+///
+/// let mut frame = Frame::new();
+/// frame[0] = (255, 0, 0);
+/// let pushed = PushedFrame::parse(&frame.to_bytes()).unwrap();
+/// assert_eq!(pushed, PushedFrame { frame, duration_ms: DEFAULT_PUSH_MS });
+///
+/// let mut bytes = frame.to_bytes().to_vec();
+/// bytes.extend_from_slice(&30u16.to_be_bytes());
+/// assert_eq!(PushedFrame::parse(&bytes).unwrap().duration_ms, 30_000);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PushedFrame {
+    pub frame: Frame,
+    pub duration_ms: u32,
+}
+
+impl PushedFrame {
+    /// Parses a pushed frame, see [`PushedFrame`].
+    ///
+    /// Payloads of exactly [`FRAME_BYTES`] or two more bytes are binary; a
+    /// JSON frame is always longer.
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        let (frame, seconds) = match payload.len() {
+            FRAME_BYTES => (payload, None),
+            len if len == FRAME_BYTES + 2 => {
+                let (frame, seconds) = payload.split_at(FRAME_BYTES);
+                (
+                    frame,
+                    Some(u32::from(u16::from_be_bytes([seconds[0], seconds[1]]))),
+                )
+            }
+            _ => return parse_json(payload),
+        };
+        let frame =
+            Frame::try_from(frame).map_err(|_| SettingsError::InvalidLength(payload.len()))?;
+        Ok(Self {
+            frame,
+            duration_ms: duration_ms(seconds)?,
+        })
+    }
+}
+
+/// Converts an optional duration in seconds, checking its range.
+fn duration_ms(seconds: Option<u32>) -> Result<u32, SettingsError> {
+    match seconds {
+        None => Ok(DEFAULT_PUSH_MS),
+        Some(seconds @ 1..=MAX_PUSH_SECONDS) => Ok(seconds * 1000),
+        Some(seconds) => Err(SettingsError::OutOfRange {
+            field: "seconds",
+            value: seconds,
+        }),
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_json(payload: &[u8]) -> Result<PushedFrame, SettingsError> {
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Push {
+        pixels: Vec<String>,
+        seconds: Option<u32>,
+    }
+
+    let push: Push = serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+    if push.pixels.len() != crate::LED_COUNT {
+        return Err(SettingsError::InvalidLength(push.pixels.len()));
+    }
+    let mut frame = Frame::new();
+    for (pixel, color) in frame.pixels_mut().iter_mut().zip(&push.pixels) {
+        *pixel = crate::parse_color(color)?;
+    }
+    Ok(PushedFrame {
+        frame,
+        duration_ms: duration_ms(push.seconds)?,
+    })
+}
+
+#[cfg(not(feature = "std"))]
+fn parse_json(_payload: &[u8]) -> Result<PushedFrame, SettingsError> {
+    Err(SettingsError::InvalidJson)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LED_COUNT;
+    use std::format;
+    use std::vec;
+
+    #[test]
+    fn test_json_frame() {
+        let json = r##"{"pixels":["red","#00ff00","off","off","off","off","off","off","off","off","off","blue"],"seconds":5}"##;
+        let pushed = PushedFrame::parse(json.as_bytes()).unwrap();
+        assert_eq!(pushed.duration_ms, 5_000);
+        assert_eq!(pushed.frame[0], (255, 0, 0));
+        assert_eq!(pushed.frame[1], (0, 255, 0));
+        assert_eq!(pushed.frame[2], (0, 0, 0));
+        assert_eq!(pushed.frame[11], (0, 0, 255));
+    }
+
+    #[test]
+    fn test_invalid_json_frames() {
+        let pixels = |n: usize| vec!["\"red\""; n].join(",");
+        let parse = |json: String| PushedFrame::parse(json.as_bytes());
+        assert_eq!(
+            parse(format!(r#"{{"pixels":[{}]}}"#, pixels(11))),
+            Err(SettingsError::InvalidLength(11))
+        );
+        assert_eq!(
+            parse(format!(
+                r#"{{"pixels":[{}],"seconds":0}}"#,
+                pixels(LED_COUNT)
+            )),
+            Err(SettingsError::OutOfRange {
+                field: "seconds",
+                value: 0
+            })
+        );
+        assert_eq!(
+            parse(format!(r#"{{"pixels":[{}],"extra":1}}"#, pixels(LED_COUNT))),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(parse("{}".into()), Err(SettingsError::InvalidJson));
+    }
+
+    #[test]
+    fn test_binary_frame() {
+        let frame = Frame::from_pixels([(1, 2, 3); LED_COUNT]);
+        let mut bytes = frame.to_bytes().to_vec();
+        bytes.extend_from_slice(&(MAX_PUSH_SECONDS as u16 + 1).to_be_bytes());
+        assert_eq!(
+            PushedFrame::parse(&bytes),
+            Err(SettingsError::OutOfRange {
+                field: "seconds",
+                value: MAX_PUSH_SECONDS + 1
+            })
+        );
+        assert_eq!(
+            PushedFrame::parse(&bytes[..FRAME_BYTES - 1]),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+}
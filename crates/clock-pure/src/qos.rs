@@ -0,0 +1,142 @@
+//! MQTT quality of service levels, with overrides for single topics.
+
+use crate::settings::SettingsError;
+
+/// Most topics that may have a level of their own.
+pub const MAX_QOS_TOPICS: usize = 8;
+
+/// The QoS level every topic uses unless configured otherwise.
+pub const DEFAULT_QOS: u8 = 1;
+
+/// The MQTT quality of service level of every topic the clock subscribes or
+/// publishes to.
+///
+/// Written as a default level, followed by up to [`MAX_QOS_TOPICS`] topics
+/// with a level of their own as `topic=level`, separated by commas; either
+/// part may be left out. Levels are 0 (at most once), 1 (at least once),
+/// and 2 (exactly once).
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::QosLevels;
+///
+/// let levels = QosLevels::parse("1,tick=0,clock/alarm=2").unwrap();
+/// assert_eq!(levels.level("clock/brightness"), 1);
+/// assert_eq!(levels.level("tick"), 0);
+/// assert_eq!(levels.level("clock/alarm"), 2);
+///
+/// let levels = QosLevels::parse("tick=0").unwrap();
+/// assert_eq!(levels.level("clock/mode"), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QosLevels<'a> {
+    default: u8,
+    topics: [(&'a str, u8); MAX_QOS_TOPICS],
+    len: usize,
+}
+
+impl<'a> QosLevels<'a> {
+    /// Every topic at [`DEFAULT_QOS`].
+    pub const fn new() -> Self {
+        Self {
+            default: DEFAULT_QOS,
+            topics: [("", DEFAULT_QOS); MAX_QOS_TOPICS],
+            len: 0,
+        }
+    }
+
+    /// Parses levels such as `1,tick=0`, see [`QosLevels`].
+    pub fn parse(text: &'a str) -> Result<Self, SettingsError> {
+        let mut levels = Self::new();
+        for (index, part) in text.split(',').map(str::trim).enumerate() {
+            match part.split_once('=') {
+                None if index == 0 => levels.default = parse_level(part)?,
+                None => return Err(SettingsError::InvalidValue),
+                Some((topic, level)) => {
+                    let topic = topic.trim();
+                    if topic.is_empty() {
+                        return Err(SettingsError::InvalidValue);
+                    }
+                    if levels.len == MAX_QOS_TOPICS {
+                        return Err(SettingsError::OutOfRange {
+                            field: "topics",
+                            value: MAX_QOS_TOPICS as u32 + 1,
+                        });
+                    }
+                    levels.topics[levels.len] = (topic, parse_level(level)?);
+                    levels.len += 1;
+                }
+            }
+        }
+        Ok(levels)
+    }
+
+    /// Returns the level of `topic`.
+    pub fn level(&self, topic: &str) -> u8 {
+        self.topics[..self.len]
+            .iter()
+            .find(|(name, _)| *name == topic)
+            .map_or(self.default, |&(_, level)| level)
+    }
+}
+
+impl Default for QosLevels<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Parses a QoS level from 0 to 2.
+fn parse_level(text: &str) -> Result<u8, SettingsError> {
+    let level: u8 = text
+        .trim()
+        .parse()
+        .map_err(|_| SettingsError::InvalidValue)?;
+    if level > 2 {
+        return Err(SettingsError::OutOfRange {
+            field: "qos",
+            value: level.into(),
+        });
+    }
+    Ok(level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn test_default_level() {
+        assert_eq!(QosLevels::default().level("tick"), DEFAULT_QOS);
+        assert_eq!(QosLevels::parse(" 0 ").unwrap().level("tick"), 0);
+        assert_eq!(
+            QosLevels::parse("3"),
+            Err(SettingsError::OutOfRange {
+                field: "qos",
+                value: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_invalid_levels() {
+        for invalid in ["", "one", "1,2", "1,=0", "1,tick", "tick=x", "1,,tick=0"] {
+            assert_eq!(
+                QosLevels::parse(invalid),
+                Err(SettingsError::InvalidValue),
+                "{invalid}"
+            );
+        }
+        let full = ["t=0"; MAX_QOS_TOPICS].join(",");
+        assert!(QosLevels::parse(&full).is_ok());
+        assert_eq!(
+            QosLevels::parse(&format!("{},u=0", full)),
+            Err(SettingsError::OutOfRange {
+                field: "topics",
+                value: MAX_QOS_TOPICS as u32 + 1
+            })
+        );
+    }
+}
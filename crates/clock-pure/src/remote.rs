@@ -0,0 +1,262 @@
+//! Structured JSON commands, routed to the MQTT topic that handles them.
+
+use crate::settings::SettingsError;
+use crate::topic::Topic;
+use serde_json::{Map, Value};
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+/// Every command: its name, the topic handling it, and the fixed payload it
+/// sends there, if it takes no arguments.
+const COMMANDS: [(&str, Topic, Option<&str>); 32] = [
+    ("set_time", Topic::Tick, None),
+    ("set_mode", Topic::Mode, None),
+    ("next_mode", Topic::Mode, Some("next")),
+    ("set_brightness", Topic::Brightness, None),
+    ("set_colors", Topic::Colors, None),
+    ("set_power", Topic::Power, None),
+    ("set_night", Topic::Night, None),
+    ("set_dnd", Topic::DoNotDisturb, None),
+    ("run_effect", Topic::Playlist, None),
+    ("notify", Topic::Notify, None),
+    ("push_frame", Topic::Frame, None),
+    ("timer_start", Topic::Pomodoro, Some("start")),
+    ("timer_stop", Topic::Pomodoro, Some("stop")),
+    ("timer_skip", Topic::Pomodoro, Some("skip")),
+    ("stopwatch_start", Topic::Stopwatch, Some("start")),
+    ("stopwatch_lap", Topic::Stopwatch, Some("lap")),
+    ("stopwatch_stop", Topic::Stopwatch, Some("stop")),
+    ("stopwatch_reset", Topic::Stopwatch, Some("reset")),
+    ("set_alarm", Topic::Alarm, None),
+    ("dismiss_alarm", Topic::Alarm, Some("dismiss")),
+    ("save_scene", Topic::Scene, None),
+    ("recall_scene", Topic::SceneRecall, None),
+    ("set_schedule", Topic::Schedule, None),
+    ("set_weather", Topic::Weather, None),
+    ("set_temperature", Topic::Temperature, None),
+    ("set_presence", Topic::Presence, None),
+    ("set_animation", Topic::Animation, None),
+    ("set_buzzer", Topic::Buzzer, None),
+    ("play_melody", Topic::BuzzerPlay, None),
+    ("set_timezone", Topic::TimeZone, None),
+    ("identify", Topic::Identify, Some("")),
+    ("self_test", Topic::SelfTest, Some("")),
+];
+
+/// Every setting of a retained configuration and the topic handling it, in
+/// the order they apply; a recalled scene comes first so the settings next
+/// to it override its own.
+const CONFIG: [(&str, Topic); 10] = [
+    ("scene", Topic::SceneRecall),
+    ("mode", Topic::Mode),
+    ("colors", Topic::Colors),
+    ("brightness", Topic::Brightness),
+    ("power", Topic::Power),
+    ("night", Topic::Night),
+    ("dnd", Topic::DoNotDisturb),
+    ("animation", Topic::Animation),
+    ("buzzer", Topic::Buzzer),
+    ("timezone", Topic::TimeZone),
+];
+
+/// A command from the JSON command topic, ready for the topic handling it.
+///
+/// A command is a JSON object naming the command in `cmd`. Commands with a
+/// single argument take it in `value`; a string is passed on as text, any
+/// other value as JSON. Commands with several arguments take the fields of
+/// the JSON object their topic accepts next to `cmd` instead. Commands
+/// such as `timer_start` take no arguments.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{RemoteCommand, Topic};
+///
+/// let command = RemoteCommand::parse(br#"{"cmd":"set_brightness","value":40}"#).unwrap();
+/// assert_eq!(command.topic, Topic::Brightness);
+/// assert_eq!(command.payload, "40");
+///
+/// let command = RemoteCommand::parse(br#"{"cmd":"set_mode","value":"weather"}"#).unwrap();
+/// assert_eq!(command.payload, "weather");
+///
+/// let command = RemoteCommand::parse(br#"{"cmd":"timer_start"}"#).unwrap();
+/// assert_eq!((command.topic, command.payload.as_str()), (Topic::Pomodoro, "start"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoteCommand {
+    /// The topic handling the command, without a clock name
+    pub topic: Topic,
+    /// The payload to handle, as if it was received on `topic`
+    pub payload: String,
+}
+
+impl RemoteCommand {
+    /// Parses a command, see [`RemoteCommand`].
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        let mut fields: Map<String, Value> =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let Some(Value::String(name)) = fields.remove("cmd") else {
+            return Err(SettingsError::InvalidJson);
+        };
+        let &(_, topic, fixed) = COMMANDS
+            .iter()
+            .find(|(command, _, _)| *command == name)
+            .ok_or(SettingsError::InvalidValue)?;
+        let payload = match (fixed, fields.remove("value")) {
+            (Some(fixed), None) if fields.is_empty() => fixed.into(),
+            (Some(_), _) => return Err(SettingsError::InvalidJson),
+            (None, Some(_)) if !fields.is_empty() => return Err(SettingsError::InvalidJson),
+            (None, Some(Value::String(text))) => text,
+            (None, Some(value)) => value.to_string(),
+            (None, None) if fields.is_empty() => return Err(SettingsError::InvalidJson),
+            (None, None) => Value::Object(fields).to_string(),
+        };
+        Ok(Self { topic, payload })
+    }
+
+    /// Parses a configuration into the commands applying it.
+    ///
+    /// A configuration is a JSON object with any of `scene`, `mode`,
+    /// `colors`, `brightness`, `power`, `night`, `dnd`, `animation`,
+    /// `buzzer`, and `timezone`, each taking what its topic takes, like the `value` of a
+    /// command. A configuration with an unknown setting is rejected as a
+    /// whole, so a typo applies nothing rather than half of it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{RemoteCommand, Topic};
+    ///
+    /// let commands =
+    ///     RemoteCommand::parse_config(br#"{"brightness":40,"mode":"clock"}"#).unwrap();
+    /// assert_eq!(commands.len(), 2);
+    /// assert_eq!((commands[0].topic, commands[0].payload.as_str()), (Topic::Mode, "clock"));
+    /// assert_eq!((commands[1].topic, commands[1].payload.as_str()), (Topic::Brightness, "40"));
+    /// ```
+    pub fn parse_config(payload: &[u8]) -> Result<Vec<Self>, SettingsError> {
+        let mut fields: Map<String, Value> =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let commands = CONFIG
+            .iter()
+            .filter_map(|&(name, topic)| {
+                let payload = match fields.remove(name)? {
+                    Value::String(text) => text,
+                    value => value.to_string(),
+                };
+                Some(Self { topic, payload })
+            })
+            .collect();
+        if !fields.is_empty() {
+            return Err(SettingsError::InvalidJson);
+        }
+        Ok(commands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Result<(Topic, String), SettingsError> {
+        RemoteCommand::parse(json.as_bytes()).map(|command| (command.topic, command.payload))
+    }
+
+    #[test]
+    fn test_value_argument() {
+        assert_eq!(
+            parse(r#"{"value":"on","cmd":"set_power"}"#),
+            Ok((Topic::Power, "on".into()))
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"set_colors","value":{"hour":"red"}}"#),
+            Ok((Topic::Colors, r#"{"hour":"red"}"#.into()))
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"set_time","value":"12:34:56"}"#),
+            Ok((Topic::Tick, "12:34:56".into()))
+        );
+    }
+
+    #[test]
+    fn test_field_arguments() {
+        let (topic, payload) =
+            parse(r#"{"cmd":"notify","color":"blue","pattern":"pulse"}"#).unwrap();
+        assert_eq!(topic, Topic::Notify);
+        let payload: Value = serde_json::from_str(&payload).unwrap();
+        assert_eq!(
+            payload,
+            serde_json::json!({"color": "blue", "pattern": "pulse"})
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"run_effect","steps":[]}"#),
+            Ok((Topic::Playlist, r#"{"steps":[]}"#.into()))
+        );
+    }
+
+    #[test]
+    fn test_commands_without_arguments() {
+        assert_eq!(
+            parse(r#"{"cmd":"dismiss_alarm"}"#),
+            Ok((Topic::Alarm, "dismiss".into()))
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"identify"}"#),
+            Ok((Topic::Identify, "".into()))
+        );
+        assert_eq!(
+            parse(r#"{"cmd":"timer_stop","value":1}"#),
+            Err(SettingsError::InvalidJson)
+        );
+    }
+
+    #[test]
+    fn test_invalid_commands() {
+        assert_eq!(
+            parse(r#"{"cmd":"explode"}"#),
+            Err(SettingsError::InvalidValue)
+        );
+        for invalid in [
+            r#"{"value":1}"#,
+            r#"{"cmd":5}"#,
+            r#"["set_power"]"#,
+            r#"{"cmd":"set_power"}"#,
+            r#"{"cmd":"set_power","value":"on","extra":1}"#,
+            "set_power on",
+        ] {
+            assert_eq!(parse(invalid), Err(SettingsError::InvalidJson), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_config() {
+        let commands = RemoteCommand::parse_config(
+            br#"{"timezone":3600,"night":{"mode":"auto"},"brightness":"64","scene":"evening"}"#,
+        )
+        .unwrap();
+        let commands: Vec<_> = commands
+            .iter()
+            .map(|command| (command.topic, command.payload.as_str()))
+            .collect();
+        assert_eq!(
+            commands,
+            [
+                (Topic::SceneRecall, "evening"),
+                (Topic::Brightness, "64"),
+                (Topic::Night, r#"{"mode":"auto"}"#),
+                (Topic::TimeZone, "3600"),
+            ]
+        );
+        assert_eq!(RemoteCommand::parse_config(b"{}"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        for invalid in [r#"{"brightness":40,"theme":"red"}"#, r#"["mode"]"#, ""] {
+            assert_eq!(
+                RemoteCommand::parse_config(invalid.as_bytes()),
+                Err(SettingsError::InvalidJson),
+                "{invalid}"
+            );
+        }
+    }
+}
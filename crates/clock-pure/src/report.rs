@@ -0,0 +1,140 @@
+//! The clock's current state, published for external controllers.
+
+use crate::date::LocalDateTime;
+use crate::pomodoro::PomodoroPhase;
+use crate::scene::Scene;
+use crate::Rgb;
+use serde_json::json;
+use std::format;
+use std::string::{String, ToString};
+
+/// What the clock currently shows and does, published whenever it changes.
+///
+/// Counters such as the remaining time of a timer are left out, so the
+/// report only changes with a setting, or when a timer or an alarm does.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{DisplayMode, HandColors, Scene, StateReport};
+///
+/// let report = StateReport {
+///     scene: Scene {
+///         mode: DisplayMode::Clock,
+///         colors: HandColors::default(),
+///         brightness: 10,
+///     },
+///     power: true,
+///     night: false,
+///     do_not_disturb: false,
+///     pomodoro: None,
+///     stopwatch_running: false,
+///     alarm_ringing: false,
+///     next_alarm: None,
+/// };
+/// let json = report.to_json();
+/// assert!(json.contains(r#""mode":"clock""#));
+/// assert!(json.contains(r##""hour":"#0000ff""##));
+/// assert!(json.contains(r#""next_alarm":null"#));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateReport {
+    /// Display mode, hand colors, and brightness
+    pub scene: Scene,
+    /// Whether the display is switched on
+    pub power: bool,
+    /// Whether night mode currently dims the face
+    pub night: bool,
+    /// Whether do-not-disturb is currently active
+    pub do_not_disturb: bool,
+    /// Phase of the Pomodoro timer, while it runs
+    pub pomodoro: Option<PomodoroPhase>,
+    pub stopwatch_running: bool,
+    pub alarm_ringing: bool,
+    pub next_alarm: Option<LocalDateTime>,
+}
+
+impl StateReport {
+    /// Returns the report as JSON, with colors in hex and the next alarm as
+    /// a local date and time such as `2025-01-13T06:45:00`.
+    pub fn to_json(&self) -> String {
+        let colors = &self.scene.colors;
+        let next_alarm = self.next_alarm.and_then(|next| {
+            let (date, time) = (next.date?, next.time);
+            Some(format!(
+                "{:04}-{:02}-{:02}T{:02}:{:02}:00",
+                date.year, date.month, date.day, time.hour, time.minute
+            ))
+        });
+        json!({
+            "mode": self.scene.mode.name(),
+            "colors": {
+                "hour": hex(colors.hour),
+                "minute": hex(colors.minute),
+                "second": hex(colors.second),
+            },
+            "brightness": self.scene.brightness,
+            "power": self.power,
+            "night": self.night,
+            "dnd": self.do_not_disturb,
+            "pomodoro": self.pomodoro.map(PomodoroPhase::name),
+            "stopwatch": if self.stopwatch_running { "running" } else { "stopped" },
+            "alarm_ringing": self.alarm_ringing,
+            "next_alarm": next_alarm,
+        })
+        .to_string()
+    }
+}
+
+/// Formats a color as `#rrggbb`, as [`parse_color`](crate::parse_color) reads it.
+pub(crate) fn hex((r, g, b): Rgb) -> String {
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Date, DisplayMode, HandColors, LocalTime};
+    use serde_json::Value;
+
+    #[test]
+    fn test_report() {
+        let report = StateReport {
+            scene: Scene {
+                mode: DisplayMode::Pomodoro,
+                colors: HandColors {
+                    hour: (255, 128, 0),
+                    minute: (0, 0, 0),
+                    second: (1, 2, 3),
+                },
+                brightness: 40,
+            },
+            power: false,
+            night: true,
+            do_not_disturb: true,
+            pomodoro: Some(PomodoroPhase::Break),
+            stopwatch_running: true,
+            alarm_ringing: true,
+            next_alarm: Some(LocalDateTime::new(
+                LocalTime::new(6, 45, 0).unwrap(),
+                Some(Date::new(2025, 1, 13).unwrap()),
+            )),
+        };
+        let json: Value = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(
+            json,
+            json!({
+                "mode": "pomodoro",
+                "colors": {"hour": "#ff8000", "minute": "#000000", "second": "#010203"},
+                "brightness": 40,
+                "power": false,
+                "night": true,
+                "dnd": true,
+                "pomodoro": "break",
+                "stopwatch": "running",
+                "alarm_ringing": true,
+                "next_alarm": "2025-01-13T06:45:00",
+            })
+        );
+    }
+}
@@ -0,0 +1,144 @@
+//! A time kept in memory that survives resets, such as the RTC memory of an
+//! ESP32.
+
+use crate::date::{Date, LocalDateTime};
+use crate::time::{LocalTime, SECONDS_PER_DAY};
+
+/// Milliseconds in a day.
+const DAY_MS: u64 = SECONDS_PER_DAY as u64 * 1000;
+
+/// Longest time a [`RetainedTime`] is restored after; beyond it, the drift
+/// of the counter measuring the gap would show.
+pub const MAX_RETAINED_AGE_US: u64 = 24 * 60 * 60 * 1_000_000;
+
+/// Marks a checksum as belonging to a saved time rather than random memory.
+const MAGIC: u32 = 0x5254_434b;
+
+/// Stands in for the days of a time without a date.
+const NO_DATE: i32 = i32::MIN;
+
+/// A time saved together with the counter value it was saved at.
+///
+/// Memory that is not cleared on reset holds random bits after power-on, so
+/// the struct consists of integers only, making any bit pattern a value,
+/// and [`RetainedTime::restore`] only accepts it if its checksum matches.
+/// The counter, in microseconds, must keep running across the resets the
+/// time should survive, and start from zero on power-on.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{LocalDateTime, LocalTime, RetainedTime};
+///
+/// let time = LocalDateTime::from(LocalTime::new(23, 59, 0).unwrap());
+/// let retained = RetainedTime::new(time, 5_000_000);
+/// let later = retained.restore(95_000_000).unwrap();
+/// assert_eq!(later.time, LocalTime::new(0, 0, 30).unwrap());
+///
+/// // The counter started over, so the time was lost with the power
+/// assert_eq!(retained.restore(1_000_000), None);
+/// assert_eq!(RetainedTime::EMPTY.restore(5_000_000), None);
+/// ```
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetainedTime {
+    millis_since_midnight: u32,
+    /// Days since 1970-01-01, or [`NO_DATE`]
+    days: i32,
+    saved_us: u64,
+    check: u32,
+}
+
+impl RetainedTime {
+    /// A value that restores to nothing.
+    pub const EMPTY: Self = Self {
+        millis_since_midnight: 0,
+        days: NO_DATE,
+        saved_us: 0,
+        check: 0,
+    };
+
+    /// Saves `time` at counter value `now_us`.
+    pub fn new(time: LocalDateTime, now_us: u64) -> Self {
+        let mut retained = Self {
+            millis_since_midnight: time.millis_since_midnight(),
+            days: time
+                .date
+                .map_or(NO_DATE, |date| date.days_since_epoch() as i32),
+            saved_us: now_us,
+            check: 0,
+        };
+        retained.check = retained.checksum();
+        retained
+    }
+
+    /// Returns the saved time advanced to counter value `now_us`, or `None`
+    /// if nothing valid was saved, the counter started over, or more than
+    /// [`MAX_RETAINED_AGE_US`] passed.
+    pub fn restore(&self, now_us: u64) -> Option<LocalDateTime> {
+        if self.check != self.checksum() || u64::from(self.millis_since_midnight) >= DAY_MS {
+            return None;
+        }
+        let elapsed_us = now_us
+            .checked_sub(self.saved_us)
+            .filter(|&elapsed| elapsed <= MAX_RETAINED_AGE_US)?;
+        let total_ms = u64::from(self.millis_since_midnight) + elapsed_us / 1000;
+        let day_ms = total_ms % DAY_MS;
+        let time = LocalTime::from_seconds((day_ms / 1000) as u32);
+        let date = (self.days != NO_DATE).then(|| {
+            Date::from_days_since_epoch(i64::from(self.days) + (total_ms / DAY_MS) as i64)
+        });
+        Some(LocalDateTime::new(time, date).with_millis((day_ms % 1000) as u16))
+    }
+
+    /// Mixes all fields but the checksum itself.
+    fn checksum(&self) -> u32 {
+        let words = [
+            self.millis_since_midnight,
+            self.days as u32,
+            self.saved_us as u32,
+            (self.saved_us >> 32) as u32,
+        ];
+        words.iter().fold(MAGIC, |hash, &word| {
+            (hash ^ word).wrapping_mul(0x0100_0193).rotate_left(5)
+        })
+    }
+}
+
+impl Default for RetainedTime {
+    fn default() -> Self {
+        Self::EMPTY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_date_advances_past_midnight() {
+        let date = Date::new(2024, 12, 31).unwrap();
+        let time =
+            LocalDateTime::new(LocalTime::new(23, 0, 0).unwrap(), Some(date)).with_millis(250);
+        let retained = RetainedTime::new(time, 0);
+        assert_eq!(retained.restore(0), Some(time));
+
+        let later = retained.restore(3_600_500_000).unwrap();
+        assert_eq!(later.time, LocalTime::new(0, 0, 0).unwrap());
+        assert_eq!(later.millis, 750);
+        assert_eq!(later.date, Some(Date::new(2025, 1, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_rejects_old_or_corrupt() {
+        let time = LocalDateTime::from(LocalTime::new(8, 0, 0).unwrap());
+        let retained = RetainedTime::new(time, 1_000);
+        assert!(retained.restore(1_000 + MAX_RETAINED_AGE_US).is_some());
+        assert_eq!(retained.restore(1_001 + MAX_RETAINED_AGE_US), None);
+
+        let mut flipped = retained;
+        flipped.millis_since_midnight ^= 1;
+        assert_eq!(flipped.restore(1_000), None);
+        assert_eq!(RetainedTime::default().restore(0), None);
+    }
+}
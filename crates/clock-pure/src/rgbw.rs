@@ -0,0 +1,136 @@
+//! Colors for RGBW LEDs such as the SK6812, which add a white die to red, green, and blue.
+
+use crate::settings::SettingsError;
+use crate::{Frame, Rgb, LED_COUNT};
+
+/// An RGBW color as a (red, green, blue, white) tuple.
+pub type Rgbw = (u8, u8, u8, u8);
+
+/// Size of an encoded SK6812 RGBW frame for the ring.
+pub const SK6812_FRAME_BYTES: usize = 4 * LED_COUNT;
+
+/// How RGB colors use the white die of RGBW LEDs.
+///
+/// Everything is drawn in RGB; the policy decides how much of each color is
+/// moved to the white die just before it is sent to the LEDs. The white die
+/// gives whites and pastels, e.g. warm-white themes, a cleaner tone than
+/// mixing red, green, and blue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WhitePolicy {
+    /// Leave the white die dark, like an RGB LED
+    Off,
+    /// Move the part all three channels share to the white die
+    #[default]
+    Extract,
+    /// Add the shared part on the white die, keeping the RGB mix for extra
+    /// brightness at the cost of a less accurate tone
+    Boost,
+}
+
+impl WhitePolicy {
+    /// Converts an RGB color to RGBW according to the policy.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::WhitePolicy;
+    ///
+    /// // Warm white: mostly white die, with some red and green for warmth
+    /// assert_eq!(WhitePolicy::Extract.to_rgbw((255, 180, 100)), (155, 80, 0, 100));
+    /// assert_eq!(WhitePolicy::Off.to_rgbw((255, 180, 100)), (255, 180, 100, 0));
+    /// ```
+    pub fn to_rgbw(self, (r, g, b): Rgb) -> Rgbw {
+        let white = r.min(g).min(b);
+        match self {
+            WhitePolicy::Off => (r, g, b, 0),
+            WhitePolicy::Extract => (r - white, g - white, b - white, white),
+            WhitePolicy::Boost => (r, g, b, white),
+        }
+    }
+
+    /// Returns the name of the policy as used in the configuration.
+    pub const fn name(self) -> &'static str {
+        match self {
+            WhitePolicy::Off => "off",
+            WhitePolicy::Extract => "extract",
+            WhitePolicy::Boost => "boost",
+        }
+    }
+}
+
+impl core::str::FromStr for WhitePolicy {
+    type Err = SettingsError;
+
+    /// Parses `off`, `extract`, or `boost` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("off") => Ok(WhitePolicy::Off),
+            t if t.eq_ignore_ascii_case("extract") => Ok(WhitePolicy::Extract),
+            t if t.eq_ignore_ascii_case("boost") => Ok(WhitePolicy::Boost),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// Encodes `frame` for a ring of SK6812 RGBW LEDs.
+///
+/// Each LED takes four bytes in the green, red, blue, white order the LEDs
+/// expect, sent most significant bit first.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{encode_sk6812, Frame, WhitePolicy};
+///
+/// let mut frame = Frame::new();
+/// frame[0] = (255, 255, 255);
+/// frame[1] = (0, 0, 255);
+/// let bytes = encode_sk6812(&frame, WhitePolicy::Extract);
+/// assert_eq!(bytes[..8], [0, 0, 0, 255, 0, 0, 255, 0]);
+/// ```
+pub fn encode_sk6812(frame: &Frame, policy: WhitePolicy) -> [u8; SK6812_FRAME_BYTES] {
+    let mut bytes = [0; SK6812_FRAME_BYTES];
+    for (led, &color) in bytes.chunks_exact_mut(4).zip(frame.pixels()) {
+        let (r, g, b, w) = policy.to_rgbw(color);
+        led.copy_from_slice(&[g, r, b, w]);
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saturated_colors_leave_white_dark() {
+        for policy in [WhitePolicy::Off, WhitePolicy::Extract, WhitePolicy::Boost] {
+            assert_eq!(policy.to_rgbw((255, 0, 0)), (255, 0, 0, 0));
+            assert_eq!(policy.to_rgbw((0, 40, 200)), (0, 40, 200, 0));
+        }
+    }
+
+    #[test]
+    fn test_pure_white() {
+        assert_eq!(WhitePolicy::Extract.to_rgbw((90, 90, 90)), (0, 0, 0, 90));
+        assert_eq!(WhitePolicy::Boost.to_rgbw((90, 90, 90)), (90, 90, 90, 90));
+        assert_eq!(WhitePolicy::Off.to_rgbw((90, 90, 90)), (90, 90, 90, 0));
+    }
+
+    #[test]
+    fn test_encode_order() {
+        let frame = Frame::from_pixels([(10, 20, 30); LED_COUNT]);
+        let bytes = encode_sk6812(&frame, WhitePolicy::Extract);
+        assert_eq!(bytes[44..], [10, 0, 20, 10]);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(" Boost ".parse(), Ok(WhitePolicy::Boost));
+        assert_eq!("off".parse(), Ok(WhitePolicy::Off));
+        assert_eq!(
+            "warm".parse::<WhitePolicy>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(WhitePolicy::default().name(), "extract");
+    }
+}
@@ -0,0 +1,456 @@
+//! Named presets bundling the display mode, hand colors, and brightness.
+
+use crate::mode::DisplayMode;
+use crate::settings::{HandColors, SettingsError};
+#[cfg(feature = "std")]
+use std::string::{String, ToString};
+
+/// Maximum number of scenes in [`Scenes`].
+pub const MAX_SCENES: usize = 8;
+
+/// Maximum length of a scene name in bytes.
+pub const MAX_SCENE_NAME: usize = 16;
+
+/// How the clock looks in a scene.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scene {
+    pub mode: DisplayMode,
+    pub colors: HandColors,
+    /// Brightness level (0-255) of the face
+    pub brightness: u8,
+}
+
+impl Scene {
+    /// Size of the binary form used by [`Scene::to_bytes`].
+    pub const BYTES: usize = DisplayMode::BYTES + HandColors::BYTES + 1;
+
+    /// Encodes the scene for persistent storage: mode, colors, then brightness.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        let (mode, rest) = bytes.split_at_mut(DisplayMode::BYTES);
+        let (colors, brightness) = rest.split_at_mut(HandColors::BYTES);
+        mode.copy_from_slice(&self.mode.to_bytes());
+        colors.copy_from_slice(&self.colors.to_bytes());
+        brightness[0] = self.brightness;
+        bytes
+    }
+
+    /// Decodes a scene stored with [`Scene::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        if bytes.len() != Self::BYTES {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        }
+        let (mode, rest) = bytes.split_at(DisplayMode::BYTES);
+        let (colors, brightness) = rest.split_at(HandColors::BYTES);
+        Ok(Self {
+            mode: DisplayMode::from_bytes(mode)?,
+            colors: HandColors::from_bytes(colors)?,
+            brightness: brightness[0],
+        })
+    }
+}
+
+/// A scene name: 1 to [`MAX_SCENE_NAME`] ASCII letters, digits, `-`, or `_`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SceneName {
+    /// The name, zero-padded
+    bytes: [u8; MAX_SCENE_NAME],
+}
+
+impl SceneName {
+    /// Checks `text` and stores it as a name.
+    pub fn new(text: &str) -> Result<Self, SettingsError> {
+        let valid = |b: u8| b.is_ascii_alphanumeric() || b == b'-' || b == b'_';
+        if text.is_empty() || !text.bytes().all(valid) {
+            return Err(SettingsError::InvalidValue);
+        }
+        if text.len() > MAX_SCENE_NAME {
+            return Err(SettingsError::OutOfRange {
+                field: "scene name",
+                value: text.len() as u32,
+            });
+        }
+        let mut bytes = [0; MAX_SCENE_NAME];
+        bytes[..text.len()].copy_from_slice(text.as_bytes());
+        Ok(Self { bytes })
+    }
+
+    /// Returns the name as written.
+    pub fn as_str(&self) -> &str {
+        let len = self
+            .bytes
+            .iter()
+            .position(|&b| b == 0)
+            .unwrap_or(MAX_SCENE_NAME);
+        // Only ASCII is ever stored
+        core::str::from_utf8(&self.bytes[..len]).unwrap_or_default()
+    }
+
+    /// Returns true if the name equals `text`, ignoring ASCII case.
+    fn matches(&self, text: &str) -> bool {
+        self.as_str().eq_ignore_ascii_case(text)
+    }
+}
+
+impl core::fmt::Debug for SceneName {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("SceneName").field(&self.as_str()).finish()
+    }
+}
+
+/// A table of named scenes, recalled by name.
+///
+/// Names are compared ignoring ASCII case, so `Movie-Night` recalls
+/// `movie-night`.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{DisplayMode, HandColors, Scene, Scenes};
+///
+/// let movie = Scene {
+///     mode: DisplayMode::Ambient { color: (255, 60, 0) },
+///     colors: HandColors::default(),
+///     brightness: 3,
+/// };
+/// let mut scenes = Scenes::new();
+/// scenes.set("movie-night", movie).unwrap();
+///
+/// assert_eq!(scenes.get("Movie-Night"), Some(movie));
+/// assert_eq!(Scenes::from_bytes(&scenes.to_bytes()), Ok(scenes));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Scenes {
+    slots: [Option<(SceneName, Scene)>; MAX_SCENES],
+}
+
+impl Scenes {
+    /// Size of all slots in the binary form used by [`Scenes::to_bytes`].
+    pub const BYTES: usize = MAX_SCENES * (MAX_SCENE_NAME + Scene::BYTES);
+
+    /// Creates an empty table.
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; MAX_SCENES],
+        }
+    }
+
+    /// Returns the scene called `name`, if any.
+    pub fn get(&self, name: &str) -> Option<Scene> {
+        self.iter()
+            .find(|(existing, _)| existing.eq_ignore_ascii_case(name))
+            .map(|(_, scene)| scene)
+    }
+
+    /// Returns the names and scenes in the table.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Scene)> + '_ {
+        self.slots
+            .iter()
+            .flatten()
+            .map(|(name, scene)| (name.as_str(), *scene))
+    }
+
+    /// Stores `scene` as `name`, replacing a scene of the same name.
+    ///
+    /// Fails if the name is invalid or all [`MAX_SCENES`] slots hold other scenes.
+    pub fn set(&mut self, name: &str, scene: Scene) -> Result<(), SettingsError> {
+        let name = SceneName::new(name)?;
+        let full = SettingsError::OutOfRange {
+            field: "scenes",
+            value: MAX_SCENES as u32 + 1,
+        };
+        let slot = self
+            .position(name.as_str())
+            .or_else(|| self.slots.iter().position(Option::is_none))
+            .ok_or(full)?;
+        self.slots[slot] = Some((name, scene));
+        Ok(())
+    }
+
+    /// Removes the scene called `name`; returns false if there was none.
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(slot) => {
+                self.slots[slot] = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.slots
+            .iter()
+            .position(|slot| slot.is_some_and(|(existing, _)| existing.matches(name)))
+    }
+
+    /// Returns the slots in their binary form for persistent storage.
+    ///
+    /// Each slot holds the zero-padded name followed by the scene; an empty
+    /// slot is all zeros.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        let chunks = bytes.chunks_exact_mut(MAX_SCENE_NAME + Scene::BYTES);
+        for (chunk, slot) in chunks.zip(self.slots.iter()) {
+            if let Some((name, scene)) = slot {
+                chunk[..MAX_SCENE_NAME].copy_from_slice(&name.bytes);
+                chunk[MAX_SCENE_NAME..].copy_from_slice(&scene.to_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Restores the slots from the form written by [`Scenes::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        if bytes.len() != Self::BYTES {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        }
+
+        let mut scenes = Self::new();
+        let chunks = bytes.chunks_exact(MAX_SCENE_NAME + Scene::BYTES);
+        for (slot, chunk) in scenes.slots.iter_mut().zip(chunks) {
+            let (name, scene) = chunk.split_at(MAX_SCENE_NAME);
+            if name[0] == 0 {
+                continue;
+            }
+            let len = name.iter().position(|&b| b == 0).unwrap_or(MAX_SCENE_NAME);
+            let name =
+                core::str::from_utf8(&name[..len]).map_err(|_| SettingsError::InvalidUtf8)?;
+            *slot = Some((SceneName::new(name)?, Scene::from_bytes(scene)?));
+        }
+        Ok(scenes)
+    }
+
+    /// Returns the scenes with an update from an MQTT payload applied.
+    ///
+    /// The payload is a JSON object naming the scene and the fields to
+    /// change: `mode` (a mode name, or an object as accepted on the mode
+    /// topic), `colors` (as accepted on the colors topic), and `brightness`.
+    /// A new scene starts from `current`, so `{"name":"reading"}` saves the
+    /// clock as it looks now; `"delete": true` removes the scene. Nothing
+    /// changes on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{DisplayMode, HandColors, Scene, Scenes};
+    ///
+    /// let now = Scene {
+    ///     mode: DisplayMode::Clock,
+    ///     colors: HandColors::default(),
+    ///     brightness: 10,
+    /// };
+    /// let scenes = Scenes::new()
+    ///     .with_update(br#"{"name":"night","mode":"off","brightness":2}"#, now)
+    ///     .unwrap();
+    /// assert_eq!(scenes.get("night").unwrap().mode, DisplayMode::Off);
+    ///
+    /// let scenes = scenes.with_update(br#"{"name":"night","delete":true}"#, now).unwrap();
+    /// assert_eq!(scenes.get("night"), None);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_update(mut self, payload: &[u8], current: Scene) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            name: String,
+            mode: Option<serde_json::Value>,
+            colors: Option<serde_json::Value>,
+            brightness: Option<u8>,
+            #[serde(default)]
+            delete: bool,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        if update.delete {
+            return match self.remove(&update.name) {
+                true => Ok(self),
+                false => Err(SettingsError::InvalidValue),
+            };
+        }
+
+        let base = self.get(&update.name).unwrap_or(current);
+        let mode = match update.mode {
+            Some(serde_json::Value::String(name)) => DisplayMode::parse(name.as_bytes())?,
+            Some(mode @ serde_json::Value::Object(_)) => {
+                DisplayMode::parse(mode.to_string().as_bytes())?
+            }
+            Some(_) => return Err(SettingsError::InvalidJson),
+            None => base.mode,
+        };
+        let colors = match update.colors {
+            Some(colors) => base.colors.with_update(colors.to_string().as_bytes())?,
+            None => base.colors,
+        };
+        self.set(
+            &update.name,
+            Scene {
+                mode,
+                colors,
+                brightness: update.brightness.unwrap_or(base.brightness),
+            },
+        )?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::format;
+
+    fn scene(brightness: u8) -> Scene {
+        Scene {
+            mode: DisplayMode::Clock,
+            colors: HandColors::default(),
+            brightness,
+        }
+    }
+
+    #[test]
+    fn test_set_replaces_same_name() {
+        let mut scenes = Scenes::new();
+        scenes.set("reading", scene(40)).unwrap();
+        scenes.set("READING", scene(60)).unwrap();
+        assert_eq!(scenes.iter().count(), 1);
+        assert_eq!(scenes.get("reading"), Some(scene(60)));
+        // The name takes the latest spelling
+        assert_eq!(scenes.iter().next().unwrap().0, "READING");
+    }
+
+    #[test]
+    fn test_set_fails_when_full() {
+        let mut scenes = Scenes::new();
+        for i in 0..MAX_SCENES {
+            scenes.set(&format!("scene-{i}"), scene(1)).unwrap();
+        }
+        assert_eq!(
+            scenes.set("one-more", scene(1)),
+            Err(SettingsError::OutOfRange {
+                field: "scenes",
+                value: 9
+            })
+        );
+        assert!(scenes.remove("scene-3"));
+        assert!(!scenes.remove("scene-3"));
+        assert_eq!(scenes.set("one-more", scene(1)), Ok(()));
+    }
+
+    #[test]
+    fn test_invalid_names() {
+        let mut scenes = Scenes::new();
+        for name in ["", "movie night", "kino/abend", "café"] {
+            assert_eq!(
+                scenes.set(name, scene(1)),
+                Err(SettingsError::InvalidValue),
+                "{name}"
+            );
+        }
+        assert_eq!(
+            scenes.set("a-much-too-long-name", scene(1)),
+            Err(SettingsError::OutOfRange {
+                field: "scene name",
+                value: 20
+            })
+        );
+        assert_eq!(scenes.set("exactly_16_chars", scene(1)), Ok(()));
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut scenes = Scenes::new();
+        scenes
+            .set(
+                "movie-night",
+                Scene {
+                    mode: DisplayMode::Ambient {
+                        color: (255, 60, 0),
+                    },
+                    colors: HandColors::default(),
+                    brightness: 3,
+                },
+            )
+            .unwrap();
+        scenes.set("exactly_16_chars", scene(200)).unwrap();
+        scenes.remove("movie-night");
+        assert_eq!(Scenes::from_bytes(&scenes.to_bytes()), Ok(scenes));
+        assert_eq!(
+            Scenes::from_bytes(&[0; Scenes::BYTES]),
+            Ok(Scenes::default())
+        );
+    }
+
+    #[test]
+    fn test_from_bytes_errors() {
+        assert_eq!(
+            Scenes::from_bytes(&[0; 10]),
+            Err(SettingsError::InvalidLength(10))
+        );
+        let mut bytes = [0; Scenes::BYTES];
+        bytes[0] = b' ';
+        assert_eq!(Scenes::from_bytes(&bytes), Err(SettingsError::InvalidValue));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_keeps_other_fields() {
+        let scenes = Scenes::new()
+            .with_update(
+                br#"{"name":"focus","mode":{"mode":"countdown","seconds":60},"colors":{"hour":"blue"}}"#,
+                scene(10),
+            )
+            .unwrap();
+        let focus = scenes.get("focus").unwrap();
+        assert_eq!(
+            focus.mode,
+            DisplayMode::Countdown {
+                duration_ms: 60_000
+            }
+        );
+        assert_eq!(focus.colors.hour, (0, 0, 255));
+        assert_eq!(focus.brightness, 10);
+
+        let scenes = scenes
+            .with_update(br#"{"name":"focus","brightness":50}"#, scene(99))
+            .unwrap();
+        let focus = scenes.get("focus").unwrap();
+        assert_eq!(
+            focus.mode,
+            DisplayMode::Countdown {
+                duration_ms: 60_000
+            }
+        );
+        assert_eq!(focus.brightness, 50);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_errors() {
+        let scenes = Scenes::new();
+        for (payload, error) in [
+            (
+                &br#"{"name":"x","delete":true}"#[..],
+                SettingsError::InvalidValue,
+            ),
+            (
+                br#"{"name":"x","mode":"sparkle"}"#,
+                SettingsError::InvalidValue,
+            ),
+            (br#"{"name":"x","mode":3}"#, SettingsError::InvalidJson),
+            (
+                br#"{"name":"x","colors":{"hour":"gold"}}"#,
+                SettingsError::UnknownColor,
+            ),
+            (br#"{"name":"x","volume":3}"#, SettingsError::InvalidJson),
+            (br#"{"mode":"off"}"#, SettingsError::InvalidJson),
+        ] {
+            assert_eq!(
+                scenes.clone().with_update(payload, scene(1)),
+                Err(error),
+                "{}",
+                core::str::from_utf8(payload).unwrap()
+            );
+        }
+    }
+}
@@ -0,0 +1,558 @@
+//! Actions the clock runs by itself at set times, without a home-automation hub.
+
+use crate::alarm::AlarmDays;
+use crate::date::{Date, Weekday};
+use crate::effects::StartupAnimation;
+use crate::scene::{SceneName, MAX_SCENE_NAME};
+use crate::settings::SettingsError;
+use crate::time::LocalTime;
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// Number of schedule slots.
+pub const MAX_SCHEDULE_ENTRIES: usize = 8;
+
+/// How long a scheduled effect plays unless told otherwise.
+#[cfg(feature = "std")]
+const DEFAULT_EFFECT_SECONDS: u16 = 30;
+
+/// What a schedule entry does when it is due.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduledAction {
+    /// Recalls the scene with this name
+    Scene(SceneName),
+    /// Sets the brightness (0-255) of the face
+    Brightness(u8),
+    /// Plays a built-in animation for some seconds, then returns to the face
+    Effect {
+        animation: StartupAnimation,
+        seconds: u16,
+    },
+    /// Switches the display on or off
+    Power(bool),
+}
+
+impl ScheduledAction {
+    /// Size of the binary form used by [`ScheduledAction::to_bytes`].
+    pub const BYTES: usize = 1 + MAX_SCENE_NAME;
+
+    /// Encodes the action for persistent storage: a tag byte, then its value.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        let (tag, value) = bytes.split_at_mut(1);
+        match *self {
+            ScheduledAction::Scene(name) => {
+                tag[0] = 0;
+                value[..name.as_str().len()].copy_from_slice(name.as_str().as_bytes());
+            }
+            ScheduledAction::Brightness(level) => {
+                tag[0] = 1;
+                value[0] = level;
+            }
+            ScheduledAction::Effect { animation, seconds } => {
+                tag[0] = 2;
+                let index = StartupAnimation::ALL.iter().position(|&a| a == animation);
+                value[0] = index.unwrap_or_default() as u8;
+                value[1..3].copy_from_slice(&seconds.to_le_bytes());
+            }
+            ScheduledAction::Power(on) => {
+                tag[0] = 3;
+                value[0] = u8::from(on);
+            }
+        }
+        bytes
+    }
+
+    /// Decodes an action stored with [`ScheduledAction::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        if bytes.len() != Self::BYTES {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        }
+        let value = &bytes[1..];
+        match bytes[0] {
+            0 => {
+                let len = value.iter().position(|&b| b == 0).unwrap_or(value.len());
+                let name =
+                    core::str::from_utf8(&value[..len]).map_err(|_| SettingsError::InvalidUtf8)?;
+                Ok(ScheduledAction::Scene(SceneName::new(name)?))
+            }
+            1 => Ok(ScheduledAction::Brightness(value[0])),
+            2 => Ok(ScheduledAction::Effect {
+                animation: *StartupAnimation::ALL
+                    .get(usize::from(value[0]))
+                    .ok_or(SettingsError::InvalidValue)?,
+                seconds: u16::from_le_bytes([value[1], value[2]]),
+            }),
+            3 => Ok(ScheduledAction::Power(value[0] != 0)),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// An action with the time and days it runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScheduleEntry {
+    /// When the action runs; seconds are ignored.
+    pub time: LocalTime,
+    pub days: AlarmDays,
+    pub action: ScheduledAction,
+    pub enabled: bool,
+}
+
+impl ScheduleEntry {
+    /// Size of one entry in the binary form used by [`Schedule::to_bytes`].
+    pub const BYTES: usize = 4 + ScheduledAction::BYTES;
+
+    /// Creates an enabled entry.
+    pub const fn new(time: LocalTime, days: AlarmDays, action: ScheduledAction) -> Self {
+        Self {
+            time,
+            days,
+            action,
+            enabled: true,
+        }
+    }
+
+    /// Returns true if the entry is enabled and runs on `day`.
+    pub const fn runs_on(&self, day: Weekday) -> bool {
+        self.enabled && self.days.contains(day)
+    }
+
+    /// Returns the minutes since midnight the entry runs at.
+    const fn minute_of_day(&self) -> u16 {
+        self.time.hour as u16 * 60 + self.time.minute as u16
+    }
+}
+
+/// The schedule slots, checked against the local time.
+///
+/// Call [`Schedule::update`] regularly with the current date and time; each
+/// entry is due once in its minute, like an alarm.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{AlarmDays, Date, LocalTime, Schedule, ScheduleEntry, ScheduledAction};
+///
+/// let mut schedule = Schedule::new();
+/// let dim = ScheduleEntry::new(
+///     LocalTime::new(22, 30, 0).unwrap(),
+///     AlarmDays::EVERY_DAY,
+///     ScheduledAction::Brightness(2),
+/// );
+/// schedule.set(0, dim).unwrap();
+///
+/// let today = Date::new(2024, 6, 10).unwrap();
+/// let due: Vec<_> = schedule.update(today, LocalTime::new(22, 30, 12).unwrap()).collect();
+/// assert_eq!(due, [(0, ScheduledAction::Brightness(2))]);
+/// // Only once per minute
+/// assert_eq!(schedule.update(today, LocalTime::new(22, 30, 13).unwrap()).count(), 0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Schedule {
+    slots: [Option<ScheduleEntry>; MAX_SCHEDULE_ENTRIES],
+    /// The date and minute of the last update, so an entry runs once per minute.
+    last_checked: Option<(Date, u16)>,
+}
+
+impl Schedule {
+    /// Size of all slots in the binary form used by [`Schedule::to_bytes`].
+    pub const BYTES: usize = MAX_SCHEDULE_ENTRIES * ScheduleEntry::BYTES;
+
+    /// Creates empty schedule slots.
+    pub const fn new() -> Self {
+        Self {
+            slots: [None; MAX_SCHEDULE_ENTRIES],
+            last_checked: None,
+        }
+    }
+
+    /// Returns the entry in `slot`, if any.
+    pub fn get(&self, slot: usize) -> Option<ScheduleEntry> {
+        self.slots.get(slot).copied().flatten()
+    }
+
+    /// Returns the occupied slots and their entries.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, ScheduleEntry)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(slot, entry)| entry.map(|entry| (slot, entry)))
+    }
+
+    /// Stores `entry` in `slot`, replacing any entry there.
+    pub fn set(&mut self, slot: usize, entry: ScheduleEntry) -> Result<(), SettingsError> {
+        *self.slot_mut(slot)? = Some(entry);
+        Ok(())
+    }
+
+    /// Clears `slot`.
+    pub fn remove(&mut self, slot: usize) -> Result<(), SettingsError> {
+        *self.slot_mut(slot)? = None;
+        Ok(())
+    }
+
+    /// Checks the schedule at the given local date and time.
+    ///
+    /// Returns the slots and actions of the entries due now, in slot order.
+    /// Each entry is due at most once in its minute, however often this is
+    /// called.
+    pub fn update(
+        &mut self,
+        date: Date,
+        time: LocalTime,
+    ) -> impl Iterator<Item = (usize, ScheduledAction)> + '_ {
+        let minute = time.hour as u16 * 60 + time.minute as u16;
+        let first_check = self.last_checked != Some((date, minute));
+        self.last_checked = Some((date, minute));
+
+        let weekday = date.weekday();
+        self.iter()
+            .filter(move |(_, entry)| {
+                first_check && entry.runs_on(weekday) && entry.minute_of_day() == minute
+            })
+            .map(|(slot, entry)| (slot, entry.action))
+    }
+
+    /// Returns the slots in their binary form for persistent storage.
+    ///
+    /// Each slot takes [`ScheduleEntry::BYTES`]: hour, minute, days, a flags
+    /// byte (bit 0: occupied, bit 1: enabled), and the action.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        for (slot, entry) in self.iter() {
+            let flags = 0b01 | if entry.enabled { 0b10 } else { 0 };
+            let chunk = &mut bytes[slot * ScheduleEntry::BYTES..][..ScheduleEntry::BYTES];
+            chunk[..4].copy_from_slice(&[
+                entry.time.hour,
+                entry.time.minute,
+                entry.days.bits(),
+                flags,
+            ]);
+            chunk[4..].copy_from_slice(&entry.action.to_bytes());
+        }
+        bytes
+    }
+
+    /// Restores the slots from the form written by [`Schedule::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        if bytes.len() != Self::BYTES {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        }
+
+        let mut schedule = Self::new();
+        for (slot, chunk) in bytes.chunks_exact(ScheduleEntry::BYTES).enumerate() {
+            let [hour, minute, days, flags] = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            if flags & 0b01 == 0 {
+                continue;
+            }
+            let time = LocalTime::new(hour, minute, 0).map_err(|_| SettingsError::OutOfRange {
+                field: "schedule time",
+                value: u32::from(hour) * 100 + u32::from(minute),
+            })?;
+            schedule.slots[slot] = Some(ScheduleEntry {
+                time,
+                days: AlarmDays::from_bits(days),
+                action: ScheduledAction::from_bytes(&chunk[4..])?,
+                enabled: flags & 0b10 != 0,
+            });
+        }
+        Ok(schedule)
+    }
+
+    /// Returns the schedule with an update from an MQTT payload applied.
+    ///
+    /// The payload is a JSON object naming a `slot` (0-7) and the fields to
+    /// change: `time` (`HH:MM`), `days` (see [`AlarmDays`]), `enabled`, and
+    /// `action`, which holds exactly one of `scene` (a scene name),
+    /// `brightness` (0-255), `effect` (a startup animation name, with
+    /// optional `seconds`, default 30), or `power` (true or false). A new
+    /// entry needs a `time` and an `action` and defaults to every day;
+    /// `"delete": true` clears the slot. Nothing changes on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{AlarmDays, Schedule, ScheduledAction};
+    ///
+    /// let schedule = Schedule::new()
+    ///     .with_update(br#"{"slot":2,"time":"23:00","days":"daily","action":{"power":false}}"#)
+    ///     .unwrap();
+    /// assert_eq!(schedule.get(2).unwrap().action, ScheduledAction::Power(false));
+    ///
+    /// let schedule = schedule.with_update(br#"{"slot":2,"days":"weekdays"}"#).unwrap();
+    /// assert_eq!(schedule.get(2).unwrap().days, AlarmDays::WEEKDAYS);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_update(mut self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            slot: usize,
+            time: Option<String>,
+            days: Option<String>,
+            action: Option<ActionUpdate>,
+            enabled: Option<bool>,
+            #[serde(default)]
+            delete: bool,
+        }
+
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct ActionUpdate {
+            scene: Option<String>,
+            brightness: Option<u8>,
+            effect: Option<String>,
+            seconds: Option<u16>,
+            power: Option<bool>,
+        }
+
+        impl ActionUpdate {
+            fn parse(self) -> Result<ScheduledAction, SettingsError> {
+                match self {
+                    ActionUpdate {
+                        scene: Some(name),
+                        brightness: None,
+                        effect: None,
+                        seconds: None,
+                        power: None,
+                    } => Ok(ScheduledAction::Scene(SceneName::new(&name)?)),
+                    ActionUpdate {
+                        scene: None,
+                        brightness: Some(level),
+                        effect: None,
+                        seconds: None,
+                        power: None,
+                    } => Ok(ScheduledAction::Brightness(level)),
+                    ActionUpdate {
+                        scene: None,
+                        brightness: None,
+                        effect: Some(name),
+                        seconds,
+                        power: None,
+                    } => Ok(ScheduledAction::Effect {
+                        animation: name.parse().map_err(|_| SettingsError::InvalidValue)?,
+                        seconds: seconds.unwrap_or(DEFAULT_EFFECT_SECONDS),
+                    }),
+                    ActionUpdate {
+                        scene: None,
+                        brightness: None,
+                        effect: None,
+                        seconds: None,
+                        power: Some(on),
+                    } => Ok(ScheduledAction::Power(on)),
+                    _ => Err(SettingsError::InvalidValue),
+                }
+            }
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        if update.delete {
+            self.remove(update.slot)?;
+            return Ok(self);
+        }
+
+        let current = self.get(update.slot);
+        let time = match (update.time, current) {
+            (Some(text), _) => text.parse().map_err(|_| SettingsError::InvalidValue)?,
+            (None, Some(entry)) => entry.time,
+            (None, None) => return Err(SettingsError::InvalidValue),
+        };
+        let days = match (update.days, current) {
+            (Some(text), _) => text.parse()?,
+            (None, Some(entry)) => entry.days,
+            (None, None) => AlarmDays::EVERY_DAY,
+        };
+        let action = match (update.action, current) {
+            (Some(action), _) => action.parse()?,
+            (None, Some(entry)) => entry.action,
+            (None, None) => return Err(SettingsError::InvalidValue),
+        };
+        let enabled = update
+            .enabled
+            .or(current.map(|entry| entry.enabled))
+            .unwrap_or(true);
+        self.set(
+            update.slot,
+            ScheduleEntry {
+                time,
+                days,
+                action,
+                enabled,
+            },
+        )?;
+        Ok(self)
+    }
+
+    fn slot_mut(&mut self, slot: usize) -> Result<&mut Option<ScheduleEntry>, SettingsError> {
+        self.slots.get_mut(slot).ok_or(SettingsError::OutOfRange {
+            field: "schedule slot",
+            value: slot as u32,
+        })
+    }
+}
+
+impl Default for Schedule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::vec::Vec;
+
+    fn at(hour: u8, minute: u8) -> LocalTime {
+        LocalTime::new(hour, minute, 0).unwrap()
+    }
+
+    fn monday() -> Date {
+        Date::new(2024, 6, 10).unwrap()
+    }
+
+    #[test]
+    fn test_due_only_on_its_days() {
+        let mut schedule = Schedule::new();
+        let entry = ScheduleEntry::new(at(7, 0), AlarmDays::WEEKEND, ScheduledAction::Power(true));
+        schedule.set(0, entry).unwrap();
+        assert_eq!(schedule.update(monday(), at(7, 0)).count(), 0);
+        let saturday = Date::new(2024, 6, 15).unwrap();
+        assert_eq!(schedule.update(saturday, at(7, 0)).count(), 1);
+    }
+
+    #[test]
+    fn test_disabled_entry_never_due() {
+        let mut schedule = Schedule::new();
+        let mut entry =
+            ScheduleEntry::new(at(7, 0), AlarmDays::EVERY_DAY, ScheduledAction::Power(true));
+        entry.enabled = false;
+        schedule.set(0, entry).unwrap();
+        assert_eq!(schedule.update(monday(), at(7, 0)).count(), 0);
+    }
+
+    #[test]
+    fn test_entries_in_same_minute_all_due() {
+        let mut schedule = Schedule::new();
+        let scene = ScheduledAction::Scene(SceneName::new("night").unwrap());
+        schedule
+            .set(
+                3,
+                ScheduleEntry::new(at(22, 0), AlarmDays::EVERY_DAY, scene),
+            )
+            .unwrap();
+        schedule
+            .set(
+                1,
+                ScheduleEntry::new(
+                    at(22, 0),
+                    AlarmDays::EVERY_DAY,
+                    ScheduledAction::Brightness(1),
+                ),
+            )
+            .unwrap();
+        let due: Vec<_> = schedule.update(monday(), at(22, 0)).collect();
+        assert_eq!(due, [(1, ScheduledAction::Brightness(1)), (3, scene)]);
+    }
+
+    #[test]
+    fn test_due_again_next_day() {
+        let mut schedule = Schedule::new();
+        let entry = ScheduleEntry::new(
+            at(6, 0),
+            AlarmDays::EVERY_DAY,
+            ScheduledAction::Brightness(40),
+        );
+        schedule.set(0, entry).unwrap();
+        assert_eq!(schedule.update(monday(), at(6, 0)).count(), 1);
+        assert_eq!(schedule.update(monday(), at(6, 0)).count(), 0);
+        assert_eq!(schedule.update(monday().add_days(1), at(6, 0)).count(), 1);
+    }
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let mut schedule = Schedule::new();
+        let actions = [
+            ScheduledAction::Scene(SceneName::new("movie-night").unwrap()),
+            ScheduledAction::Brightness(200),
+            ScheduledAction::Effect {
+                animation: StartupAnimation::SpiralFill,
+                seconds: 600,
+            },
+            ScheduledAction::Power(false),
+        ];
+        for (slot, action) in actions.into_iter().enumerate() {
+            let mut entry = ScheduleEntry::new(at(slot as u8, 15), AlarmDays::WEEKDAYS, action);
+            entry.enabled = slot % 2 == 1;
+            schedule.set(slot * 2, entry).unwrap();
+        }
+        assert_eq!(Schedule::from_bytes(&schedule.to_bytes()), Ok(schedule));
+    }
+
+    #[test]
+    fn test_from_bytes_rejects_bad_input() {
+        assert_eq!(
+            Schedule::from_bytes(&[0; 3]),
+            Err(SettingsError::InvalidLength(3))
+        );
+        let mut bytes = [0; Schedule::BYTES];
+        bytes[..5].copy_from_slice(&[7, 0, 0x7f, 0b11, 9]);
+        assert_eq!(
+            Schedule::from_bytes(&bytes),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_actions() {
+        let schedule = Schedule::new()
+            .with_update(br#"{"slot":0,"time":"21:00","action":{"scene":"movie-night"}}"#)
+            .and_then(|s| {
+                s.with_update(br#"{"slot":1,"time":"07:00","action":{"effect":"rainbow_swirl"}}"#)
+            })
+            .and_then(|s| s.with_update(br#"{"slot":2,"time":"07:05","action":{"brightness":80}}"#))
+            .unwrap();
+        assert_eq!(
+            schedule.get(0).unwrap().action,
+            ScheduledAction::Scene(SceneName::new("movie-night").unwrap())
+        );
+        assert_eq!(
+            schedule.get(1).unwrap().action,
+            ScheduledAction::Effect {
+                animation: StartupAnimation::RainbowSwirl,
+                seconds: DEFAULT_EFFECT_SECONDS,
+            }
+        );
+        assert_eq!(schedule.get(2).unwrap().days, AlarmDays::EVERY_DAY);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_rejects_invalid() {
+        let schedule = Schedule::new();
+        for payload in [
+            &br#"{"slot":0,"time":"21:00"}"#[..],
+            br#"{"slot":0,"action":{"power":true}}"#,
+            br#"{"slot":0,"time":"21:00","action":{}}"#,
+            br#"{"slot":0,"time":"21:00","action":{"power":true,"brightness":3}}"#,
+            br#"{"slot":0,"time":"21:00","action":{"effect":"fireworks"}}"#,
+            br#"{"slot":0,"time":"21:00","action":{"brightness":3,"seconds":5}}"#,
+            br#"{"slot":8,"time":"21:00","action":{"power":true}}"#,
+        ] {
+            assert!(schedule.clone().with_update(payload).is_err());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_update_delete() {
+        let schedule = Schedule::new()
+            .with_update(br#"{"slot":4,"time":"12:00","action":{"power":true}}"#)
+            .unwrap()
+            .with_update(br#"{"slot":4,"delete":true}"#)
+            .unwrap();
+        assert_eq!(schedule.iter().count(), 0);
+    }
+}
@@ -0,0 +1,140 @@
+//! A dim ambient glow replacing the clock face while nobody is around.
+
+use crate::lut::phase_of;
+use crate::settings::{parse_on_off, SettingsError};
+use crate::{color_wheel, Rgb};
+
+/// How long the screensaver takes to drift once through all colors.
+pub const SCREENSAVER_CYCLE_MS: u32 = 300_000;
+
+/// Tracks activity and presence to decide when the ring may go idle.
+///
+/// The screensaver kicks in once there has been no activity for the timeout
+/// and no presence is reported. Reported presence keeps the clock awake
+/// until it is cleared; the timeout then counts from that moment.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::Screensaver;
+///
+/// let mut screensaver = Screensaver::new(60_000);
+/// assert!(!screensaver.is_idle(59_999));
+/// assert!(screensaver.is_idle(60_000));
+///
+/// assert!(screensaver.wake(70_000));
+/// assert!(!screensaver.is_idle(100_000));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Screensaver {
+    timeout_ms: u32,
+    last_activity_ms: u32,
+    present: bool,
+}
+
+impl Screensaver {
+    /// Creates a screensaver starting after `timeout_ms` without activity, counting from 0.
+    pub const fn new(timeout_ms: u32) -> Self {
+        Self {
+            timeout_ms,
+            last_activity_ms: 0,
+            present: false,
+        }
+    }
+
+    /// Returns true if the ring should show the screensaver at `now_ms`.
+    pub const fn is_idle(&self, now_ms: u32) -> bool {
+        !self.present && now_ms.wrapping_sub(self.last_activity_ms) >= self.timeout_ms
+    }
+
+    /// Records activity at `now_ms`, returning true if this ends the screensaver.
+    pub fn wake(&mut self, now_ms: u32) -> bool {
+        let was_idle = self.is_idle(now_ms);
+        self.last_activity_ms = now_ms;
+        was_idle
+    }
+
+    /// Records whether someone is present at `now_ms`, returning true if this ends the screensaver.
+    pub fn set_present(&mut self, present: bool, now_ms: u32) -> bool {
+        let woken = self.wake(now_ms);
+        self.present = present;
+        woken
+    }
+
+    /// Renders the ambient glow at `now_ms` into `buffer`, slowly drifting through the colors.
+    ///
+    /// Every pixel is overwritten at full intensity; dim the result for display.
+    pub fn render(&self, now_ms: u32, buffer: &mut [Rgb]) {
+        buffer.fill(color_wheel(phase_of(now_ms, SCREENSAVER_CYCLE_MS)));
+    }
+}
+
+/// Parses a presence payload: `on`/`off`, `true`/`false`, or `1`/`0` (case-insensitive).
+///
+/// These cover the states of a Home Assistant occupancy sensor.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::parse_presence;
+///
+/// assert_eq!(parse_presence(b"ON\n"), Ok(true));
+/// assert_eq!(parse_presence(b"0"), Ok(false));
+/// assert!(parse_presence(b"maybe").is_err());
+/// ```
+pub fn parse_presence(payload: &[u8]) -> Result<bool, SettingsError> {
+    parse_on_off(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_after_timeout() {
+        let mut screensaver = Screensaver::new(1_000);
+        screensaver.wake(5_000);
+        assert!(!screensaver.is_idle(5_999));
+        assert!(screensaver.is_idle(6_000));
+        assert!(screensaver.wake(6_500));
+        assert!(!screensaver.wake(7_000));
+    }
+
+    #[test]
+    fn test_presence_keeps_awake() {
+        let mut screensaver = Screensaver::new(1_000);
+        assert!(screensaver.set_present(true, 2_000));
+        assert!(!screensaver.is_idle(100_000));
+
+        // The timeout counts from when presence is cleared
+        assert!(!screensaver.set_present(false, 100_000));
+        assert!(!screensaver.is_idle(100_999));
+        assert!(screensaver.is_idle(101_000));
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let mut screensaver = Screensaver::new(1_000);
+        screensaver.wake(u32::MAX - 499);
+        assert!(!screensaver.is_idle(499));
+        assert!(screensaver.is_idle(500));
+    }
+
+    #[test]
+    fn test_render_drifts() {
+        let screensaver = Screensaver::new(0);
+        let mut buffer = [(0, 0, 0); 12];
+        screensaver.render(0, &mut buffer);
+        assert_eq!(buffer, [color_wheel(0); 12]);
+        screensaver.render(SCREENSAVER_CYCLE_MS / 2, &mut buffer);
+        assert_eq!(buffer[5], color_wheel(128));
+    }
+
+    #[test]
+    fn test_parse_presence() {
+        assert_eq!(parse_presence(b"true"), Ok(true));
+        assert_eq!(parse_presence(b" Off "), Ok(false));
+        assert_eq!(parse_presence(b""), Err(SettingsError::InvalidValue));
+        assert_eq!(parse_presence(&[0xff]), Err(SettingsError::InvalidUtf8));
+    }
+}
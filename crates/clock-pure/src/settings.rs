@@ -0,0 +1,357 @@
+//! Parsing of runtime settings received over MQTT.
+
+use crate::Rgb;
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// Error type for settings payloads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsError {
+    /// The payload is not valid UTF-8
+    InvalidUtf8,
+    /// The payload is not a valid value for the setting
+    InvalidValue,
+    /// The payload is not valid JSON or has unexpected fields
+    InvalidJson,
+    /// A color is neither a hex color nor a known color name
+    UnknownColor,
+    /// A binary settings blob has the wrong number of bytes
+    InvalidLength(usize),
+    /// A numeric value is outside the setting's range
+    OutOfRange { field: &'static str, value: u32 },
+}
+
+impl core::fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SettingsError::InvalidUtf8 => write!(f, "Invalid UTF-8 in settings payload"),
+            SettingsError::InvalidValue => write!(f, "Invalid settings value"),
+            SettingsError::InvalidJson => write!(f, "Invalid JSON in settings payload"),
+            SettingsError::UnknownColor => write!(f, "Unknown color"),
+            SettingsError::InvalidLength(len) => {
+                write!(f, "Invalid settings length: {} bytes", len)
+            }
+            SettingsError::OutOfRange { field, value } => {
+                write!(f, "Setting {} out of range: {}", field, value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for SettingsError {}
+
+/// Returns the payload as trimmed text.
+fn payload_text(payload: &[u8]) -> Result<&str, SettingsError> {
+    core::str::from_utf8(payload)
+        .map(str::trim)
+        .map_err(|_| SettingsError::InvalidUtf8)
+}
+
+/// Parses a brightness payload: a decimal number from 0 (off) to 255.
+///
+/// The brightness dims the hand colors to `brightness`/255 of their
+/// intensity; small values already give a clearly visible clock face.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::parse_brightness;
+///
+/// assert_eq!(parse_brightness(b"40\n"), Ok(40));
+/// assert!(parse_brightness(b"300").is_err());
+/// ```
+pub fn parse_brightness(payload: &[u8]) -> Result<u8, SettingsError> {
+    let text = payload_text(payload)?;
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(SettingsError::InvalidValue);
+    }
+    let value: u32 = text.parse().map_err(|_| SettingsError::InvalidValue)?;
+    u8::try_from(value).map_err(|_| SettingsError::OutOfRange {
+        field: "brightness",
+        value,
+    })
+}
+
+/// Parses a switch payload: `on`/`off`, `true`/`false`, or `1`/`0` (case-insensitive).
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::parse_on_off;
+///
+/// assert_eq!(parse_on_off(b"ON\n"), Ok(true));
+/// assert_eq!(parse_on_off(b"0"), Ok(false));
+/// assert!(parse_on_off(b"maybe").is_err());
+/// ```
+pub fn parse_on_off(payload: &[u8]) -> Result<bool, SettingsError> {
+    match payload_text(payload)? {
+        "1" => Ok(true),
+        "0" => Ok(false),
+        t if t.eq_ignore_ascii_case("on") || t.eq_ignore_ascii_case("true") => Ok(true),
+        t if t.eq_ignore_ascii_case("off") || t.eq_ignore_ascii_case("false") => Ok(false),
+        _ => Err(SettingsError::InvalidValue),
+    }
+}
+
+/// Named colors accepted by [`parse_color`], at full intensity.
+const NAMED_COLORS: [(&str, Rgb); 12] = [
+    ("black", (0, 0, 0)),
+    ("white", (255, 255, 255)),
+    ("red", (255, 0, 0)),
+    ("green", (0, 255, 0)),
+    ("blue", (0, 0, 255)),
+    ("yellow", (255, 255, 0)),
+    ("cyan", (0, 255, 255)),
+    ("magenta", (255, 0, 255)),
+    ("orange", (255, 128, 0)),
+    ("purple", (128, 0, 255)),
+    ("pink", (255, 64, 128)),
+    ("warmwhite", (255, 160, 64)),
+];
+
+/// Parses a color given as hex (`#ff8000` or `ff8000`) or by name (`orange`).
+///
+/// Names are case-insensitive; `off` is accepted as an alias for `black`.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::parse_color;
+///
+/// assert_eq!(parse_color("#ff8000"), Ok((255, 128, 0)));
+/// assert_eq!(parse_color("Blue"), Ok((0, 0, 255)));
+/// assert!(parse_color("chartreuse-ish").is_err());
+/// ```
+pub fn parse_color(text: &str) -> Result<Rgb, SettingsError> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("off") {
+        return Ok((0, 0, 0));
+    }
+    if let Some(&(_, color)) = NAMED_COLORS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(text))
+    {
+        return Ok(color);
+    }
+
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(SettingsError::UnknownColor);
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+    match (channel(0), channel(2), channel(4)) {
+        (Ok(r), Ok(g), Ok(b)) => Ok((r, g, b)),
+        _ => Err(SettingsError::UnknownColor),
+    }
+}
+
+/// The colors of the hour, minute, and second hands at full intensity.
+///
+/// The clock dims them by its brightness before they reach the LEDs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandColors {
+    pub hour: Rgb,
+    pub minute: Rgb,
+    pub second: Rgb,
+}
+
+impl HandColors {
+    /// Number of bytes in the binary form, see [`HandColors::to_bytes`].
+    pub const BYTES: usize = 9;
+
+    /// Returns the colors as `[hour, minute, second]`.
+    pub const fn to_array(self) -> [Rgb; 3] {
+        [self.hour, self.minute, self.second]
+    }
+
+    /// Encodes the colors as nine bytes (hour, minute, second; RGB each) for storage.
+    pub const fn to_bytes(self) -> [u8; Self::BYTES] {
+        let [h, m, s] = self.to_array();
+        [h.0, h.1, h.2, m.0, m.1, m.2, s.0, s.1, s.2]
+    }
+
+    /// Decodes colors stored with [`HandColors::to_bytes`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::HandColors;
+    ///
+    /// let colors = HandColors::default();
+    /// assert_eq!(HandColors::from_bytes(&colors.to_bytes()), Ok(colors));
+    /// ```
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        let &[hr, hg, hb, mr, mg, mb, sr, sg, sb] = bytes else {
+            return Err(SettingsError::InvalidLength(bytes.len()));
+        };
+        Ok(Self {
+            hour: (hr, hg, hb),
+            minute: (mr, mg, mb),
+            second: (sr, sg, sb),
+        })
+    }
+
+    /// Applies a JSON color update such as `{"hour":"#ff8000","second":"off"}`.
+    ///
+    /// Each hand is optional and keeps its current color when left out, so a
+    /// theme can be adjusted one hand at a time. Nothing changes on error.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::HandColors;
+    ///
+    /// let colors = HandColors::default()
+    ///     .with_update(br#"{"minute":"orange"}"#)
+    ///     .unwrap();
+    /// assert_eq!(colors.minute, (255, 128, 0));
+    /// assert_eq!(colors.hour, HandColors::default().hour);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with_update(self, payload: &[u8]) -> Result<Self, SettingsError> {
+        #[derive(serde::Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Update {
+            hour: Option<String>,
+            minute: Option<String>,
+            second: Option<String>,
+        }
+
+        let update: Update =
+            serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+        let pick = |color: Option<String>, current: Rgb| match color {
+            Some(text) => parse_color(&text),
+            None => Ok(current),
+        };
+        Ok(Self {
+            hour: pick(update.hour, self.hour)?,
+            minute: pick(update.minute, self.minute)?,
+            second: pick(update.second, self.second)?,
+        })
+    }
+}
+
+impl Default for HandColors {
+    /// Blue hour, green minute, and red second hands.
+    fn default() -> Self {
+        Self {
+            hour: (0, 0, 255),
+            minute: (0, 255, 0),
+            second: (255, 0, 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_brightness() {
+        assert_eq!(parse_brightness(b"0"), Ok(0));
+        assert_eq!(parse_brightness(b" 255 "), Ok(255));
+    }
+
+    #[test]
+    fn test_parse_brightness_out_of_range() {
+        assert_eq!(
+            parse_brightness(b"256"),
+            Err(SettingsError::OutOfRange {
+                field: "brightness",
+                value: 256
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_brightness_rejects_non_numbers() {
+        for payload in [&b""[..], b"-1", b"bright", b"1.5", b"99999999999"] {
+            assert_eq!(parse_brightness(payload), Err(SettingsError::InvalidValue));
+        }
+        assert_eq!(parse_brightness(&[0xff]), Err(SettingsError::InvalidUtf8));
+    }
+
+    // ===== Color tests =====
+
+    #[test]
+    fn test_parse_color_hex() {
+        assert_eq!(parse_color("#0080ff"), Ok((0, 128, 255)));
+        assert_eq!(parse_color("0080FF"), Ok((0, 128, 255)));
+        assert_eq!(parse_color(" #000000 "), Ok((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_parse_color_names() {
+        assert_eq!(parse_color("red"), Ok((255, 0, 0)));
+        assert_eq!(parse_color("WarmWhite"), Ok((255, 160, 64)));
+        assert_eq!(parse_color("off"), Ok((0, 0, 0)));
+    }
+
+    #[test]
+    fn test_parse_color_rejects_invalid() {
+        for text in [
+            "", "#", "#fff", "#12345", "#1234567", "#gg0000", "+12345", "reddish",
+        ] {
+            assert_eq!(
+                parse_color(text),
+                Err(SettingsError::UnknownColor),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_named_colors_are_unique() {
+        for (i, (name, _)) in NAMED_COLORS.iter().enumerate() {
+            assert!(NAMED_COLORS[i + 1..].iter().all(|(other, _)| other != name));
+        }
+    }
+
+    #[test]
+    fn test_hand_colors_bytes_roundtrip() {
+        let colors = HandColors {
+            hour: (1, 2, 3),
+            minute: (4, 5, 6),
+            second: (7, 8, 9),
+        };
+        assert_eq!(colors.to_bytes(), [1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        assert_eq!(HandColors::from_bytes(&colors.to_bytes()), Ok(colors));
+        assert_eq!(
+            HandColors::from_bytes(&[0; 8]),
+            Err(SettingsError::InvalidLength(8))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hand_colors_update() {
+        let colors = HandColors::default()
+            .with_update(br##"{"hour":"#ff8000","minute":"white","second":"off"}"##)
+            .unwrap();
+        assert_eq!(
+            colors,
+            HandColors {
+                hour: (255, 128, 0),
+                minute: (255, 255, 255),
+                second: (0, 0, 0),
+            }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_hand_colors_update_errors() {
+        let colors = HandColors::default();
+        assert_eq!(
+            colors.with_update(br#"{"hour":"nope"}"#),
+            Err(SettingsError::UnknownColor)
+        );
+        assert_eq!(
+            colors.with_update(br#"{"hours":"red"}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(colors.with_update(b"red"), Err(SettingsError::InvalidJson));
+        assert_eq!(colors.with_update(b"{}"), Ok(colors));
+    }
+}
@@ -0,0 +1,200 @@
+//! Error states shown on the ring, so problems are visible without a serial console.
+
+use crate::{fade_color, hour_to_index, Rgb};
+
+const BLINK_INTERVAL_MS: u32 = 500;
+
+const WIFI_COLOR: Rgb = (0, 0, 255);
+const MQTT_COLOR: Rgb = (128, 0, 255);
+const STALE_COLOR: Rgb = (255, 96, 0);
+const OK_COLOR: Rgb = (0, 255, 0);
+
+/// A problem the clock reports on the ring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// Not connected to WiFi: blue blinking at 12 o'clock
+    NoWifi,
+    /// Not connected to the MQTT broker: purple blinking at 6 o'clock
+    NoMqtt,
+    /// No time received for a while: a dim amber ring behind the face
+    StaleTime,
+}
+
+impl Fault {
+    /// Returns a short description for logging.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Fault::NoWifi => "no WiFi",
+            Fault::NoMqtt => "no MQTT",
+            Fault::StaleTime => "stale time",
+        }
+    }
+
+    /// Returns the color the fault's pattern is drawn in.
+    pub const fn color(self) -> Rgb {
+        match self {
+            Fault::NoWifi => WIFI_COLOR,
+            Fault::NoMqtt => MQTT_COLOR,
+            Fault::StaleTime => STALE_COLOR,
+        }
+    }
+
+    /// Draws the fault's pattern at `now_ms` over the frame in `buffer`.
+    ///
+    /// The pattern is dimmed to `brightness` so it matches the face. A
+    /// blinking LED replaces the pixel beneath it; the amber ring only fills
+    /// pixels that are dark, leaving the hands readable.
+    pub fn overlay(self, now_ms: u32, brightness: u8, buffer: &mut [Rgb]) {
+        let blink_off = (now_ms / BLINK_INTERVAL_MS) % 2 == 1;
+        let blink = |hour: u8, color: Rgb, buffer: &mut [Rgb]| {
+            if let Some(pixel) = buffer.get_mut(hour_to_index(hour)).filter(|_| !blink_off) {
+                *pixel = fade_color(color, brightness);
+            }
+        };
+        match self {
+            Fault::NoWifi => blink(12, self.color(), buffer),
+            Fault::NoMqtt => blink(6, self.color(), buffer),
+            Fault::StaleTime => {
+                let amber = fade_color(self.color(), brightness / 2);
+                for pixel in buffer.iter_mut().filter(|p| **p == (0, 0, 0)) {
+                    *pixel = amber;
+                }
+            }
+        }
+    }
+}
+
+/// The health of the clock's connections and time source.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Fault, Status};
+///
+/// let mut status = Status::OK;
+/// assert_eq!(status.fault(), None);
+///
+/// status.mqtt_connected = false;
+/// status.time_stale = true;
+/// assert_eq!(status.fault(), Some(Fault::NoMqtt));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Status {
+    pub wifi_connected: bool,
+    pub mqtt_connected: bool,
+    pub time_stale: bool,
+}
+
+impl Status {
+    /// Everything working.
+    pub const OK: Self = Self {
+        wifi_connected: true,
+        mqtt_connected: true,
+        time_stale: false,
+    };
+
+    /// Returns the most fundamental problem, if any.
+    ///
+    /// Without WiFi there is no MQTT, and without MQTT no time arrives, so
+    /// only the root cause is reported.
+    pub const fn fault(&self) -> Option<Fault> {
+        if !self.wifi_connected {
+            Some(Fault::NoWifi)
+        } else if !self.mqtt_connected {
+            Some(Fault::NoMqtt)
+        } else if self.time_stale {
+            Some(Fault::StaleTime)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the color of a single status LED at `brightness`: green if
+    /// everything works, otherwise the color of the fault.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::Status;
+    ///
+    /// assert_eq!(Status::OK.led_color(255), (0, 255, 0));
+    /// let offline = Status { wifi_connected: false, ..Status::OK };
+    /// assert_eq!(offline.led_color(255), (0, 0, 255));
+    /// ```
+    pub fn led_color(&self, brightness: u8) -> Rgb {
+        let color = self.fault().map_or(OK_COLOR, Fault::color);
+        fade_color(color, brightness)
+    }
+}
+
+impl Default for Status {
+    fn default() -> Self {
+        Self::OK
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HAND: Rgb = (0, 255, 0);
+
+    #[test]
+    fn test_fault_priority() {
+        let down = Status {
+            wifi_connected: false,
+            mqtt_connected: false,
+            time_stale: true,
+        };
+        assert_eq!(down.fault(), Some(Fault::NoWifi));
+        let stale = Status {
+            time_stale: true,
+            ..Status::OK
+        };
+        assert_eq!(stale.fault(), Some(Fault::StaleTime));
+        assert_eq!(Status::default().fault(), None);
+    }
+
+    #[test]
+    fn test_led_color() {
+        let stale = Status {
+            time_stale: true,
+            ..Status::OK
+        };
+        assert_eq!(stale.led_color(255), STALE_COLOR);
+        assert_eq!(stale.led_color(0), (0, 0, 0));
+        let dim = Status::OK.led_color(16);
+        assert!(dim.1 > 0 && dim.1 < 255);
+    }
+
+    #[test]
+    fn test_no_wifi_blinks_at_twelve() {
+        let mut buffer = [(0, 0, 0); 12];
+        buffer[3] = HAND;
+        Fault::NoWifi.overlay(0, 255, &mut buffer);
+        assert_eq!(buffer[11], WIFI_COLOR);
+        assert_eq!(buffer[3], HAND);
+
+        let mut off = [(0, 0, 0); 12];
+        Fault::NoWifi.overlay(BLINK_INTERVAL_MS, 255, &mut off);
+        assert_eq!(off, [(0, 0, 0); 12]);
+    }
+
+    #[test]
+    fn test_no_mqtt_blinks_at_six() {
+        let mut buffer = [HAND; 12];
+        Fault::NoMqtt.overlay(0, 128, &mut buffer);
+        assert_eq!(buffer[5], fade_color(MQTT_COLOR, 128));
+        assert_eq!(buffer.iter().filter(|&&p| p == HAND).count(), 11);
+    }
+
+    #[test]
+    fn test_stale_time_fills_dark_pixels() {
+        let mut buffer = [(0, 0, 0); 12];
+        buffer[0] = HAND;
+        Fault::StaleTime.overlay(0, 200, &mut buffer);
+        assert_eq!(buffer[0], HAND);
+        assert_eq!(buffer[1], fade_color(STALE_COLOR, 100));
+        assert!(buffer[1..].iter().all(|&p| p == buffer[1]));
+    }
+}
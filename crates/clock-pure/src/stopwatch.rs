@@ -0,0 +1,240 @@
+//! Stopwatch with lap times, shown as a minute hand over a sweeping seconds arc.
+
+use crate::mode::render_progress;
+use crate::settings::SettingsError;
+use crate::{add_colors, minute_to_index, Rgb};
+
+const MS_PER_MINUTE: u32 = 60_000;
+
+/// A command for the stopwatch, received over MQTT or from a button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopwatchCommand {
+    /// Start or resume timing
+    Start,
+    /// Record a lap time while running
+    Lap,
+    /// Pause timing, keeping the elapsed time
+    Stop,
+    /// Stop and clear the elapsed time and laps
+    Reset,
+}
+
+impl core::str::FromStr for StopwatchCommand {
+    type Err = SettingsError;
+
+    /// Parses `start`, `lap`, `stop`, or `reset` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("start") => Ok(StopwatchCommand::Start),
+            t if t.eq_ignore_ascii_case("lap") => Ok(StopwatchCommand::Lap),
+            t if t.eq_ignore_ascii_case("stop") => Ok(StopwatchCommand::Stop),
+            t if t.eq_ignore_ascii_case("reset") => Ok(StopwatchCommand::Reset),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+/// A stopwatch driven by a millisecond counter, like [`crate::TimeKeeper`].
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::Stopwatch;
+///
+/// let mut watch = Stopwatch::new();
+/// watch.start(1_000);
+/// assert_eq!(watch.lap(4_000), Some(3_000));
+/// watch.stop(6_000);
+/// watch.start(10_000);
+/// assert_eq!(watch.elapsed_ms(11_000), 6_000);
+/// assert_eq!(watch.lap(11_000), Some(3_000));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Stopwatch {
+    /// Time accumulated before the current run.
+    accumulated_ms: u32,
+    /// Start of the current run, `None` while stopped.
+    running_since: Option<u32>,
+    /// Elapsed time at the last lap.
+    last_lap_ms: u32,
+    laps: u32,
+}
+
+impl Stopwatch {
+    /// Creates a stopped stopwatch at zero.
+    pub const fn new() -> Self {
+        Self {
+            accumulated_ms: 0,
+            running_since: None,
+            last_lap_ms: 0,
+            laps: 0,
+        }
+    }
+
+    /// Returns true while the stopwatch is running.
+    pub const fn is_running(&self) -> bool {
+        self.running_since.is_some()
+    }
+
+    /// Returns the number of laps recorded since the last reset.
+    pub const fn laps(&self) -> u32 {
+        self.laps
+    }
+
+    /// Returns the total time measured at `now_ms`.
+    pub fn elapsed_ms(&self, now_ms: u32) -> u32 {
+        match self.running_since {
+            Some(since) => self
+                .accumulated_ms
+                .saturating_add(now_ms.wrapping_sub(since)),
+            None => self.accumulated_ms,
+        }
+    }
+
+    /// Applies a command at `now_ms`, returning the lap time for [`StopwatchCommand::Lap`].
+    pub fn apply(&mut self, command: StopwatchCommand, now_ms: u32) -> Option<u32> {
+        match command {
+            StopwatchCommand::Start => self.start(now_ms),
+            StopwatchCommand::Lap => return self.lap(now_ms),
+            StopwatchCommand::Stop => self.stop(now_ms),
+            StopwatchCommand::Reset => self.reset(),
+        }
+        None
+    }
+
+    /// Starts or resumes timing at `now_ms`; does nothing if already running.
+    pub fn start(&mut self, now_ms: u32) {
+        if !self.is_running() {
+            self.running_since = Some(now_ms);
+        }
+    }
+
+    /// Pauses timing at `now_ms`.
+    pub fn stop(&mut self, now_ms: u32) {
+        self.accumulated_ms = self.elapsed_ms(now_ms);
+        self.running_since = None;
+    }
+
+    /// Stops the stopwatch and clears the time and laps.
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Records a lap at `now_ms` and returns its duration, or `None` while stopped.
+    pub fn lap(&mut self, now_ms: u32) -> Option<u32> {
+        if !self.is_running() {
+            return None;
+        }
+        let elapsed = self.elapsed_ms(now_ms);
+        let lap = elapsed - self.last_lap_ms;
+        self.last_lap_ms = elapsed;
+        self.laps += 1;
+        Some(lap)
+    }
+
+    /// Renders the stopwatch at `now_ms` into `buffer`.
+    ///
+    /// The seconds of the current minute fill the ring as an arc in
+    /// `second_color`, and the elapsed minutes are shown as a hand in
+    /// `minute_color` on top, one LED per five minutes. Every pixel is
+    /// overwritten.
+    pub fn render(&self, now_ms: u32, minute_color: Rgb, second_color: Rgb, buffer: &mut [Rgb]) {
+        let elapsed = self.elapsed_ms(now_ms);
+        render_progress(elapsed % MS_PER_MINUTE, MS_PER_MINUTE, second_color, buffer);
+
+        let minutes = (elapsed / MS_PER_MINUTE % 60) as u8;
+        let idx = minute_to_index(minutes);
+        if let Some(pixel) = buffer.get_mut(idx) {
+            *pixel = add_colors(*pixel, minute_color);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Rgb = (0, 0, 255);
+    const SECOND: Rgb = (255, 0, 0);
+
+    fn lit(buffer: &[Rgb]) -> usize {
+        buffer.iter().filter(|&&p| p != (0, 0, 0)).count()
+    }
+
+    #[test]
+    fn test_start_stop_accumulates() {
+        let mut watch = Stopwatch::new();
+        assert_eq!(watch.elapsed_ms(5_000), 0);
+        watch.start(1_000);
+        watch.start(2_000);
+        assert_eq!(watch.elapsed_ms(3_000), 2_000);
+        watch.stop(3_000);
+        assert!(!watch.is_running());
+        assert_eq!(watch.elapsed_ms(50_000), 2_000);
+        watch.start(60_000);
+        assert_eq!(watch.elapsed_ms(61_000), 3_000);
+    }
+
+    #[test]
+    fn test_laps() {
+        let mut watch = Stopwatch::new();
+        assert_eq!(watch.lap(0), None);
+        watch.start(0);
+        assert_eq!(watch.apply(StopwatchCommand::Lap, 10_000), Some(10_000));
+        assert_eq!(watch.apply(StopwatchCommand::Lap, 25_000), Some(15_000));
+        assert_eq!(watch.laps(), 2);
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut watch = Stopwatch::new();
+        watch.start(0);
+        watch.lap(1_000);
+        assert_eq!(watch.apply(StopwatchCommand::Reset, 2_000), None);
+        assert_eq!(watch, Stopwatch::new());
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let mut watch = Stopwatch::new();
+        watch.start(u32::MAX - 999);
+        assert_eq!(watch.elapsed_ms(1_000), 2_000);
+    }
+
+    #[test]
+    fn test_render_seconds_arc_and_minute_hand() {
+        let mut watch = Stopwatch::new();
+        let mut buffer = [(9, 9, 9); 12];
+        watch.start(0);
+
+        // 0:00: minute hand at 12 o'clock, no arc yet
+        watch.render(0, MINUTE, SECOND, &mut buffer);
+        assert_eq!(buffer[11], MINUTE);
+        assert_eq!(lit(&buffer), 1);
+
+        // 7:30: minute hand at 1 o'clock, half of the ring filled
+        watch.render(7 * 60_000 + 30_000, MINUTE, SECOND, &mut buffer);
+        assert_eq!(buffer[0], add_colors(SECOND, MINUTE));
+        assert_eq!(buffer[4], SECOND);
+        assert_eq!(lit(&buffer), 6);
+    }
+
+    #[test]
+    fn test_render_wraps_after_an_hour() {
+        let mut watch = Stopwatch::new();
+        let mut buffer = [(0, 0, 0); 12];
+        watch.start(0);
+        watch.render(65 * 60_000, MINUTE, SECOND, &mut buffer);
+        assert_eq!(buffer[0], MINUTE);
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!("lap".parse(), Ok(StopwatchCommand::Lap));
+        assert_eq!("Reset\n".parse(), Ok(StopwatchCommand::Reset));
+        assert_eq!(
+            "pause".parse::<StopwatchCommand>(),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,73 @@
+//! Health figures published periodically for monitoring a fleet of clocks.
+
+use std::string::String;
+
+/// A snapshot of the clock's health, published as JSON.
+///
+/// Figures the clock does not know, e.g. the signal strength without WiFi
+/// or the age of the last tick before the first one, are `null`.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::Telemetry;
+///
+/// let telemetry = Telemetry {
+///     uptime_s: 3600,
+///     free_heap: 180_000,
+///     min_free_heap: 150_000,
+///     rssi_dbm: Some(-61),
+///     frame_rate: Telemetry::frame_rate(1999, 60_000),
+///     tick_age_s: None,
+/// };
+/// assert_eq!(
+///     telemetry.to_json(),
+///     r#"{"uptime_s":3600,"free_heap":180000,"min_free_heap":150000,"rssi_dbm":-61,"frame_rate":33.3,"tick_age_s":null}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct Telemetry {
+    /// Seconds since boot
+    pub uptime_s: u32,
+    /// Free heap in bytes
+    pub free_heap: u32,
+    /// Lowest free heap since boot in bytes
+    pub min_free_heap: u32,
+    /// Signal strength of the WiFi access point
+    pub rssi_dbm: Option<i8>,
+    /// Frames drawn per second since the last report
+    pub frame_rate: f32,
+    /// Seconds since the last tick arrived
+    pub tick_age_s: Option<u32>,
+}
+
+impl Telemetry {
+    /// Returns the frame rate of `frames` drawn in `elapsed_ms`, rounded to
+    /// a tenth, or 0 if no time passed.
+    pub fn frame_rate(frames: u32, elapsed_ms: u32) -> f32 {
+        if elapsed_ms == 0 {
+            return 0.0;
+        }
+        let tenths =
+            (u64::from(frames) * 10_000 + u64::from(elapsed_ms) / 2) / u64::from(elapsed_ms);
+        tenths as f32 / 10.0
+    }
+
+    /// Returns the snapshot as JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("telemetry serializes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_rate() {
+        assert_eq!(Telemetry::frame_rate(300, 10_000), 30.0);
+        assert_eq!(Telemetry::frame_rate(333, 10_000), 33.3);
+        assert_eq!(Telemetry::frame_rate(1, 30_000), 0.0);
+        assert_eq!(Telemetry::frame_rate(5, 0), 0.0);
+    }
+}
@@ -0,0 +1,288 @@
+//! Hand colors that change with the calendar, such as for Christmas or Halloween.
+
+use crate::date::Date;
+use crate::settings::{parse_color, HandColors, SettingsError};
+
+/// Maximum number of entries in a [`ThemeCalendar`].
+pub const MAX_THEMES: usize = 8;
+
+/// A yearly range of days, from `start` to `end` inclusive, each as `(month, day)`.
+///
+/// Ranges whose end is before their start run across New Year, so
+/// `12-31..01-01` covers both days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayRange {
+    pub start: (u8, u8),
+    pub end: (u8, u8),
+}
+
+impl DayRange {
+    /// Creates a range from `start` to `end`, both `(month, day)`.
+    pub const fn new(start: (u8, u8), end: (u8, u8)) -> Self {
+        Self { start, end }
+    }
+
+    /// Returns true if `date` falls within the range in any year.
+    pub const fn contains(&self, date: Date) -> bool {
+        let (start, end) = (day_key(self.start), day_key(self.end));
+        let now = day_key((date.month, date.day));
+        if start <= end {
+            start <= now && now <= end
+        } else {
+            now >= start || now <= end
+        }
+    }
+}
+
+/// Orders `(month, day)` pairs through the year.
+const fn day_key((month, day): (u8, u8)) -> u16 {
+    month as u16 * 32 + day as u16
+}
+
+impl core::str::FromStr for DayRange {
+    type Err = SettingsError;
+
+    /// Parses `MM-DD` for a single day or `MM-DD..MM-DD` for a range.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let day = |text: &str| {
+            let (month, day) = text
+                .trim()
+                .split_once('-')
+                .ok_or(SettingsError::InvalidValue)?;
+            let month: u8 = month.parse().map_err(|_| SettingsError::InvalidValue)?;
+            let day: u8 = day.parse().map_err(|_| SettingsError::InvalidValue)?;
+            // Any leap year accepts every day that can appear in a calendar
+            Date::new(2024, month, day).map_err(|_| SettingsError::InvalidValue)?;
+            Ok((month, day))
+        };
+        match text.split_once("..") {
+            Some((start, end)) => Ok(Self::new(day(start)?, day(end)?)),
+            None => day(text).map(|d| Self::new(d, d)),
+        }
+    }
+}
+
+/// Hand colors for a range of days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub days: DayRange,
+    pub colors: HandColors,
+}
+
+/// A table of themes overriding the hand colors on certain days of the year.
+///
+/// The first theme covering a date wins. [`ThemeCalendar::holidays`] gives a
+/// festive default; any other table can be parsed from text such as
+/// `12-20..12-26=red,green,white; 10-31=orange,purple,orange`, listing the
+/// hour, minute, and second colors of each theme.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Date, ThemeCalendar};
+///
+/// let calendar: ThemeCalendar = "12-31..01-01=yellow,white,yellow".parse().unwrap();
+/// let new_year = calendar.colors_for(Date::new(2025, 1, 1).unwrap()).unwrap();
+/// assert_eq!(new_year.minute, (255, 255, 255));
+/// assert_eq!(calendar.colors_for(Date::new(2025, 1, 2).unwrap()), None);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ThemeCalendar {
+    themes: [Option<Theme>; MAX_THEMES],
+}
+
+impl ThemeCalendar {
+    /// Creates an empty calendar that never overrides the hand colors.
+    pub const fn new() -> Self {
+        Self {
+            themes: [None; MAX_THEMES],
+        }
+    }
+
+    /// Returns the built-in themes: red and green around Christmas, and orange on Halloween.
+    pub const fn holidays() -> Self {
+        let mut calendar = Self::new();
+        calendar.themes[0] = Some(Theme {
+            days: DayRange::new((12, 20), (12, 26)),
+            colors: HandColors {
+                hour: (255, 0, 0),
+                minute: (0, 255, 0),
+                second: (255, 255, 255),
+            },
+        });
+        calendar.themes[1] = Some(Theme {
+            days: DayRange::new((10, 31), (10, 31)),
+            colors: HandColors {
+                hour: (255, 128, 0),
+                minute: (128, 0, 255),
+                second: (255, 128, 0),
+            },
+        });
+        calendar
+    }
+
+    /// Returns the themes in the calendar, in the order they are checked.
+    pub fn iter(&self) -> impl Iterator<Item = &Theme> {
+        self.themes.iter().flatten()
+    }
+
+    /// Returns the hand colors of the first theme covering `date`, if any.
+    pub fn colors_for(&self, date: Date) -> Option<HandColors> {
+        self.iter()
+            .find(|theme| theme.days.contains(date))
+            .map(|theme| theme.colors)
+    }
+}
+
+impl core::str::FromStr for ThemeCalendar {
+    type Err = SettingsError;
+
+    /// Parses `;`-separated entries of `DAYS=HOUR,MINUTE,SECOND`, or `off` for no themes.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut calendar = Self::new();
+        let text = text.trim();
+        if text.eq_ignore_ascii_case("off") {
+            return Ok(calendar);
+        }
+        let mut entries = text.split(';').filter(|entry| !entry.trim().is_empty());
+        for slot in calendar.themes.iter_mut() {
+            let Some(entry) = entries.next() else {
+                break;
+            };
+            let (days, colors) = entry.split_once('=').ok_or(SettingsError::InvalidValue)?;
+            let mut colors = colors.split(',').map(parse_color);
+            let (Some(hour), Some(minute), Some(second), None) =
+                (colors.next(), colors.next(), colors.next(), colors.next())
+            else {
+                return Err(SettingsError::InvalidValue);
+            };
+            *slot = Some(Theme {
+                days: days.parse()?,
+                colors: HandColors {
+                    hour: hour?,
+                    minute: minute?,
+                    second: second?,
+                },
+            });
+        }
+        match entries.count() {
+            0 => Ok(calendar),
+            extra => Err(SettingsError::OutOfRange {
+                field: "themes",
+                value: (MAX_THEMES + extra) as u32,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(month: u8, day: u8) -> Date {
+        Date::new(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_day_range_within_year() {
+        let advent: DayRange = "12-20..12-26".parse().unwrap();
+        assert!(!advent.contains(date(12, 19)));
+        assert!(advent.contains(date(12, 20)));
+        assert!(advent.contains(date(12, 26)));
+        assert!(!advent.contains(date(12, 27)));
+    }
+
+    #[test]
+    fn test_day_range_across_new_year() {
+        let range: DayRange = "12-31..01-01".parse().unwrap();
+        assert!(range.contains(date(12, 31)));
+        assert!(range.contains(date(1, 1)));
+        assert!(!range.contains(date(1, 2)));
+        assert!(!range.contains(date(6, 15)));
+    }
+
+    #[test]
+    fn test_day_range_parse_errors() {
+        for text in [
+            "",
+            "12",
+            "13-01",
+            "02-30",
+            "12-20..",
+            "12-20..12-32",
+            "xx-01",
+        ] {
+            assert_eq!(
+                text.parse::<DayRange>(),
+                Err(SettingsError::InvalidValue),
+                "{text}"
+            );
+        }
+        assert_eq!("02-29".parse(), Ok(DayRange::new((2, 29), (2, 29))));
+    }
+
+    #[test]
+    fn test_holidays() {
+        let holidays = ThemeCalendar::holidays();
+        assert_eq!(holidays.colors_for(date(12, 24)).unwrap().hour, (255, 0, 0));
+        assert_eq!(
+            holidays.colors_for(date(10, 31)).unwrap().hour,
+            (255, 128, 0)
+        );
+        assert_eq!(holidays.colors_for(date(7, 4)), None);
+    }
+
+    #[test]
+    fn test_first_theme_wins() {
+        let calendar: ThemeCalendar = "12-24=blue,blue,blue; 12-20..12-26=red,green,white"
+            .parse()
+            .unwrap();
+        assert_eq!(calendar.iter().count(), 2);
+        assert_eq!(
+            calendar.colors_for(date(12, 24)).unwrap().minute,
+            (0, 0, 255)
+        );
+        assert_eq!(
+            calendar.colors_for(date(12, 25)).unwrap().minute,
+            (0, 255, 0)
+        );
+    }
+
+    #[test]
+    fn test_parse_calendar() {
+        assert_eq!("off".parse(), Ok(ThemeCalendar::new()));
+        assert_eq!("".parse(), Ok(ThemeCalendar::new()));
+        assert_eq!(
+            "12-20..12-26=#ff0000,green,white;10-31=orange,purple,orange;".parse(),
+            Ok(ThemeCalendar::holidays())
+        );
+    }
+
+    #[test]
+    fn test_parse_calendar_errors() {
+        assert_eq!(
+            "12-24".parse::<ThemeCalendar>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            "12-24=red,green".parse::<ThemeCalendar>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            "12-24=red,green,white,blue".parse::<ThemeCalendar>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            "12-24=red,green,gold".parse::<ThemeCalendar>(),
+            Err(SettingsError::UnknownColor)
+        );
+        let too_many = ["01-01=red,red,red"; MAX_THEMES + 1].join(";");
+        assert_eq!(
+            too_many.parse::<ThemeCalendar>(),
+            Err(SettingsError::OutOfRange {
+                field: "themes",
+                value: 9
+            })
+        );
+    }
+}
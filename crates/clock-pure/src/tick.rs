@@ -0,0 +1,218 @@
+//! Format detection for MQTT tick payloads.
+//!
+//! Home-automation systems publish time in many shapes; [`parse_tick`] accepts
+//! the common ones so the clock can follow any of them without reflashing.
+
+use crate::date::LocalDateTime;
+use crate::time::{parse_unix_timestamp, ConvertError, LocalTime, BINARY_TICK_BYTES};
+use crate::tz::TimeZone;
+
+/// The payload format a tick was recognized as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickFormat {
+    /// JSON object such as `{"hour":14,"minute":23,"second":45}` (requires `std`)
+    Json,
+    /// Text such as `14:23:45` or `14:23`, optionally in double quotes
+    Text,
+    /// Decimal Unix timestamp such as `1704112496`
+    Epoch,
+    /// Three bytes (hour, minute, second) with an optional checksum byte
+    Binary,
+}
+
+impl TickFormat {
+    /// Returns a short lowercase name for logging.
+    pub const fn name(self) -> &'static str {
+        match self {
+            TickFormat::Json => "json",
+            TickFormat::Text => "text",
+            TickFormat::Epoch => "epoch",
+            TickFormat::Binary => "binary",
+        }
+    }
+}
+
+/// Parses a tick payload in any supported format and reports which one matched.
+///
+/// Payloads of 3 or 4 bytes are binary ticks; otherwise the format is detected
+/// from the text. Unix timestamps are converted to local time in `zone`, all
+/// other formats are already local time. Unix timestamps always carry a date,
+/// JSON payloads may, and the other formats never do.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{parse_tick, LocalTime, TickFormat, TimeZone};
+///
+/// let zone = TimeZone::fixed(3600);
+/// let expected = LocalTime::new(14, 23, 45).unwrap();
+///
+/// let (date_time, format) = parse_tick(b"14:23:45", &zone).unwrap();
+/// assert_eq!((date_time.time, format), (expected, TickFormat::Text));
+///
+/// let (date_time, format) = parse_tick(b"1704115425", &zone).unwrap();
+/// assert_eq!((date_time.time, format), (expected, TickFormat::Epoch));
+/// assert!(date_time.date.is_some());
+/// ```
+pub fn parse_tick(
+    data: &[u8],
+    zone: &TimeZone,
+) -> Result<(LocalDateTime, TickFormat), ConvertError> {
+    if (BINARY_TICK_BYTES..=BINARY_TICK_BYTES + 1).contains(&data.len()) {
+        return LocalTime::from_binary(data).map(|time| (time.into(), TickFormat::Binary));
+    }
+
+    let text = core::str::from_utf8(data)
+        .map_err(|_| ConvertError::InvalidUtf8)?
+        .trim();
+    let unquoted = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text);
+
+    if text.starts_with('{') {
+        parse_json(data).map(|time| (time, TickFormat::Json))
+    } else if unquoted.contains(':') {
+        let time: LocalTime = unquoted.parse()?;
+        Ok((time.into(), TickFormat::Text))
+    } else if text.starts_with(|c: char| c.is_ascii_digit() || c == '-') {
+        parse_unix_timestamp(data).map(|unix| (zone.local_date_time(unix), TickFormat::Epoch))
+    } else {
+        Err(ConvertError::UnknownFormat)
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_json(data: &[u8]) -> Result<LocalDateTime, ConvertError> {
+    LocalDateTime::try_from(data)
+}
+
+#[cfg(not(feature = "std"))]
+fn parse_json(_data: &[u8]) -> Result<LocalDateTime, ConvertError> {
+    Err(ConvertError::InvalidJson)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u8, minute: u8, second: u8) -> LocalTime {
+        LocalTime::new(hour, minute, second).unwrap()
+    }
+
+    /// Parses a tick and drops the date.
+    fn parse_time(data: &[u8], zone: &TimeZone) -> Result<(LocalTime, TickFormat), ConvertError> {
+        parse_tick(data, zone).map(|(date_time, format)| (date_time.time, format))
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_detects_json() {
+        let data = br#" {"hour":14,"minute":23,"second":45}"#;
+        assert_eq!(
+            parse_time(data, &TimeZone::utc()),
+            Ok((time(14, 23, 45), TickFormat::Json))
+        );
+    }
+
+    #[test]
+    fn test_detects_text() {
+        assert_eq!(
+            parse_time(b"14:23:45\n", &TimeZone::utc()),
+            Ok((time(14, 23, 45), TickFormat::Text))
+        );
+        assert_eq!(
+            parse_time(b"\"07:30\"", &TimeZone::utc()),
+            Ok((time(7, 30, 0), TickFormat::Text))
+        );
+    }
+
+    #[test]
+    fn test_detects_epoch_with_offset() {
+        // 2024-01-01T12:34:56Z
+        assert_eq!(
+            parse_time(b"1704112496", &TimeZone::fixed(-3600)),
+            Ok((time(11, 34, 56), TickFormat::Epoch))
+        );
+    }
+
+    #[test]
+    fn test_detects_binary() {
+        assert_eq!(
+            parse_time(&[9, 5, 0], &TimeZone::utc()),
+            Ok((time(9, 5, 0), TickFormat::Binary))
+        );
+        assert_eq!(
+            parse_time(&[9, 5, 0, 9 ^ 5], &TimeZone::utc()),
+            Ok((time(9, 5, 0), TickFormat::Binary))
+        );
+    }
+
+    #[test]
+    fn test_epoch_uses_zone_dst() {
+        // 2024-07-15T12:00:00Z, summer time in Berlin
+        let berlin: TimeZone = "Europe/Berlin".parse().unwrap();
+        assert_eq!(
+            parse_time(b"1721044800", &berlin),
+            Ok((time(14, 0, 0), TickFormat::Epoch))
+        );
+    }
+
+    #[test]
+    fn test_epoch_carries_date() {
+        let (date_time, _) = parse_tick(b"1721044800", &TimeZone::utc()).unwrap();
+        assert_eq!(date_time.date, Some(crate::Date::new(2024, 7, 15).unwrap()));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_json_carries_optional_date() {
+        let data = br#"{"hour":1,"minute":0,"second":0,"year":2024,"month":7,"day":15}"#;
+        let (date_time, _) = parse_tick(data, &TimeZone::utc()).unwrap();
+        assert_eq!(date_time.date, Some(crate::Date::new(2024, 7, 15).unwrap()));
+    }
+
+    #[test]
+    fn test_text_has_no_date() {
+        let (date_time, _) = parse_tick(b"12:00:00", &TimeZone::utc()).unwrap();
+        assert_eq!(date_time.date, None);
+    }
+
+    #[test]
+    fn test_binary_wins_over_short_text() {
+        // Four bytes are always binary, even if they look like text
+        assert_eq!(
+            parse_time(b"1:23", &TimeZone::utc()),
+            Err(ConvertError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn test_text_errors_are_reported() {
+        assert_eq!(
+            parse_time(b"24:00:00", &TimeZone::utc()),
+            Err(ConvertError::OutOfRange {
+                field: "hour",
+                value: 24
+            })
+        );
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        assert_eq!(
+            parse_time(b"noon-ish", &TimeZone::utc()),
+            Err(ConvertError::UnknownFormat)
+        );
+        assert_eq!(
+            parse_time(b"", &TimeZone::utc()),
+            Err(ConvertError::UnknownFormat)
+        );
+    }
+
+    #[test]
+    fn test_format_names() {
+        assert_eq!(TickFormat::Json.name(), "json");
+        assert_eq!(TickFormat::Binary.name(), "binary");
+    }
+}
@@ -0,0 +1,623 @@
+//! Wall-clock time as received from the time source.
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
+/// Size of a binary tick without checksum: hour, minute, and second bytes.
+pub const BINARY_TICK_BYTES: usize = 3;
+
+/// Represents a local time with hour, minute, and second components.
+///
+/// Deserialization rejects out-of-range fields; use [`LocalTime::new`] to get
+/// the same check when building a time from raw values.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(try_from = "RawLocalTime"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+}
+
+impl LocalTime {
+    /// Creates a time, checking that every field is within range.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::{ConvertError, LocalTime};
+    ///
+    /// assert!(LocalTime::new(23, 59, 59).is_ok());
+    /// assert_eq!(
+    ///     LocalTime::new(24, 0, 0),
+    ///     Err(ConvertError::OutOfRange { field: "hour", value: 24 })
+    /// );
+    /// ```
+    pub fn new(hour: u8, minute: u8, second: u8) -> Result<Self, ConvertError> {
+        Ok(Self {
+            hour: check_range("hour", hour.into(), 23)?,
+            minute: check_range("minute", minute.into(), 59)?,
+            second: check_range("second", second.into(), 59)?,
+        })
+    }
+
+    /// Returns the time `seconds` later, wrapping at midnight.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::LocalTime;
+    ///
+    /// let time = LocalTime::new(23, 59, 30).unwrap();
+    /// assert_eq!(time.add_seconds(45), LocalTime::new(0, 0, 15).unwrap());
+    /// ```
+    pub fn add_seconds(self, seconds: u32) -> Self {
+        Self::from_seconds(self.to_seconds() + seconds % SECONDS_PER_DAY)
+    }
+
+    /// Returns the time `seconds` earlier, wrapping at midnight.
+    pub fn sub_seconds(self, seconds: u32) -> Self {
+        Self::from_seconds(self.to_seconds() + SECONDS_PER_DAY - seconds % SECONDS_PER_DAY)
+    }
+
+    /// Returns the seconds from `earlier` forward to `self`, wrapping at midnight.
+    ///
+    /// The result is always less than a day, which makes it a countdown:
+    /// `alarm.diff(now)` is the time left until the alarm next goes off.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::LocalTime;
+    ///
+    /// let now = LocalTime::new(23, 0, 0).unwrap();
+    /// let alarm = LocalTime::new(7, 30, 0).unwrap();
+    /// assert_eq!(alarm.diff(now), 8 * 3600 + 30 * 60);
+    /// ```
+    pub fn diff(self, earlier: LocalTime) -> u32 {
+        (self.to_seconds() + SECONDS_PER_DAY - earlier.to_seconds()) % SECONDS_PER_DAY
+    }
+
+    /// Returns the number of seconds since midnight (0 to 86 399).
+    ///
+    /// Handy for comparing times and checking windows with integer math.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::LocalTime;
+    ///
+    /// let time = LocalTime::new(1, 2, 3).unwrap();
+    /// assert_eq!(time.to_seconds(), 3723);
+    /// assert_eq!(LocalTime::from_seconds(3723), time);
+    /// ```
+    pub const fn to_seconds(self) -> u32 {
+        self.hour as u32 * 3600 + self.minute as u32 * 60 + self.second as u32
+    }
+
+    /// Converts a Unix timestamp to the local time at `utc_offset_seconds` from UTC.
+    ///
+    /// Leap seconds are ignored, as they are in Unix time itself.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::LocalTime;
+    ///
+    /// // 2024-01-01T12:34:56Z, shown in UTC+1
+    /// let time = LocalTime::from_unix_timestamp(1_704_112_496, 3600);
+    /// assert_eq!(time, LocalTime::new(13, 34, 56).unwrap());
+    /// ```
+    pub const fn from_unix_timestamp(timestamp: i64, utc_offset_seconds: i32) -> Self {
+        let local = timestamp + utc_offset_seconds as i64;
+        Self::from_seconds(local.rem_euclid(SECONDS_PER_DAY as i64) as u32)
+    }
+
+    /// Parses a tick payload holding a decimal Unix timestamp, such as `1704112496`.
+    ///
+    /// Surrounding whitespace is ignored; the result is shifted by `utc_offset_seconds`.
+    pub fn from_epoch_payload(
+        message: &[u8],
+        utc_offset_seconds: i32,
+    ) -> Result<Self, ConvertError> {
+        let timestamp = parse_unix_timestamp(message)?;
+        Ok(Self::from_unix_timestamp(timestamp, utc_offset_seconds))
+    }
+    /// Decodes a binary tick: `[hour, minute, second]`, optionally followed by a checksum byte.
+    ///
+    /// The checksum is the XOR of the three time bytes.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::LocalTime;
+    ///
+    /// let time = LocalTime::new(14, 23, 45).unwrap();
+    /// assert_eq!(LocalTime::from_binary(&[14, 23, 45]), Ok(time));
+    /// assert_eq!(LocalTime::from_binary(&time.to_binary_with_checksum()), Ok(time));
+    /// ```
+    pub fn from_binary(data: &[u8]) -> Result<Self, ConvertError> {
+        let (time, checksum) = match data {
+            [h, m, s] => ([*h, *m, *s], None),
+            [h, m, s, c] => ([*h, *m, *s], Some(*c)),
+            _ => return Err(ConvertError::InvalidLength(data.len())),
+        };
+        if checksum.is_some_and(|c| c != binary_checksum(time)) {
+            return Err(ConvertError::ChecksumMismatch);
+        }
+        Self::new(time[0], time[1], time[2])
+    }
+
+    /// Encodes the time as a 3-byte binary tick.
+    pub const fn to_binary(self) -> [u8; BINARY_TICK_BYTES] {
+        [self.hour, self.minute, self.second]
+    }
+
+    /// Encodes the time as a binary tick followed by its checksum byte.
+    pub const fn to_binary_with_checksum(self) -> [u8; BINARY_TICK_BYTES + 1] {
+        let time = self.to_binary();
+        [time[0], time[1], time[2], binary_checksum(time)]
+    }
+
+    /// Builds a time from seconds since midnight, wrapping values of a day or more.
+    pub const fn from_seconds(seconds: u32) -> Self {
+        let seconds = seconds % SECONDS_PER_DAY;
+        Self {
+            hour: (seconds / 3600) as u8,
+            minute: (seconds / 60 % 60) as u8,
+            second: (seconds % 60) as u8,
+        }
+    }
+}
+
+/// Number of seconds in a day.
+pub const SECONDS_PER_DAY: u32 = 24 * 60 * 60;
+
+/// Parses a decimal Unix timestamp, ignoring surrounding whitespace.
+pub(crate) fn parse_unix_timestamp(message: &[u8]) -> Result<i64, ConvertError> {
+    let text = core::str::from_utf8(message).map_err(|_| ConvertError::InvalidUtf8)?;
+    text.trim()
+        .parse()
+        .map_err(|_| ConvertError::InvalidTimestamp)
+}
+
+/// XOR checksum of a binary tick.
+const fn binary_checksum(time: [u8; BINARY_TICK_BYTES]) -> u8 {
+    time[0] ^ time[1] ^ time[2]
+}
+
+/// Returns `value` as a `u8` if it is at most `max`.
+fn check_range(field: &'static str, value: u32, max: u32) -> Result<u8, ConvertError> {
+    if value > max {
+        return Err(ConvertError::OutOfRange { field, value });
+    }
+    Ok(value as u8)
+}
+
+/// Unchecked wire format of a [`LocalTime`], with the optional date and
+/// milliseconds of a [`LocalDateTime`](crate::LocalDateTime).
+///
+/// Fields are wider than `u8` so that values such as `300` are reported as
+/// out of range rather than as malformed JSON.
+#[cfg(feature = "std")]
+#[derive(Deserialize)]
+pub(crate) struct RawLocalTime {
+    hour: u32,
+    minute: u32,
+    second: u32,
+    #[serde(default)]
+    pub(crate) year: Option<u32>,
+    #[serde(default)]
+    pub(crate) month: Option<u32>,
+    #[serde(default)]
+    pub(crate) day: Option<u32>,
+    #[serde(default)]
+    pub(crate) millis: Option<u32>,
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<RawLocalTime> for LocalTime {
+    type Error = ConvertError;
+
+    fn try_from(raw: RawLocalTime) -> Result<Self, Self::Error> {
+        Ok(Self {
+            hour: check_range("hour", raw.hour, 23)?,
+            minute: check_range("minute", raw.minute, 59)?,
+            second: check_range("second", raw.second, 59)?,
+        })
+    }
+}
+
+/// Error type for LocalTime conversion failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The provided data is not valid UTF-8
+    InvalidUtf8,
+    /// The JSON data could not be parsed into a LocalTime
+    InvalidJson,
+    /// The payload is not a decimal Unix timestamp
+    InvalidTimestamp,
+    /// The text is not in `HH:MM:SS` or `HH:MM` form
+    InvalidText,
+    /// The payload does not match any known tick format
+    UnknownFormat,
+    /// A binary tick is not 3 or 4 bytes long
+    InvalidLength(usize),
+    /// A binary tick's checksum byte does not match its time bytes
+    ChecksumMismatch,
+    /// A date has some but not all of `year`, `month`, and `day`
+    IncompleteDate,
+    /// A field is outside its valid range (hour 0-23, minute and second 0-59)
+    OutOfRange { field: &'static str, value: u32 },
+}
+
+impl core::fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ConvertError::InvalidUtf8 => write!(f, "Invalid UTF-8 in message data"),
+            ConvertError::InvalidJson => write!(f, "Failed to parse JSON into LocalTime"),
+            ConvertError::InvalidTimestamp => write!(f, "Failed to parse Unix timestamp"),
+            ConvertError::InvalidText => write!(f, "Expected time as HH:MM:SS or HH:MM"),
+            ConvertError::UnknownFormat => write!(f, "Unrecognized tick payload format"),
+            ConvertError::IncompleteDate => write!(f, "Date needs year, month, and day"),
+            ConvertError::InvalidLength(len) => {
+                write!(f, "Binary tick must be 3 or 4 bytes, got {}", len)
+            }
+            ConvertError::ChecksumMismatch => write!(f, "Binary tick checksum mismatch"),
+            ConvertError::OutOfRange { field, value } => {
+                write!(f, "LocalTime {} out of range: {}", field, value)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConvertError {}
+
+impl core::str::FromStr for LocalTime {
+    type Err = ConvertError;
+
+    /// Parses a time written as `HH:MM:SS` or `HH:MM` (seconds default to zero).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::LocalTime;
+    ///
+    /// let time: LocalTime = "07:05:09".parse().unwrap();
+    /// assert_eq!(time, LocalTime::new(7, 5, 9).unwrap());
+    /// assert_eq!("7:05".parse::<LocalTime>(), LocalTime::new(7, 5, 0));
+    /// ```
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parts = text.trim().split(':');
+        let mut next = |required: bool| -> Result<u32, ConvertError> {
+            match parts.next() {
+                Some(part) if !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) => {
+                    part.parse().map_err(|_| ConvertError::InvalidText)
+                }
+                None if !required => Ok(0),
+                _ => Err(ConvertError::InvalidText),
+            }
+        };
+        let (hour, minute, second) = (next(true)?, next(true)?, next(false)?);
+        if parts.next().is_some() {
+            return Err(ConvertError::InvalidText);
+        }
+        Ok(Self {
+            hour: check_range("hour", hour, 23)?,
+            minute: check_range("minute", minute, 59)?,
+            second: check_range("second", second, 59)?,
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+impl TryFrom<&[u8]> for LocalTime {
+    type Error = ConvertError;
+
+    /// Parses a JSON tick, or a binary tick if the message is 3 or 4 bytes long.
+    ///
+    /// A date in the payload is validated but dropped; use
+    /// [`LocalDateTime`](crate::LocalDateTime) to keep it.
+    fn try_from(message: &[u8]) -> Result<Self, Self::Error> {
+        crate::LocalDateTime::try_from(message).map(|date_time| date_time.time)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn test_parse_json_tick() {
+        let time = LocalTime::try_from(&br#"{"hour":14,"minute":23,"second":45}"#[..]).unwrap();
+        assert_eq!(
+            time,
+            LocalTime {
+                hour: 14,
+                minute: 23,
+                second: 45
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_utf8() {
+        let result = LocalTime::try_from(&[0xff, 0xfe][..]);
+        assert_eq!(result, Err(ConvertError::InvalidUtf8));
+    }
+
+    #[test]
+    fn test_parse_invalid_json() {
+        let result = LocalTime::try_from(&b"not json"[..]);
+        assert_eq!(result, Err(ConvertError::InvalidJson));
+    }
+
+    #[test]
+    fn test_parse_missing_field() {
+        let result = LocalTime::try_from(&br#"{"hour":14,"minute":23}"#[..]);
+        assert_eq!(result, Err(ConvertError::InvalidJson));
+    }
+
+    #[test]
+    fn test_parse_hour_out_of_range() {
+        let result = LocalTime::try_from(&br#"{"hour":99,"minute":0,"second":0}"#[..]);
+        assert_eq!(
+            result,
+            Err(ConvertError::OutOfRange {
+                field: "hour",
+                value: 99
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_value_wider_than_u8_is_out_of_range() {
+        let result = LocalTime::try_from(&br#"{"hour":1,"minute":300,"second":0}"#[..]);
+        assert_eq!(
+            result,
+            Err(ConvertError::OutOfRange {
+                field: "minute",
+                value: 300
+            })
+        );
+    }
+
+    #[test]
+    fn test_deserialize_rejects_out_of_range() {
+        let result = serde_json::from_str::<LocalTime>(r#"{"hour":1,"minute":2,"second":60}"#);
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains("second out of range: 60"));
+    }
+
+    #[test]
+    fn test_new_accepts_boundaries() {
+        assert_eq!(
+            LocalTime::new(23, 59, 59),
+            Ok(LocalTime {
+                hour: 23,
+                minute: 59,
+                second: 59
+            })
+        );
+        assert!(LocalTime::new(0, 0, 0).is_ok());
+    }
+
+    #[test]
+    fn test_new_rejects_out_of_range() {
+        assert_eq!(
+            LocalTime::new(12, 60, 0),
+            Err(ConvertError::OutOfRange {
+                field: "minute",
+                value: 60
+            })
+        );
+    }
+
+    // ===== arithmetic tests =====
+
+    fn time(hour: u8, minute: u8, second: u8) -> LocalTime {
+        LocalTime::new(hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn test_add_seconds_carries_into_minutes_and_hours() {
+        assert_eq!(time(10, 59, 59).add_seconds(1), time(11, 0, 0));
+        assert_eq!(time(10, 0, 0).add_seconds(3 * 3600 + 61), time(13, 1, 1));
+    }
+
+    #[test]
+    fn test_add_seconds_wraps_at_midnight() {
+        assert_eq!(time(23, 59, 59).add_seconds(1), time(0, 0, 0));
+        assert_eq!(
+            time(12, 0, 0).add_seconds(u32::MAX),
+            time(12, 0, 0).add_seconds(u32::MAX % SECONDS_PER_DAY)
+        );
+    }
+
+    #[test]
+    fn test_sub_seconds_wraps_at_midnight() {
+        assert_eq!(time(0, 0, 10).sub_seconds(20), time(23, 59, 50));
+        assert_eq!(time(8, 30, 0).sub_seconds(30 * 60), time(8, 0, 0));
+        assert_eq!(time(8, 0, 0).sub_seconds(SECONDS_PER_DAY), time(8, 0, 0));
+    }
+
+    #[test]
+    fn test_diff_forward_and_across_midnight() {
+        assert_eq!(time(8, 0, 30).diff(time(8, 0, 0)), 30);
+        assert_eq!(time(0, 0, 0).diff(time(23, 59, 0)), 60);
+        assert_eq!(time(7, 0, 0).diff(time(7, 0, 0)), 0);
+    }
+
+    #[test]
+    fn test_diff_inverts_add_seconds() {
+        let start = time(22, 15, 5);
+        assert_eq!(start.add_seconds(9_999).diff(start), 9_999);
+    }
+
+    // ===== seconds conversion tests =====
+
+    #[test]
+    fn test_to_seconds_boundaries() {
+        assert_eq!(time(0, 0, 0).to_seconds(), 0);
+        assert_eq!(time(12, 0, 0).to_seconds(), 43_200);
+        assert_eq!(time(23, 59, 59).to_seconds(), SECONDS_PER_DAY - 1);
+    }
+
+    #[test]
+    fn test_from_seconds_wraps() {
+        assert_eq!(LocalTime::from_seconds(SECONDS_PER_DAY), time(0, 0, 0));
+        assert_eq!(LocalTime::from_seconds(SECONDS_PER_DAY + 61), time(0, 1, 1));
+    }
+
+    #[test]
+    fn test_seconds_order_matches_time_order() {
+        assert!(time(6, 59, 59).to_seconds() < time(7, 0, 0).to_seconds());
+    }
+
+    // ===== epoch tests =====
+
+    #[test]
+    fn test_from_unix_timestamp_utc() {
+        // 2024-01-01T12:34:56Z
+        assert_eq!(
+            LocalTime::from_unix_timestamp(1_704_112_496, 0),
+            time(12, 34, 56)
+        );
+        assert_eq!(LocalTime::from_unix_timestamp(0, 0), time(0, 0, 0));
+    }
+
+    #[test]
+    fn test_from_unix_timestamp_offset_crosses_midnight() {
+        // 2024-01-01T23:30:00Z
+        assert_eq!(
+            LocalTime::from_unix_timestamp(1_704_151_800, 3600),
+            time(0, 30, 0)
+        );
+        assert_eq!(
+            LocalTime::from_unix_timestamp(1_704_067_200, -5 * 3600),
+            time(19, 0, 0)
+        );
+    }
+
+    #[test]
+    fn test_from_unix_timestamp_before_epoch() {
+        assert_eq!(LocalTime::from_unix_timestamp(-1, 0), time(23, 59, 59));
+    }
+
+    #[test]
+    fn test_from_epoch_payload_trims_whitespace() {
+        assert_eq!(
+            LocalTime::from_epoch_payload(b" 1704112496\n", 0),
+            Ok(time(12, 34, 56))
+        );
+    }
+
+    #[test]
+    fn test_from_epoch_payload_rejects_non_numbers() {
+        assert_eq!(
+            LocalTime::from_epoch_payload(b"12:34", 0),
+            Err(ConvertError::InvalidTimestamp)
+        );
+        assert_eq!(
+            LocalTime::from_epoch_payload(&[0xff], 0),
+            Err(ConvertError::InvalidUtf8)
+        );
+    }
+
+    // ===== binary tick tests =====
+
+    #[test]
+    fn test_parse_binary_tick() {
+        assert_eq!(
+            LocalTime::try_from(&[14u8, 23, 45][..]),
+            Ok(time(14, 23, 45))
+        );
+    }
+
+    #[test]
+    fn test_parse_binary_tick_with_checksum() {
+        let bytes = [14, 23, 45, 14 ^ 23 ^ 45];
+        assert_eq!(LocalTime::try_from(&bytes[..]), Ok(time(14, 23, 45)));
+    }
+
+    #[test]
+    fn test_binary_checksum_mismatch() {
+        let bytes = [14, 23, 45, 0];
+        assert_eq!(
+            LocalTime::try_from(&bytes[..]),
+            Err(ConvertError::ChecksumMismatch)
+        );
+    }
+
+    #[test]
+    fn test_binary_tick_out_of_range() {
+        assert_eq!(
+            LocalTime::from_binary(&[24, 0, 0]),
+            Err(ConvertError::OutOfRange {
+                field: "hour",
+                value: 24
+            })
+        );
+    }
+
+    #[test]
+    fn test_binary_tick_invalid_length() {
+        assert_eq!(
+            LocalTime::from_binary(&[1, 2]),
+            Err(ConvertError::InvalidLength(2))
+        );
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let original = time(23, 59, 58);
+        assert_eq!(LocalTime::from_binary(&original.to_binary()), Ok(original));
+        assert_eq!(
+            LocalTime::from_binary(&original.to_binary_with_checksum()),
+            Ok(original)
+        );
+    }
+
+    // ===== text tests =====
+
+    #[test]
+    fn test_parse_text_with_and_without_seconds() {
+        assert_eq!("14:23:45".parse(), Ok(time(14, 23, 45)));
+        assert_eq!(" 14:23 ".parse(), Ok(time(14, 23, 0)));
+    }
+
+    #[test]
+    fn test_parse_text_out_of_range() {
+        assert_eq!(
+            "25:00:00".parse::<LocalTime>(),
+            Err(ConvertError::OutOfRange {
+                field: "hour",
+                value: 25
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_text_rejects_malformed() {
+        for text in ["", "14", "14:", "14:23:45:00", "a:b:c", "+1:00", "14:23:4x"] {
+            assert_eq!(
+                text.parse::<LocalTime>(),
+                Err(ConvertError::InvalidText),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_out_of_range_display_names_field() {
+        let error = ConvertError::OutOfRange {
+            field: "hour",
+            value: 99,
+        };
+        assert_eq!(error.to_string(), "LocalTime hour out of range: 99");
+    }
+}
@@ -0,0 +1,109 @@
+//! Choosing between MQTT ticks and SNTP as the source of the time.
+
+use crate::settings::SettingsError;
+
+/// How long after the last tick the ticks count as down, in milliseconds.
+pub const TICK_STALE_MS: u32 = 60_000;
+
+/// How long after the last SNTP sync its time is no longer trusted, in
+/// milliseconds; three missed hourly syncs.
+pub const SNTP_STALE_MS: u32 = 3 * 60 * 60 * 1000;
+
+/// Where the clock takes its time from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeSource {
+    /// Ticks published on the `tick` topic
+    #[default]
+    Tick,
+    /// NTP servers, queried by the clock itself
+    Sntp,
+}
+
+impl TimeSource {
+    /// Returns the source to show the time from, with `self` being the
+    /// preferred one.
+    ///
+    /// `tick_age_ms` and `sntp_age_ms` are the times since the last tick
+    /// and the last SNTP sync, or `None` if there was none yet. The
+    /// preferred source is used while it is fresh, and the other one while
+    /// only that is. Returns `None` if neither is fresh.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use clock_pure::TimeSource;
+    ///
+    /// // SNTP stands in when the ticks stop
+    /// assert_eq!(TimeSource::Tick.pick(Some(1_000), Some(0)), Some(TimeSource::Tick));
+    /// assert_eq!(TimeSource::Tick.pick(Some(120_000), Some(0)), Some(TimeSource::Sntp));
+    /// // Preferred, SNTP wins while it is synced
+    /// assert_eq!(TimeSource::Sntp.pick(Some(1_000), Some(0)), Some(TimeSource::Sntp));
+    /// assert_eq!(TimeSource::Sntp.pick(Some(1_000), None), Some(TimeSource::Tick));
+    /// ```
+    pub fn pick(self, tick_age_ms: Option<u32>, sntp_age_ms: Option<u32>) -> Option<TimeSource> {
+        let tick = tick_age_ms.is_some_and(|age| age < TICK_STALE_MS);
+        let sntp = sntp_age_ms.is_some_and(|age| age < SNTP_STALE_MS);
+        match (self, tick, sntp) {
+            (TimeSource::Tick, true, _) | (TimeSource::Sntp, true, false) => Some(TimeSource::Tick),
+            (TimeSource::Sntp, _, true) | (TimeSource::Tick, false, true) => Some(TimeSource::Sntp),
+            (_, false, false) => None,
+        }
+    }
+
+    /// Returns the name of the source, as parsed by `from_str`.
+    pub fn name(self) -> &'static str {
+        match self {
+            TimeSource::Tick => "tick",
+            TimeSource::Sntp => "sntp",
+        }
+    }
+}
+
+impl core::str::FromStr for TimeSource {
+    type Err = SettingsError;
+
+    /// Parses `tick` or `sntp` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        match text.trim() {
+            t if t.eq_ignore_ascii_case("tick") => Ok(TimeSource::Tick),
+            t if t.eq_ignore_ascii_case("sntp") => Ok(TimeSource::Sntp),
+            _ => Err(SettingsError::InvalidValue),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_sources() {
+        for preferred in [TimeSource::Tick, TimeSource::Sntp] {
+            assert_eq!(preferred.pick(None, None), None);
+            assert_eq!(
+                preferred.pick(Some(TICK_STALE_MS), Some(SNTP_STALE_MS)),
+                None
+            );
+            assert_eq!(
+                preferred.pick(Some(TICK_STALE_MS - 1), Some(SNTP_STALE_MS)),
+                Some(TimeSource::Tick)
+            );
+            assert_eq!(
+                preferred.pick(None, Some(SNTP_STALE_MS - 1)),
+                Some(TimeSource::Sntp)
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(" SNTP ".parse(), Ok(TimeSource::Sntp));
+        for source in [TimeSource::Tick, TimeSource::Sntp] {
+            assert_eq!(source.name().parse(), Ok(source));
+        }
+        assert_eq!(
+            "ntp".parse::<TimeSource>(),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,327 @@
+//! Local timekeeping between received time ticks.
+
+use crate::date::{Date, LocalDateTime};
+use crate::time::{LocalTime, SECONDS_PER_DAY};
+
+/// Milliseconds in a day.
+const DAY_MS: i64 = SECONDS_PER_DAY as i64 * 1000;
+
+/// Tick errors up to this many milliseconds are slewed in; larger ones jump.
+pub const MAX_SLEW_MS: u32 = 2_000;
+
+/// Corrections are applied at 1 ms per this many elapsed milliseconds (10%).
+const SLEW_RATE_DIVISOR: u32 = 10;
+
+/// Keeps the time of day running between ticks.
+///
+/// After the first tick, the time advances locally from a monotonic
+/// millisecond counter, so the clock stays correct when ticks are sparse or
+/// stop for a while. A later tick that differs only slightly from the local
+/// estimate is slewed in gradually, so the second hand never jumps back or
+/// skips; larger differences are applied at once.
+///
+/// Timestamps are milliseconds from any monotonic source, for example an
+/// [`AnimationClock`](crate::animation::AnimationClock) or a wrapping uptime
+/// counter. Ages are computed with wrapping arithmetic, so a millisecond
+/// counter that overflows after 49 days does not break staleness detection.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{LocalTime, TimeKeeper};
+///
+/// let mut keeper = TimeKeeper::new();
+/// keeper.update(LocalTime::new(12, 0, 0).unwrap(), 1_000);
+///
+/// assert_eq!(keeper.now(61_000), Some(LocalTime::new(12, 1, 0).unwrap()));
+/// assert!(!keeper.is_stale(5_000, 10_000));
+/// assert!(keeper.is_stale(20_000, 10_000));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeKeeper {
+    last_tick: Option<(LocalTime, u32)>,
+    /// Milliseconds since midnight at a counter value, the base of the estimate
+    anchor: Option<(u32, u32)>,
+    /// The date at the anchor, if any tick carried one
+    anchor_date: Option<Date>,
+    /// Correction still being slewed in since the anchor; positive runs ahead
+    pending_ms: i32,
+}
+
+impl TimeKeeper {
+    /// Creates a time keeper that has not received any time yet.
+    pub const fn new() -> Self {
+        Self {
+            last_tick: None,
+            anchor: None,
+            anchor_date: None,
+            pending_ms: 0,
+        }
+    }
+
+    /// Records a tick received at `now_ms` and reconciles the local estimate.
+    ///
+    /// Accepts a [`LocalTime`] or a [`LocalDateTime`]. Once a tick carried a
+    /// date, the date keeps advancing at midnight even if later ticks have none.
+    pub fn update(&mut self, tick: impl Into<LocalDateTime>, now_ms: u32) {
+        let tick = tick.into();
+        let date = tick.date;
+        self.last_tick = Some((tick.time, now_ms));
+
+        let tick_ms = i64::from(tick.millis_since_midnight());
+        let Some(total) = self.total_ms_at(now_ms) else {
+            self.anchor = Some((tick_ms as u32, now_ms));
+            self.anchor_date = date;
+            self.pending_ms = 0;
+            return;
+        };
+
+        let estimate = total.rem_euclid(DAY_MS);
+        let estimate_date = self.date_after(total);
+        // Signed difference in -12 h..12 h, so midnight does not count as an error
+        let error = (tick_ms - estimate + DAY_MS / 2).rem_euclid(DAY_MS) - DAY_MS / 2;
+
+        if error.unsigned_abs() <= u64::from(MAX_SLEW_MS) {
+            // A tick just past midnight belongs to the day after the estimate, and vice versa
+            let day_shift = (tick_ms - estimate - error) / DAY_MS;
+            self.anchor = Some((estimate as u32, now_ms));
+            self.anchor_date = date.map(|d| d.add_days(day_shift)).or(estimate_date);
+            self.pending_ms = error as i32;
+        } else {
+            self.anchor = Some((tick_ms as u32, now_ms));
+            self.anchor_date = date.or(estimate_date);
+            self.pending_ms = 0;
+        }
+    }
+
+    /// Returns the current local time at `now_ms`, or `None` before the first tick.
+    pub fn now(&self, now_ms: u32) -> Option<LocalTime> {
+        self.total_ms_at(now_ms)
+            .map(|total| LocalTime::from_seconds((total.rem_euclid(DAY_MS) / 1000) as u32))
+    }
+
+    /// Returns the current milliseconds since midnight at `now_ms`, or `None` before the first tick.
+    ///
+    /// Sub-second precision follows the `millis` of the ticks, so smooth
+    /// animations can stay aligned with the time source.
+    pub fn millis_since_midnight(&self, now_ms: u32) -> Option<u32> {
+        self.total_ms_at(now_ms)
+            .map(|total| total.rem_euclid(DAY_MS) as u32)
+    }
+
+    /// Returns the current date at `now_ms`, or `None` if no tick carried a date.
+    pub fn today(&self, now_ms: u32) -> Option<Date> {
+        self.total_ms_at(now_ms)
+            .and_then(|total| self.date_after(total))
+    }
+
+    /// Returns the estimated milliseconds from the anchor's midnight to `now_ms`.
+    ///
+    /// The result exceeds a day once the time has passed midnight.
+    fn total_ms_at(&self, now_ms: u32) -> Option<i64> {
+        let (day_ms, anchor_ms) = self.anchor?;
+        let elapsed = now_ms.wrapping_sub(anchor_ms);
+        let max_step = i64::from(elapsed / SLEW_RATE_DIVISOR);
+        let slewed = i64::from(self.pending_ms).clamp(-max_step, max_step);
+        Some(i64::from(day_ms) + i64::from(elapsed) + slewed)
+    }
+
+    /// Returns the date `total_ms` after the anchor's midnight.
+    fn date_after(&self, total_ms: i64) -> Option<Date> {
+        self.anchor_date
+            .map(|date| date.add_days(total_ms.div_euclid(DAY_MS)))
+    }
+
+    /// Returns the last received time, if any.
+    pub fn last_time(&self) -> Option<LocalTime> {
+        self.last_tick.map(|(time, _)| time)
+    }
+
+    /// Returns the milliseconds since the last tick, or `None` before the first tick.
+    pub fn age_ms(&self, now_ms: u32) -> Option<u32> {
+        self.last_tick
+            .map(|(_, received_ms)| now_ms.wrapping_sub(received_ms))
+    }
+
+    /// Returns true if no tick arrived within `max_age_ms`, including when none arrived yet.
+    pub fn is_stale(&self, now_ms: u32, max_age_ms: u32) -> bool {
+        !matches!(self.age_ms(now_ms), Some(age) if age <= max_age_ms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u8, minute: u8, second: u8) -> LocalTime {
+        LocalTime::new(hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn test_new_keeper_has_no_time_and_is_stale() {
+        let keeper = TimeKeeper::new();
+        assert_eq!(keeper.last_time(), None);
+        assert_eq!(keeper.age_ms(1_000), None);
+        assert!(keeper.is_stale(0, u32::MAX));
+    }
+
+    #[test]
+    fn test_update_records_time_and_age() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(8, 15, 0), 500);
+        assert_eq!(keeper.last_time(), Some(time(8, 15, 0)));
+        assert_eq!(keeper.age_ms(1_750), Some(1_250));
+    }
+
+    #[test]
+    fn test_stale_boundary() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(8, 15, 0), 0);
+        assert!(!keeper.is_stale(5_000, 5_000));
+        assert!(keeper.is_stale(5_001, 5_000));
+    }
+
+    #[test]
+    fn test_new_tick_makes_time_fresh_again() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(8, 15, 0), 0);
+        assert!(keeper.is_stale(60_000, 5_000));
+        keeper.update(time(8, 16, 0), 60_000);
+        assert!(!keeper.is_stale(60_500, 5_000));
+    }
+
+    // ===== local timekeeping tests =====
+
+    #[test]
+    fn test_now_before_first_tick() {
+        assert_eq!(TimeKeeper::new().now(1_000), None);
+    }
+
+    #[test]
+    fn test_now_advances_locally() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(12, 0, 0), 0);
+        assert_eq!(keeper.now(999), Some(time(12, 0, 0)));
+        assert_eq!(keeper.now(1_500), Some(time(12, 0, 1)));
+        assert_eq!(keeper.now(3_600_000), Some(time(13, 0, 0)));
+    }
+
+    #[test]
+    fn test_now_wraps_at_midnight() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(23, 59, 59), 0);
+        assert_eq!(keeper.now(2_000), Some(time(0, 0, 1)));
+    }
+
+    #[test]
+    fn test_small_error_is_slewed_in() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(12, 0, 0), 0);
+        // The local estimate says 12:00:10, the tick says one second later
+        keeper.update(time(12, 0, 11), 10_000);
+        assert_eq!(keeper.now(10_000), Some(time(12, 0, 10)));
+        assert_eq!(keeper.now(15_000), Some(time(12, 0, 15)));
+        assert_eq!(keeper.now(20_000), Some(time(12, 0, 21)));
+        assert_eq!(keeper.now(30_000), Some(time(12, 0, 31)));
+    }
+
+    #[test]
+    fn test_slewing_back_never_goes_backwards() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(12, 0, 0), 0);
+        keeper.update(time(12, 0, 8), 10_000);
+        let mut previous = keeper.now(10_000).unwrap();
+        for now_ms in (10_000..40_000).step_by(100) {
+            let current = keeper.now(now_ms).unwrap();
+            assert!(current.to_seconds() >= previous.to_seconds());
+            previous = current;
+        }
+        assert_eq!(keeper.now(40_000), Some(time(12, 0, 38)));
+    }
+
+    #[test]
+    fn test_large_error_jumps() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(12, 0, 0), 0);
+        keeper.update(time(12, 5, 0), 10_000);
+        assert_eq!(keeper.now(10_000), Some(time(12, 5, 0)));
+    }
+
+    #[test]
+    fn test_tick_across_midnight_is_small_error() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(23, 59, 58), 0);
+        keeper.update(time(0, 0, 0), 1_000);
+        // One second ahead of the estimate: slewed, not a 24 hour jump
+        assert_eq!(keeper.now(1_000), Some(time(23, 59, 59)));
+        assert_eq!(keeper.now(11_000), Some(time(0, 0, 10)));
+    }
+
+    #[test]
+    fn test_millis_align_sub_second_time() {
+        let mut keeper = TimeKeeper::new();
+        let tick = LocalDateTime::from(time(12, 0, 0)).with_millis(900);
+        keeper.update(tick, 0);
+        assert_eq!(keeper.millis_since_midnight(50), Some(12 * 3_600_000 + 950));
+        assert_eq!(keeper.now(100), Some(time(12, 0, 1)));
+    }
+
+    // ===== date tests =====
+
+    fn date(year: u16, month: u8, day: u8) -> Date {
+        Date::new(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_today_without_date_is_none() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(12, 0, 0), 0);
+        assert_eq!(keeper.today(0), None);
+    }
+
+    #[test]
+    fn test_date_advances_at_midnight() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(
+            LocalDateTime::new(time(23, 59, 0), Some(date(2024, 12, 31))),
+            0,
+        );
+        assert_eq!(keeper.today(59_999), Some(date(2024, 12, 31)));
+        assert_eq!(keeper.today(60_000), Some(date(2025, 1, 1)));
+    }
+
+    #[test]
+    fn test_time_only_tick_keeps_date() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(
+            LocalDateTime::new(time(23, 59, 59), Some(date(2024, 3, 1))),
+            0,
+        );
+        keeper.update(time(0, 0, 1), 2_000);
+        assert_eq!(keeper.today(2_000), Some(date(2024, 3, 2)));
+        // A large jump keeps the current date as well
+        keeper.update(time(6, 0, 0), 3_000);
+        assert_eq!(keeper.today(3_000), Some(date(2024, 3, 2)));
+    }
+
+    #[test]
+    fn test_dated_tick_just_past_midnight_while_slewing() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(23, 59, 58), 0);
+        // Estimate is 23:59:59 on an unknown day; the tick says 00:00:00 on March 2
+        keeper.update(
+            LocalDateTime::new(time(0, 0, 0), Some(date(2024, 3, 2))),
+            1_000,
+        );
+        assert_eq!(keeper.now(1_000), Some(time(23, 59, 59)));
+        assert_eq!(keeper.today(1_000), Some(date(2024, 3, 1)));
+        assert_eq!(keeper.today(11_000), Some(date(2024, 3, 2)));
+    }
+
+    #[test]
+    fn test_age_survives_counter_wraparound() {
+        let mut keeper = TimeKeeper::new();
+        keeper.update(time(8, 15, 0), u32::MAX - 99);
+        assert_eq!(keeper.age_ms(400), Some(500));
+    }
+}
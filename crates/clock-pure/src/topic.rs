@@ -0,0 +1,156 @@
+//! The topics the clock handles messages on.
+
+/// A topic the clock handles, named as the first clock subscribes it, e.g.
+/// `clock/brightness` for [`Topic::Brightness`].
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::Topic;
+///
+/// assert_eq!(Topic::parse("clock/brightness"), Some(Topic::Brightness));
+/// assert_eq!(Topic::SceneRecall.name(), "clock/scene/recall");
+/// assert_eq!(Topic::parse("clock/state"), None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    Tick,
+    Playlist,
+    Brightness,
+    Colors,
+    Night,
+    DoNotDisturb,
+    Mode,
+    Pomodoro,
+    Stopwatch,
+    Alarm,
+    Notify,
+    Frame,
+    Weather,
+    Temperature,
+    Presence,
+    Identify,
+    Startup,
+    Animation,
+    SelfTest,
+    Power,
+    Scene,
+    SceneRecall,
+    Schedule,
+    Calibration,
+    DeadPixels,
+    Buzzer,
+    BuzzerPlay,
+    TimeZone,
+    Ota,
+    /// JSON commands for most of the other topics, see [`RemoteCommand`](crate::RemoteCommand)
+    Cmd,
+    /// A retained configuration, see [`RemoteCommand::parse_config`](crate::RemoteCommand::parse_config)
+    Config,
+}
+
+impl Topic {
+    /// Every topic, in the order the clock subscribes them.
+    pub const ALL: [Topic; 31] = [
+        Topic::Tick,
+        Topic::Playlist,
+        Topic::Brightness,
+        Topic::Colors,
+        Topic::Night,
+        Topic::DoNotDisturb,
+        Topic::Mode,
+        Topic::Pomodoro,
+        Topic::Stopwatch,
+        Topic::Alarm,
+        Topic::Notify,
+        Topic::Frame,
+        Topic::Weather,
+        Topic::Temperature,
+        Topic::Presence,
+        Topic::Identify,
+        Topic::Startup,
+        Topic::Animation,
+        Topic::SelfTest,
+        Topic::Power,
+        Topic::Scene,
+        Topic::SceneRecall,
+        Topic::Schedule,
+        Topic::Calibration,
+        Topic::DeadPixels,
+        Topic::Buzzer,
+        Topic::BuzzerPlay,
+        Topic::TimeZone,
+        Topic::Ota,
+        Topic::Cmd,
+        Topic::Config,
+    ];
+
+    /// Returns the topic called `name`, or `None` if the clock handles no
+    /// messages there.
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|topic| topic.name() == name)
+    }
+
+    /// Returns the name of the topic, e.g. `clock/brightness`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Topic::Tick => "tick",
+            Topic::Playlist => "clock/playlist",
+            Topic::Brightness => "clock/brightness",
+            Topic::Colors => "clock/colors",
+            Topic::Night => "clock/night",
+            Topic::DoNotDisturb => "clock/dnd",
+            Topic::Mode => "clock/mode",
+            Topic::Pomodoro => "clock/pomodoro",
+            Topic::Stopwatch => "clock/stopwatch",
+            Topic::Alarm => "clock/alarm",
+            Topic::Notify => "clock/notify",
+            Topic::Frame => "clock/frame",
+            Topic::Weather => "clock/weather",
+            Topic::Temperature => "clock/temperature",
+            Topic::Presence => "clock/presence",
+            Topic::Identify => "clock/identify",
+            Topic::Startup => "clock/startup",
+            Topic::Animation => "clock/animation",
+            Topic::SelfTest => "clock/selftest",
+            Topic::Power => "clock/power",
+            Topic::Scene => "clock/scene",
+            Topic::SceneRecall => "clock/scene/recall",
+            Topic::Schedule => "clock/schedule",
+            Topic::Calibration => "clock/calibration",
+            Topic::DeadPixels => "clock/calibration/dead",
+            Topic::Buzzer => "clock/buzzer",
+            Topic::BuzzerPlay => "clock/buzzer/play",
+            Topic::TimeZone => "clock/timezone",
+            Topic::Ota => "clock/ota",
+            Topic::Cmd => "clock/cmd",
+            Topic::Config => "clock/config",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        for (i, topic) in Topic::ALL.into_iter().enumerate() {
+            assert_eq!(Topic::parse(topic.name()), Some(topic));
+            assert!(
+                Topic::ALL[..i].iter().all(|other| *other != topic),
+                "{topic:?}"
+            );
+        }
+        for unknown in [
+            "",
+            "clock",
+            "clock/",
+            "Clock/mode",
+            "clock/mode/",
+            "clock/state",
+        ] {
+            assert_eq!(Topic::parse(unknown), None, "{unknown}");
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Cross-fades between whole frames, so the ring never jumps from one picture to the next.
+
+use crate::{mix_colors, Frame};
+
+/// A cross-fade from a fixed frame to whatever is drawn next.
+///
+/// The transition remembers the frame it starts from; each new frame is
+/// blended over it, starting fully at the old frame and reaching the new one
+/// after `duration_ms`. Starting from a dark frame fades the new content in.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Frame, Transition};
+///
+/// let transition = Transition::new(Frame::new(), 1_000, 500);
+/// let mut face = Frame::new();
+/// face[0] = (255, 0, 0);
+///
+/// let mut halfway = face;
+/// transition.apply(1_250, &mut halfway);
+/// assert_eq!(halfway[0], (127, 0, 0));
+///
+/// assert!(transition.is_finished(1_500));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    from: Frame,
+    started_ms: u32,
+    duration_ms: u32,
+}
+
+impl Transition {
+    /// Starts a cross-fade away from `from` at `started_ms`, lasting `duration_ms`.
+    pub const fn new(from: Frame, started_ms: u32, duration_ms: u32) -> Self {
+        Self {
+            from,
+            started_ms,
+            duration_ms,
+        }
+    }
+
+    /// Returns true once the new frames are shown unchanged at `now_ms`.
+    pub const fn is_finished(&self, now_ms: u32) -> bool {
+        now_ms.wrapping_sub(self.started_ms) >= self.duration_ms
+    }
+
+    /// Returns how far the cross-fade has progressed at `now_ms`, from 0 to 255.
+    pub const fn progress(&self, now_ms: u32) -> u8 {
+        let elapsed_ms = now_ms.wrapping_sub(self.started_ms);
+        if elapsed_ms >= self.duration_ms {
+            u8::MAX
+        } else {
+            (elapsed_ms as u64 * 255 / self.duration_ms as u64) as u8
+        }
+    }
+
+    /// Blends the starting frame over `frame` for the state of the cross-fade at `now_ms`.
+    pub fn apply(&self, now_ms: u32, frame: &mut Frame) {
+        let progress = self.progress(now_ms);
+        for (pixel, &from) in frame.pixels_mut().iter_mut().zip(self.from.pixels()) {
+            *pixel = mix_colors(from, *pixel, progress);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(color: crate::Rgb) -> Frame {
+        Frame::from_pixels([color; crate::LED_COUNT])
+    }
+
+    #[test]
+    fn test_starts_at_old_frame() {
+        let transition = Transition::new(solid((0, 0, 255)), 0, 600);
+        let mut frame = solid((255, 0, 0));
+        transition.apply(0, &mut frame);
+        assert_eq!(frame, solid((0, 0, 255)));
+        assert!(!transition.is_finished(599));
+    }
+
+    #[test]
+    fn test_ends_at_new_frame() {
+        let transition = Transition::new(solid((0, 0, 255)), 0, 600);
+        let mut frame = solid((255, 0, 0));
+        transition.apply(600, &mut frame);
+        assert_eq!(frame, solid((255, 0, 0)));
+        assert!(transition.is_finished(600));
+    }
+
+    #[test]
+    fn test_zero_duration_is_instant() {
+        let transition = Transition::new(solid((0, 0, 255)), 100, 0);
+        let mut frame = solid((255, 0, 0));
+        transition.apply(100, &mut frame);
+        assert_eq!(frame, solid((255, 0, 0)));
+    }
+
+    #[test]
+    fn test_counter_wraparound() {
+        let transition = Transition::new(Frame::new(), u32::MAX - 99, 400);
+        assert_eq!(transition.progress(100), 127);
+        assert!(!transition.is_finished(299));
+        assert!(transition.is_finished(300));
+    }
+}
@@ -0,0 +1,692 @@
+//! Time zones with daylight saving time, described by POSIX TZ strings.
+//!
+//! A POSIX TZ string such as `CET-1CEST,M3.5.0,M10.5.0/3` carries the
+//! standard offset, the DST offset, and the rules for switching between
+//! them, so UTC can be converted to local time without a time zone database.
+//! A handful of common IANA names are mapped to their POSIX equivalents.
+
+use crate::date::{
+    civil_from_days, days_from_civil, days_in_month, is_leap_year, Date, LocalDateTime,
+};
+use crate::settings::SettingsError;
+use crate::time::{LocalTime, SECONDS_PER_DAY};
+
+/// Error type for time zone parsing failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimeZoneError {
+    /// A zone abbreviation is missing or shorter than three characters
+    InvalidName,
+    /// A UTC offset is missing or malformed
+    InvalidOffset,
+    /// A DST transition rule is malformed
+    InvalidRule,
+    /// Unexpected characters follow a complete TZ string
+    TrailingCharacters,
+    /// The IANA zone name is not in the built-in table
+    UnknownZone,
+}
+
+impl core::fmt::Display for TimeZoneError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TimeZoneError::InvalidName => write!(f, "Invalid time zone abbreviation"),
+            TimeZoneError::InvalidOffset => write!(f, "Invalid UTC offset in time zone"),
+            TimeZoneError::InvalidRule => write!(f, "Invalid DST rule in time zone"),
+            TimeZoneError::TrailingCharacters => {
+                write!(f, "Unexpected characters after time zone")
+            }
+            TimeZoneError::UnknownZone => {
+                write!(f, "Unknown time zone name, use a POSIX TZ string instead")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for TimeZoneError {}
+
+/// Common IANA zone names and their POSIX TZ strings.
+const KNOWN_ZONES: &[(&str, &str)] = &[
+    ("UTC", "UTC0"),
+    ("Etc/UTC", "UTC0"),
+    ("Europe/London", "GMT0BST,M3.5.0/1,M10.5.0"),
+    ("Europe/Dublin", "IST-1GMT0,M10.5.0,M3.5.0/1"),
+    ("Europe/Lisbon", "WET0WEST,M3.5.0/1,M10.5.0"),
+    ("Europe/Berlin", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Amsterdam", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Brussels", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Paris", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Madrid", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Rome", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Vienna", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Zurich", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Stockholm", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Warsaw", "CET-1CEST,M3.5.0,M10.5.0/3"),
+    ("Europe/Athens", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Helsinki", "EET-2EEST,M3.5.0/3,M10.5.0/4"),
+    ("Europe/Moscow", "MSK-3"),
+    ("America/New_York", "EST5EDT,M3.2.0,M11.1.0"),
+    ("America/Chicago", "CST6CDT,M3.2.0,M11.1.0"),
+    ("America/Denver", "MST7MDT,M3.2.0,M11.1.0"),
+    ("America/Phoenix", "MST7"),
+    ("America/Los_Angeles", "PST8PDT,M3.2.0,M11.1.0"),
+    ("America/Sao_Paulo", "<-03>3"),
+    ("Asia/Kolkata", "IST-5:30"),
+    ("Asia/Shanghai", "CST-8"),
+    ("Asia/Tokyo", "JST-9"),
+    ("Australia/Sydney", "AEST-10AEDT,M10.1.0,M4.1.0/3"),
+    ("Pacific/Auckland", "NZST-12NZDT,M9.5.0,M4.1.0/3"),
+];
+
+/// The day a DST transition happens on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleDate {
+    /// `Jn`: day 1-365, February 29 is never counted
+    Julian1(u16),
+    /// `n`: zero-based day 0-365, counting February 29 in leap years
+    Julian0(u16),
+    /// `Mm.w.d`: weekday `d` (0 = Sunday) of week `w` (1-5, 5 = last) in month `m`
+    MonthWeekDay { month: u8, week: u8, weekday: u8 },
+}
+
+/// A DST transition: a rule date plus the local time of day it happens at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Transition {
+    date: RuleDate,
+    /// Seconds after local midnight; may be negative or exceed a day
+    time: i32,
+}
+
+impl Transition {
+    /// Size of the binary form used by [`Transition::to_bytes`].
+    const BYTES: usize = 8;
+
+    /// Encodes the kind of rule date, its fields, and the time of day.
+    fn to_bytes(self) -> [u8; Self::BYTES] {
+        let date = match self.date {
+            RuleDate::Julian1(n) => {
+                let [low, high] = n.to_le_bytes();
+                [0, low, high, 0]
+            }
+            RuleDate::Julian0(n) => {
+                let [low, high] = n.to_le_bytes();
+                [1, low, high, 0]
+            }
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => [2, month, week, weekday],
+        };
+        let mut bytes = [0; Self::BYTES];
+        bytes[..4].copy_from_slice(&date);
+        bytes[4..].copy_from_slice(&self.time.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a transition stored with [`Transition::to_bytes`], checking
+    /// its fields as the parser does.
+    fn from_bytes(bytes: &[u8; Self::BYTES]) -> Result<Self, SettingsError> {
+        let [kind, a, b, c, time @ ..] = *bytes;
+        let n = u16::from_le_bytes([a, b]);
+        let date = match kind {
+            0 if (1..=365).contains(&n) => RuleDate::Julian1(n),
+            1 if n <= 365 => RuleDate::Julian0(n),
+            2 if (1..=12).contains(&a) && (1..=5).contains(&b) && c <= 6 => {
+                RuleDate::MonthWeekDay {
+                    month: a,
+                    week: b,
+                    weekday: c,
+                }
+            }
+            _ => return Err(SettingsError::InvalidValue),
+        };
+        Ok(Self {
+            date,
+            time: i32::from_le_bytes(time),
+        })
+    }
+
+    /// Returns the transition as seconds since the epoch, in local wall-clock time.
+    fn local_seconds(&self, year: i64) -> i64 {
+        let days = match self.date {
+            RuleDate::Julian1(n) => {
+                let leap_day = i64::from(is_leap_year(year) && n >= 60);
+                days_from_civil(year, 1, 1) + i64::from(n) - 1 + leap_day
+            }
+            RuleDate::Julian0(n) => days_from_civil(year, 1, 1) + i64::from(n),
+            RuleDate::MonthWeekDay {
+                month,
+                week,
+                weekday,
+            } => {
+                let first = days_from_civil(year, month, 1);
+                let first_weekday = (first + 4).rem_euclid(7) as u8;
+                let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+                first + i64::from(day) - 1
+            }
+        };
+        days * i64::from(SECONDS_PER_DAY) + i64::from(self.time)
+    }
+}
+
+/// Daylight saving time: its offset and when it starts and ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DstRule {
+    offset: i32,
+    start: Transition,
+    end: Transition,
+}
+
+/// A time zone: a standard UTC offset and optional daylight saving time rules.
+///
+/// Offsets are in seconds east of UTC (positive for Europe, negative for the
+/// Americas), unlike the inverted sign used inside POSIX TZ strings.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{LocalTime, TimeZone};
+///
+/// let berlin: TimeZone = "Europe/Berlin".parse().unwrap();
+///
+/// // 2024-01-15T12:00:00Z is winter time, 2024-07-15T12:00:00Z summer time
+/// assert_eq!(berlin.local_time(1_705_320_000), LocalTime::new(13, 0, 0).unwrap());
+/// assert_eq!(berlin.local_time(1_721_044_800), LocalTime::new(14, 0, 0).unwrap());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeZone {
+    std_offset: i32,
+    dst: Option<DstRule>,
+}
+
+impl Default for TimeZone {
+    fn default() -> Self {
+        Self::utc()
+    }
+}
+
+impl TimeZone {
+    /// Size of the binary form used by [`TimeZone::to_bytes`].
+    pub const BYTES: usize = 9 + 2 * Transition::BYTES;
+
+    /// Coordinated Universal Time.
+    pub const fn utc() -> Self {
+        Self::fixed(0)
+    }
+
+    /// A zone with a fixed offset of `utc_offset_seconds` east of UTC and no DST.
+    pub const fn fixed(utc_offset_seconds: i32) -> Self {
+        Self {
+            std_offset: utc_offset_seconds,
+            dst: None,
+        }
+    }
+
+    /// Parses a POSIX TZ string such as `EST5EDT,M3.2.0,M11.1.0`.
+    ///
+    /// Without explicit rules, DST follows the current US rules, as in glibc.
+    pub fn from_posix(tz: &str) -> Result<Self, TimeZoneError> {
+        let mut parser = Parser { rest: tz.trim() };
+
+        parser.name()?;
+        let std_offset = parser.offset().ok_or(TimeZoneError::InvalidOffset)?;
+        if parser.rest.is_empty() {
+            return Ok(Self::fixed(std_offset));
+        }
+
+        parser.name()?;
+        let dst_offset = parser.offset().unwrap_or(std_offset + 3600);
+        let (start, end) = if parser.eat(',') {
+            let start = parser.transition()?;
+            if !parser.eat(',') {
+                return Err(TimeZoneError::InvalidRule);
+            }
+            (start, parser.transition()?)
+        } else {
+            (US_DST_START, US_DST_END)
+        };
+        if !parser.rest.is_empty() {
+            return Err(TimeZoneError::TrailingCharacters);
+        }
+
+        Ok(Self {
+            std_offset,
+            dst: Some(DstRule {
+                offset: dst_offset,
+                start,
+                end,
+            }),
+        })
+    }
+
+    /// Encodes the offsets and DST rules for persistent storage.
+    pub fn to_bytes(&self) -> [u8; Self::BYTES] {
+        let mut bytes = [0; Self::BYTES];
+        bytes[..4].copy_from_slice(&self.std_offset.to_le_bytes());
+        if let Some(dst) = self.dst {
+            bytes[4] = 1;
+            bytes[5..9].copy_from_slice(&dst.offset.to_le_bytes());
+            bytes[9..17].copy_from_slice(&dst.start.to_bytes());
+            bytes[17..].copy_from_slice(&dst.end.to_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes a zone stored with [`TimeZone::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SettingsError> {
+        let bytes: &[u8; Self::BYTES] = bytes
+            .try_into()
+            .map_err(|_| SettingsError::InvalidLength(bytes.len()))?;
+        let offset = |at: usize| {
+            i32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+        };
+        let transition = |at: usize| {
+            let mut transition = [0; Transition::BYTES];
+            transition.copy_from_slice(&bytes[at..at + Transition::BYTES]);
+            Transition::from_bytes(&transition)
+        };
+        let dst = match bytes[4] {
+            0 => None,
+            1 => Some(DstRule {
+                offset: offset(5),
+                start: transition(9)?,
+                end: transition(17)?,
+            }),
+            _ => return Err(SettingsError::InvalidValue),
+        };
+        Ok(Self {
+            std_offset: offset(0),
+            dst,
+        })
+    }
+
+    /// Returns true if the zone observes daylight saving time.
+    pub fn has_dst(&self) -> bool {
+        self.dst.is_some()
+    }
+
+    /// Returns the UTC offset in seconds in effect at the Unix timestamp `unix_seconds`.
+    pub fn offset_at(&self, unix_seconds: i64) -> i32 {
+        let Some(dst) = self.dst else {
+            return self.std_offset;
+        };
+
+        let day = (unix_seconds + i64::from(self.std_offset)).div_euclid(SECONDS_PER_DAY.into());
+        let (year, _, _) = civil_from_days(day);
+
+        // Start is given in standard time, end in daylight saving time
+        let start = dst.start.local_seconds(year) - i64::from(self.std_offset);
+        let end = dst.end.local_seconds(year) - i64::from(dst.offset);
+        let in_dst = if start < end {
+            (start..end).contains(&unix_seconds)
+        } else {
+            // Southern hemisphere: DST spans the turn of the year
+            unix_seconds >= start || unix_seconds < end
+        };
+
+        if in_dst {
+            dst.offset
+        } else {
+            self.std_offset
+        }
+    }
+
+    /// Converts the Unix timestamp `unix_seconds` to local time in this zone.
+    pub fn local_time(&self, unix_seconds: i64) -> LocalTime {
+        LocalTime::from_unix_timestamp(unix_seconds, self.offset_at(unix_seconds))
+    }
+
+    /// Converts the Unix timestamp `unix_seconds` to the local date and time in this zone.
+    pub fn local_date_time(&self, unix_seconds: i64) -> LocalDateTime {
+        let local = unix_seconds + i64::from(self.offset_at(unix_seconds));
+        let day = local.div_euclid(SECONDS_PER_DAY.into());
+        LocalDateTime::new(
+            self.local_time(unix_seconds),
+            Some(Date::from_days_since_epoch(day)),
+        )
+    }
+}
+
+impl core::str::FromStr for TimeZone {
+    type Err = TimeZoneError;
+
+    /// Parses a known IANA zone name such as `Europe/Berlin`, a POSIX TZ
+    /// string, or a fixed offset in seconds east of UTC such as `3600`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let name = name.trim();
+        if let Ok(offset) = name.parse::<i32>() {
+            if offset.unsigned_abs() > SECONDS_PER_DAY {
+                return Err(TimeZoneError::InvalidOffset);
+            }
+            return Ok(Self::fixed(offset));
+        }
+        if let Some((_, posix)) = KNOWN_ZONES.iter().find(|(zone, _)| *zone == name) {
+            return Self::from_posix(posix);
+        }
+        Self::from_posix(name).map_err(|e| {
+            // `Area/Location` looks like an IANA name rather than a broken TZ string
+            if name.contains('/') && !name.contains(',') {
+                TimeZoneError::UnknownZone
+            } else {
+                e
+            }
+        })
+    }
+}
+
+/// US rules, used when a TZ string names a DST zone without rules.
+const US_DST_START: Transition = Transition {
+    date: RuleDate::MonthWeekDay {
+        month: 3,
+        week: 2,
+        weekday: 0,
+    },
+    time: 2 * 3600,
+};
+const US_DST_END: Transition = Transition {
+    date: RuleDate::MonthWeekDay {
+        month: 11,
+        week: 1,
+        weekday: 0,
+    },
+    time: 2 * 3600,
+};
+
+/// Cursor over the remaining characters of a POSIX TZ string.
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl Parser<'_> {
+    /// Consumes `c` if it is next.
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Consumes a zone abbreviation: three or more letters, or `<...>` quoted.
+    fn name(&mut self) -> Result<(), TimeZoneError> {
+        let len = if self.eat('<') {
+            let end = self.rest.find('>').ok_or(TimeZoneError::InvalidName)?;
+            self.rest = &self.rest[end + 1..];
+            end
+        } else {
+            let end = self
+                .rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(self.rest.len());
+            self.rest = &self.rest[end..];
+            end
+        };
+        if len < 3 {
+            return Err(TimeZoneError::InvalidName);
+        }
+        Ok(())
+    }
+
+    /// Consumes a decimal number of at most `max`.
+    fn number(&mut self, max: u32) -> Option<u32> {
+        let end = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let value = self.rest[..end].parse().ok().filter(|v| *v <= max)?;
+        self.rest = &self.rest[end..];
+        Some(value)
+    }
+
+    /// Consumes `[+-]hh[:mm[:ss]]` with hours up to `max_hours`, returning seconds.
+    fn signed_hms(&mut self, max_hours: u32) -> Option<i32> {
+        let negative = self.eat('-');
+        if !negative {
+            self.eat('+');
+        }
+        let mut seconds = self.number(max_hours)? * 3600;
+        if self.eat(':') {
+            seconds += self.number(59)? * 60;
+            if self.eat(':') {
+                seconds += self.number(59)?;
+            }
+        }
+        let seconds = seconds as i32;
+        Some(if negative { -seconds } else { seconds })
+    }
+
+    /// Consumes a UTC offset; POSIX counts west of UTC as positive, so the sign flips.
+    fn offset(&mut self) -> Option<i32> {
+        let before = self.rest;
+        let offset = self.signed_hms(24).map(|seconds| -seconds);
+        if offset.is_none() {
+            self.rest = before;
+        }
+        offset
+    }
+
+    /// Consumes a transition rule `date[/time]`.
+    fn transition(&mut self) -> Result<Transition, TimeZoneError> {
+        let date = if self.eat('J') {
+            let day = self
+                .number(365)
+                .filter(|d| *d >= 1)
+                .ok_or(TimeZoneError::InvalidRule)?;
+            RuleDate::Julian1(day as u16)
+        } else if self.eat('M') {
+            let month = self
+                .number(12)
+                .filter(|m| *m >= 1)
+                .ok_or(TimeZoneError::InvalidRule)?;
+            let week = self
+                .eat('.')
+                .then(|| self.number(5))
+                .flatten()
+                .filter(|w| *w >= 1)
+                .ok_or(TimeZoneError::InvalidRule)?;
+            let weekday = self
+                .eat('.')
+                .then(|| self.number(6))
+                .flatten()
+                .ok_or(TimeZoneError::InvalidRule)?;
+            RuleDate::MonthWeekDay {
+                month: month as u8,
+                week: week as u8,
+                weekday: weekday as u8,
+            }
+        } else {
+            RuleDate::Julian0(self.number(365).ok_or(TimeZoneError::InvalidRule)? as u16)
+        };
+
+        let time = if self.eat('/') {
+            self.signed_hms(167).ok_or(TimeZoneError::InvalidRule)?
+        } else {
+            2 * 3600
+        };
+        Ok(Transition { date, time })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn zone(name: &str) -> TimeZone {
+        name.parse().unwrap()
+    }
+
+    // ===== rule tests =====
+
+    #[test]
+    fn test_last_sunday_of_march() {
+        let rule = Transition {
+            date: RuleDate::MonthWeekDay {
+                month: 3,
+                week: 5,
+                weekday: 0,
+            },
+            time: 0,
+        };
+        assert_eq!(
+            rule.local_seconds(2024),
+            days_from_civil(2024, 3, 31) * 86_400
+        );
+        assert_eq!(
+            rule.local_seconds(2023),
+            days_from_civil(2023, 3, 26) * 86_400
+        );
+    }
+
+    #[test]
+    fn test_julian_rules_and_leap_day() {
+        let j60 = Transition {
+            date: RuleDate::Julian1(60),
+            time: 0,
+        };
+        let n59 = Transition {
+            date: RuleDate::Julian0(59),
+            time: 0,
+        };
+        // J60 is always March 1; zero-based day 59 is February 29 in leap years
+        assert_eq!(
+            j60.local_seconds(2024),
+            days_from_civil(2024, 3, 1) * 86_400
+        );
+        assert_eq!(
+            n59.local_seconds(2024),
+            days_from_civil(2024, 2, 29) * 86_400
+        );
+        assert_eq!(
+            n59.local_seconds(2023),
+            days_from_civil(2023, 3, 1) * 86_400
+        );
+    }
+
+    // ===== parsing tests =====
+
+    #[test]
+    fn test_parse_fixed_offsets() {
+        assert_eq!(zone("UTC0"), TimeZone::utc());
+        assert_eq!(zone("JST-9"), TimeZone::fixed(9 * 3600));
+        assert_eq!(zone("IST-5:30"), TimeZone::fixed(5 * 3600 + 30 * 60));
+        assert_eq!(zone("<-03>3"), TimeZone::fixed(-3 * 3600));
+        assert_eq!(zone("3600"), TimeZone::fixed(3600));
+        assert_eq!(zone(" -16200 "), TimeZone::fixed(-16_200));
+        assert_eq!(
+            "90000".parse::<TimeZone>(),
+            Err(TimeZoneError::InvalidOffset)
+        );
+    }
+
+    #[test]
+    fn test_parse_dst_defaults() {
+        let tz = zone("EST5EDT");
+        assert!(tz.has_dst());
+        assert_eq!(tz, zone("EST5EDT,M3.2.0/2,M11.1.0/2"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!("".parse::<TimeZone>(), Err(TimeZoneError::InvalidName));
+        assert_eq!("CE-1".parse::<TimeZone>(), Err(TimeZoneError::InvalidName));
+        assert_eq!("CET".parse::<TimeZone>(), Err(TimeZoneError::InvalidOffset));
+        assert_eq!(
+            "CET-1CEST,M3.5.0".parse::<TimeZone>(),
+            Err(TimeZoneError::InvalidRule)
+        );
+        assert_eq!(
+            "CET-1CEST,M13.5.0,M10.5.0".parse::<TimeZone>(),
+            Err(TimeZoneError::InvalidRule)
+        );
+        assert_eq!(
+            "CET-1CEST,M3.5.0,M10.5.0/3x".parse::<TimeZone>(),
+            Err(TimeZoneError::TrailingCharacters)
+        );
+        assert_eq!(
+            "Mars/Olympus_Mons".parse::<TimeZone>(),
+            Err(TimeZoneError::UnknownZone)
+        );
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for name in [
+            "UTC",
+            "IST-5:30",
+            "Europe/Berlin",
+            "Australia/Sydney",
+            "XST3XDT,J60/-1,300/26",
+        ] {
+            let tz = zone(name);
+            assert_eq!(TimeZone::from_bytes(&tz.to_bytes()), Ok(tz), "{name}");
+        }
+        assert_eq!(
+            TimeZone::from_bytes(&[0; 4]),
+            Err(SettingsError::InvalidLength(4))
+        );
+        let mut bytes = zone("Europe/Berlin").to_bytes();
+        bytes[9 + 1] = 13;
+        assert_eq!(
+            TimeZone::from_bytes(&bytes),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_known_zones_all_parse() {
+        for (name, posix) in KNOWN_ZONES {
+            assert!(TimeZone::from_posix(posix).is_ok(), "{name}: {posix}");
+        }
+    }
+
+    // ===== conversion tests =====
+
+    #[test]
+    fn test_berlin_transitions() {
+        let berlin = zone("Europe/Berlin");
+        // DST starts 2024-03-31T01:00:00Z and ends 2024-10-27T01:00:00Z
+        assert_eq!(berlin.offset_at(1_711_846_800 - 1), 3600);
+        assert_eq!(berlin.offset_at(1_711_846_800), 7200);
+        assert_eq!(berlin.offset_at(1_729_990_800 - 1), 7200);
+        assert_eq!(berlin.offset_at(1_729_990_800), 3600);
+    }
+
+    #[test]
+    fn test_new_york_transitions() {
+        let new_york = zone("America/New_York");
+        // DST starts 2024-03-10T07:00:00Z and ends 2024-11-03T06:00:00Z
+        assert_eq!(new_york.offset_at(1_710_054_000 - 1), -5 * 3600);
+        assert_eq!(new_york.offset_at(1_710_054_000), -4 * 3600);
+        assert_eq!(new_york.offset_at(1_730_613_600 - 1), -4 * 3600);
+        assert_eq!(new_york.offset_at(1_730_613_600), -5 * 3600);
+    }
+
+    #[test]
+    fn test_southern_hemisphere_dst_spans_new_year() {
+        let sydney = zone("Australia/Sydney");
+        // 2024-01-15T12:00:00Z is summer, 2024-07-15T12:00:00Z winter
+        assert_eq!(sydney.offset_at(1_705_320_000), 11 * 3600);
+        assert_eq!(sydney.offset_at(1_721_044_800), 10 * 3600);
+    }
+
+    #[test]
+    fn test_local_date_time_crosses_date_line() {
+        // 2024-12-31T23:30:00Z is already New Year's Day in Berlin
+        let date_time = zone("Europe/Berlin").local_date_time(1_735_687_800);
+        assert_eq!(date_time.time, LocalTime::new(0, 30, 0).unwrap());
+        assert_eq!(date_time.date, Some(Date::new(2025, 1, 1).unwrap()));
+    }
+
+    #[test]
+    fn test_local_time_with_fractional_offset() {
+        // 2024-06-01T12:00:00Z in India
+        let time = zone("Asia/Kolkata").local_time(1_717_243_200);
+        assert_eq!(time, LocalTime::new(17, 30, 0).unwrap());
+    }
+}
@@ -0,0 +1,376 @@
+//! Weather shown on the ring: an animation for the condition, then the
+//! temperature as an arc.
+
+use crate::gauge::TemperatureGauge;
+use crate::lut::{phase_of, sine8};
+use crate::settings::SettingsError;
+use crate::{fade_color, Rgb};
+#[cfg(feature = "std")]
+use std::string::String;
+
+/// How long the condition animation plays before the temperature arc.
+pub const WEATHER_CONDITION_MS: u32 = 5_000;
+
+/// One full weather cycle: the condition, then the temperature.
+pub const WEATHER_CYCLE_MS: u32 = 2 * WEATHER_CONDITION_MS;
+
+/// Outdoor temperatures (°C) mapped to an empty and a full ring.
+const WEATHER_GAUGE: TemperatureGauge = TemperatureGauge::new(-10, 35);
+
+const SUN_COLOR: Rgb = (255, 200, 0);
+const CLOUD_COLOR: Rgb = (160, 160, 160);
+const RAIN_COLOR: Rgb = (0, 64, 255);
+const SNOW_COLOR: Rgb = (255, 255, 255);
+const STORM_COLOR: Rgb = (64, 0, 96);
+
+const SUN_PULSE_MS: u32 = 2_000;
+const FOG_PULSE_MS: u32 = 4_000;
+const RAIN_STEP_MS: u32 = 120;
+const SNOW_TWINKLE_MS: u32 = 1_500;
+const STORM_PERIOD_MS: u32 = 3_000;
+
+/// A weather condition, as reported by a weather service.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherCondition {
+    /// A slowly pulsing yellow ring
+    Clear,
+    /// A steady grey ring
+    Clouds,
+    /// A blue drop sweeping around the ring
+    Rain,
+    /// White twinkling
+    Snow,
+    /// Purple with lightning flashes
+    Storm,
+    /// A dim grey ring that fades in and out
+    Fog,
+}
+
+/// Condition names accepted by [`WeatherCondition`]'s `parse()`, including
+/// the ones Home Assistant weather entities report.
+const CONDITION_NAMES: [(&str, WeatherCondition); 24] = [
+    ("clear", WeatherCondition::Clear),
+    ("sunny", WeatherCondition::Clear),
+    ("clear-night", WeatherCondition::Clear),
+    ("clouds", WeatherCondition::Clouds),
+    ("cloudy", WeatherCondition::Clouds),
+    ("partlycloudy", WeatherCondition::Clouds),
+    ("overcast", WeatherCondition::Clouds),
+    ("windy", WeatherCondition::Clouds),
+    ("windy-variant", WeatherCondition::Clouds),
+    ("rain", WeatherCondition::Rain),
+    ("rainy", WeatherCondition::Rain),
+    ("pouring", WeatherCondition::Rain),
+    ("drizzle", WeatherCondition::Rain),
+    ("snow", WeatherCondition::Snow),
+    ("snowy", WeatherCondition::Snow),
+    ("snowy-rainy", WeatherCondition::Snow),
+    ("hail", WeatherCondition::Snow),
+    ("storm", WeatherCondition::Storm),
+    ("thunderstorm", WeatherCondition::Storm),
+    ("lightning", WeatherCondition::Storm),
+    ("lightning-rainy", WeatherCondition::Storm),
+    ("fog", WeatherCondition::Fog),
+    ("mist", WeatherCondition::Fog),
+    ("haze", WeatherCondition::Fog),
+];
+
+impl WeatherCondition {
+    /// Returns a short lowercase name for logging.
+    pub const fn name(self) -> &'static str {
+        match self {
+            WeatherCondition::Clear => "clear",
+            WeatherCondition::Clouds => "clouds",
+            WeatherCondition::Rain => "rain",
+            WeatherCondition::Snow => "snow",
+            WeatherCondition::Storm => "storm",
+            WeatherCondition::Fog => "fog",
+        }
+    }
+
+    /// Renders the condition's animation at `elapsed_ms` into `buffer`.
+    ///
+    /// Every pixel is overwritten.
+    pub fn render(self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        match self {
+            WeatherCondition::Clear => {
+                let level = 96 + sine8(phase_of(elapsed_ms, SUN_PULSE_MS)) as u16 * 159 / 255;
+                buffer.fill(fade_color(SUN_COLOR, level as u8));
+            }
+            WeatherCondition::Clouds => buffer.fill(fade_color(CLOUD_COLOR, 128)),
+            WeatherCondition::Rain => {
+                buffer.fill((0, 0, 0));
+                let len = buffer.len();
+                if len == 0 {
+                    return;
+                }
+                // A bright drop with a fading tail, moving clockwise
+                let head = (elapsed_ms / RAIN_STEP_MS) as usize % len;
+                for (offset, level) in [(2, 32), (1, 96), (0, 255)] {
+                    buffer[(head + 2 * len - offset) % len] = fade_color(RAIN_COLOR, level);
+                }
+            }
+            WeatherCondition::Snow => {
+                for (idx, pixel) in buffer.iter_mut().enumerate() {
+                    // Spread the flakes' phases so they don't twinkle in step
+                    let offset = (idx as u32 * 7 % 12) * SNOW_TWINKLE_MS / 12;
+                    let level = sine8(phase_of(elapsed_ms + offset, SNOW_TWINKLE_MS));
+                    *pixel = fade_color(SNOW_COLOR, level / 2 + 32);
+                }
+            }
+            WeatherCondition::Storm => {
+                // Two quick flashes at the start of every period
+                let flash = matches!(elapsed_ms % STORM_PERIOD_MS, 0..=99 | 250..=349);
+                buffer.fill(if flash { SNOW_COLOR } else { STORM_COLOR });
+            }
+            WeatherCondition::Fog => {
+                let level = 32 + sine8(phase_of(elapsed_ms, FOG_PULSE_MS)) / 4;
+                buffer.fill(fade_color(CLOUD_COLOR, level));
+            }
+        }
+    }
+}
+
+impl core::str::FromStr for WeatherCondition {
+    type Err = SettingsError;
+
+    /// Parses a condition name such as `sunny`, `rainy`, or `fog` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        CONDITION_NAMES
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(text))
+            .map(|&(_, condition)| condition)
+            .ok_or(SettingsError::InvalidValue)
+    }
+}
+
+/// The current weather: a condition and, optionally, the temperature.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Weather, WeatherCondition};
+///
+/// let weather = Weather::parse(br#"{"condition":"rainy","temperature":12.6}"#).unwrap();
+/// assert_eq!(weather.condition, WeatherCondition::Rain);
+/// assert_eq!(weather.temperature, Some(13));
+///
+/// assert_eq!(Weather::parse(b"sunny").unwrap().temperature, None);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Weather {
+    pub condition: WeatherCondition,
+    /// Degrees Celsius
+    pub temperature: Option<i8>,
+}
+
+impl Weather {
+    /// Creates the weather for a condition without a temperature.
+    pub const fn new(condition: WeatherCondition) -> Self {
+        Self {
+            condition,
+            temperature: None,
+        }
+    }
+
+    /// Sets the temperature in degrees Celsius.
+    pub const fn with_temperature(mut self, temperature: i8) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Renders the weather at `elapsed_ms` into `buffer`.
+    ///
+    /// Each [`WEATHER_CYCLE_MS`] cycle plays the condition animation, then
+    /// shows the temperature on a [`TemperatureGauge`] filling the ring
+    /// from -10 °C to 35 °C. Without a temperature the animation plays
+    /// throughout. Every pixel is overwritten.
+    pub fn render(&self, elapsed_ms: u32, buffer: &mut [Rgb]) {
+        let cycle_ms = elapsed_ms % WEATHER_CYCLE_MS;
+        match self.temperature {
+            Some(temperature) if cycle_ms >= WEATHER_CONDITION_MS => {
+                WEATHER_GAUGE.render(temperature.into(), buffer);
+            }
+            _ => self.condition.render(elapsed_ms, buffer),
+        }
+    }
+
+    /// Parses a weather update received over MQTT.
+    ///
+    /// The payload is a condition name, or a JSON object with a `condition`
+    /// and optionally a `temperature` in °C, rounded to whole degrees.
+    pub fn parse(payload: &[u8]) -> Result<Self, SettingsError> {
+        let text = core::str::from_utf8(payload)
+            .map_err(|_| SettingsError::InvalidUtf8)?
+            .trim();
+        if text.starts_with('{') {
+            return parse_json(payload);
+        }
+        text.parse().map(Self::new)
+    }
+}
+
+#[cfg(feature = "std")]
+fn parse_json(payload: &[u8]) -> Result<Weather, SettingsError> {
+    #[derive(serde::Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Update {
+        condition: String,
+        temperature: Option<f32>,
+    }
+
+    let update: Update = serde_json::from_slice(payload).map_err(|_| SettingsError::InvalidJson)?;
+    let weather = Weather::new(update.condition.parse()?);
+    match update.temperature {
+        None => Ok(weather),
+        Some(t) if (-100.0..=100.0).contains(&t) => Ok(weather.with_temperature(t.round() as i8)),
+        Some(t) => Err(SettingsError::OutOfRange {
+            field: "temperature",
+            value: t as i32 as u32,
+        }),
+    }
+}
+
+#[cfg(not(feature = "std"))]
+fn parse_json(_payload: &[u8]) -> Result<Weather, SettingsError> {
+    Err(SettingsError::InvalidJson)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lit(buffer: &[Rgb]) -> usize {
+        buffer.iter().filter(|&&p| p != (0, 0, 0)).count()
+    }
+
+    // ===== Condition tests =====
+
+    #[test]
+    fn test_condition_names() {
+        assert_eq!("Sunny".parse(), Ok(WeatherCondition::Clear));
+        assert_eq!("partlycloudy".parse(), Ok(WeatherCondition::Clouds));
+        assert_eq!("lightning-rainy".parse(), Ok(WeatherCondition::Storm));
+        assert_eq!(
+            "exceptional".parse::<WeatherCondition>(),
+            Err(SettingsError::InvalidValue)
+        );
+        for (name, condition) in CONDITION_NAMES {
+            assert_eq!(name.parse(), Ok(condition), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_rain_sweeps_clockwise() {
+        let mut buffer = [(0, 0, 0); 12];
+        WeatherCondition::Rain.render(0, &mut buffer);
+        assert_eq!(buffer[0], RAIN_COLOR);
+        assert_eq!(buffer[11], fade_color(RAIN_COLOR, 96));
+        assert_eq!(buffer[10], fade_color(RAIN_COLOR, 32));
+        assert_eq!(lit(&buffer), 3);
+
+        WeatherCondition::Rain.render(RAIN_STEP_MS, &mut buffer);
+        assert_eq!(buffer[1], RAIN_COLOR);
+        assert_eq!(buffer[0], fade_color(RAIN_COLOR, 96));
+    }
+
+    #[test]
+    fn test_sun_pulses() {
+        let mut dim = [(0, 0, 0); 12];
+        let mut bright = [(0, 0, 0); 12];
+        WeatherCondition::Clear.render(SUN_PULSE_MS * 3 / 4, &mut dim);
+        WeatherCondition::Clear.render(SUN_PULSE_MS / 4, &mut bright);
+        assert_eq!(bright[0], SUN_COLOR);
+        assert!(dim[0].0 < bright[0].0 && dim[0].0 > 0);
+    }
+
+    #[test]
+    fn test_storm_flashes() {
+        let mut buffer = [(0, 0, 0); 12];
+        WeatherCondition::Storm.render(STORM_PERIOD_MS + 50, &mut buffer);
+        assert_eq!(buffer[0], SNOW_COLOR);
+        WeatherCondition::Storm.render(STORM_PERIOD_MS + 150, &mut buffer);
+        assert_eq!(buffer[0], STORM_COLOR);
+    }
+
+    #[test]
+    fn test_every_condition_lights_the_ring() {
+        for (_, condition) in CONDITION_NAMES {
+            let mut buffer = [(0, 0, 0); 12];
+            condition.render(1_000, &mut buffer);
+            assert!(lit(&buffer) > 0, "{}", condition.name());
+        }
+    }
+
+    // ===== Weather tests =====
+
+    #[test]
+    fn test_render_alternates_condition_and_temperature() {
+        let weather =
+            Weather::new(WeatherCondition::Clouds).with_temperature(WEATHER_GAUGE.min + 15);
+        let mut buffer = [(0, 0, 0); 12];
+
+        weather.render(0, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+
+        // 15 of 45 degrees: a third of the ring
+        weather.render(WEATHER_CONDITION_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 4);
+        assert_eq!(buffer[11], (0, 0, 255));
+
+        weather.render(WEATHER_CYCLE_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+    }
+
+    #[test]
+    fn test_temperature_is_clamped() {
+        let mut buffer = [(0, 0, 0); 12];
+        let hot = Weather::new(WeatherCondition::Clear).with_temperature(50);
+        hot.render(WEATHER_CONDITION_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+
+        let cold = Weather::new(WeatherCondition::Snow).with_temperature(-30);
+        cold.render(WEATHER_CONDITION_MS, &mut buffer);
+        assert_eq!(lit(&buffer), 0);
+    }
+
+    #[test]
+    fn test_without_temperature_only_animates() {
+        let weather = Weather::new(WeatherCondition::Clouds);
+        let mut buffer = [(0, 0, 0); 12];
+        weather.render(WEATHER_CONDITION_MS + 1, &mut buffer);
+        assert_eq!(lit(&buffer), 12);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            Weather::parse(br#"{"condition":"snowy","temperature":-2.5}"#),
+            Ok(Weather::new(WeatherCondition::Snow).with_temperature(-3))
+        );
+        assert_eq!(
+            Weather::parse(b" fog\n"),
+            Ok(Weather::new(WeatherCondition::Fog))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Weather::parse(b"meatballs"),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            Weather::parse(br#"{"temperature":20}"#),
+            Err(SettingsError::InvalidJson)
+        );
+        assert_eq!(
+            Weather::parse(br#"{"condition":"sunny","temperature":1000}"#),
+            Err(SettingsError::OutOfRange {
+                field: "temperature",
+                value: 1000
+            })
+        );
+    }
+}
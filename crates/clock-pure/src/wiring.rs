@@ -0,0 +1,271 @@
+//! Maps positions on the clock face to LEDs on the strip, and colors to the
+//! channels of the LEDs.
+
+use crate::settings::SettingsError;
+use crate::{Frame, Rgb, LED_COUNT};
+
+/// The order in which the LEDs of the ring are wired.
+///
+/// Everything is drawn by position on the face, from position 0 at 1 o'clock
+/// clockwise to position 11 at 12 o'clock. A ring wired in that order needs
+/// no remapping; any other ring, e.g. one wired counterclockwise or built
+/// from two 6-LED segments, lists for every position the index of its LED on
+/// the strip.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{Frame, WiringOrder};
+///
+/// // Two segments: the second half of the face is wired first
+/// let order: WiringOrder = "6,7,8,9,10,11,0,1,2,3,4,5".parse().unwrap();
+/// let mut frame = Frame::new();
+/// frame[0] = (255, 0, 0);
+/// assert_eq!(order.apply(&frame)[6], (255, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WiringOrder {
+    /// Strip index of every position
+    strip: [u8; LED_COUNT],
+}
+
+impl Default for WiringOrder {
+    /// Position `n` is wired to strip LED `n`.
+    fn default() -> Self {
+        Self {
+            strip: core::array::from_fn(|position| position as u8),
+        }
+    }
+}
+
+impl WiringOrder {
+    /// Creates a wiring order from the strip index of every position.
+    ///
+    /// Every strip index must appear exactly once.
+    pub fn new(strip: [u8; LED_COUNT]) -> Result<Self, SettingsError> {
+        let mut seen = [false; LED_COUNT];
+        for &index in &strip {
+            let Some(seen) = seen.get_mut(usize::from(index)) else {
+                return Err(SettingsError::OutOfRange {
+                    field: "index",
+                    value: index.into(),
+                });
+            };
+            if *seen {
+                return Err(SettingsError::InvalidValue);
+            }
+            *seen = true;
+        }
+        Ok(Self { strip })
+    }
+
+    /// Returns the strip index of the LED at `position`.
+    pub fn strip_index(&self, position: usize) -> usize {
+        self.strip[position % LED_COUNT].into()
+    }
+
+    /// Returns true if every position is wired to the LED of the same index.
+    pub fn is_standard(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Reorders `frame` from positions on the face into strip order.
+    pub fn apply(&self, frame: &Frame) -> Frame {
+        let mut strip = Frame::new();
+        for (position, &pixel) in frame.pixels().iter().enumerate() {
+            strip[self.strip_index(position)] = pixel;
+        }
+        strip
+    }
+}
+
+impl core::str::FromStr for WiringOrder {
+    type Err = SettingsError;
+
+    /// Parses twelve comma-separated strip indices, one for every position.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut strip = [0; LED_COUNT];
+        let mut count = 0;
+        for part in text.trim().split(',') {
+            let index = part
+                .trim()
+                .parse()
+                .map_err(|_| SettingsError::InvalidValue)?;
+            *strip.get_mut(count).ok_or(SettingsError::InvalidValue)? = index;
+            count += 1;
+        }
+        if count != LED_COUNT {
+            return Err(SettingsError::InvalidValue);
+        }
+        Self::new(strip)
+    }
+}
+
+/// The order in which the LEDs of a strip take their color channels.
+///
+/// WS2812 LEDs take green, red, and blue, in that order, and the LED
+/// drivers send colors that way. Some clone strips expect another order, so
+/// red and green, or all three, come out swapped; naming the strip's order
+/// makes [`ChannelOrder::apply`] rearrange every color so it arrives right.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::ChannelOrder;
+///
+/// let order: ChannelOrder = "rgb".parse().unwrap();
+/// assert_eq!(order.apply((255, 0, 0)), (0, 255, 0));
+/// assert_eq!(ChannelOrder::Grb.apply((255, 0, 0)), (255, 0, 0));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChannelOrder {
+    Rgb,
+    Rbg,
+    /// Standard WS2812 order
+    #[default]
+    Grb,
+    Gbr,
+    Brg,
+    Bgr,
+}
+
+impl ChannelOrder {
+    /// All orders, for parsing.
+    const ALL: [ChannelOrder; 6] = [
+        ChannelOrder::Rgb,
+        ChannelOrder::Rbg,
+        ChannelOrder::Grb,
+        ChannelOrder::Gbr,
+        ChannelOrder::Brg,
+        ChannelOrder::Bgr,
+    ];
+
+    /// Rearranges `color` so a strip of this order shows it as drawn.
+    ///
+    /// The driver sends the green channel first, then red, then blue; a
+    /// strip of this order takes them as its first, second, and third
+    /// channel.
+    pub const fn apply(self, (r, g, b): Rgb) -> Rgb {
+        match self {
+            ChannelOrder::Rgb => (g, r, b),
+            ChannelOrder::Rbg => (b, r, g),
+            ChannelOrder::Grb => (r, g, b),
+            ChannelOrder::Gbr => (b, g, r),
+            ChannelOrder::Brg => (r, b, g),
+            ChannelOrder::Bgr => (g, b, r),
+        }
+    }
+
+    /// Returns the name of the order as used in the configuration.
+    pub const fn name(self) -> &'static str {
+        match self {
+            ChannelOrder::Rgb => "rgb",
+            ChannelOrder::Rbg => "rbg",
+            ChannelOrder::Grb => "grb",
+            ChannelOrder::Gbr => "gbr",
+            ChannelOrder::Brg => "brg",
+            ChannelOrder::Bgr => "bgr",
+        }
+    }
+}
+
+impl core::str::FromStr for ChannelOrder {
+    type Err = SettingsError;
+
+    /// Parses an order such as `grb` or `RGB` (case-insensitive).
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        Self::ALL
+            .into_iter()
+            .find(|order| order.name().eq_ignore_ascii_case(text))
+            .ok_or(SettingsError::InvalidValue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_is_standard() {
+        let order = WiringOrder::default();
+        assert!(order.is_standard());
+        let frame = Frame::from_pixels(core::array::from_fn(|i| (i as u8, 0, 0)));
+        assert_eq!(order.apply(&frame), frame);
+    }
+
+    #[test]
+    fn test_counterclockwise() {
+        let order: WiringOrder = "11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0".parse().unwrap();
+        assert!(!order.is_standard());
+        let mut frame = Frame::new();
+        frame[0] = (1, 2, 3);
+        frame[11] = (4, 5, 6);
+        let strip = order.apply(&frame);
+        assert_eq!(strip[11], (1, 2, 3));
+        assert_eq!(strip[0], (4, 5, 6));
+    }
+
+    #[test]
+    fn test_rejects_invalid_tables() {
+        let mut duplicate: [u8; LED_COUNT] = core::array::from_fn(|i| i as u8);
+        duplicate[1] = 0;
+        assert_eq!(
+            WiringOrder::new(duplicate),
+            Err(SettingsError::InvalidValue)
+        );
+
+        let mut outside: [u8; LED_COUNT] = core::array::from_fn(|i| i as u8);
+        outside[3] = 12;
+        assert_eq!(
+            WiringOrder::new(outside),
+            Err(SettingsError::OutOfRange {
+                field: "index",
+                value: 12
+            })
+        );
+    }
+
+    /// Returns the channels a strip of `order` takes from the driver's green,
+    /// red, and blue bytes, as (red, green, blue).
+    fn received(order: ChannelOrder, (r, g, b): Rgb) -> Rgb {
+        let wire = [g, r, b];
+        let name = order.name().as_bytes();
+        let channel = |c: u8| wire[name.iter().position(|&n| n == c).unwrap()];
+        (channel(b'r'), channel(b'g'), channel(b'b'))
+    }
+
+    #[test]
+    fn test_every_order_arrives_right() {
+        for order in ChannelOrder::ALL {
+            assert_eq!(received(order, order.apply((1, 2, 3))), (1, 2, 3));
+            assert_eq!(order.name().parse(), Ok(order));
+        }
+    }
+
+    #[test]
+    fn test_parse_channel_order() {
+        assert_eq!(" BGR ".parse(), Ok(ChannelOrder::Bgr));
+        assert_eq!(ChannelOrder::default().name(), "grb");
+        assert_eq!(
+            "rgbw".parse::<ChannelOrder>(),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "0,1,2".parse::<WiringOrder>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            "0,1,2,3,4,5,6,7,8,9,10,11,12".parse::<WiringOrder>(),
+            Err(SettingsError::InvalidValue)
+        );
+        assert_eq!(
+            "0,1,2,3,4,5,6,7,8,9,10,x".parse::<WiringOrder>(),
+            Err(SettingsError::InvalidValue)
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Encodes colors for WS2812 LEDs as an SPI bit stream, for hosts without
+//! an RMT peripheral, e.g. a Raspberry Pi.
+
+use crate::Rgb;
+
+/// SPI clock for [`encode_ws2812_spi`]: three SPI bits make one 1.25 µs WS2812 bit.
+pub const WS2812_SPI_HZ: u32 = 2_400_000;
+
+/// SPI bytes per LED: 24 color bits of three SPI bits each.
+pub const WS2812_SPI_LED_BYTES: usize = 9;
+
+/// Zero bytes after the last LED, holding the line low for the 100 µs
+/// that latch the colors; newer LEDs need more than the original 50 µs.
+pub const WS2812_SPI_RESET_BYTES: usize = 30;
+
+/// Returns the number of SPI bytes [`encode_ws2812_spi`] needs for `leds` LEDs.
+pub const fn ws2812_spi_len(leds: usize) -> usize {
+    leds * WS2812_SPI_LED_BYTES + WS2812_SPI_RESET_BYTES
+}
+
+/// Encodes `pixels` for a WS2812 strip on the MOSI line of an SPI bus
+/// clocked at [`WS2812_SPI_HZ`].
+///
+/// Every WS2812 bit becomes three SPI bits, `110` for a one and `100` for a
+/// zero, and every LED takes its colors in green, red, blue order. The
+/// reset follows the last LED. Only as many LEDs as fit into `bytes` with
+/// the reset are encoded; returns the number of bytes used.
+///
+/// # Example
+///
+/// ```
+/// use clock_pure::{encode_ws2812_spi, ws2812_spi_len};
+///
+/// let mut bytes = [0xff; ws2812_spi_len(1)];
+/// assert_eq!(encode_ws2812_spi(&[(255, 0, 0)], &mut bytes), bytes.len());
+/// // Green off, red full on, blue off
+/// assert_eq!(bytes[..6], [0x92, 0x49, 0x24, 0xdb, 0x6d, 0xb6]);
+/// assert!(bytes[9..].iter().all(|&b| b == 0));
+/// ```
+pub fn encode_ws2812_spi(pixels: &[Rgb], bytes: &mut [u8]) -> usize {
+    let leds = pixels
+        .len()
+        .min(bytes.len().saturating_sub(WS2812_SPI_RESET_BYTES) / WS2812_SPI_LED_BYTES);
+    let (data, rest) = bytes.split_at_mut(leds * WS2812_SPI_LED_BYTES);
+    for (led, &(r, g, b)) in data.chunks_exact_mut(WS2812_SPI_LED_BYTES).zip(pixels) {
+        for (out, channel) in led.chunks_exact_mut(3).zip([g, r, b]) {
+            out.copy_from_slice(&spread(channel)[1..]);
+        }
+    }
+    let reset = rest.len().min(WS2812_SPI_RESET_BYTES);
+    rest[..reset].fill(0);
+    ws2812_spi_len(leds).min(bytes.len())
+}
+
+/// Spreads the bits of `channel` to three SPI bits each, in the low 24 bits.
+const fn spread(channel: u8) -> [u8; 4] {
+    let mut bits = 0u32;
+    let mut bit = 8;
+    while bit > 0 {
+        bit -= 1;
+        let pattern = if channel & (1 << bit) != 0 {
+            0b110
+        } else {
+            0b100
+        };
+        bits = bits << 3 | pattern;
+    }
+    bits.to_be_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decodes the WS2812 bits back from SPI bytes.
+    fn decode(bytes: &[u8]) -> u8 {
+        let bits = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        (0..8).fold(0, |value, i| {
+            let pattern = bits >> (21 - 3 * i) & 0b111;
+            assert!(pattern == 0b110 || pattern == 0b100);
+            value << 1 | u8::from(pattern == 0b110)
+        })
+    }
+
+    #[test]
+    fn test_channels_round_trip() {
+        for channel in 0..=255u8 {
+            assert_eq!(decode(&spread(channel)[1..]), channel);
+        }
+    }
+
+    #[test]
+    fn test_grb_order() {
+        let mut bytes = [0; ws2812_spi_len(2)];
+        encode_ws2812_spi(&[(1, 2, 3), (4, 5, 6)], &mut bytes);
+        let channels: [u8; 6] = core::array::from_fn(|i| decode(&bytes[3 * i..]));
+        assert_eq!(channels, [2, 1, 3, 5, 4, 6]);
+    }
+
+    #[test]
+    fn test_short_buffer_keeps_reset() {
+        let mut bytes = [0xff; ws2812_spi_len(1) + 5];
+        let used = encode_ws2812_spi(&[(9, 9, 9); 12], &mut bytes);
+        assert_eq!(used, ws2812_spi_len(1));
+        assert!(bytes[9..used].iter().all(|&b| b == 0));
+
+        let mut tiny = [0xff; 4];
+        assert_eq!(encode_ws2812_spi(&[(9, 9, 9)], &mut tiny), 4);
+        assert_eq!(tiny, [0; 4]);
+    }
+}
@@ -0,0 +1,60 @@
+use anyhow::Result;
+use clock_pure::{encode_apa102, Frame};
+use esp_idf_hal::gpio::{AnyIOPin, OutputPin};
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_hal::spi::{config::Config, SpiAnyPins, SpiDeviceDriver, SpiDriver, SpiDriverConfig};
+use esp_idf_hal::units::FromValueType;
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+
+/// A driver for rings of APA102 (DotStar) LEDs on the SPI bus.
+///
+/// APA102 LEDs take a separate clock line and add a 5-bit global brightness
+/// to every LED, set once when the driver is created. Built with the
+/// `apa102` feature, the clock uses it in place of the RMT driver.
+pub struct Apa102Driver<'d> {
+    spi: SpiDeviceDriver<'d, SpiDriver<'d>>,
+    brightness: u8,
+}
+
+impl<'d> Apa102Driver<'d> {
+    /// Creates a driver sending frames on `sdo`, clocked on `sclk` at 4 MHz.
+    ///
+    /// `brightness` is the global brightness of every LED (0-31).
+    pub fn new(
+        spi: impl Peripheral<P = impl SpiAnyPins> + 'd,
+        sclk: impl Peripheral<P = impl OutputPin> + 'd,
+        sdo: impl Peripheral<P = impl OutputPin> + 'd,
+        brightness: u8,
+    ) -> Result<Self> {
+        let spi = SpiDeviceDriver::new_single(
+            spi,
+            sclk,
+            sdo,
+            Option::<AnyIOPin>::None,
+            Option::<AnyIOPin>::None,
+            &SpiDriverConfig::new(),
+            &Config::new().baudrate(4.MHz().into()),
+        )?;
+        Ok(Self { spi, brightness })
+    }
+}
+
+impl SmartLedsWrite for Apa102Driver<'_> {
+    type Error = anyhow::Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let mut frame = Frame::new();
+        for (pixel, color) in frame.pixels_mut().iter_mut().zip(iterator) {
+            let color = color.into();
+            *pixel = (color.r, color.g, color.b);
+        }
+        self.spi.write(&encode_apa102(&frame, self.brightness))?;
+        Ok(())
+    }
+}
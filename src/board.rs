@@ -0,0 +1,99 @@
+use esp_idf_hal::gpio::{AnyIOPin, AnyOutputPin, Pins};
+
+#[cfg(all(feature = "esp32c3", feature = "esp32s3"))]
+compile_error!("the esp32c3 and esp32s3 features select different boards; enable only one");
+
+/// The GPIOs the clock uses on the dev board it is built for.
+///
+/// The ESP32-C6-DevKitC-1 is the default; the `esp32c3` and `esp32s3`
+/// features select the ESP32-C3-DevKitM-1 and the ESP32-S3-DevKitC-1 instead.
+/// Every pin is taken, whether the configuration uses it or not, so settings
+/// naming a GPIO can only pick one of [`FREE_GPIOS`].
+pub struct Board {
+    /// Data line of the ring (WS2812, SK6812, or APA102)
+    pub ring: AnyOutputPin,
+    /// Clock line of an APA102 ring, or data line of an outer ring on its own
+    pub ring_clock: AnyOutputPin,
+    /// Data line of the second clock
+    pub second_clock: AnyOutputPin,
+    pub buzzer: AnyOutputPin,
+    /// I2C data line of the DS3231
+    pub sda: AnyIOPin,
+    /// I2C clock line of the DS3231
+    pub scl: AnyIOPin,
+    /// The board's onboard RGB LED
+    pub status_led: AnyOutputPin,
+    /// The board's BOOT button, pulled low while pressed
+    pub button: AnyIOPin,
+}
+
+macro_rules! board {
+    ($name:literal, {$($field:ident: $gpio:ident),* $(,)?}) => {
+        /// Name of the board the firmware is built for.
+        pub const NAME: &str = $name;
+
+        impl Board {
+            /// Takes the board's pins from `pins`.
+            pub fn take(pins: Pins) -> Self {
+                Self {
+                    $($field: pins.$gpio.into(),)*
+                }
+            }
+        }
+    };
+}
+
+#[cfg(not(any(feature = "esp32c3", feature = "esp32s3")))]
+board!("ESP32-C6-DevKitC-1", {
+    ring: gpio10,
+    ring_clock: gpio11,
+    second_clock: gpio2,
+    buzzer: gpio3,
+    sda: gpio6,
+    scl: gpio7,
+    status_led: gpio8,
+    button: gpio9,
+});
+
+/// GPIOs free for outputs such as the alarm output; the others are taken by
+/// the clock, the USB serial console (GPIO12 and GPIO13), or the flash.
+#[cfg(not(any(feature = "esp32c3", feature = "esp32s3")))]
+pub const FREE_GPIOS: &[i32] = &[0, 1, 4, 5, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23];
+
+#[cfg(feature = "esp32c3")]
+board!("ESP32-C3-DevKitM-1", {
+    ring: gpio10,
+    ring_clock: gpio7,
+    second_clock: gpio1,
+    buzzer: gpio3,
+    sda: gpio4,
+    scl: gpio5,
+    status_led: gpio8,
+    button: gpio9,
+});
+
+/// GPIOs free for outputs such as the alarm output; the others are taken by
+/// the clock, the USB serial console (GPIO18 and GPIO19), or the flash.
+#[cfg(feature = "esp32c3")]
+pub const FREE_GPIOS: &[i32] = &[0, 2, 6, 20, 21];
+
+#[cfg(feature = "esp32s3")]
+board!("ESP32-S3-DevKitC-1", {
+    ring: gpio10,
+    ring_clock: gpio11,
+    second_clock: gpio12,
+    buzzer: gpio13,
+    sda: gpio8,
+    scl: gpio9,
+    // GPIO38 on revision 1.1 of the board
+    status_led: gpio48,
+    button: gpio0,
+});
+
+/// GPIOs free for outputs such as the alarm output; the others are taken by
+/// the clock, the USB serial console (GPIO19 and GPIO20), the flash, or the
+/// octal PSRAM, or are strapping pins.
+#[cfg(feature = "esp32s3")]
+pub const FREE_GPIOS: &[i32] = &[
+    1, 2, 4, 5, 6, 7, 14, 15, 16, 17, 18, 21, 38, 39, 40, 41, 42, 47,
+];
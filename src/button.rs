@@ -0,0 +1,39 @@
+use crate::command::{ClockHandle, Command};
+use anyhow::Result;
+use clock_pure::Button;
+use esp_idf_hal::gpio::{AnyIOPin, PinDriver, Pull};
+use std::time::{Duration, Instant};
+
+// How often the button level is sampled
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Watches the mode button in a background thread and sends presses to the clock.
+///
+/// The DevKit's BOOT button, on GPIO9 of the ESP32-C6 board, pulls the pin
+/// low while pressed. See `RGBClock::press_button()` for what presses do.
+///
+/// # Arguments
+/// * `pin` - The button GPIO
+/// * `clock` - Handle for sending commands to the clock
+///
+/// # Returns
+/// A join handle for the button thread
+pub fn run_button(pin: AnyIOPin, clock: ClockHandle) -> Result<std::thread::JoinHandle<()>> {
+    let mut input = PinDriver::input(pin)?;
+    input.set_pull(Pull::Up)?;
+
+    Ok(std::thread::spawn(move || {
+        let started = Instant::now();
+        let mut button = Button::new();
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+
+            let now_ms = started.elapsed().as_millis() as u32;
+            let Some(press) = button.update(input.is_low(), now_ms) else {
+                continue;
+            };
+            log::info!("Button press: {:?}", press);
+            clock.send(Command::PressButton(press));
+        }
+    }))
+}
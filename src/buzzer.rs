@@ -0,0 +1,124 @@
+use anyhow::Result;
+use clock_pure::Melody;
+use esp_idf_hal::gpio::OutputPin;
+use esp_idf_hal::ledc::config::TimerConfig;
+use esp_idf_hal::ledc::{LedcDriver, LedcTimerDriver, Resolution, CHANNEL0, TIMER0};
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_hal::units::FromValueType;
+use esp_idf_svc::sys::{esp, ledc_mode_t_LEDC_LOW_SPEED_MODE, ledc_set_freq, ledc_timer_t};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+
+enum BuzzerCommand {
+    Play { melody: Melody, repeat: bool },
+    Stop,
+}
+
+/// Plays melodies on a piezo buzzer; clone it for every thread that needs one.
+#[derive(Clone)]
+pub struct Buzzer {
+    commands: Sender<BuzzerCommand>,
+}
+
+impl Buzzer {
+    /// Plays `melody` in place of the one playing, over and over if `repeat`
+    /// is set, until stopped.
+    pub fn play(&self, melody: Melody, repeat: bool) {
+        self.send(BuzzerCommand::Play { melody, repeat });
+    }
+
+    /// Silences the buzzer.
+    pub fn stop(&self) {
+        self.send(BuzzerCommand::Stop);
+    }
+
+    fn send(&self, command: BuzzerCommand) {
+        if self.commands.send(command).is_err() {
+            log::error!("Buzzer stopped, dropping melody");
+        }
+    }
+}
+
+/// Drives a passive piezo buzzer from the LEDC in a background thread.
+///
+/// Each note sets the PWM frequency to its pitch at half duty; rests and the
+/// end of a melody set the duty to zero.
+///
+/// # Arguments
+/// * `timer` - The LEDC timer setting the pitch
+/// * `channel` - The LEDC channel driving the pin
+/// * `pin` - The buzzer GPIO
+///
+/// # Returns
+/// A handle for playing melodies
+pub fn run_buzzer(
+    timer: TIMER0,
+    channel: CHANNEL0,
+    pin: impl Peripheral<P = impl OutputPin> + 'static,
+) -> Result<Buzzer> {
+    let config = TimerConfig::new()
+        .frequency(1.kHz().into())
+        .resolution(Resolution::Bits10);
+    let timer = LedcTimerDriver::new(timer, &config)?;
+    let timer_id = timer.timer();
+    let mut pwm = LedcDriver::new(channel, timer, pin)?;
+    pwm.set_duty(0)?;
+
+    let (commands, receiver) = channel();
+    std::thread::spawn(move || {
+        // Wait for a melody while silent, then play until told otherwise
+        while let Ok(command) = receiver.recv() {
+            let mut next = Some(command);
+            while let Some(BuzzerCommand::Play { melody, repeat }) = next.take() {
+                next = play(&mut pwm, timer_id, &melody, repeat, &receiver);
+            }
+        }
+    });
+    Ok(Buzzer { commands })
+}
+
+/// Plays `melody`, returning early with a command that arrives meanwhile.
+fn play(
+    pwm: &mut LedcDriver<'_>,
+    timer: ledc_timer_t,
+    melody: &Melody,
+    repeat: bool,
+    commands: &Receiver<BuzzerCommand>,
+) -> Option<BuzzerCommand> {
+    let notes = melody.notes().iter().cycle().take(if repeat {
+        usize::MAX
+    } else {
+        melody.notes().len()
+    });
+    let mut next = None;
+    for note in notes {
+        if let Err(e) = tone(pwm, timer, note.frequency_hz) {
+            log::warn!("Cannot play {} Hz: {:?}", note.frequency_hz, e);
+        }
+        let duration = Duration::from_millis(note.duration_ms.into());
+        match commands.recv_timeout(duration) {
+            Ok(command) => {
+                next = Some(command);
+                break;
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    if let Err(e) = tone(pwm, timer, 0) {
+        log::warn!("Cannot silence the buzzer: {:?}", e);
+    }
+    next
+}
+
+/// Sounds `frequency_hz`, or silences the buzzer for 0.
+fn tone(pwm: &mut LedcDriver<'_>, timer: ledc_timer_t, frequency_hz: u16) -> Result<()> {
+    if frequency_hz == 0 {
+        pwm.set_duty(0)?;
+        return Ok(());
+    }
+    // SAFETY: the timer was configured by the LEDC timer driver the channel owns
+    esp!(unsafe { ledc_set_freq(ledc_mode_t_LEDC_LOW_SPEED_MODE, timer, frequency_hz.into()) })?;
+    pwm.set_duty(pwm.get_max_duty() / 2)?;
+    Ok(())
+}
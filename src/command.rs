@@ -0,0 +1,269 @@
+use crate::rgb_clock::RGBClock;
+use clock_pure::animation::AnimationConfig;
+use clock_pure::effects::Sequence;
+use clock_pure::{
+    Alarms, ButtonPress, Calibration, Date, DeadPixels, DisplayMode, DoNotDisturb, Frame,
+    HandColors, LocalDateTime, LocalTime, NightMode, Notification, PomodoroCommand, PomodoroPhase,
+    Scene, SettingsError, StateReport, Status, StopwatchCommand, Weather,
+};
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// A change to the clock, sent to the render thread.
+///
+/// The render thread owns the clock and applies commands between frames, so
+/// MQTT callbacks, the button, and the main loop never wait for a frame to be
+/// drawn. Adding a command means adding a variant here and a line to
+/// [`Command::apply`].
+pub enum Command {
+    SetTime(LocalDateTime),
+    SetBrightness(u8),
+    UpdateHandColors(Patch<HandColors>),
+    UpdateNightMode(Patch<NightMode>),
+    UpdateDoNotDisturb(Patch<DoNotDisturb>),
+    UpdateAnimation(Patch<AnimationConfig>),
+    UpdateCalibration(Patch<Calibration>),
+    UpdateDeadPixels(Patch<DeadPixels>),
+    SetMode(DisplayMode),
+    /// Switches to the mode after the current one
+    NextMode,
+    Pomodoro(PomodoroCommand),
+    Stopwatch(StopwatchCommand),
+    UpdateAlarms(Patch<Alarms>),
+    DismissAlarm,
+    Notify(Notification),
+    SetPresence(bool),
+    SetPower(bool),
+    ApplyScene(Scene),
+    SetWeather(Weather),
+    SetTemperature(f32),
+    PressButton(ButtonPress),
+    SetWifiConnected(bool),
+    SetMqttConnected(bool),
+    /// Stops waiting for the first time, e.g. when there is no network
+    EndStartup,
+    /// Plays a sequence in place of the display mode; `done` is told whether
+    /// it played to the end
+    RunEffect {
+        sequence: Sequence,
+        done: Sender<bool>,
+    },
+    /// Replies with a snapshot of the clock's state
+    GetState(Sender<ClockState>),
+}
+
+impl Command {
+    /// Applies the command to the clock.
+    pub fn apply(self, clock: &mut RGBClock<'_>) {
+        match self {
+            Command::SetTime(time) => clock.set_local_time(time),
+            Command::SetBrightness(brightness) => clock.set_brightness(brightness),
+            Command::UpdateHandColors(patch) => {
+                if let Some(colors) = patch.apply(clock.scene().colors, HandColors::with_update) {
+                    clock.set_hand_colors(colors);
+                }
+            }
+            Command::UpdateNightMode(patch) => {
+                if let Some(night) = patch.apply(clock.night_mode(), NightMode::with_update) {
+                    clock.set_night_mode(night);
+                }
+            }
+            Command::UpdateDoNotDisturb(patch) => {
+                let current = clock.do_not_disturb();
+                if let Some(dnd) = patch.apply(current, DoNotDisturb::with_update) {
+                    clock.set_do_not_disturb(dnd);
+                }
+            }
+            Command::UpdateAnimation(patch) => {
+                let current = clock.animation_config();
+                if let Some(config) = patch.apply(current, AnimationConfig::with_update) {
+                    clock.set_animation_config(config);
+                }
+            }
+            Command::UpdateCalibration(patch) => {
+                let current = clock.calibration();
+                if let Some(calibration) = patch.apply(current, Calibration::with_update) {
+                    clock.set_calibration(calibration);
+                }
+            }
+            Command::UpdateDeadPixels(patch) => {
+                let current = clock.dead_pixels();
+                if let Some(dead) = patch.apply(current, DeadPixels::with_update) {
+                    clock.set_dead_pixels(dead);
+                }
+            }
+            Command::SetMode(mode) => clock.set_mode(mode),
+            Command::NextMode => clock.set_mode(clock.mode().next()),
+            Command::Pomodoro(command) => clock.pomodoro_command(command),
+            Command::Stopwatch(command) => {
+                clock.stopwatch_command(command);
+            }
+            Command::UpdateAlarms(patch) => {
+                if let Some(alarms) = patch.apply(clock.alarms().clone(), Alarms::with_update) {
+                    clock.set_alarms(alarms);
+                }
+            }
+            Command::DismissAlarm => {
+                if clock.dismiss_alarm() {
+                    log::info!("Alarm dismissed");
+                } else {
+                    log::info!("No alarm to dismiss");
+                }
+            }
+            Command::Notify(notification) => clock.notify(notification),
+            Command::SetPresence(present) => clock.set_presence(present),
+            Command::SetPower(on) => clock.set_power(on),
+            Command::ApplyScene(scene) => clock.apply_scene(scene),
+            Command::SetWeather(weather) => clock.set_weather(weather),
+            Command::SetTemperature(temperature) => clock.set_temperature(temperature),
+            Command::PressButton(press) => clock.press_button(press),
+            Command::SetWifiConnected(connected) => clock.set_wifi_connected(connected),
+            Command::SetMqttConnected(connected) => clock.set_mqtt_connected(connected),
+            Command::EndStartup => clock.end_startup(),
+            Command::RunEffect { sequence, done } => clock.play_sequence(sequence, done),
+            Command::GetState(reply) => {
+                // The asker may have given up
+                let _ = reply.send(ClockState::of(clock));
+            }
+        }
+    }
+}
+
+/// A partial JSON update of a setting, applied to the clock's current value
+/// on the render thread, so that updates sent one after the other all apply.
+pub struct Patch<T> {
+    pub json: Vec<u8>,
+    /// Told the updated setting, to be saved, or why the update failed
+    pub updated: Sender<Result<T, SettingsError>>,
+}
+
+impl<T: Clone> Patch<T> {
+    /// Returns `current` updated by the patch, or `None` if the update failed;
+    /// either way the sender is told.
+    fn apply(
+        self,
+        current: T,
+        update: impl FnOnce(T, &[u8]) -> Result<T, SettingsError>,
+    ) -> Option<T> {
+        let result = update(current, &self.json);
+        // The sender may have given up
+        let _ = self.updated.send(result.clone());
+        result.ok()
+    }
+}
+
+/// A snapshot of the clock's settings and time.
+#[derive(Debug, Clone)]
+pub struct ClockState {
+    /// Display mode, hand colors, and brightness
+    pub scene: Scene,
+    pub alarms: Alarms,
+    /// Whether do-not-disturb is currently active
+    pub quiet: bool,
+    /// Whether night mode currently dims the face
+    pub night: bool,
+    pub status: Status,
+    pub next_alarm: Option<LocalDateTime>,
+    /// The local date and time, once a time was received
+    pub now: Option<(Date, LocalTime)>,
+    /// Frames drawn since boot, wrapping
+    pub frames: u32,
+    /// The frame on the LEDs, once one was shown
+    pub frame: Option<Frame>,
+    /// Milliseconds since the last tick, once one arrived
+    pub tick_age_ms: Option<u32>,
+    /// Whether the display is switched on
+    pub power: bool,
+    /// Phase of the Pomodoro timer, while it runs
+    pub pomodoro: Option<PomodoroPhase>,
+    pub stopwatch_running: bool,
+}
+
+impl ClockState {
+    fn of(clock: &RGBClock<'_>) -> Self {
+        Self {
+            scene: clock.scene(),
+            alarms: clock.alarms().clone(),
+            quiet: clock.is_do_not_disturb(),
+            night: clock.is_night(),
+            status: clock.status(),
+            next_alarm: clock.next_alarm(),
+            now: clock.local_now(),
+            frames: clock.frames(),
+            frame: clock.shown_frame(),
+            tick_age_ms: clock.tick_age_ms(),
+            power: clock.is_powered(),
+            pomodoro: clock.pomodoro_phase(),
+            stopwatch_running: clock.is_stopwatch_running(),
+        }
+    }
+
+    /// Returns the state published to external controllers.
+    pub fn report(&self) -> StateReport {
+        StateReport {
+            scene: self.scene,
+            power: self.power,
+            night: self.night,
+            do_not_disturb: self.quiet,
+            pomodoro: self.pomodoro,
+            stopwatch_running: self.stopwatch_running,
+            alarm_ringing: self.alarms.is_ringing(),
+            next_alarm: self.next_alarm,
+        }
+    }
+}
+
+/// Sends commands to the render thread; clone it for every thread that needs one.
+#[derive(Clone)]
+pub struct ClockHandle {
+    commands: Sender<Command>,
+}
+
+impl ClockHandle {
+    /// Creates a handle and the receiver the render thread reads commands from.
+    pub fn new() -> (Self, Receiver<Command>) {
+        let (commands, receiver) = channel();
+        (Self { commands }, receiver)
+    }
+
+    /// Sends a command; it takes effect with the next frame.
+    pub fn send(&self, command: Command) {
+        if self.commands.send(command).is_err() {
+            log::error!("Render loop stopped, dropping command");
+        }
+    }
+
+    /// Plays a sequence in place of the display mode, replacing any effect
+    /// that is playing.
+    ///
+    /// The returned receiver is told whether the sequence played to the end
+    /// (`true`) or was replaced by another effect (`false`).
+    pub fn run_effect(&self, sequence: Sequence) -> Receiver<bool> {
+        let (done, finished) = channel();
+        self.send(Command::RunEffect { sequence, done });
+        finished
+    }
+
+    /// Updates a setting of the clock with a partial JSON update, sent as
+    /// `command`, and returns the updated setting, or `None` if the render
+    /// loop stopped.
+    pub fn update<T>(
+        &self,
+        json: &[u8],
+        command: fn(Patch<T>) -> Command,
+    ) -> Option<Result<T, SettingsError>> {
+        let (updated, result) = channel();
+        self.send(command(Patch {
+            json: json.to_vec(),
+            updated,
+        }));
+        result.recv().ok()
+    }
+
+    /// Returns a snapshot of the clock's state, waiting for the render thread
+    /// to take it, or `None` if the render loop stopped.
+    pub fn state(&self) -> Option<ClockState> {
+        let (reply, state) = channel();
+        self.send(Command::GetState(reply));
+        state.recv().ok()
+    }
+}
@@ -0,0 +1,519 @@
+//! Handles messages on the clock's topics, from the broker or the web server.
+
+use crate::buzzer::Buzzer;
+use crate::command::{ClockHandle, Command};
+use crate::ota;
+use crate::retained;
+use crate::rtc::RtcSync;
+use crate::settings_store::SettingsStore;
+use crate::sntp::SntpSync;
+use anyhow::Result;
+use clock_net::{FirmwareKey, OtaRequest, OtaStatus, Route};
+use clock_pure::effects::{Identify, Playlist, SelfTest, Sequence, StartupChoice, IDENTIFY_MS};
+use clock_pure::{
+    DisplayMode, Melody, Notification, PomodoroCommand, PushedFrame, RemoteCommand, Scenes,
+    Schedule, StopwatchCommand, TimeZone, Topic, Weather, LED_COUNT,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+/// The settings storage of the clock a message is for; second clocks have none.
+type Settings<'a> = Option<&'a Mutex<Option<SettingsStore>>>;
+
+/// Everything the clock's topics change, shared by the MQTT callbacks and
+/// the web server.
+pub struct Dispatcher {
+    /// The first clock, whose settings are saved
+    pub clock: ClockHandle,
+    /// The second clocks and their names, addressed under `clock/<name>/`
+    pub others: Vec<(&'static str, ClockHandle)>,
+    pub settings: Arc<Mutex<Option<SettingsStore>>>,
+    pub time_zone: Arc<Mutex<TimeZone>>,
+    pub sntp: Option<Arc<Mutex<SntpSync>>>,
+    pub rtc: Option<Arc<Mutex<RtcSync<'static>>>>,
+    pub scenes: Arc<Mutex<Scenes>>,
+    pub schedule: Arc<Mutex<Schedule>>,
+    pub buzzer: Option<Buzzer>,
+    pub buzzer_on: Arc<AtomicBool>,
+    /// Self-test reports, published from the main loop, which owns the client
+    pub reports: Sender<String>,
+    /// Firmware update progress, published from the main loop
+    pub ota_statuses: Sender<OtaStatus>,
+    pub ota_key: Option<FirmwareKey>,
+}
+
+impl Dispatcher {
+    /// Handles a message on one of the clock's topics, e.g. `clock/mode` or
+    /// `clock/<name>/mode` for a second clock.
+    pub fn dispatch(&self, topic: &str, data: &[u8]) {
+        match clock_net::route(topic, self.others.iter().map(|(name, _)| *name)) {
+            Some(route) => self.handle(route, data),
+            None => log::warn!("Ignoring message on unexpected topic {}", topic),
+        }
+    }
+
+    /// Handles a message on a routed topic; JSON commands and configurations
+    /// are handled like their payloads on the topics they name.
+    pub fn handle(&self, route: Route, data: &[u8]) {
+        match route.topic {
+            Topic::Cmd => match RemoteCommand::parse(data) {
+                Ok(command) => {
+                    log::info!("Command for {}: {}", command.topic.name(), command.payload);
+                    self.handle_command(route, command);
+                }
+                Err(e) => log::error!("Failed to parse command: {}", e),
+            },
+            // An empty retained message clears the configuration, keeping the settings
+            Topic::Config if data.is_empty() => {}
+            Topic::Config => match RemoteCommand::parse_config(data) {
+                Ok(commands) => {
+                    log::info!("Applying configuration of {} settings", commands.len());
+                    for command in commands {
+                        self.handle_command(route, command);
+                    }
+                }
+                Err(e) => log::error!("Failed to parse configuration: {}", e),
+            },
+            _ => self.handle_topic(route, data),
+        }
+    }
+
+    /// Handles a command of a message on `route`, for the same clock.
+    fn handle_command(&self, route: Route, command: RemoteCommand) {
+        match route.to(command.topic) {
+            Some(route) => self.handle_topic(route, command.payload.as_bytes()),
+            None => log::warn!("Ignoring {} for a second clock", command.topic.name()),
+        }
+    }
+
+    /// Handles a message on a routed topic other than JSON commands and
+    /// configurations.
+    fn handle_topic(&self, route: Route, data: &[u8]) {
+        // Settings changed for a second clock are not saved; the settings storage belongs to the first one
+        let (clock, settings) = match route.second {
+            None => (&self.clock, Some(&*self.settings)),
+            Some(i) => match self.others.get(i) {
+                Some((_, clock)) => (clock, None),
+                None => return,
+            },
+        };
+        match route.topic {
+            Topic::Tick => self.tick(data),
+            Topic::Playlist => playlist(clock, data),
+            Topic::Buzzer => self.buzzer(data),
+            Topic::BuzzerPlay => self.play_melody(data),
+            Topic::Identify => identify(clock),
+            Topic::SelfTest => self.self_test(clock),
+            Topic::Ota => self.update_firmware(data),
+            Topic::Brightness => brightness(clock, data),
+            Topic::Colors => colors(clock, settings, data),
+            Topic::Night => night(clock, data),
+            Topic::DoNotDisturb => do_not_disturb(clock, settings, data),
+            Topic::Calibration => calibration(clock, settings, data),
+            Topic::DeadPixels => dead_pixels(clock, settings, data),
+            Topic::TimeZone => self.time_zone(settings, data),
+            Topic::Animation => animation(clock, data),
+            Topic::Startup => startup(settings, data),
+            Topic::Mode => mode(clock, data),
+            Topic::Pomodoro => pomodoro(clock, data),
+            Topic::Stopwatch => stopwatch(clock, data),
+            Topic::Alarm => alarm(clock, settings, data),
+            Topic::Notify => notify(clock, data),
+            Topic::Frame => frame(clock, data),
+            Topic::Presence => presence(clock, data),
+            Topic::Power => power(clock, data),
+            Topic::Scene => self.save_scene(clock, settings, data),
+            Topic::SceneRecall => self.recall_scene(clock, data),
+            Topic::Schedule => self.schedule(settings, data),
+            Topic::Weather => weather(clock, data),
+            Topic::Temperature => temperature(clock, data),
+            // No command or setting of a configuration stands for another one
+            Topic::Cmd | Topic::Config => log::warn!("Ignoring nested {}", route.topic.name()),
+        }
+    }
+
+    /// Returns the first clock and the second ones.
+    fn clocks(&self) -> impl Iterator<Item = &ClockHandle> {
+        std::iter::once(&self.clock).chain(self.others.iter().map(|(_, clock)| clock))
+    }
+
+    fn tick(&self, data: &[u8]) {
+        let zone = self.time_zone.lock().map(|zone| *zone).unwrap_or_default();
+        match clock_pure::parse_tick(data, &zone) {
+            Ok((time, format)) => {
+                log::debug!("Received {} tick: {:?}", format.name(), time);
+                if let Some(Ok(mut sntp)) = self.sntp.as_ref().map(|sntp| sntp.lock()) {
+                    if !sntp.network_tick() {
+                        log::debug!("Preferring SNTP, ignoring the tick");
+                        return;
+                    }
+                }
+                for clock in self.clocks() {
+                    clock.send(Command::SetTime(time));
+                }
+                retained::save(time);
+                if let Some(Ok(mut rtc)) = self.rtc.as_ref().map(|rtc| rtc.lock()) {
+                    rtc.network_tick(time);
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to parse time: {} (raw: {:02x?})", e, data);
+            }
+        }
+    }
+
+    fn buzzer(&self, data: &[u8]) {
+        match clock_pure::parse_on_off(data) {
+            Ok(on) => {
+                log::info!("Buzzer {}", if on { "on" } else { "muted" });
+                self.buzzer_on.store(on, Ordering::Relaxed);
+                if let (false, Some(buzzer)) = (on, &self.buzzer) {
+                    buzzer.stop();
+                }
+            }
+            Err(e) => log::error!("Failed to parse buzzer: {}", e),
+        }
+    }
+
+    fn play_melody(&self, data: &[u8]) {
+        match (
+            String::from_utf8_lossy(data).parse::<Melody>(),
+            &self.buzzer,
+        ) {
+            (Ok(melody), Some(buzzer)) => {
+                log::info!("Playing a melody of {} notes", melody.notes().len());
+                buzzer.play(melody, false);
+            }
+            (Ok(_), None) => log::warn!("No buzzer to play the melody on"),
+            (Err(e), _) => log::error!("Failed to parse melody: {}", e),
+        }
+    }
+
+    fn self_test(&self, clock: &ClockHandle) {
+        log::info!("Starting LED self-test");
+        let test = SelfTest::new(LED_COUNT);
+        let duration_ms = test.duration_ms().unwrap_or_default();
+        let reports = self.reports.clone();
+        let finished = clock.run_effect(Sequence::new().then(test.clone(), duration_ms));
+        std::thread::spawn(move || {
+            let completed = finished.recv().unwrap_or(false);
+            if !completed {
+                log::warn!("LED self-test interrupted");
+            }
+            if reports.send(test.report(completed)).is_err() {
+                log::warn!("Failed to queue the self-test report");
+            }
+        });
+    }
+
+    fn update_firmware(&self, data: &[u8]) {
+        match (OtaRequest::parse(data), self.ota_key) {
+            (Ok(request), Some(key)) => {
+                log::info!("Updating the firmware from {}", request.url);
+                if let Err(e) = ota::start(request, key, self.ota_statuses.clone()) {
+                    log::error!("Failed to start the firmware update: {:?}", e);
+                }
+            }
+            (Ok(_), None) => {
+                log::error!("Refusing the firmware update without an OTA_PUBLIC_KEY");
+                let failed = OtaStatus::Failed("No OTA_PUBLIC_KEY".into());
+                if self.ota_statuses.send(failed).is_err() {
+                    log::warn!("Failed to queue the firmware update status");
+                }
+            }
+            (Err(e), _) => log::error!("Failed to parse firmware update: {}", e),
+        }
+    }
+
+    fn time_zone(&self, settings: Settings<'_>, data: &[u8]) {
+        let name = std::str::from_utf8(data).unwrap_or_default().trim();
+        let name = name.trim_matches('"');
+        match name.parse::<TimeZone>() {
+            Ok(zone) => {
+                log::info!("Setting time zone to {}", name);
+                save(settings, "time zone", |s| s.save_time_zone(&zone));
+                if let Ok(mut time_zone) = self.time_zone.lock() {
+                    *time_zone = zone;
+                }
+            }
+            Err(e) => {
+                log::error!("Failed to parse time zone {:?}: {}", name, e)
+            }
+        }
+    }
+
+    fn save_scene(&self, clock: &ClockHandle, settings: Settings<'_>, data: &[u8]) {
+        let (Some(state), Ok(mut scenes)) = (clock.state(), self.scenes.lock()) else {
+            return;
+        };
+        match scenes.clone().with_update(data, state.scene) {
+            Ok(updated) => {
+                log::info!(
+                    "Scenes: {:?}",
+                    updated.iter().map(|(name, _)| name).collect::<Vec<_>>()
+                );
+                save(settings, "scenes", |s| s.save_scenes(&updated));
+                *scenes = updated;
+            }
+            Err(e) => log::error!("Failed to parse scene: {}", e),
+        }
+    }
+
+    fn recall_scene(&self, clock: &ClockHandle, data: &[u8]) {
+        let name = std::str::from_utf8(data).unwrap_or_default().trim();
+        let name = name.trim_matches('"');
+        let Some(scene) = self.scenes.lock().ok().and_then(|s| s.get(name)) else {
+            log::error!("No scene called {:?}", name);
+            return;
+        };
+        log::info!("Recalling scene {:?}", name);
+        clock.send(Command::ApplyScene(scene));
+    }
+
+    fn schedule(&self, settings: Settings<'_>, data: &[u8]) {
+        let Ok(mut schedule) = self.schedule.lock() else {
+            return;
+        };
+        match schedule.clone().with_update(data) {
+            Ok(updated) => {
+                log::info!(
+                    "Setting schedule to {:?}",
+                    updated.iter().collect::<Vec<_>>()
+                );
+                save(settings, "schedule", |s| s.save_schedule(&updated));
+                *schedule = updated;
+            }
+            Err(e) => log::error!("Failed to parse schedule: {}", e),
+        }
+    }
+}
+
+/// Saves a setting with `save`, unless there is no settings storage.
+fn save(settings: Settings<'_>, what: &str, save: impl FnOnce(&mut SettingsStore) -> Result<()>) {
+    if let Some(Ok(mut settings)) = settings.map(Mutex::lock) {
+        if let Some(Err(e)) = settings.as_mut().map(save) {
+            log::error!("Failed to save {}: {:?}", what, e);
+        }
+    }
+}
+
+fn playlist(clock: &ClockHandle, data: &[u8]) {
+    match serde_json::from_slice::<Playlist>(data) {
+        Ok(_) if clock.state().is_some_and(|state| state.quiet) => {
+            log::info!("Do not disturb: ignoring playlist");
+        }
+        Ok(playlist) => {
+            log::info!("Playing playlist with {} steps", playlist.steps.len());
+            clock.run_effect(playlist.to_sequence());
+        }
+        Err(e) => log::error!("Failed to parse playlist: {}", e),
+    }
+}
+
+fn identify(clock: &ClockHandle) {
+    log::info!("Identifying this clock");
+    let identify = Identify::new((255, 255, 255));
+    clock.run_effect(Sequence::new().then(identify, IDENTIFY_MS));
+}
+
+fn brightness(clock: &ClockHandle, data: &[u8]) {
+    match clock_pure::parse_brightness(data) {
+        Ok(brightness) => {
+            log::info!("Setting brightness to {}", brightness);
+            clock.send(Command::SetBrightness(brightness));
+        }
+        Err(e) => log::error!("Failed to parse brightness: {}", e),
+    }
+}
+
+fn colors(clock: &ClockHandle, settings: Settings<'_>, data: &[u8]) {
+    match clock.update(data, Command::UpdateHandColors) {
+        Some(Ok(colors)) => {
+            log::info!("Set hand colors to {:?}", colors);
+            save(settings, "hand colors", |s| s.save_hand_colors(&colors));
+        }
+        Some(Err(e)) => log::error!("Failed to parse hand colors: {}", e),
+        None => {}
+    }
+}
+
+fn night(clock: &ClockHandle, data: &[u8]) {
+    match clock.update(data, Command::UpdateNightMode) {
+        Some(Ok(night)) => log::info!("Set night mode to {:?}", night),
+        Some(Err(e)) => log::error!("Failed to parse night mode: {}", e),
+        None => {}
+    }
+}
+
+fn do_not_disturb(clock: &ClockHandle, settings: Settings<'_>, data: &[u8]) {
+    match clock.update(data, Command::UpdateDoNotDisturb) {
+        Some(Ok(dnd)) => {
+            log::info!("Set do-not-disturb to {:?}", dnd);
+            save(settings, "do-not-disturb", |s| s.save_dnd(&dnd));
+        }
+        Some(Err(e)) => log::error!("Failed to parse do-not-disturb: {}", e),
+        None => {}
+    }
+}
+
+fn calibration(clock: &ClockHandle, settings: Settings<'_>, data: &[u8]) {
+    match clock.update(data, Command::UpdateCalibration) {
+        Some(Ok(calibration)) => {
+            log::info!("Set LED calibration to {:?}", calibration.scales());
+            save(settings, "LED calibration", |s| {
+                s.save_calibration(&calibration)
+            });
+        }
+        Some(Err(e)) => log::error!("Failed to parse LED calibration: {}", e),
+        None => {}
+    }
+}
+
+fn dead_pixels(clock: &ClockHandle, settings: Settings<'_>, data: &[u8]) {
+    match clock.update(data, Command::UpdateDeadPixels) {
+        Some(Ok(dead)) => {
+            log::info!("Set dead LEDs to {:?}", dead);
+            save(settings, "dead LEDs", |s| s.save_dead_pixels(&dead));
+        }
+        Some(Err(e)) => log::error!("Failed to parse dead LEDs: {}", e),
+        None => {}
+    }
+}
+
+fn animation(clock: &ClockHandle, data: &[u8]) {
+    match clock.update(data, Command::UpdateAnimation) {
+        Some(Ok(config)) => log::info!("Set animation config to {:?}", config),
+        Some(Err(e)) => log::error!("Failed to parse animation config: {}", e),
+        None => {}
+    }
+}
+
+fn startup(settings: Settings<'_>, data: &[u8]) {
+    match StartupChoice::parse(data) {
+        Ok(choice) => {
+            log::info!("Startup animation from the next boot: {}", choice.name());
+            save(settings, "startup animation", |s| s.save_startup(choice));
+        }
+        Err(e) => log::error!("Failed to parse startup animation: {}", e),
+    }
+}
+
+fn mode(clock: &ClockHandle, data: &[u8]) {
+    if std::str::from_utf8(data).is_ok_and(|t| t.trim() == "next") {
+        log::info!("Switching to the next mode");
+        clock.send(Command::NextMode);
+        return;
+    }
+    match DisplayMode::parse(data) {
+        Ok(mode) => {
+            log::info!("Switching to {} mode", mode.name());
+            clock.send(Command::SetMode(mode));
+        }
+        Err(e) => log::error!("Failed to parse mode: {}", e),
+    }
+}
+
+fn pomodoro(clock: &ClockHandle, data: &[u8]) {
+    let command = std::str::from_utf8(data)
+        .map_err(|_| clock_pure::SettingsError::InvalidUtf8)
+        .and_then(str::parse::<PomodoroCommand>);
+    match command {
+        Ok(command) => {
+            log::info!("Pomodoro command: {:?}", command);
+            clock.send(Command::Pomodoro(command));
+        }
+        Err(e) => log::error!("Failed to parse Pomodoro command: {}", e),
+    }
+}
+
+fn stopwatch(clock: &ClockHandle, data: &[u8]) {
+    let command = std::str::from_utf8(data)
+        .map_err(|_| clock_pure::SettingsError::InvalidUtf8)
+        .and_then(str::parse::<StopwatchCommand>);
+    match command {
+        Ok(command) => {
+            log::info!("Stopwatch command: {:?}", command);
+            clock.send(Command::Stopwatch(command));
+        }
+        Err(e) => log::error!("Failed to parse stopwatch command: {}", e),
+    }
+}
+
+fn alarm(clock: &ClockHandle, settings: Settings<'_>, data: &[u8]) {
+    if std::str::from_utf8(data).is_ok_and(|t| t.trim() == "dismiss") {
+        clock.send(Command::DismissAlarm);
+        return;
+    }
+    match clock.update(data, Command::UpdateAlarms) {
+        Some(Ok(alarms)) => {
+            log::info!("Set alarms to {:?}", alarms.iter().collect::<Vec<_>>());
+            save(settings, "alarms", |s| s.save_alarms(&alarms));
+        }
+        Some(Err(e)) => log::error!("Failed to parse alarm: {}", e),
+        None => {}
+    }
+}
+
+fn notify(clock: &ClockHandle, data: &[u8]) {
+    match Notification::parse(data) {
+        Ok(notification) => {
+            log::info!("Showing notification: {:?}", notification);
+            clock.send(Command::Notify(notification));
+        }
+        Err(e) => log::error!("Failed to parse notification: {}", e),
+    }
+}
+
+fn frame(clock: &ClockHandle, data: &[u8]) {
+    match PushedFrame::parse(data) {
+        Ok(_) if clock.state().is_some_and(|state| state.quiet) => {
+            log::info!("Do not disturb: ignoring pushed frame");
+        }
+        Ok(pushed) => {
+            log::info!("Showing a pushed frame for {} ms", pushed.duration_ms);
+            clock.run_effect(Sequence::new().then(pushed.frame, pushed.duration_ms));
+        }
+        Err(e) => log::error!("Failed to parse frame: {}", e),
+    }
+}
+
+fn presence(clock: &ClockHandle, data: &[u8]) {
+    match clock_pure::parse_presence(data) {
+        Ok(present) => {
+            log::info!("Presence: {}", if present { "detected" } else { "cleared" });
+            clock.send(Command::SetPresence(present));
+        }
+        Err(e) => log::error!("Failed to parse presence: {}", e),
+    }
+}
+
+fn power(clock: &ClockHandle, data: &[u8]) {
+    match clock_pure::parse_on_off(data) {
+        Ok(on) => {
+            log::info!("Display {}", if on { "on" } else { "off" });
+            clock.send(Command::SetPower(on));
+        }
+        Err(e) => log::error!("Failed to parse power: {}", e),
+    }
+}
+
+fn weather(clock: &ClockHandle, data: &[u8]) {
+    match Weather::parse(data) {
+        Ok(weather) => {
+            log::info!("Weather update: {:?}", weather);
+            clock.send(Command::SetWeather(weather));
+        }
+        Err(e) => log::error!("Failed to parse weather: {}", e),
+    }
+}
+
+fn temperature(clock: &ClockHandle, data: &[u8]) {
+    match clock_pure::parse_temperature(data) {
+        Ok(temperature) => {
+            log::debug!("Temperature: {} °C", temperature);
+            clock.send(Command::SetTemperature(temperature));
+        }
+        Err(e) => log::error!("Failed to parse temperature: {}", e),
+    }
+}
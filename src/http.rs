@@ -0,0 +1,291 @@
+use crate::command::ClockHandle;
+use crate::dispatch::Dispatcher;
+use crate::settings_store::SettingsStore;
+use anyhow::Result;
+use clock_net::{api_topic, authorized, frame_json, Route};
+use clock_pure::NetworkSettings;
+use esp_idf_svc::http::server::ws::{EspHttpWsConnection, EspHttpWsDetachedSender};
+use esp_idf_svc::http::server::{Configuration, EspHttpConnection, EspHttpServer, Request};
+use esp_idf_svc::http::Method;
+use esp_idf_svc::io::{EspIOError, Read, Write};
+use esp_idf_svc::ws::FrameType;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// Largest request body accepted; configurations and playlists fit
+const MAX_BODY: usize = 4096;
+
+// Handlers parse JSON like the MQTT callbacks, which needs more than the default stack
+const STACK_SIZE: usize = 10 * 1024;
+
+// Time for the response to reach the browser before a restart
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+
+// How often the live preview checks for a new frame, at most 10 frames per second
+const PREVIEW_INTERVAL: Duration = Duration::from_millis(100);
+
+const JSON_HEADERS: [(&str, &str); 1] = [("Content-Type", "application/json")];
+
+const HTML_HEADERS: [(&str, &str); 1] = [("Content-Type", "text/html; charset=utf-8")];
+
+// The web interface, a single page talking to the REST API
+const INDEX_HTML: &str = include_str!("../web/index.html");
+
+/// What the web server serves and changes.
+pub struct WebContext {
+    pub clock: ClockHandle,
+    /// Handles requests to `/api/<resource>` as messages on their topics,
+    /// as if they arrived over MQTT
+    pub dispatch: Arc<Dispatcher>,
+    /// The settings storage of the main clock, for the network settings
+    pub settings: Arc<Mutex<Option<SettingsStore>>>,
+    /// The network settings the clock runs with
+    pub network: NetworkSettings,
+    /// The bearer token requests that change the clock need; without one,
+    /// the server is read-only
+    pub token: Option<&'static str>,
+}
+
+/// Starts the web server with the web interface and the REST API on `port`.
+///
+/// `GET /` serves the web interface. `GET /api/state` returns the state as
+/// published on `clock/state`, `GET /api/frame` the frame on the LEDs, see
+/// [`frame_json`], and `GET /api/alarm` the alarms. A `POST` to
+/// `/api/<resource>`, see [`api_topic`], is handled by `dispatch` like a
+/// message on its topic and answered with the state once it applied.
+///
+/// `GET /api/network` returns the network settings without the password and
+/// a `POST` there saves changes to them, which apply after a `POST` to
+/// `/api/restart`.
+///
+/// Every `POST` needs the `token` in an `Authorization: Bearer` header and
+/// is answered with 401 otherwise, or with 403 if there is no token.
+///
+/// A WebSocket on `/ws/frames` streams the frames on the LEDs as they change,
+/// each as a text message like `GET /api/frame` returns.
+///
+/// The server stops when the returned handle is dropped.
+pub fn start(port: u16, context: WebContext) -> Result<EspHttpServer<'static>> {
+    let WebContext {
+        clock,
+        dispatch,
+        settings,
+        network,
+        token,
+    } = context;
+    let mut server = EspHttpServer::new(&Configuration {
+        http_port: port,
+        stack_size: STACK_SIZE,
+        uri_match_wildcard: true,
+        ..Default::default()
+    })?;
+
+    server.fn_handler("/", Method::Get, |request| -> Result<()> {
+        let mut response = request.into_response(200, None, &HTML_HEADERS)?;
+        response.write_all(INDEX_HTML.as_bytes())?;
+        Ok(())
+    })?;
+
+    let state_clock = clock.clone();
+    server.fn_handler("/api/state", Method::Get, move |request| -> Result<()> {
+        let state = state_clock.state().map(|state| state.report().to_json());
+        respond(request, state)
+    })?;
+
+    let frame_clock = clock.clone();
+    server.fn_handler("/api/frame", Method::Get, move |request| -> Result<()> {
+        let frame = frame_clock
+            .state()
+            .and_then(|state| state.frame)
+            .map(|frame| frame_json(&frame));
+        respond(request, frame)
+    })?;
+
+    let alarm_clock = clock.clone();
+    server.fn_handler("/api/alarm", Method::Get, move |request| -> Result<()> {
+        let alarms = alarm_clock.state().map(|state| state.alarms.to_json());
+        respond(request, alarms)
+    })?;
+
+    // The network settings as they apply after the next restart
+    let network = Arc::new(Mutex::new(network));
+    let shown_network = Arc::clone(&network);
+    server.fn_handler("/api/network", Method::Get, move |request| -> Result<()> {
+        let json = shown_network.lock().ok().map(|network| network.to_json());
+        respond(request, json)
+    })?;
+
+    server.fn_handler(
+        "/api/network",
+        Method::Post,
+        move |mut request| -> Result<()> {
+            if let Some(status) = refusal(&request, token) {
+                request.into_status_response(status)?;
+                return Ok(());
+            }
+            let Some(body) = read_body(&mut request)? else {
+                request.into_status_response(413)?;
+                return Ok(());
+            };
+            let (Ok(mut network), Ok(mut settings)) = (network.lock(), settings.lock()) else {
+                request.into_status_response(503)?;
+                return Ok(());
+            };
+            let Some(settings) = settings.as_mut() else {
+                // Without the storage, changes would be lost on the restart they need
+                request.into_status_response(503)?;
+                return Ok(());
+            };
+            let updated = match network.clone().with_update(&body) {
+                Ok(updated) => updated,
+                Err(e) => {
+                    log::warn!("Invalid network settings: {}", e);
+                    request.into_status_response(400)?;
+                    return Ok(());
+                }
+            };
+            if let Err(e) = settings.save_network(&updated) {
+                log::warn!("Failed to save the network settings: {:?}", e);
+                request.into_status_response(500)?;
+                return Ok(());
+            }
+            log::info!("Saved network settings, applied after a restart");
+            *network = updated;
+            respond(request, Some(network.to_json()))
+        },
+    )?;
+
+    let viewers = Arc::new(Mutex::new(Vec::new()));
+    let preview_clock = clock.clone();
+    let new_viewers = Arc::clone(&viewers);
+    server.ws_handler(
+        "/ws/frames",
+        move |ws: &mut EspHttpWsConnection| -> Result<()> {
+            if ws.is_new() {
+                log::info!("Live preview {} opened", ws.session());
+                // The current frame first, the stream only sends changes
+                let frame = preview_clock.state().and_then(|state| state.frame);
+                if let Some(frame) = frame {
+                    ws.send(FrameType::Text(false), frame_json(&frame).as_bytes())?;
+                }
+                if let Ok(mut viewers) = new_viewers.lock() {
+                    viewers.push(ws.create_detached_sender()?);
+                }
+            } else if ws.is_closed() {
+                log::info!("Live preview {} closed", ws.session());
+            } else {
+                // Viewers only listen; whatever they send is dropped
+                let (_, len) = ws.recv(&mut [])?;
+                ws.recv(&mut vec![0; len.min(MAX_BODY)])?;
+            }
+            Ok(())
+        },
+    )?;
+    let stream_clock = clock.clone();
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || stream_frames(&stream_clock, &viewers))?;
+
+    server.fn_handler("/api/restart", Method::Post, move |request| -> Result<()> {
+        if let Some(status) = refusal(&request, token) {
+            request.into_status_response(status)?;
+            return Ok(());
+        }
+        log::info!("Restart requested over the web interface");
+        request.into_status_response(202)?;
+        std::thread::spawn(|| {
+            std::thread::sleep(RESTART_DELAY);
+            esp_idf_svc::hal::reset::restart();
+        });
+        Ok(())
+    })?;
+
+    server.fn_handler("/api/*", Method::Post, move |mut request| -> Result<()> {
+        if let Some(status) = refusal(&request, token) {
+            request.into_status_response(status)?;
+            return Ok(());
+        }
+        let uri = request.uri();
+        let resource = uri.strip_prefix("/api/").unwrap_or(uri);
+        let resource = resource.split('?').next().unwrap_or_default();
+        let Some(topic) = api_topic(resource) else {
+            request.into_status_response(404)?;
+            return Ok(());
+        };
+        let Some(body) = read_body(&mut request)? else {
+            request.into_status_response(413)?;
+            return Ok(());
+        };
+        log::info!("REST request for {}", topic.name());
+        dispatch.handle(Route::first(topic), &body);
+        // Commands apply in order, so the state taken next includes this one
+        let state = clock.state().map(|state| state.report().to_json());
+        respond(request, state)
+    })?;
+
+    log::info!("Web interface listening on port {}", port);
+    Ok(server)
+}
+
+/// Sends every new frame on the LEDs to the live preview `viewers`, dropping
+/// those that closed.
+fn stream_frames(clock: &ClockHandle, viewers: &Mutex<Vec<EspHttpWsDetachedSender>>) {
+    let mut last = None;
+    loop {
+        std::thread::sleep(PREVIEW_INTERVAL);
+        if viewers.lock().map_or(true, |viewers| viewers.is_empty()) {
+            last = None;
+            continue;
+        }
+        let Some(frame) = clock.state().and_then(|state| state.frame) else {
+            continue;
+        };
+        if last == Some(frame) {
+            continue;
+        }
+        last = Some(frame);
+        let json = frame_json(&frame);
+        if let Ok(mut viewers) = viewers.lock() {
+            viewers
+                .retain_mut(|viewer| viewer.send(FrameType::Text(false), json.as_bytes()).is_ok());
+        }
+    }
+}
+
+/// Answers with `json`, or with 503 if there is none because the render
+/// loop stopped or no frame was shown yet.
+fn respond(request: Request<&mut EspHttpConnection<'_>>, json: Option<String>) -> Result<()> {
+    let Some(json) = json else {
+        request.into_status_response(503)?;
+        return Ok(());
+    };
+    let mut response = request.into_response(200, None, &JSON_HEADERS)?;
+    response.write_all(json.as_bytes())?;
+    Ok(())
+}
+
+/// Returns the status refusing a request that changes the clock: 403 while
+/// there is no `token`, 401 if the request does not carry it.
+fn refusal(request: &Request<&mut EspHttpConnection<'_>>, token: Option<&str>) -> Option<u16> {
+    match token {
+        None => Some(403),
+        Some(token) if !authorized(request.header("Authorization"), token) => Some(401),
+        Some(_) => None,
+    }
+}
+
+/// Reads the request body, or returns `None` if it exceeds [`MAX_BODY`].
+fn read_body(request: &mut impl Read<Error = EspIOError>) -> Result<Option<Vec<u8>>> {
+    let mut body = Vec::new();
+    let mut buffer = [0; 512];
+    loop {
+        let len = request.read(&mut buffer)?;
+        if len == 0 {
+            return Ok(Some(body));
+        }
+        if body.len() + len > MAX_BODY {
+            return Ok(None);
+        }
+        body.extend_from_slice(&buffer[..len]);
+    }
+}
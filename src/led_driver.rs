@@ -0,0 +1,49 @@
+use anyhow::{anyhow, Result};
+use rgb::RGB8;
+use rustyfarian_esp_idf_ws2812::WS2812RMT;
+use smart_leds_trait::SmartLedsWrite;
+use std::fmt::Debug;
+
+/// A strip driver the clock sends its frames to.
+///
+/// Every strip driver from the smart-leds ecosystem is one, so the clock can
+/// drive e.g. SK6812 or APA102 rings without an adapter. The clock writes
+/// the whole strip at once, in strip order, from the render thread, so the
+/// driver must be `Send`.
+pub trait LedDriver: Send {
+    /// Writes `pixels` to the strip.
+    fn write_pixels(&mut self, pixels: &[RGB8]) -> Result<()>;
+}
+
+impl<W> LedDriver for W
+where
+    W: SmartLedsWrite + Send,
+    W::Error: Debug,
+    RGB8: Into<W::Color>,
+{
+    fn write_pixels(&mut self, pixels: &[RGB8]) -> Result<()> {
+        self.write(pixels.iter().copied())
+            .map_err(|e| anyhow!("LED write failed: {:?}", e))
+    }
+}
+
+/// The ESP-IDF RMT driver for WS2812 rings, as a smart-leds driver.
+///
+/// It writes as many LEDs as it gets colors, so rings chained after the
+/// clock face are driven as well.
+pub struct RmtDriver<'d>(pub WS2812RMT<'d>);
+
+impl SmartLedsWrite for RmtDriver<'_> {
+    type Error = anyhow::Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let pixels: Vec<RGB8> = iterator.into_iter().map(Into::into).collect();
+        self.0.set_pixels_slice(&pixels)?;
+        Ok(())
+    }
+}
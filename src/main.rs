@@ -1,15 +1,81 @@
+#[cfg(feature = "apa102")]
+mod apa102;
+mod board;
+mod button;
+mod buzzer;
+mod command;
+mod dispatch;
+mod http;
+mod led_driver;
+mod mdns;
+mod ota;
+mod retained;
 mod rgb_clock;
+mod rtc;
+mod settings_store;
+#[cfg(feature = "sk6812")]
+mod sk6812;
+mod sntp;
+mod switch;
 
+#[cfg(all(feature = "apa102", feature = "sk6812"))]
+compile_error!("the apa102 and sk6812 features select different rings; enable only one");
+
+#[cfg(feature = "apa102")]
+use crate::apa102::Apa102Driver;
+use crate::board::Board;
+use crate::command::{ClockHandle, ClockState, Command};
+use crate::dispatch::Dispatcher;
+use crate::led_driver::RmtDriver;
 use crate::rgb_clock::RGBClock;
+use crate::rtc::{Ds3231, RtcSync};
+use crate::settings_store::SettingsStore;
+#[cfg(feature = "sk6812")]
+use crate::sk6812::Sk6812Driver;
+use crate::sntp::SntpSync;
+use crate::switch::Switch;
 use anyhow::Context;
+use clock_net::{FirmwareKey, OtaStatus, TopicLayout};
+use clock_pure::effects::{Effect, Ripple, Sequence, StartupChoice, RIPPLE_MS};
+#[cfg(feature = "sk6812")]
+use clock_pure::WhitePolicy;
+#[cfg(feature = "apa102")]
+use clock_pure::APA102_MAX_BRIGHTNESS;
+use clock_pure::{
+    AlarmStyle, Backoff, ChannelOrder, ClockFace, HourMarkers, LocalDateTime, Melody,
+    NetworkSettings, NightMode, OverlapPolicy, Pomodoro, PomodoroPhase, PowerBudget, QosLevels,
+    RingLayout, Scenes, ScheduledAction, Telemetry, TemperatureGauge, ThemeCalendar, TimeSource,
+    TimeZone, Topic, WiringOrder, LED_COUNT,
+};
+use esp_idf_hal::gpio::{AnyOutputPin, Pin};
 use esp_idf_hal::peripherals::Peripherals;
 use esp_idf_svc::eventloop::EspSystemEventLoop;
+use esp_idf_svc::mqtt::client::QoS;
 use esp_idf_svc::nvs::EspDefaultNvsPartition;
+use esp_idf_svc::tls::X509;
+use rgb::RGB8;
 use rustyfarian_esp_idf_mqtt::{MqttBuilder, MqttConfig};
 use rustyfarian_esp_idf_wifi::{WiFiConfig, WiFiManager};
 use rustyfarian_esp_idf_ws2812::WS2812RMT;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// How often the main thread runs due scheduled actions and checks whether the next alarm changed
+const NEXT_ALARM_INTERVAL: Duration = Duration::from_secs(1);
+
+// The onboard LED is bright, so the status shows dimmed there, and not at all at night
+const STATUS_LED_BRIGHTNESS: u8 = 12;
+
+// Topics under clock/ with subtopics of their own, which no second clock may be named after
+const NESTED_TOPICS: [&str; 5] = ["alarm", "buzzer", "calibration", "scene", "selftest"];
+
+// Color of the ripple chiming the hour
+const CHIME_COLOR: (u8, u8, u8) = (255, 180, 40);
+
+// The broker's CA certificate from the optional MQTT_CA_CERT setting, embedded by
+// the build script; empty without one
+const MQTT_CA_CERT: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/mqtt_ca_cert.pem"));
 
 fn main() -> anyhow::Result<()> {
     // It is necessary to call this function once. Otherwise, some patches to the runtime
@@ -22,28 +88,277 @@ fn main() -> anyhow::Result<()> {
     let peripherals = Peripherals::take()?;
     let sys_loop = EspSystemEventLoop::take()?;
     let nvs = EspDefaultNvsPartition::take()?;
+    // The GPIOs in the comments below are those of the ESP32-C6 board
+    let board = Board::take(peripherals.pins);
+    log::info!("Running on {}", board::NAME);
 
-    // ESP32-C6 GPIO10 for the NeoPixel clock - start animation before WiFi
-    let clock_driver = WS2812RMT::new(peripherals.pins.gpio10)?;
-    let rgb_clock = RGBClock::new(clock_driver)?;
+    // GPIO10 for the NeoPixel clock - start animation before WiFi
+    #[cfg(not(any(feature = "apa102", feature = "sk6812")))]
+    let clock_driver = RmtDriver(WS2812RMT::new(board.ring)?);
+    // APA102 rings take their data on GPIO10 and the SPI clock on GPIO11
+    #[cfg(feature = "apa102")]
+    let clock_driver = Apa102Driver::new(
+        peripherals.spi2,
+        board.ring_clock,
+        board.ring,
+        apa102_brightness(),
+    )?;
+    // SK6812 RGBW rings take their data on GPIO10 as well
+    #[cfg(feature = "sk6812")]
+    let clock_driver = Sk6812Driver::new(peripherals.rmt.channel1, board.ring, white_policy())?;
 
-    // Wrap clock in Arc<Mutex<>> for sharing between threads
-    let clock = Arc::new(Mutex::new(rgb_clock));
+    // Restore settings changed at runtime before anything is drawn
+    let settings = match SettingsStore::new(nvs.clone()) {
+        Ok(settings) => Some(settings),
+        Err(e) => {
+            log::warn!(
+                "Settings storage unavailable, changes will not persist: {:?}",
+                e
+            );
+            None
+        }
+    };
+    let mut builder = RGBClock::builder(clock_driver);
+    if let Some(face) = clock_face() {
+        log::info!("Clock face of {} LEDs", face.leds());
+        builder = builder.face(face);
+    }
+    if let Some(colors) = settings.as_ref().and_then(SettingsStore::load_hand_colors) {
+        log::info!("Restoring hand colors: {:?}", colors);
+        builder = builder.hand_colors(colors);
+    }
+    if let Some(themes) = theme_calendar() {
+        log::info!("Holiday themes: {}", themes.iter().count());
+        builder = builder.theme_calendar(themes);
+    }
+    if let Some(brightness) = brightness() {
+        log::info!("Starting at brightness {}", brightness);
+        builder = builder.brightness(brightness);
+    }
+    if let Some(budget) = power_budget() {
+        log::info!("Current limit: {} mA", budget.max_ma());
+        builder = builder.power_budget(budget);
+    }
+    let order = channel_order();
+    if let Some(order) = order {
+        log::info!("LED channel order: {}", order.name());
+        builder = builder.channel_order(order);
+    }
+    let mut rgb_clock = builder.build()?;
+    let ring_power = output_gpio("RING_POWER_GPIO", option_env!("RING_POWER_GPIO"));
+    if let Some(gpio) = ring_power {
+        let active_low = option_env!("RING_POWER_ACTIVE_LOW").is_some_and(|v| v == "true");
+        log::info!("Ring power on GPIO{} (active low: {})", gpio, active_low);
+        // SAFETY: output_gpio() only accepts GPIOs the board leaves free
+        let pin = unsafe { AnyOutputPin::new(gpio) };
+        rgb_clock.set_ring_power(Switch::new(pin, active_low, true)?);
+    }
+    if let Some(wiring) = wiring_order() {
+        log::info!("LED wiring order: {:?}", wiring);
+        rgb_clock.set_wiring_order(wiring);
+    }
+    if let Some(overlap) = overlap_policy() {
+        log::info!("Hand overlap policy: {}", overlap.name());
+        rgb_clock.set_overlap_policy(overlap);
+    }
+    if let Some(markers) = hour_markers() {
+        log::info!("Hour markers enabled: {:?}", markers.dimmed_color());
+        rgb_clock.set_hour_markers(Some(markers));
+    }
+    if option_env!("SMOOTH_SECONDS").is_some_and(|v| v == "true") {
+        log::info!("Smooth second hand enabled");
+        rgb_clock.set_smooth_seconds(true);
+    }
+    if let Some(ring) = outer_ring() {
+        log::info!("Outer ring of {} LEDs: {:?}", ring.leds(), ring);
+        rgb_clock.set_outer_ring(Some(ring));
+        // A ring on its own data line takes GPIO11 instead of chaining after the face
+        #[cfg(not(any(feature = "apa102", feature = "sk6812")))]
+        if option_env!("OUTER_RING_SEPARATE").is_some_and(|v| v == "true") {
+            log::info!("Outer ring driven on GPIO{}", board.ring_clock.pin());
+            rgb_clock.set_outer_driver(RmtDriver(WS2812RMT::new(board.ring_clock)?));
+        }
+    }
+    if option_env!("GAMMA_CORRECTION").is_some_and(|v| v == "true") {
+        log::info!("Gamma correction enabled");
+        rgb_clock.set_gamma_correction(true);
+    }
+    rgb_clock.set_pomodoro(pomodoro());
+    if let Some(gauge) = temperature_gauge() {
+        log::info!("Temperature gauge from {} to {} °C", gauge.min, gauge.max);
+        rgb_clock.set_temperature_gauge(gauge);
+    }
+    if let Some(interval) = weather_interval() {
+        log::info!("Showing the weather every {:?}", interval);
+        rgb_clock.set_weather_interval(Some(interval));
+    }
+    if let Some(timeout) = screensaver() {
+        log::info!("Screensaver after {:?} without activity", timeout);
+        rgb_clock.set_screensaver(Some(timeout));
+    }
+    let alarms = settings
+        .as_ref()
+        .and_then(SettingsStore::load_alarms)
+        .unwrap_or_default();
+    log::info!("Restoring {} alarms", alarms.iter().count());
+    rgb_clock.set_alarms(alarms.with_style(alarm_style()));
+    if let Some(sunrise) = sunrise() {
+        log::info!("Sunrise starts {:?} before each alarm", sunrise);
+        rgb_clock.set_sunrise(Some(sunrise));
+    }
+    let alarm_output = match output_gpio("ALARM_OUTPUT_GPIO", option_env!("ALARM_OUTPUT_GPIO")) {
+        Some(gpio) if ring_power == Some(gpio) => {
+            log::warn!("ALARM_OUTPUT_GPIO {} is taken by the ring power", gpio);
+            None
+        }
+        gpio => gpio,
+    };
+    if let Some(gpio) = alarm_output {
+        let active_low = option_env!("ALARM_OUTPUT_ACTIVE_LOW").is_some_and(|v| v == "true");
+        log::info!("Alarm output on GPIO{} (active low: {})", gpio, active_low);
+        // SAFETY: output_gpio() only accepts GPIOs the board leaves free, and
+        // the ring power pin was ruled out above
+        let pin = unsafe { AnyOutputPin::new(gpio) };
+        rgb_clock.set_alarm_output(Switch::new(pin, active_low, false)?);
+    }
+    if let Some(night) = night_mode(rgb_clock.night_mode()) {
+        log::info!("Night mode scheduled: {:?}", night);
+        rgb_clock.set_night_mode(night);
+    }
+    if let Some(dnd) = settings.as_ref().and_then(SettingsStore::load_dnd) {
+        log::info!("Restoring do-not-disturb: {:?}", dnd);
+        rgb_clock.set_do_not_disturb(dnd);
+    }
+    if let Some(calibration) = settings.as_ref().and_then(SettingsStore::load_calibration) {
+        log::info!("Restoring LED calibration: {:?}", calibration.scales());
+        rgb_clock.set_calibration(calibration);
+    }
+    if let Some(dead) = settings.as_ref().and_then(SettingsStore::load_dead_pixels) {
+        log::info!("Restoring dead LEDs: {:?}", dead);
+        rgb_clock.set_dead_pixels(dead);
+    }
+    let startup = startup_choice(settings.as_ref().and_then(SettingsStore::load_startup));
+    let scenes = settings
+        .as_ref()
+        .and_then(SettingsStore::load_scenes)
+        .unwrap_or_default();
+    log::info!("Restoring {} scenes", scenes.iter().count());
+    let scenes = Arc::new(Mutex::new(scenes));
+    let schedule = settings
+        .as_ref()
+        .and_then(SettingsStore::load_schedule)
+        .unwrap_or_default();
+    log::info!("Restoring {} scheduled actions", schedule.iter().count());
+    let schedule = Arc::new(Mutex::new(schedule));
+    // A time zone set over MQTT replaces the one from .env
+    let time_zone = settings
+        .as_ref()
+        .and_then(SettingsStore::load_time_zone)
+        .unwrap_or_else(time_zone);
+    let time_zone = Arc::new(Mutex::new(time_zone));
+    // Network settings saved from the web interface replace those in .env
+    let network = match settings.as_ref().and_then(SettingsStore::load_network) {
+        Some(network) => {
+            log::info!("Using the network settings saved from the web interface");
+            network
+        }
+        None => env_network()?,
+    };
+    // Lives as long as the firmware; the WiFi and MQTT configurations borrow it
+    let network: &'static NetworkSettings = Box::leak(Box::new(network));
+    let settings = Arc::new(Mutex::new(settings));
+
+    // Play the startup animation; it gives way to the clock face once the
+    // first time arrives
+    // SAFETY: esp_random has no preconditions; it reads the hardware RNG.
+    let random = unsafe { esp_idf_svc::sys::esp_random() };
+    match startup.pick(random) {
+        Some(animation) => rgb_clock.play_startup_animation(animation),
+        None => log::info!("Startup animation is off"),
+    }
+
+    // The render thread owns the clock from here on; everything else sends it commands
+    let (clock, commands) = ClockHandle::new();
+    let _render_handle = rgb_clock::run_render_loop(rgb_clock, commands);
+
+    // A second clock on GPIO2 runs on its own render thread, addressed under clock/<name>/
+    let mut others = Vec::new();
+    if let Some((name, face)) = second_clock() {
+        log::info!(
+            "Second clock '{}' of {} LEDs on GPIO{}",
+            name,
+            face.leds(),
+            board.second_clock.pin()
+        );
+        let mut second = RGBClock::builder(RmtDriver(WS2812RMT::new(board.second_clock)?))
+            .face(face)
+            .channel_order(order.unwrap_or_default())
+            .build()?;
+        if let Some(animation) = startup.pick(random) {
+            second.play_startup_animation(animation);
+        }
+        let (handle, commands) = ClockHandle::new();
+        let _second_render_handle = rgb_clock::run_render_loop(second, commands);
+        others.push((name, handle));
+    }
+    // Time and connection status reach every clock
+    let every_clock: Vec<ClockHandle> = std::iter::once(clock.clone())
+        .chain(others.iter().map(|(_, handle)| handle.clone()))
+        .collect();
+
+    // After a soft reset or deep sleep, the time kept in RTC memory shows at once
+    let restored = retained::restore();
+    if let Some(time) = restored {
+        log::info!("Restored time from RTC memory: {:?}", time.time);
+        for clock in &every_clock {
+            clock.send(Command::SetTime(time));
+        }
+    }
+
+    // A DS3231 on GPIO6 (SDA) and GPIO7 (SCL) shows the time before the network is up
+    let rtc = if option_env!("DS3231").is_some_and(|v| v == "true") {
+        let mut rtc = Ds3231::new(peripherals.i2c0, board.sda, board.scl)?;
+        match rtc.read() {
+            // The time from RTC memory is more precise
+            Ok(Some(_)) if restored.is_some() => {}
+            Ok(Some(time)) => {
+                log::info!("RTC time: {:?} {:?}", time.date, time.time);
+                for clock in &every_clock {
+                    clock.send(Command::SetTime(time));
+                }
+            }
+            Ok(None) => log::warn!("RTC lost power, waiting for network time"),
+            Err(e) => log::warn!("Failed to read the RTC: {:?}", e),
+        }
+        Some(Arc::new(Mutex::new(RtcSync::new(rtc))))
+    } else {
+        None
+    };
 
-    // Start the startup animation in a background thread
-    let animation_cancel = Arc::new(AtomicBool::new(false));
-    let _animation_handle =
-        rgb_clock::run_startup_animation(Arc::clone(&clock), Arc::clone(&animation_cancel));
+    // BOOT button on GPIO9 switches modes and controls the Pomodoro timer
+    let _button_handle = button::run_button(board.button, clock.clone())?;
 
-    // WiFi credentials from .env
-    const WIFI_SSID: &str = env!("WIFI_SSID");
-    const WIFI_PASS: &str = env!("WIFI_PASS");
+    // A piezo buzzer on GPIO3 makes alarms and the hourly chime audible
+    let buzzer = if option_env!("BUZZER").is_some_and(|v| v == "true") {
+        log::info!("Buzzer on GPIO{}", board.buzzer.pin());
+        Some(buzzer::run_buzzer(
+            peripherals.ledc.timer0,
+            peripherals.ledc.channel0,
+            board.buzzer,
+        )?)
+    } else {
+        None
+    };
+    // Muted on clock/buzzer; do-not-disturb mutes it as well
+    let buzzer_on = Arc::new(AtomicBool::new(true));
+    let alarm_melody = melody("ALARM_MELODY", option_env!("ALARM_MELODY"), Melody::ALARM);
+    let chime_melody = melody("CHIME_MELODY", option_env!("CHIME_MELODY"), Melody::CHIME);
 
-    // ESP32-C6 DevKit onboard RGB LED is on GPIO8
-    let mut driver = WS2812RMT::new(peripherals.pins.gpio8)?;
+    // The DevKit's onboard RGB LED (GPIO8) shows the status once WiFi is up
+    let mut driver = WS2812RMT::new(board.status_led)?;
 
     // Initialize Wi-Fi with an LED indicator
-    let wifi_config = WiFiConfig::new(WIFI_SSID, WIFI_PASS);
+    let wifi_config = WiFiConfig::new(&network.wifi_ssid, &network.wifi_pass);
     let wifi = WiFiManager::new(
         peripherals.modem,
         sys_loop,
@@ -53,54 +368,1175 @@ fn main() -> anyhow::Result<()> {
     )?;
 
     // Wait some seconds for an IP address
-    if let Some(ip) = wifi.get_ip(10000)? {
+    let wifi_connected = if let Some(ip) = wifi.get_ip(10000)? {
         log::info!("Got IP address: {:?}", ip);
+        true
     } else {
         log::error!("Failed to get IP address within timeout");
+        false
+    };
+    for clock in &every_clock {
+        clock.send(Command::SetWifiConnected(wifi_connected));
+        if !wifi_connected {
+            // No time will arrive soon, so stop the animation and show the problem instead
+            clock.send(Command::EndStartup);
+        }
     }
 
-    // MQTT configuration from .env
-    const MQTT_HOST: &str = env!("MQTT_HOST");
-    const MQTT_PORT: &str = env!("MQTT_PORT");
-    const MQTT_CLIENT_ID: &str = env!("MQTT_CLIENT_ID");
+    // Without a broker, the clock takes its time from SNTP alone
+    let standalone = option_env!("STANDALONE").is_some_and(|v| v == "true");
+
+    // NTP servers keep the time when the ticks stop, or in place of them with TIME_SOURCE=sntp
+    let sntp = match sntp_servers(standalone) {
+        Some(servers) => Some(Arc::new(Mutex::new(SntpSync::start(
+            &servers,
+            time_source(),
+        )?))),
+        None => None,
+    };
+
+    // MQTT client ID from .env
+    const MQTT_CLIENT_ID: &str = match option_env!("MQTT_CLIENT_ID") {
+        Some(client_id) => client_id,
+        None => "rgb-clock",
+    };
+
+    // MQTT topics the clock publishes on; it handles messages on those of clock_pure::Topic
+    const NEXT_ALARM_TOPIC: &str = "clock/alarm/next";
+    const SELFTEST_REPORT_TOPIC: &str = "clock/selftest/report";
+    // Progress of firmware updates, published by the main loop
+    const OTA_STATUS_TOPIC: &str = "clock/ota/status";
+    // Retained `online` while connected; the broker publishes the last will,
+    // `offline`, when the connection drops without a goodbye
+    const AVAILABILITY_TOPIC: &str = "clock/availability";
+    // Firmware and device details, published (retained) by the main loop on every connect
+    const INFO_TOPIC: &str = "clock/info";
+    // The full state, published (retained) by the main loop whenever it changes
+    const STATE_TOPIC: &str = "clock/state";
+    // Health figures for monitoring, published by the main loop
+    const TELEMETRY_TOPIC: &str = "clock/telemetry";
+    // Where the topics above are on the broker: shared by all clocks, except
+    // for the device topics under clock/<MQTT_CLIENT_ID>/, or all of them
+    // under <MQTT_TOPIC_PREFIX>/<MQTT_CLIENT_ID>/
+    let layout = topic_layout(MQTT_CLIENT_ID);
+    let availability_topic = layout.broker_topic(AVAILABILITY_TOPIC);
+    let online_topic = availability_topic.clone();
+    let info_topic = layout.broker_topic(INFO_TOPIC);
+    let state_topic = layout.broker_topic(STATE_TOPIC);
+    let telemetry_topic = layout.broker_topic(TELEMETRY_TOPIC);
+    let next_alarm_topic = layout.broker_topic(NEXT_ALARM_TOPIC);
+    let selftest_report_topic = layout.broker_topic(SELFTEST_REPORT_TOPIC);
+    let ota_status_topic = layout.broker_topic(OTA_STATUS_TOPIC);
+    // Levels of the topics on the broker and whether state such as the availability is retained
+    let qos_levels = mqtt_qos();
+    let retain = !option_env!("MQTT_RETAIN").is_some_and(|v| v == "false");
+    let availability_qos = qos(&qos_levels, &availability_topic);
 
     // Connect to MQTT broker - MUST assign to a variable to keep it alive!
-    let clock_clone = Arc::clone(&clock);
-    let animation_cancel_clone = Arc::clone(&animation_cancel);
-    let mqtt_port: u16 = MQTT_PORT
-        .parse()
-        .context("MQTT_PORT must be a valid port number (0-65535)")?;
-    let mqtt_config = MqttConfig::new(MQTT_HOST, mqtt_port, MQTT_CLIENT_ID);
-    let _mqtt = MqttBuilder::new(mqtt_config)
-        .on_connect(|client, _is_clean| {
-            use esp_idf_svc::mqtt::client::QoS;
-            client.subscribe("tick", QoS::AtLeastOnce)?;
-            Ok(())
-        })
-        .on_message(move |_topic: &str, data: &[u8]| {
-            use rgb_clock::LocalTime;
-
-            // Cancel any running startup animation on the first time update
-            animation_cancel_clone.store(true, Ordering::Relaxed);
-
-            match LocalTime::try_from(data) {
-                Ok(time) => {
-                    if let Ok(mut c) = clock_clone.lock() {
-                        if let Err(e) = c.set_local_time(time) {
-                            log::error!("Failed to set time: {:?}", e);
-                        }
+    let clocks_connect = every_clock.clone();
+    let clocks_disconnect = every_clock.clone();
+    let names: Vec<&'static str> = others.iter().map(|(name, _)| *name).collect();
+    // Self-test reports are published from the main loop, which owns the client
+    let (report_sender, reports) = std::sync::mpsc::channel::<String>();
+    let (ota_sender, ota_statuses) = std::sync::mpsc::channel::<OtaStatus>();
+    let ota_key = ota_public_key();
+    if ota_key.is_none() {
+        log::warn!("No OTA_PUBLIC_KEY, refusing firmware updates");
+    }
+    let web_settings = Arc::clone(&settings);
+    // Handles messages on the clock's topics, from the broker or the web server
+    let dispatch = Arc::new(Dispatcher {
+        clock: clock.clone(),
+        others,
+        settings,
+        time_zone: Arc::clone(&time_zone),
+        sntp: sntp.clone(),
+        rtc: rtc.clone(),
+        scenes: Arc::clone(&scenes),
+        schedule: Arc::clone(&schedule),
+        buzzer: buzzer.clone(),
+        buzzer_on: Arc::clone(&buzzer_on),
+        reports: report_sender,
+        ota_statuses: ota_sender,
+        ota_key,
+    });
+    let dispatch_mqtt = Arc::clone(&dispatch);
+    let mqtt_config = if standalone {
+        log::info!("Running standalone, without MQTT");
+        // No broker to miss
+        for clock in &every_clock {
+            clock.send(Command::SetMqttConnected(true));
+        }
+        None
+    } else {
+        let mqtt_host = network
+            .mqtt_host
+            .as_deref()
+            .context("MQTT_HOST must be set unless STANDALONE=true")?;
+        let mqtt_port = network
+            .mqtt_port
+            .context("MQTT_PORT must be set unless STANDALONE=true")?;
+        let mqtt_config = MqttConfig::new(mqtt_host, mqtt_port, MQTT_CLIENT_ID).with_last_will(
+            &availability_topic,
+            b"offline",
+            availability_qos,
+            retain,
+        );
+        // Reconnect from the main loop, backing off, instead of every 10 seconds
+        let mqtt_config = mqtt_config.with_auto_reconnect(false);
+        let mqtt_config = match mqtt_tls() {
+            Some(certificate) => mqtt_config.with_tls(certificate),
+            None => mqtt_config,
+        };
+        let mqtt_config = match mqtt_websocket_path() {
+            Some(path) => mqtt_config.with_websocket(path),
+            None => mqtt_config,
+        };
+        Some(mqtt_config)
+    };
+    let mqtt = match mqtt_config {
+        None => None,
+        Some(mqtt_config) => Some(
+            MqttBuilder::new(mqtt_config)
+                .on_connect(move |client, _is_clean| {
+                    let mut subscribe = |topic: &str| {
+                        let topic = layout.broker_topic(topic);
+                        client.subscribe(&topic, qos(&qos_levels, &topic))
+                    };
+                    // Including tick next to the set_time command on clock/cmd, as
+                    // existing time sources publish there
+                    for topic in Topic::ALL {
+                        subscribe(topic.name())?;
+                    }
+                    for name in &names {
+                        subscribe(&format!("clock/{}/#", name))?;
                     }
+                    client.publish(&online_topic, availability_qos, retain, b"online")?;
+                    for clock in &clocks_connect {
+                        clock.send(Command::SetMqttConnected(true));
+                    }
+                    Ok(())
+                })
+                .on_disconnect(move || {
+                    log::warn!("MQTT disconnected");
+                    for clock in &clocks_disconnect {
+                        clock.send(Command::SetMqttConnected(false));
+                    }
+                })
+                .on_message(move |topic: &str, data: &[u8]| {
+                    let Some(topic) = layout.clock_topic(topic) else {
+                        log::warn!("Ignoring message on unexpected topic {}", topic);
+                        return;
+                    };
+                    dispatch_mqtt.dispatch(&topic, data);
+                })
+                .build()?,
+        ),
+    };
+
+    // The web interface and REST API control the clock with or without a broker
+    let web_port = http_port();
+    let _http_server = match web_port {
+        Some(port) => Some(http::start(
+            port,
+            http::WebContext {
+                clock: clock.clone(),
+                dispatch,
+                settings: web_settings,
+                network: network.clone(),
+                token: http_token(),
+            },
+        )?),
+        None => None,
+    };
+
+    // Lets the web interface and other tools find the clock without knowing its IP address
+    let _mdns = if option_env!("MDNS").is_some_and(|v| v == "false") {
+        None
+    } else {
+        let broker = match (&network.mqtt_host, network.mqtt_port) {
+            (Some(host), Some(port)) if !standalone => Some(format!("{}:{}", host, port)),
+            _ => None,
+        };
+        let state_topic = layout.broker_topic(STATE_TOPIC);
+        let mut txt = vec![
+            ("client_id", MQTT_CLIENT_ID),
+            ("version", env!("CARGO_PKG_VERSION")),
+            ("board", board::NAME),
+            ("state", state_topic.as_str()),
+        ];
+        if let Some(broker) = &broker {
+            txt.push(("mqtt", broker.as_str()));
+        }
+        let hostname = clock_pure::mdns_hostname(MQTT_CLIENT_ID);
+        match mdns::start(&hostname, web_port, &txt) {
+            Ok(mdns) => Some(mdns),
+            Err(e) => {
+                log::warn!(
+                    "Failed to start mDNS, the clock is only reachable by IP: {:?}",
+                    e
+                );
+                None
+            }
+        }
+    };
+
+    log::info!("Setup complete");
+    // MQTT callbacks and the web server handle all commands; the main thread
+    // watches the WiFi connection, runs scheduled actions (also while the
+    // broker is down), falls back to the RTC without network time,
+    // reconnects to the broker, publishes self-test reports, firmware update
+    // progress, and telemetry, and keeps the state and the next alarm
+    // published (retained) so Home Assistant can show them
+    let mut published: Option<String> = None;
+    let mut published_state: Option<String> = None;
+    let mut info_published = false;
+    let mut backoff = Backoff::new();
+    let mut reconnect_at: Option<Instant> = None;
+    let telemetry_interval = telemetry_interval();
+    // When the last report was due, and the frames drawn by then
+    let mut last_report: Option<(Instant, u32)> = None;
+    let chime = hourly_chime();
+    // The date and hour of the time last shown; the hour chimes once, in its first minute
+    let mut last_hour = None;
+    let mut alarm_sounding = false;
+    let status_led = !option_env!("STATUS_LED").is_some_and(|v| v == "false");
+    let mut status_color = None;
+    // Firmware fresh from an update is kept once WiFi, MQTT, and the ring work
+    let mut boot_check = match ota::BootCheck::start(ota_health_timeout()) {
+        Ok(check) => check,
+        Err(e) => {
+            log::warn!("Failed to check the firmware's health: {:?}", e);
+            None
+        }
+    };
+    loop {
+        if let Some(mqtt) = &mqtt {
+            for report in reports.try_iter() {
+                if let Err(e) = mqtt.publish(
+                    &selftest_report_topic,
+                    qos(&qos_levels, &selftest_report_topic),
+                    false,
+                    report.as_bytes(),
+                ) {
+                    log::warn!("Failed to publish self-test report: {:?}", e);
                 }
+            }
+            for status in ota_statuses.try_iter() {
+                if let Err(e) = mqtt.publish(
+                    &ota_status_topic,
+                    qos(&qos_levels, &ota_status_topic),
+                    retain,
+                    status.to_json().as_bytes(),
+                ) {
+                    log::warn!("Failed to publish firmware update status: {:?}", e);
+                }
+            }
+        }
+        let wifi_connected = matches!(wifi.get_ip(0), Ok(Some(_)));
+        if let Some(Ok(sntp)) = sntp.as_ref().map(|sntp| sntp.lock()) {
+            let zone = time_zone.lock().map(|zone| *zone).unwrap_or_default();
+            if let Some(time) = sntp.time(&zone) {
+                for clock in &every_clock {
+                    clock.send(Command::SetTime(time));
+                }
+                retained::save(time);
+                if let Some(Ok(mut rtc)) = rtc.as_ref().map(|rtc| rtc.lock()) {
+                    rtc.network_tick(time);
+                }
+            }
+        }
+        if let Some(Ok(mut rtc)) = rtc.as_ref().map(|rtc| rtc.lock()) {
+            if let Some(time) = rtc.fallback() {
+                log::debug!("No network time, using the RTC: {:?}", time.time);
+                for clock in &every_clock {
+                    clock.send(Command::SetTime(time));
+                }
+            }
+        }
+        for clock in &every_clock {
+            clock.send(Command::SetWifiConnected(wifi_connected));
+        }
+        let Some(state) = clock.state() else {
+            log::error!("Render loop stopped");
+            return Ok(());
+        };
+        if let Some(check) = &boot_check {
+            let healthy = wifi_connected && state.status.mqtt_connected && state.frames > 0;
+            match check.update(healthy) {
+                Ok(false) => {}
+                Ok(true) => boot_check = None,
                 Err(e) => {
-                    log::error!("Failed to parse time: {} (raw: {:02x?})", e, data);
+                    log::error!("Failed to finish the firmware health check: {:?}", e);
+                    boot_check = None;
+                }
+            }
+        }
+        // The broker while connected; standalone, there is none
+        let broker = mqtt.as_ref().filter(|_| state.status.mqtt_connected);
+        if state.status.mqtt_connected {
+            backoff.reset();
+            reconnect_at = None;
+        } else if let (Some(mqtt), true) = (&mqtt, wifi_connected) {
+            let now = Instant::now();
+            match reconnect_at {
+                Some(at) if now < at => {}
+                Some(_) => {
+                    log::info!("Reconnecting to MQTT (attempt {})", backoff.attempts());
+                    if let Err(e) = mqtt.reconnect() {
+                        log::debug!("MQTT reconnect not started: {:?}", e);
+                    }
+                    reconnect_at = Some(now + reconnect_delay(&mut backoff));
+                }
+                // The first delay lets a connection in progress finish
+                None => reconnect_at = Some(now + reconnect_delay(&mut backoff)),
+            }
+        }
+        if let Some(interval) = telemetry_interval {
+            let now = Instant::now();
+            match last_report {
+                Some((at, _)) if now.duration_since(at) < interval => {}
+                Some((at, frames)) => {
+                    let elapsed = now.duration_since(at);
+                    let telemetry = telemetry(&state, state.frames.wrapping_sub(frames), elapsed);
+                    if let Some(mqtt) = broker {
+                        if let Err(e) = mqtt.publish(
+                            &telemetry_topic,
+                            qos(&qos_levels, &telemetry_topic),
+                            false,
+                            telemetry.to_json().as_bytes(),
+                        ) {
+                            log::warn!("Failed to publish telemetry: {:?}", e);
+                        }
+                    }
+                    last_report = Some((now, state.frames));
+                }
+                // The first report covers a full interval
+                None => last_report = Some((now, state.frames)),
+            }
+        }
+        match broker {
+            None => info_published = false,
+            Some(_) if info_published => {}
+            Some(mqtt) => {
+                let ip = wifi.get_ip(0).ok().flatten().map(|ip| ip.to_string());
+                let info = device_info(MQTT_CLIENT_ID, ip.as_deref());
+                match mqtt.publish(
+                    &info_topic,
+                    qos(&qos_levels, &info_topic),
+                    retain,
+                    info.as_bytes(),
+                ) {
+                    Ok(_) => {
+                        log::info!("Device info: {}", info);
+                        info_published = true;
+                    }
+                    Err(e) => log::warn!("Failed to publish device info: {:?}", e),
+                }
+            }
+        }
+        let report = state.report().to_json();
+        if let Some(mqtt) = broker.filter(|_| published_state.as_ref() != Some(&report)) {
+            match mqtt.publish(
+                &state_topic,
+                qos(&qos_levels, &state_topic),
+                retain,
+                report.as_bytes(),
+            ) {
+                Ok(_) => published_state = Some(report),
+                Err(e) => log::warn!("Failed to publish state: {:?}", e),
+            }
+        }
+        let next = next_alarm_payload(state.next_alarm);
+        let brightness = if status_led && !state.night {
+            STATUS_LED_BRIGHTNESS
+        } else {
+            0
+        };
+        let (r, g, b) = state.status.led_color(brightness);
+        if status_color != Some((r, g, b)) {
+            match driver.set_pixels_slice(&[RGB8::new(r, g, b)]) {
+                Ok(()) => status_color = Some((r, g, b)),
+                Err(e) => log::warn!("Failed to set the status LED: {:?}", e),
+            }
+        }
+        if let (Some((date, time)), Ok(mut schedule)) = (state.now, schedule.lock()) {
+            let due: Vec<_> = schedule.update(date, time).collect();
+            drop(schedule);
+            for (slot, action) in due {
+                log::info!("Running scheduled action {}: {:?}", slot, action);
+                run_scheduled(action, &clock, &scenes, &state);
+            }
+        }
+        if let (Some(strength), Some((date, time))) = (chime, state.now) {
+            // Not on the first time after booting, which may be at minute 0
+            let hour = (date, time.hour);
+            let on_the_hour = time.minute == 0 && last_hour.is_some_and(|last| last != hour);
+            last_hour = Some(hour);
+            if on_the_hour && !state.quiet {
+                log::info!("Chiming {} o'clock", time.hour);
+                let ripple = Ripple::new(CHIME_COLOR).with_strength(strength);
+                clock.run_effect(Sequence::new().then(ripple, RIPPLE_MS));
+                if let (Some(buzzer), true) = (&buzzer, buzzer_on.load(Ordering::Relaxed)) {
+                    buzzer.play(chime_melody, false);
+                }
+            }
+        }
+        // The alarm melody repeats until the alarm is dismissed or the buzzer muted
+        if let Some(buzzer) = &buzzer {
+            let audible = buzzer_on.load(Ordering::Relaxed) && !state.quiet;
+            let sound = state.alarms.is_ringing() && audible;
+            if sound != alarm_sounding {
+                alarm_sounding = sound;
+                if sound {
+                    buzzer.play(alarm_melody, true);
+                } else {
+                    buzzer.stop();
                 }
             }
-        })
-        .build()?;
+        }
+        if let Some(mqtt) = broker.filter(|_| published.as_ref() != Some(&next)) {
+            match mqtt.publish(
+                &next_alarm_topic,
+                qos(&qos_levels, &next_alarm_topic),
+                retain,
+                next.as_bytes(),
+            ) {
+                Ok(_) => {
+                    log::info!("Next alarm: {}", next);
+                    published = Some(next);
+                }
+                Err(e) => log::warn!("Failed to publish next alarm: {:?}", e),
+            }
+        }
+        std::thread::sleep(NEXT_ALARM_INTERVAL);
+    }
+}
+
+/// Returns how long to wait before the next attempt to reach the broker.
+fn reconnect_delay(backoff: &mut Backoff) -> Duration {
+    // SAFETY: esp_random has no preconditions; it reads the hardware RNG.
+    let random = unsafe { esp_idf_svc::sys::esp_random() };
+    Duration::from_millis(backoff.next_delay_ms(random).into())
+}
+
+/// Runs an action from the schedule.
+///
+/// A scene that no longer exists is skipped; an effect plays like a playlist
+/// and replaces any effect that is playing.
+fn run_scheduled(
+    action: ScheduledAction,
+    clock: &ClockHandle,
+    scenes: &Mutex<Scenes>,
+    state: &ClockState,
+) {
+    match action {
+        ScheduledAction::Scene(name) => {
+            match scenes.lock().ok().and_then(|s| s.get(name.as_str())) {
+                Some(scene) => clock.send(Command::ApplyScene(scene)),
+                None => log::warn!("Scheduled scene {:?} does not exist", name.as_str()),
+            }
+        }
+        ScheduledAction::Brightness(level) => clock.send(Command::SetBrightness(level)),
+        ScheduledAction::Power(on) => clock.send(Command::SetPower(on)),
+        ScheduledAction::Effect { animation, seconds } => {
+            let scene = &state.scene;
+            let hands = scene
+                .colors
+                .to_array()
+                .map(|color| clock_pure::fade_color(color, scene.brightness));
+            let effect = animation.effect(hands, scene.brightness);
+            clock.run_effect(Sequence::new().then(effect, u32::from(seconds) * 1000));
+        }
+    }
+}
+
+/// Formats the next alarm as a local ISO 8601 date and time, or `none`.
+fn next_alarm_payload(next: Option<LocalDateTime>) -> String {
+    match next {
+        Some(LocalDateTime {
+            time,
+            date: Some(date),
+            ..
+        }) => format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:00",
+            date.year, date.month, date.day, time.hour, time.minute
+        ),
+        _ => "none".to_string(),
+    }
+}
+
+/// Describes the firmware and the device as JSON, for inventory and for
+/// targeting updates.
+fn device_info(client_id: &str, ip: Option<&str>) -> String {
+    serde_json::json!({
+        "client_id": client_id,
+        "version": env!("CARGO_PKG_VERSION"),
+        "git_hash": env!("GIT_HASH"),
+        "build_date": env!("BUILD_DATE"),
+        "board": board::NAME,
+        "ip": ip,
+    })
+    .to_string()
+}
+
+/// Takes a snapshot of the clock's health, with the frame rate of `frames`
+/// drawn in `elapsed`.
+fn telemetry(state: &ClockState, frames: u32, elapsed: Duration) -> Telemetry {
+    use esp_idf_svc::sys::{
+        esp, esp_get_free_heap_size, esp_get_minimum_free_heap_size, esp_timer_get_time,
+        esp_wifi_sta_get_ap_info, wifi_ap_record_t,
+    };
+
+    // SAFETY: the functions have no preconditions.
+    let (uptime_us, free_heap, min_free_heap) = unsafe {
+        (
+            esp_timer_get_time(),
+            esp_get_free_heap_size(),
+            esp_get_minimum_free_heap_size(),
+        )
+    };
+    let mut access_point = wifi_ap_record_t::default();
+    // SAFETY: the record outlives the call filling it, which fails without a
+    // WiFi connection.
+    let rssi_dbm = esp!(unsafe { esp_wifi_sta_get_ap_info(&mut access_point) })
+        .ok()
+        .map(|()| access_point.rssi);
+    let elapsed_ms = u32::try_from(elapsed.as_millis()).unwrap_or(u32::MAX);
+    Telemetry {
+        uptime_s: (uptime_us / 1_000_000) as u32,
+        free_heap,
+        min_free_heap,
+        rssi_dbm,
+        frame_rate: Telemetry::frame_rate(frames, elapsed_ms),
+        tick_age_s: state.tick_age_ms.map(|age| age / 1000),
+    }
+}
+
+/// Reads how often telemetry is published from the optional
+/// `TELEMETRY_INTERVAL` setting (seconds) in `.env`.
+///
+/// Returns a minute if the setting is missing or invalid, and `None`,
+/// publishing no telemetry, for 0.
+fn telemetry_interval() -> Option<Duration> {
+    const DEFAULT: Duration = Duration::from_secs(60);
+    let Some(value) = option_env!("TELEMETRY_INTERVAL") else {
+        return Some(DEFAULT);
+    };
+    match value.parse::<u64>() {
+        Ok(0) => None,
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            log::warn!("Invalid TELEMETRY_INTERVAL '{}'", value);
+            Some(DEFAULT)
+        }
+    }
+}
+
+/// Reads how long firmware fresh from an update has to become healthy from
+/// the optional `OTA_HEALTH_TIMEOUT` setting (seconds) in `.env`.
+///
+/// Returns two minutes if the setting is missing or invalid.
+fn ota_health_timeout() -> Duration {
+    const DEFAULT: Duration = Duration::from_secs(120);
+    let Some(value) = option_env!("OTA_HEALTH_TIMEOUT") else {
+        return DEFAULT;
+    };
+    match value.parse::<u64>() {
+        Ok(seconds) if seconds > 0 => Duration::from_secs(seconds),
+        _ => {
+            log::warn!("Invalid OTA_HEALTH_TIMEOUT '{}'", value);
+            DEFAULT
+        }
+    }
+}
+
+/// Reads the key firmware images must be signed with from the
+/// `OTA_PUBLIC_KEY` setting in `.env`, the hex of a raw Ed25519 public key.
+///
+/// Returns `None`, refusing every firmware update, if the setting is missing
+/// or invalid.
+fn ota_public_key() -> Option<FirmwareKey> {
+    let value = option_env!("OTA_PUBLIC_KEY")?;
+    match value.parse() {
+        Ok(key) => Some(key),
+        Err(_) => {
+            log::warn!("Invalid OTA_PUBLIC_KEY '{}'", value);
+            None
+        }
+    }
+}
+
+/// Reads how bright the hourly chime is from the optional `HOURLY_CHIME`
+/// setting in `.env` (1-255).
+///
+/// Returns `None`, leaving the hour unchimed, if the setting is missing or invalid.
+fn hourly_chime() -> Option<u8> {
+    let value = option_env!("HOURLY_CHIME")?;
+    match value.parse::<u8>() {
+        Ok(strength) if strength > 0 => Some(strength),
+        _ => {
+            log::warn!("Invalid HOURLY_CHIME '{}'", value);
+            None
+        }
+    }
+}
+
+/// Reads a GPIO to switch from the optional setting `name` in `.env`, such
+/// as `ALARM_OUTPUT_GPIO` or `RING_POWER_GPIO`.
+///
+/// Returns `None`, leaving the output unused, if the setting is missing or
+/// invalid, or names a pin that is not among the board's free GPIOs.
+fn output_gpio(name: &str, value: Option<&str>) -> Option<i32> {
+    let value = value?;
+    match value.parse::<i32>() {
+        Ok(gpio) if board::FREE_GPIOS.contains(&gpio) => Some(gpio),
+        _ => {
+            log::warn!("Invalid {} '{}'", name, value);
+            None
+        }
+    }
+}
+
+/// Reads how long the ring brightens before an alarm from the optional
+/// `SUNRISE_MINUTES` setting in `.env`.
+///
+/// Returns `None`, disabling the sunrise, if the setting is missing or invalid.
+fn sunrise() -> Option<Duration> {
+    let value = option_env!("SUNRISE_MINUTES")?;
+    match value.parse::<u64>() {
+        Ok(minutes) if (1..=120).contains(&minutes) => Some(Duration::from_secs(minutes * 60)),
+        _ => {
+            log::warn!("Invalid SUNRISE_MINUTES '{}'", value);
+            None
+        }
+    }
+}
+
+/// Reads a buzzer melody from the optional `.env` setting `name`, e.g.
+/// `ALARM_MELODY`, holding `value`.
+///
+/// Falls back to `default` when the setting is missing or invalid.
+fn melody(name: &str, value: Option<&str>, default: Melody) -> Melody {
+    let Some(value) = value else {
+        return default;
+    };
+    value.parse().unwrap_or_else(|e| {
+        log::warn!("Invalid {} '{}': {}", name, value, e);
+        default
+    })
+}
+
+/// Reads how a ringing alarm is shown from the optional `ALARM_STYLE` setting in `.env`.
+///
+/// Falls back to flashing when the setting is missing or unknown.
+fn alarm_style() -> AlarmStyle {
+    let Some(name) = option_env!("ALARM_STYLE") else {
+        return AlarmStyle::default();
+    };
+    name.parse().unwrap_or_else(|e| {
+        log::warn!("Invalid ALARM_STYLE '{}': {}", name, e);
+        AlarmStyle::default()
+    })
+}
+
+/// Reads the time zone for Unix timestamp ticks from `.env`.
+///
+/// `TIMEZONE` takes an IANA name such as `Europe/Berlin` or a POSIX TZ string
+/// and handles daylight saving time. Without it, the fixed `UTC_OFFSET_SECONDS`
+/// applies, defaulting to UTC. The other tick formats are already local. A
+/// zone set on `clock/timezone` is saved and takes precedence.
+fn time_zone() -> TimeZone {
+    if let Some(name) = option_env!("TIMEZONE") {
+        match name.parse() {
+            Ok(zone) => return zone,
+            Err(e) => log::warn!("Invalid TIMEZONE '{}': {}", name, e),
+        }
+    }
+
+    match option_env!("UTC_OFFSET_SECONDS") {
+        None => TimeZone::utc(),
+        Some(value) => value.parse().map(TimeZone::fixed).unwrap_or_else(|_| {
+            log::warn!("Invalid UTC_OFFSET_SECONDS '{}', using UTC", value);
+            TimeZone::utc()
+        }),
+    }
+}
+
+/// Reads the NTP servers from the optional `SNTP_SERVERS` setting in `.env`,
+/// separated by commas, e.g. `pool.ntp.org,time.cloudflare.com`.
+///
+/// Without the setting, a `standalone` clock uses `pool.ntp.org`, as it has
+/// no other time source. Returns `None`, leaving SNTP off, if the setting is
+/// otherwise missing or empty.
+fn sntp_servers(standalone: bool) -> Option<Vec<&'static str>> {
+    let Some(servers) = option_env!("SNTP_SERVERS") else {
+        return standalone.then(|| vec!["pool.ntp.org"]);
+    };
+    let servers: Vec<&'static str> = servers
+        .split(',')
+        .map(str::trim)
+        .filter(|server| !server.is_empty())
+        .collect();
+    (!servers.is_empty()).then_some(servers)
+}
+
+/// Reads whether the clock prefers ticks or SNTP from the optional
+/// `TIME_SOURCE` setting in `.env`.
+///
+/// Falls back to ticks, with SNTP standing in while they stop, if the
+/// setting is missing or unknown.
+fn time_source() -> TimeSource {
+    let Some(name) = option_env!("TIME_SOURCE") else {
+        return TimeSource::default();
+    };
+    name.parse().unwrap_or_else(|e| {
+        log::warn!("Invalid TIME_SOURCE '{}': {}", name, e);
+        TimeSource::default()
+    })
+}
+
+/// Reads the second clock from the optional `SECOND_CLOCK` setting in `.env`.
+///
+/// The setting names the clock, which is addressed under `clock/<name>/`, and
+/// `SECOND_CLOCK_LED_COUNT` optionally sets the size of its face. Returns
+/// `None` if the setting is missing or the name is not a valid topic level.
+fn second_clock() -> Option<(&'static str, ClockFace)> {
+    let name = option_env!("SECOND_CLOCK")?;
+    let valid = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_';
+    if name.is_empty() || !name.chars().all(valid) || NESTED_TOPICS.contains(&name) {
+        log::warn!("Invalid SECOND_CLOCK '{}'", name);
+        return None;
+    }
+    let face = match option_env!("SECOND_CLOCK_LED_COUNT") {
+        None => ClockFace::default(),
+        Some(leds) => leds.parse().unwrap_or_else(|e| {
+            log::warn!("Invalid SECOND_CLOCK_LED_COUNT '{}': {}", leds, e);
+            ClockFace::default()
+        }),
+    };
+    Some((name, face))
+}
+
+/// Reads the size of the clock face ring from the optional `LED_COUNT` setting in `.env`.
+///
+/// Returns `None` to keep the 12-LED face when the setting is missing or invalid.
+fn clock_face() -> Option<ClockFace> {
+    let leds = option_env!("LED_COUNT")?;
+    leds.parse()
+        .map_err(|e| log::warn!("Invalid LED_COUNT '{}': {}", leds, e))
+        .ok()
+}
+
+/// Reads the color channel order of the strip from the optional
+/// `CHANNEL_ORDER` setting in `.env`.
+///
+/// Returns `None` to keep the standard WS2812 order when the setting is missing or invalid.
+fn channel_order() -> Option<ChannelOrder> {
+    let order = option_env!("CHANNEL_ORDER")?;
+    order
+        .parse()
+        .map_err(|e| log::warn!("Invalid CHANNEL_ORDER '{}': {}", order, e))
+        .ok()
+}
+
+/// Reads the LED wiring order from the optional `LED_ORDER` setting in `.env`.
+///
+/// Returns `None` to keep the standard order when the setting is missing or invalid.
+fn wiring_order() -> Option<WiringOrder> {
+    let order = option_env!("LED_ORDER")?;
+    order
+        .parse()
+        .map_err(|e| log::warn!("Invalid LED_ORDER '{}': {}", order, e))
+        .ok()
+}
+
+/// Reads the global brightness of APA102 LEDs from the optional
+/// `APA102_BRIGHTNESS` setting in `.env`.
+///
+/// Falls back to full brightness (31) if the setting is missing or invalid.
+#[cfg(feature = "apa102")]
+fn apa102_brightness() -> u8 {
+    let Some(value) = option_env!("APA102_BRIGHTNESS") else {
+        return APA102_MAX_BRIGHTNESS;
+    };
+    match value.parse::<u8>() {
+        Ok(brightness) if brightness <= APA102_MAX_BRIGHTNESS => brightness,
+        _ => {
+            log::warn!("Invalid APA102_BRIGHTNESS '{}'", value);
+            APA102_MAX_BRIGHTNESS
+        }
+    }
+}
+
+/// Reads how SK6812 RGBW LEDs use their white die from the optional
+/// `WHITE_CHANNEL` setting in `.env`.
+///
+/// Falls back to moving the white part of every color to the white die if
+/// the setting is missing or unknown.
+#[cfg(feature = "sk6812")]
+fn white_policy() -> WhitePolicy {
+    let Some(name) = option_env!("WHITE_CHANNEL") else {
+        return WhitePolicy::default();
+    };
+    name.parse().unwrap_or_else(|e| {
+        log::warn!("Invalid WHITE_CHANNEL '{}': {}", name, e);
+        WhitePolicy::default()
+    })
+}
+
+/// Reads the brightness the clock starts at from the optional `BRIGHTNESS` setting in `.env`.
+///
+/// Returns `None` to keep the default when the setting is missing or invalid.
+fn brightness() -> Option<u8> {
+    let value = option_env!("BRIGHTNESS")?;
+    clock_pure::parse_brightness(value.as_bytes())
+        .map_err(|e| log::warn!("Invalid BRIGHTNESS '{}': {}", value, e))
+        .ok()
+}
+
+/// Reads the maximum current the ring may draw from the optional
+/// `CURRENT_LIMIT_MA` setting in `.env`.
+///
+/// Returns `None`, leaving the current unlimited, if the setting is missing or invalid.
+fn power_budget() -> Option<PowerBudget> {
+    let value = option_env!("CURRENT_LIMIT_MA")?;
+    match value.parse::<u32>() {
+        Ok(max_ma) if max_ma > 0 => Some(PowerBudget::new(max_ma)),
+        _ => {
+            log::warn!("Invalid CURRENT_LIMIT_MA '{}'", value);
+            None
+        }
+    }
+}
+
+/// Reads the ring around the clock face from the optional `OUTER_RING` setting in `.env`.
+///
+/// `true` selects a 60-LED ring showing minutes and seconds; otherwise the
+/// setting is a layout such as `24,hands=hm,start=6`. Returns `None`, leaving
+/// the ring off, when the setting is missing or invalid.
+fn outer_ring() -> Option<RingLayout> {
+    let layout = option_env!("OUTER_RING")?;
+    if layout == "true" {
+        return Some(RingLayout::default());
+    }
+    layout
+        .parse()
+        .map_err(|e| log::warn!("Invalid OUTER_RING '{}': {}", layout, e))
+        .ok()
+}
+
+/// Reads how overlapping hands are drawn from the optional `HAND_OVERLAP` setting in `.env`.
+///
+/// Returns `None` to keep the default when the setting is missing or unknown.
+fn overlap_policy() -> Option<OverlapPolicy> {
+    let name = option_env!("HAND_OVERLAP")?;
+    name.parse()
+        .map_err(|e| log::warn!("Invalid HAND_OVERLAP '{}': {}", name, e))
+        .ok()
+}
+
+/// Reads the hour markers from the optional `HOUR_MARKERS` setting in `.env`.
+///
+/// `HOUR_MARKERS` takes a color (hex or name) and `HOUR_MARKER_LEVEL` optionally
+/// dims it (0-255). Returns `None`, leaving the markers off, if no valid color is set.
+fn hour_markers() -> Option<HourMarkers> {
+    let name = option_env!("HOUR_MARKERS")?;
+    let markers = match clock_pure::parse_color(name) {
+        Ok(color) => HourMarkers::new(color),
+        Err(e) => {
+            log::warn!("Invalid HOUR_MARKERS '{}': {}", name, e);
+            return None;
+        }
+    };
+
+    match option_env!("HOUR_MARKER_LEVEL") {
+        None => Some(markers),
+        Some(value) => match clock_pure::parse_brightness(value.as_bytes()) {
+            Ok(level) => Some(markers.with_level(level)),
+            Err(e) => {
+                log::warn!("Invalid HOUR_MARKER_LEVEL '{}': {}", value, e);
+                Some(markers)
+            }
+        },
+    }
+}
+
+/// Builds the Pomodoro timer from the optional durations in `.env`.
+///
+/// `POMODORO_WORK_MINUTES` and `POMODORO_BREAK_MINUTES` default to 25 and 5.
+fn pomodoro() -> Pomodoro {
+    let default = Pomodoro::new();
+    let minutes = |name: &str, value: Option<&str>, phase: PomodoroPhase| {
+        let default_ms = default.duration_ms(phase);
+        let Some(value) = value else {
+            return default_ms;
+        };
+        match value
+            .parse::<u32>()
+            .ok()
+            .and_then(|m| m.checked_mul(60_000))
+        {
+            Some(ms) if ms > 0 => ms,
+            _ => {
+                log::warn!("Invalid {} '{}', using the default", name, value);
+                default_ms
+            }
+        }
+    };
+
+    default.with_durations(
+        minutes(
+            "POMODORO_WORK_MINUTES",
+            option_env!("POMODORO_WORK_MINUTES"),
+            PomodoroPhase::Work,
+        ),
+        minutes(
+            "POMODORO_BREAK_MINUTES",
+            option_env!("POMODORO_BREAK_MINUTES"),
+            PomodoroPhase::Break,
+        ),
+    )
+}
+
+/// Reads the WiFi credentials from the `WIFI_SSID` and `WIFI_PASS` settings
+/// and the broker from the optional `MQTT_HOST` and `MQTT_PORT` settings in
+/// `.env`.
+fn env_network() -> anyhow::Result<NetworkSettings> {
+    let mqtt_port = option_env!("MQTT_PORT")
+        .map(str::parse)
+        .transpose()
+        .context("MQTT_PORT must be a valid port number (0-65535)")?;
+    Ok(NetworkSettings {
+        mqtt_host: option_env!("MQTT_HOST").map(Into::into),
+        mqtt_port,
+        ..NetworkSettings::new(env!("WIFI_SSID"), env!("WIFI_PASS"))
+    })
+}
+
+/// Reads how to secure the broker connection from the optional `MQTT_TLS`
+/// and `MQTT_CA_CERT` settings in `.env`.
+///
+/// Returns `None` for a plain connection, otherwise the CA certificate to
+/// check the broker's against: the one embedded from `MQTT_CA_CERT`, or
+/// `None` for ESP-IDF's bundle of public CAs.
+fn mqtt_tls() -> Option<Option<X509<'static>>> {
+    if !MQTT_CA_CERT.is_empty() {
+        log::info!("MQTT over TLS with the CA certificate from MQTT_CA_CERT");
+        return Some(Some(X509::pem_until_nul(MQTT_CA_CERT)));
+    }
+    if option_env!("MQTT_TLS").is_some_and(|v| v == "true") {
+        log::info!("MQTT over TLS with the public CA bundle");
+        return Some(None);
+    }
+    None
+}
+
+/// Reads where the clock's topics are on the broker from the optional
+/// `MQTT_TOPIC_PREFIX` setting in `.env`.
+///
+/// Without the setting, or with an invalid one, the topics are shared with
+/// other clocks on the broker.
+fn topic_layout(client_id: &'static str) -> TopicLayout<'static> {
+    let prefix = option_env!("MQTT_TOPIC_PREFIX").filter(|value| {
+        let valid = !value.is_empty()
+            && !value.starts_with('/')
+            && !value.ends_with('/')
+            && !value.contains(['+', '#']);
+        if !valid {
+            log::warn!("Invalid MQTT_TOPIC_PREFIX '{}'", value);
+        }
+        valid
+    });
+    if let Some(prefix) = prefix {
+        log::info!("Topics under {}/{}/", prefix, client_id);
+    }
+    TopicLayout::new(prefix, client_id)
+}
+
+/// Reads the MQTT QoS levels from the optional `MQTT_QOS` setting in `.env`.
+///
+/// Returns the default levels if the setting is missing or invalid.
+fn mqtt_qos() -> QosLevels<'static> {
+    let Some(value) = option_env!("MQTT_QOS") else {
+        return QosLevels::default();
+    };
+    QosLevels::parse(value)
+        .inspect_err(|e| log::warn!("Invalid MQTT_QOS '{}': {}", value, e))
+        .unwrap_or_default()
+}
+
+/// Returns the QoS of `topic` from its level in `levels`.
+fn qos(levels: &QosLevels, topic: &str) -> QoS {
+    match levels.level(topic) {
+        0 => QoS::AtMostOnce,
+        1 => QoS::AtLeastOnce,
+        _ => QoS::ExactlyOnce,
+    }
+}
+
+/// Reads the path of the broker's WebSocket endpoint from the optional
+/// `MQTT_WEBSOCKET_PATH` setting in `.env`, e.g. `/mqtt`.
+///
+/// Returns `None`, connecting over plain MQTT, if the setting is missing or
+/// does not start with a slash.
+fn mqtt_websocket_path() -> Option<&'static str> {
+    let value = option_env!("MQTT_WEBSOCKET_PATH")?;
+    if !value.starts_with('/') {
+        log::warn!("Invalid MQTT_WEBSOCKET_PATH '{}'", value);
+        return None;
+    }
+    log::info!("MQTT over WebSocket at {}", value);
+    Some(value)
+}
+
+/// Reads the port of the web server from the optional `HTTP_PORT` setting
+/// in `.env`.
+///
+/// Returns port 80 if the setting is missing or invalid, and `None`, running
+/// no web server, for 0.
+fn http_port() -> Option<u16> {
+    const DEFAULT: u16 = 80;
+    let Some(value) = option_env!("HTTP_PORT") else {
+        return Some(DEFAULT);
+    };
+    match value.parse::<u16>() {
+        Ok(0) => None,
+        Ok(port) => Some(port),
+        Err(_) => {
+            log::warn!("Invalid HTTP_PORT '{}'", value);
+            Some(DEFAULT)
+        }
+    }
+}
+
+/// Reads the token the web interface and REST API need to change the clock
+/// from the optional `HTTP_TOKEN` setting in `.env`.
+///
+/// Returns `None`, leaving the web server read-only, if the setting is missing
+/// or empty.
+fn http_token() -> Option<&'static str> {
+    let token = option_env!("HTTP_TOKEN").filter(|token| !token.is_empty());
+    if token.is_none() {
+        log::warn!("No HTTP_TOKEN, the web interface is read-only");
+    }
+    token
+}
+
+/// Reads the temperature gauge range from the optional `TEMPERATURE_MIN`
+/// and `TEMPERATURE_MAX` settings (°C) in `.env`.
+///
+/// A missing bound keeps its default. Returns `None` if neither is set or
+/// the range is invalid.
+fn temperature_gauge() -> Option<TemperatureGauge> {
+    let (min, max) = (
+        option_env!("TEMPERATURE_MIN"),
+        option_env!("TEMPERATURE_MAX"),
+    );
+    if min.is_none() && max.is_none() {
+        return None;
+    }
+
+    let default = TemperatureGauge::default();
+    let bound = |name: &str, value: Option<&str>, default: i8| match value {
+        None => Some(default),
+        Some(value) => value
+            .parse()
+            .map_err(|_| log::warn!("Invalid {} '{}'", name, value))
+            .ok(),
+    };
+    let min = bound("TEMPERATURE_MIN", min, default.min)?;
+    let max = bound("TEMPERATURE_MAX", max, default.max)?;
+    if min >= max {
+        log::warn!("TEMPERATURE_MIN must be below TEMPERATURE_MAX");
+        return None;
+    }
+    Some(TemperatureGauge::new(min, max))
+}
 
-    log::info!("Setup complete, parking main thread");
-    // Park the main thread indefinitely - MQTT callbacks handle all work
-    std::thread::park();
+/// Reads the holiday themes from the optional `HOLIDAY_THEMES` setting in `.env`.
+///
+/// Returns `None`, keeping the built-in themes, if the setting is missing or invalid.
+fn theme_calendar() -> Option<ThemeCalendar> {
+    let value = option_env!("HOLIDAY_THEMES")?;
+    value
+        .parse()
+        .inspect_err(|e| log::warn!("Invalid HOLIDAY_THEMES '{}': {}", value, e))
+        .ok()
+}
 
-    Ok(())
+/// Reads how long the clock waits without activity before the screensaver
+/// starts from the optional `SCREENSAVER_MINUTES` setting in `.env`.
+///
+/// Returns `None`, keeping the clock face on, if the setting is missing or invalid.
+fn screensaver() -> Option<Duration> {
+    let value = option_env!("SCREENSAVER_MINUTES")?;
+    match value.parse::<u64>() {
+        Ok(minutes) if minutes > 0 => Some(Duration::from_secs(minutes * 60)),
+        _ => {
+            log::warn!("Invalid SCREENSAVER_MINUTES '{}'", value);
+            None
+        }
+    }
+}
+
+/// Reads how often the weather is shown by itself from the optional
+/// `WEATHER_INTERVAL_MINUTES` setting in `.env`.
+///
+/// Returns `None`, showing the weather only on request, if the setting is missing or invalid.
+fn weather_interval() -> Option<Duration> {
+    let value = option_env!("WEATHER_INTERVAL_MINUTES")?;
+    match value.parse::<u64>() {
+        Ok(minutes) if minutes > 0 => Some(Duration::from_secs(minutes * 60)),
+        _ => {
+            log::warn!("Invalid WEATHER_INTERVAL_MINUTES '{}'", value);
+            None
+        }
+    }
+}
+
+/// Reads the night mode schedule from `.env`, starting from `default`.
+///
+/// `NIGHT_MODE` takes a window such as `22:00-07:00` and switches night mode
+/// on automatically; `NIGHT_BRIGHTNESS` optionally sets the dimmed level.
+/// Returns `None` if no schedule is configured.
+fn night_mode(default: NightMode) -> Option<NightMode> {
+    let window = option_env!("NIGHT_MODE")?;
+    let window = match window.parse() {
+        Ok(window) => window,
+        Err(e) => {
+            log::warn!("Invalid NIGHT_MODE '{}': {}", window, e);
+            return None;
+        }
+    };
+
+    let brightness = match option_env!("NIGHT_BRIGHTNESS") {
+        None => default.brightness,
+        Some(value) => clock_pure::parse_brightness(value.as_bytes()).unwrap_or_else(|e| {
+            log::warn!("Invalid NIGHT_BRIGHTNESS '{}': {}", value, e);
+            default.brightness
+        }),
+    };
+    Some(NightMode::new(window, brightness))
+}
+
+/// Chooses the startup animation: the one last set over MQTT, if any, or
+/// else the optional `STARTUP_ANIMATION` setting in `.env`.
+///
+/// Picks one at random when neither is set or the setting is unknown.
+fn startup_choice(stored: Option<StartupChoice>) -> StartupChoice {
+    if let Some(choice) = stored {
+        log::info!("Restoring startup animation: {}", choice.name());
+        return choice;
+    }
+    match option_env!("STARTUP_ANIMATION") {
+        None => StartupChoice::Random,
+        Some(name) => name.parse().unwrap_or_else(|_| {
+            log::warn!(
+                "Unknown STARTUP_ANIMATION '{}', picking one at random",
+                name
+            );
+            StartupChoice::Random
+        }),
+    }
 }
@@ -0,0 +1,21 @@
+use anyhow::Result;
+use esp_idf_svc::mdns::EspMdns;
+
+/// Advertises the clock as `<hostname>.local` over mDNS.
+///
+/// The web interface is announced as an `_http._tcp` service on `http_port`
+/// if the web server runs, and the clock itself as an `_rgbclock._tcp`
+/// service, on the same port or 0, with the `txt` records.
+///
+/// The advertisement ends when the returned handle is dropped.
+pub fn start(hostname: &str, http_port: Option<u16>, txt: &[(&str, &str)]) -> Result<EspMdns> {
+    let mut mdns = EspMdns::take()?;
+    mdns.set_hostname(hostname)?;
+    mdns.set_instance_name(hostname)?;
+    if let Some(port) = http_port {
+        mdns.add_service(None, "_http", "_tcp", port, &[("path", "/")])?;
+    }
+    mdns.add_service(None, "_rgbclock", "_tcp", http_port.unwrap_or(0), txt)?;
+    log::info!("Advertising {}.local over mDNS", hostname);
+    Ok(mdns)
+}
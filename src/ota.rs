@@ -0,0 +1,173 @@
+use anyhow::{bail, Result};
+use clock_net::{FirmwareKey, ImageVerifier, OtaRequest, OtaStatus};
+use esp_idf_svc::http::client::{Configuration, EspHttpConnection};
+use esp_idf_svc::http::Method;
+use esp_idf_svc::io::{Read, Write};
+use esp_idf_svc::ota::{EspOta, EspOtaUpdate, SlotState};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
+
+// Progress is reported whenever this many more bytes were written
+const REPORT_BYTES: u32 = 64 * 1024;
+
+// Time for the last status to reach the broker before the reboot
+const REBOOT_DELAY: Duration = Duration::from_secs(2);
+
+// HTTPS needs more stack than the default thread has
+const STACK_SIZE: usize = 16 * 1024;
+
+static RUNNING: AtomicBool = AtomicBool::new(false);
+
+/// Starts updating the firmware from `request` on a thread of its own,
+/// sending its progress to `statuses`.
+///
+/// The image goes to the inactive OTA partition. Once it is complete and
+/// ESP-IDF verified it, the clock reboots into it; on any error it keeps
+/// running the current firmware. Only one update runs at a time.
+///
+/// The request carries the image's signature, which must be made with the
+/// private half of `key`; an image that does not match it is never booted.
+pub fn start(request: OtaRequest, key: FirmwareKey, statuses: Sender<OtaStatus>) -> Result<()> {
+    if RUNNING.swap(true, Ordering::SeqCst) {
+        bail!("A firmware update is already running");
+    }
+    let spawned = std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            let report = |status: OtaStatus| {
+                if statuses.send(status).is_err() {
+                    log::warn!("Failed to queue the firmware update status");
+                }
+            };
+            match update(&request, key, report) {
+                Ok(()) => {
+                    log::info!("Firmware update complete, rebooting");
+                    report(OtaStatus::Rebooting);
+                    std::thread::sleep(REBOOT_DELAY);
+                    esp_idf_svc::hal::reset::restart();
+                }
+                Err(e) => {
+                    log::error!("Firmware update failed: {:?}", e);
+                    report(OtaStatus::Failed(e.to_string()));
+                    RUNNING.store(false, Ordering::SeqCst);
+                }
+            }
+        });
+    if let Err(e) = spawned {
+        RUNNING.store(false, Ordering::SeqCst);
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// Waits for firmware booted for the first time after an update to prove
+/// itself, rolling back to the previous firmware otherwise.
+///
+/// The bootloader rolls back by itself if the new firmware resets before it
+/// was marked valid, e.g. after a crash.
+pub struct BootCheck {
+    deadline: Instant,
+}
+
+impl BootCheck {
+    /// Starts the check if the running firmware still awaits verification,
+    /// giving it `timeout` to become healthy.
+    pub fn start(timeout: Duration) -> Result<Option<Self>> {
+        let slot = EspOta::new()?.get_running_slot()?;
+        if slot.state != SlotState::Unverified {
+            return Ok(None);
+        }
+        log::info!("New firmware, checking its health for {:?}", timeout);
+        Ok(Some(Self {
+            deadline: Instant::now() + timeout,
+        }))
+    }
+
+    /// Marks the firmware valid once it is `healthy`, or rolls back and
+    /// reboots when the timeout passed. Returns true when the check is done.
+    pub fn update(&self, healthy: bool) -> Result<bool> {
+        if healthy {
+            EspOta::new()?.mark_running_slot_valid()?;
+            log::info!("New firmware is healthy, keeping it");
+            return Ok(true);
+        }
+        if Instant::now() < self.deadline {
+            return Ok(false);
+        }
+        log::error!("New firmware did not become healthy in time, rolling back");
+        Err(EspOta::new()?.mark_running_slot_invalid_and_reboot().into())
+    }
+}
+
+/// Downloads the image to the inactive OTA partition and marks it to boot next.
+fn update(request: &OtaRequest, key: FirmwareKey, report: impl Fn(OtaStatus)) -> Result<()> {
+    let mut verifier = key.verifier(&request.signature)?;
+    let mut connection = EspHttpConnection::new(&Configuration {
+        crt_bundle_attach: Some(esp_idf_svc::sys::esp_crt_bundle_attach),
+        ..Default::default()
+    })?;
+    connection.initiate_request(Method::Get, &request.url, &[])?;
+    connection.initiate_response()?;
+    let status = connection.status();
+    if status != 200 {
+        bail!("HTTP status {}", status);
+    }
+    let total: Option<u32> = connection
+        .header("Content-Length")
+        .and_then(|length| length.parse().ok());
+    log::info!("Downloading {:?} bytes from {}", total, request.url);
+
+    let mut ota = EspOta::new()?;
+    let mut update = ota.initiate_update()?;
+    match download(&mut connection, &mut update, &mut verifier, total, &report) {
+        Ok(()) => {
+            report(OtaStatus::Verifying);
+            // Checks the image's checksum and digest before switching to it
+            update.complete()?;
+            Ok(())
+        }
+        Err(e) => {
+            if let Err(abort) = update.abort() {
+                log::warn!("Failed to abort the firmware update: {:?}", abort);
+            }
+            Err(e)
+        }
+    }
+}
+
+/// Writes the response body to `update`, checking it with `verifier` and
+/// reporting the progress.
+fn download(
+    connection: &mut EspHttpConnection,
+    update: &mut EspOtaUpdate,
+    verifier: &mut ImageVerifier,
+    total: Option<u32>,
+    report: &impl Fn(OtaStatus),
+) -> Result<()> {
+    let mut buffer = [0; 4096];
+    let mut received: u32 = 0;
+    let mut reported = 0;
+    report(OtaStatus::Downloading { received, total });
+    loop {
+        let len = connection.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        update.write_all(&buffer[..len])?;
+        verifier.update(&buffer[..len]);
+        received = received.saturating_add(len as u32);
+        if received - reported >= REPORT_BYTES {
+            reported = received;
+            report(OtaStatus::Downloading { received, total });
+        }
+    }
+    if total.is_some_and(|total| received != total) {
+        bail!("Incomplete image: {} of {:?} bytes", received, total);
+    }
+    if !verifier.verify() {
+        bail!("Invalid signature");
+    }
+    report(OtaStatus::Downloading { received, total });
+    Ok(())
+}
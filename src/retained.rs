@@ -0,0 +1,40 @@
+use clock_pure::{LocalDateTime, RetainedTime};
+use std::ptr::{addr_of, addr_of_mut};
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Kept across soft resets and deep sleep, not cleared on boot; holds random
+// bits after power-on, which the checksum rejects
+#[link_section = ".rtc_noinit"]
+static mut RETAINED: RetainedTime = RetainedTime::EMPTY;
+
+// Times are saved by the MQTT and web server threads and by the main loop
+static SAVING: Mutex<()> = Mutex::new(());
+
+/// Saves `time` in RTC memory, so it is shown right away after a soft reset
+/// or deep sleep.
+pub fn save(time: LocalDateTime) {
+    let _saving = SAVING.lock().unwrap_or_else(PoisonError::into_inner);
+    // SAFETY: the static is only written here, one thread at a time, and
+    // read before any of those threads start
+    unsafe { addr_of_mut!(RETAINED).write_volatile(RetainedTime::new(time, now_us())) }
+}
+
+/// Returns the time saved before the last soft reset or deep sleep, advanced
+/// to now, or `None` after power loss.
+pub fn restore() -> Option<LocalDateTime> {
+    // SAFETY: any bit pattern is a valid `RetainedTime`, and nothing writes
+    // the static yet
+    let retained = unsafe { addr_of!(RETAINED).read_volatile() };
+    retained.restore(now_us())
+}
+
+/// Returns the system time in microseconds.
+///
+/// Nothing sets the system time, so it counts from power-on; ESP-IDF keeps
+/// it in the RTC timer across soft resets and deep sleep.
+fn now_us() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_micros() as u64)
+}
@@ -1,207 +1,1511 @@
+use crate::command::Command;
+use crate::led_driver::LedDriver;
+use crate::switch::Switch;
 use anyhow::Result;
-use clock_pure::{add_colors, hour_to_index, minute_to_index, scale_color, second_to_index, Rgb};
-use ferriswheel::{Direction, RainbowEffect};
+use clock_pure::animation::{AnimationClock, AnimationConfig, MonotonicClock, Ticker};
+use clock_pure::effects::{
+    Effect, Envelope, Keyframes, Sequence, StartupAnimation, StartupEffect, SUNRISE,
+};
+use clock_pure::lut::gamma_correct_lit;
+use clock_pure::{
+    fade_color, hour_to_index, minute_to_index, render_hands, render_progress, render_sweep,
+    second_to_index, Alarms, ButtonPress, Calibration, ChannelOrder, ClockFace, Date, DeadPixels,
+    DisplayMode, DoNotDisturb, FaceFrame, Frame, HandColors, HourMarkers, LocalDateTime, NightMode,
+    NightSwitch, Notification, OuterFrame, OverlapPolicy, Pomodoro, PomodoroCommand, PomodoroPhase,
+    PowerBudget, PowerGate, Ramp, RingLayout, Scene, Screensaver, SecondHand, Status, Stopwatch,
+    StopwatchCommand, TemperatureGauge, ThemeCalendar, TimeKeeper, TimeWindow, Transition, Weather,
+    WiringOrder, LED_COUNT, MAX_FACE_LEDS, OUTER_LED_COUNT, WEATHER_CYCLE_MS,
+};
 use log::debug;
 use rgb::RGB8;
-use rustyfarian_esp_idf_ws2812::WS2812RMT;
-use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex};
-
-// Default colors for clock hands
-const DEFAULT_HOUR_COLOR: Rgb = (0, 0, 1); // Blue
-const DEFAULT_MINUTE_COLOR: Rgb = (0, 1, 0); // Green
-const DEFAULT_SECOND_COLOR: Rgb = (1, 0, 0); // Red
+use std::sync::mpsc::{Receiver, Sender};
+use std::time::{Duration, Instant};
+
+pub use clock_pure::LocalTime;
+
+// Default level the hand colors are dimmed to (out of 255)
 const DEFAULT_BRIGHTNESS: u8 = 10;
 
-// Rainbow animation settings
-const RAINBOW_SPEED: u8 = 3;
-const RAINBOW_BRIGHTNESS: u8 = 30;
-const RAINBOW_FRAME_DELAY_MS: u32 = 30;
+// Night mode defaults, off until configured or switched on over MQTT
+const DEFAULT_NIGHT_WINDOW: TimeWindow = TimeWindow::new(
+    LocalTime {
+        hour: 22,
+        minute: 0,
+        second: 0,
+    },
+    LocalTime {
+        hour: 7,
+        minute: 0,
+        second: 0,
+    },
+);
+const DEFAULT_NIGHT_BRIGHTNESS: u8 = 2;
+
+// Do-not-disturb caps the brightness to this level, off until configured
+const DEFAULT_DND_BRIGHTNESS: u8 = 4;
+
+// Brightness of the screensaver glow, just enough to find the clock in the dark
+const SCREENSAVER_BRIGHTNESS: u8 = 2;
+
+// Default animation frame interval, adjustable over MQTT
+const FRAME_INTERVAL_MS: u32 = 30;
+
+// The smooth second hand sweeps around the ring once per minute
+const SWEEP_PERIOD_MS: u32 = 60_000;
+
+// Stale time indication: without a tick for this long, the second hand blinks
+const STALE_AFTER: Duration = Duration::from_secs(90);
+const STALE_BLINK_INTERVAL_MS: u32 = 500;
+
+// Demo mode: how long each built-in effect plays before the next one
+const DEMO_EFFECT_MS: u32 = 10_000;
+
+// Startup animation settings
+const STARTUP_BRIGHTNESS: u8 = 30;
+const STARTUP_FADE_IN_MS: u32 = 300;
+const STARTUP_FADE_OUT_MS: u32 = 600;
+const STARTUP_HANDOFF_MS: u32 = 800;
+
+// How long the hour and minute hands take to fade over to their next LED
+const HAND_MOVE_MS: u32 = 600;
+
+// Brightness changes ease in over half a second instead of jumping
+const BRIGHTNESS_RAMP_MS: u32 = 500;
+
+// How long the LEDs take to start after the ring is switched on
+const RING_POWER_UP: Duration = Duration::from_millis(5);
 
 /// An RGB LED clock that represents time using 12 RGB LEDs arranged in a circle.
 /// Each LED corresponds to an hour position on a traditional clock face.
+///
+/// Rendering is double-buffered: the clock face and effects are drawn into a
+/// back buffer, and `show()` presents the finished frame in one driver write,
+/// so a half-drawn frame never reaches the LEDs. The clock is owned by the
+/// render loop (see [`run_render_loop`]), which applies commands to it and
+/// draws it with `refresh()` on every frame; setters only change its state.
 pub struct RGBClock<'a> {
-    hours_base_color: Rgb,
-    minutes_base_color: Rgb,
-    seconds_base_color: Rgb,
+    colors: HandColors,
+    /// How hands on the same LED are combined.
+    overlap: OverlapPolicy,
+    /// Faint orientation marks beneath the hands, if enabled.
+    markers: Option<HourMarkers>,
+    /// Sweep the second hand continuously instead of stepping every five seconds.
+    smooth_seconds: bool,
     brightness: u8,
-    driver: WS2812RMT<'a>,
-    state: [Rgb; 12],
+    /// Hand colors overriding `colors` on holidays.
+    themes: ThemeCalendar,
+    /// Dims `brightness` during the night.
+    night: NightMode,
+    /// Keeps the ring quiet and dim during a daily window.
+    dnd: DoNotDisturb,
+    /// What the ring shows when no effect is playing.
+    mode: DisplayMode,
+    /// Uptime at which `mode` was entered.
+    mode_started_ms: u32,
+    /// Shown in `DisplayMode::Pomodoro`; keeps running in other modes.
+    pomodoro: Pomodoro,
+    /// Shown in `DisplayMode::Stopwatch`; keeps running in other modes.
+    stopwatch: Stopwatch,
+    /// Rings over any display mode until dismissed.
+    alarms: Alarms,
+    /// Asserted while an alarm rings, if there is one.
+    alarm_output: Option<Switch<'a>>,
+    /// How long the ring brightens before an alarm, if at all.
+    sunrise_ms: Option<u32>,
+    /// A notification drawn over the ring and when it started.
+    notification: Option<(Notification, u32)>,
+    /// The latest temperature reading (°C) and its gauge, shown in `DisplayMode::Temperature`.
+    temperature: Option<f32>,
+    gauge: TemperatureGauge,
+    /// The latest weather report, shown in `DisplayMode::Weather`.
+    weather: Option<Weather>,
+    /// How often the clock shows the weather for one cycle by itself, if at all.
+    weather_interval_ms: Option<u32>,
+    /// Uptime at which the weather was last shown by itself.
+    weather_shown_ms: u32,
+    /// Set while the weather is shown by itself; it then returns to the clock.
+    weather_brief: bool,
+    /// Replaces the display mode with a dim glow after a while without activity, if enabled.
+    screensaver: Option<Screensaver>,
+    /// Connection and time problems, shown over the display mode.
+    status: Status,
+    /// Playback speed and frame rate of effects.
+    animation: AnimationConfig,
+    /// Eases the brightness shown towards the brightness for the current time.
+    brightness_ramp: Ramp,
+    /// While cleared the LEDs stay dark, but everything keeps running behind them.
+    powered: bool,
+    /// Whether colors are gamma corrected on output.
+    gamma: bool,
+    /// Evens out LEDs that look brighter than the rest, applied on output.
+    calibration: Calibration,
+    /// Failed LEDs whose content moves elsewhere, applied on output.
+    dead_pixels: DeadPixels,
+    /// Strip index of every position on the face.
+    wiring: WiringOrder,
+    /// Maximum current the ring may draw, applied on output.
+    power_budget: Option<PowerBudget>,
+    /// Whether the last frame shown was dimmed to stay within the budget.
+    current_limited: bool,
+    /// Switches the ring's power, if it is behind a switch.
+    ring_power: Option<Switch<'a>>,
+    /// Decides when the dark ring is switched off.
+    power_gate: PowerGate,
+    /// The order the strip takes its color channels in.
+    channel_order: ChannelOrder,
+    driver: Box<dyn LedDriver + 'a>,
+    /// The frame being drawn, already scaled to its final output colors.
+    back: Frame,
+    /// The frame currently on the LEDs before dead-pixel remapping, gamma
+    /// correction, calibration, and current limiting, `None` until the first
+    /// `show()`.
+    front: Option<Frame>,
+    /// The size of the clock face ring.
+    face: ClockFace,
+    /// The hands at the resolution of a face of more than 12 LEDs, shown in
+    /// place of the frame when nothing is drawn over it.
+    face_back: Option<FaceFrame>,
+    /// The face currently on the LEDs if it has more than 12 LEDs, `None`
+    /// until the first `show()`.
+    face_front: Option<FaceFrame>,
+    /// The ring around the clock face, if there is one.
+    outer_ring: Option<RingLayout>,
+    /// Drives the outer ring on its own data line; without one it is chained
+    /// after the clock face.
+    outer_driver: Option<Box<dyn LedDriver + 'a>>,
+    /// The outer ring being drawn; it stays dark unless the clock face draws it.
+    outer_back: OuterFrame,
+    /// The outer ring currently on the LEDs, `None` until the first `show()`.
+    outer_front: Option<OuterFrame>,
+    /// Keeps time running locally between ticks.
+    timekeeper: TimeKeeper,
+    /// Start of the millisecond uptime counter used to age ticks.
+    started: Instant,
+    /// Shown in place of the display mode while it plays.
+    effect: Option<PlayingEffect>,
+    /// Cross-fade from the previous picture, blended over each new frame until it finishes.
+    transition: Option<Transition>,
+    /// Cleared once the startup animation should give way, even without a time.
+    starting_up: bool,
+    /// LEDs of the hour and minute hands last drawn, to fade them when they move.
+    hand_leds: Option<(usize, usize)>,
+    /// Frames the render loop drew since boot, wrapping, for the frame rate.
+    frames: u32,
 }
 
-// The RGBClock is built from 12 RGB LEDs, one for each hour.
-// The LEDs are ordered in a circle, with the first LED at 1 o'clock.
+// The RGBClock is built from 12 RGB LEDs, one for each hour, unless its face is larger.
+// The LEDs are ordered in a circle, with the first LED one step past 12 o'clock.
 impl<'a> RGBClock<'a> {
-    /// Creates a new RGB clock with default color settings.
-    ///
-    /// # Default colors
-    /// - Hours: Blue (0, 0, 1)
-    /// - Minutes: Green (0, 1, 0)
-    /// - Seconds: Red (1, 0, 0)
-    pub fn new(driver: WS2812RMT<'a>) -> Result<Self> {
-        let clock = Self {
-            hours_base_color: DEFAULT_HOUR_COLOR,
-            minutes_base_color: DEFAULT_MINUTE_COLOR,
-            seconds_base_color: DEFAULT_SECOND_COLOR,
+    /// Starts building a clock that draws with `driver`.
+    ///
+    /// `driver` is any smart-leds strip driver, e.g. the ESP-IDF RMT driver
+    /// wrapped in [`RmtDriver`](crate::led_driver::RmtDriver). Settings not
+    /// given to the builder start from their defaults.
+    pub fn builder(driver: impl LedDriver + 'a) -> RGBClockBuilder<'a> {
+        RGBClockBuilder {
+            driver: Box::new(driver),
+            face: ClockFace::default(),
+            colors: HandColors::default(),
+            themes: ThemeCalendar::holidays(),
             brightness: DEFAULT_BRIGHTNESS,
-            driver,
-            state: [(0, 0, 0); 12],
-        };
-
-        Ok(clock)
+            power_budget: None,
+            channel_order: ChannelOrder::default(),
+        }
     }
 
     /// Sets the complete time on the clock (hours, minutes, and seconds).
     ///
+    /// The clock keeps running from this time until the next update, which is
+    /// blended in gradually if it differs only slightly. While an effect is
+    /// playing the time is only recorded and shown once the effect ends.
+    ///
     /// # Arguments
-    /// * `time` - A `LocalTime` struct containing hour, minute, and second values
-    pub fn set_local_time(&mut self, time: LocalTime) -> Result<()> {
-        self.clear()?;
+    /// * `time` - A `LocalTime`, or a `LocalDateTime` if the tick carried a date or milliseconds
+    pub fn set_local_time(&mut self, time: impl Into<LocalDateTime>) {
+        self.timekeeper.update(time, self.uptime_ms());
+    }
+
+    /// Sets the brightness level (0-255) of the clock hands.
+    ///
+    /// 0 turns the clock face off; the default is 10. The ring eases over to
+    /// the new level, as it does when night mode or do-not-disturb dims it.
+    pub fn set_brightness(&mut self, brightness: u8) {
+        self.brightness = brightness;
+    }
 
-        let hour_idx = hour_to_index(time.hour);
-        let minute_idx = minute_to_index(time.minute);
-        let second_idx = second_to_index(time.second);
+    /// Sets the hand colors.
+    pub fn set_hand_colors(&mut self, colors: HandColors) {
+        self.colors = colors;
+    }
+
+    /// Sets how hands pointing at the same LED are combined.
+    ///
+    /// The default adds their colors.
+    pub fn set_overlap_policy(&mut self, overlap: OverlapPolicy) {
+        self.overlap = overlap;
+    }
 
-        // Set state of hour LED
-        self.state[hour_idx] = self.hours_base_color;
+    /// Shows or hides the hour markers at 12, 3, 6, and 9 o'clock.
+    pub fn set_hour_markers(&mut self, markers: Option<HourMarkers>) {
+        self.markers = markers;
+    }
 
-        // Add minute LED (may overlap with hour)
-        self.state[minute_idx] = add_colors(self.state[minute_idx], self.minutes_base_color);
+    /// Switches between a continuously sweeping and a stepping second hand.
+    ///
+    /// The sweep moves with every refresh of the clock face, using the
+    /// milliseconds kept by the time keeper.
+    pub fn set_smooth_seconds(&mut self, smooth: bool) {
+        self.smooth_seconds = smooth;
+    }
 
-        // Add LED for the seconds (may overlap with hour or minute)
-        self.state[second_idx] = add_colors(self.state[second_idx], self.seconds_base_color);
+    /// Adds a ring around the clock face, or removes it with `None`.
+    ///
+    /// The outer ring shows the hands its layout lists, stepping or sweeping
+    /// the second hand like the clock face would; if it shows seconds, the
+    /// clock face leaves them out. Everything else, from effects to alarms,
+    /// keeps to the clock face and leaves the outer ring dark.
+    pub fn set_outer_ring(&mut self, layout: Option<RingLayout>) {
+        self.outer_ring = layout;
+        self.outer_front = None;
+    }
 
-        self.show()
+    /// Drives the outer ring on its own data line instead of chaining it
+    /// after the clock face.
+    pub fn set_outer_driver(&mut self, driver: impl LedDriver + 'a) {
+        self.outer_driver = Some(Box::new(driver));
+        self.outer_front = None;
     }
 
-    /// Clears all LEDs by setting them to black (off).
-    pub fn clear(&mut self) -> Result<()> {
-        self.state = [(0, 0, 0); 12];
-        Ok(())
+    /// Returns the night mode schedule and switch.
+    pub fn night_mode(&self) -> NightMode {
+        self.night
     }
 
-    /// Sets all pixels directly from an RGB8 buffer.
+    /// Sets the night mode.
     ///
-    /// This bypasses the internal state and writes directly to the LEDs.
-    /// Useful for effects like `RainbowEffect` that produce RGB8 buffers.
-    pub fn set_pixels(&mut self, pixels: &[RGB8; 12]) -> Result<()> {
-        self.driver.set_pixels_slice(pixels.as_slice())?;
-        Ok(())
+    /// By default night mode is switched off, with a 22:00-07:00 window
+    /// dimming to brightness 2 once it is switched on.
+    pub fn set_night_mode(&mut self, night: NightMode) {
+        self.night = night;
     }
 
-    /// Updates the physical LEDs with the current state.
-    pub fn show(&mut self) -> Result<()> {
-        let pixels: [RGB8; 12] = self.state.map(|(r, g, b)| {
-            let scaled = scale_color((r, g, b), self.brightness);
-            RGB8::new(scaled.0, scaled.1, scaled.2)
+    /// Returns the do-not-disturb window and switch.
+    pub fn do_not_disturb(&self) -> DoNotDisturb {
+        self.dnd
+    }
+
+    /// Sets the do-not-disturb window.
+    ///
+    /// By default do-not-disturb is switched off, with a 22:00-07:00 window
+    /// capping the brightness at 4 once it is switched on.
+    pub fn set_do_not_disturb(&mut self, dnd: DoNotDisturb) {
+        self.dnd = dnd;
+    }
+
+    /// Returns true if do-not-disturb currently keeps the ring quiet.
+    ///
+    /// Notifications are dropped and playlists should not be started while it is.
+    pub fn is_do_not_disturb(&self) -> bool {
+        self.timekeeper
+            .now(self.uptime_ms())
+            .is_some_and(|time| self.dnd.is_active(time))
+    }
+
+    /// Returns what the ring currently shows.
+    pub fn mode(&self) -> DisplayMode {
+        self.mode
+    }
+
+    /// Switches what the ring shows.
+    ///
+    /// Timed modes such as a countdown start now; a finished countdown
+    /// returns to the clock.
+    pub fn set_mode(&mut self, mode: DisplayMode) {
+        self.wake_screensaver();
+        self.mode = mode;
+        self.hand_leds = None;
+        self.weather_brief = false;
+        self.mode_started_ms = self.uptime_ms();
+    }
+
+    /// Returns the display mode, hand colors, and brightness as a scene.
+    pub fn scene(&self) -> Scene {
+        Scene {
+            mode: self.mode,
+            colors: self.colors,
+            brightness: self.brightness,
+        }
+    }
+
+    /// Switches to the display mode, hand colors, and brightness of `scene`.
+    pub fn apply_scene(&mut self, scene: Scene) {
+        self.colors = scene.colors;
+        self.brightness = scene.brightness;
+        self.set_mode(scene.mode);
+    }
+
+    /// Replaces the Pomodoro timer, e.g. to change its durations.
+    pub fn set_pomodoro(&mut self, pomodoro: Pomodoro) {
+        self.pomodoro = pomodoro;
+    }
+
+    /// Returns the phase of the Pomodoro timer while it runs.
+    pub fn pomodoro_phase(&self) -> Option<PomodoroPhase> {
+        self.pomodoro.is_running().then(|| self.pomodoro.phase())
+    }
+
+    /// Controls the Pomodoro timer.
+    ///
+    /// Starting or skipping shows the timer; stopping returns to the clock.
+    pub fn pomodoro_command(&mut self, command: PomodoroCommand) {
+        self.pomodoro.apply(command, self.uptime_ms());
+        match command {
+            PomodoroCommand::Stop if self.mode == DisplayMode::Pomodoro => {
+                self.set_mode(DisplayMode::Clock)
+            }
+            PomodoroCommand::Start | PomodoroCommand::Skip
+                if self.mode != DisplayMode::Pomodoro =>
+            {
+                self.set_mode(DisplayMode::Pomodoro)
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns true while the stopwatch is running.
+    pub fn is_stopwatch_running(&self) -> bool {
+        self.stopwatch.is_running()
+    }
+
+    /// Controls the stopwatch and shows it.
+    ///
+    /// Returns the lap time for `StopwatchCommand::Lap` while running.
+    pub fn stopwatch_command(&mut self, command: StopwatchCommand) -> Option<Duration> {
+        let lap_ms = self.stopwatch.apply(command, self.uptime_ms());
+        if let Some(ms) = lap_ms {
+            log::info!("Lap {}: {} ms", self.stopwatch.laps(), ms);
+        }
+        if self.mode != DisplayMode::Stopwatch {
+            self.set_mode(DisplayMode::Stopwatch);
+        }
+        lap_ms.map(|ms| Duration::from_millis(ms.into()))
+    }
+
+    /// Returns the alarm slots.
+    pub fn alarms(&self) -> &Alarms {
+        &self.alarms
+    }
+
+    /// Replaces the alarm slots.
+    pub fn set_alarms(&mut self, alarms: Alarms) {
+        self.alarms = alarms;
+    }
+
+    /// Stops a ringing alarm and returns to the current display mode.
+    ///
+    /// Returns false if no alarm was ringing.
+    pub fn dismiss_alarm(&mut self) -> bool {
+        self.alarms.dismiss()
+    }
+
+    /// Returns when the next alarm rings, or `None` if no alarm is set or
+    /// no date has been received yet.
+    pub fn next_alarm(&self) -> Option<LocalDateTime> {
+        let (date, time) = self.local_now()?;
+        self.alarms.next_after(date, time).map(|(_, next)| next)
+    }
+
+    /// Returns the local date and time, or `None` if no date has been received yet.
+    pub fn local_now(&self) -> Option<(Date, LocalTime)> {
+        let now_ms = self.uptime_ms();
+        Some((self.timekeeper.today(now_ms)?, self.timekeeper.now(now_ms)?))
+    }
+
+    /// Asserts `output` while an alarm rings, from when it starts until it
+    /// is dismissed or removed.
+    pub fn set_alarm_output(&mut self, output: Switch<'a>) {
+        self.alarm_output = Some(output);
+    }
+
+    /// Switches the ring off with `power` while it stays dark, e.g. while
+    /// the display is off, so the LEDs draw no idle current.
+    ///
+    /// `power` must be switched on; as the ring may only just have come
+    /// up, the next frame is written in full.
+    pub fn set_ring_power(&mut self, power: Switch<'a>) {
+        self.ring_power = Some(power);
+        self.power_gate = PowerGate::new();
+        self.front = None;
+        self.face_front = None;
+        self.outer_front = None;
+    }
+
+    /// Lights the ring like a sunrise for `duration` before each alarm, or
+    /// never with `None`.
+    ///
+    /// The ring brightens from dim red through orange to warm white and hands
+    /// over to the alarm animation once the alarm rings.
+    pub fn set_sunrise(&mut self, duration: Option<Duration>) {
+        self.sunrise_ms = duration.map(|d| u32::try_from(d.as_millis()).unwrap_or(u32::MAX));
+    }
+
+    /// Stores the latest temperature reading (°C).
+    pub fn set_temperature(&mut self, temperature: f32) {
+        self.temperature = Some(temperature);
+    }
+
+    /// Sets the range of the temperature gauge.
+    ///
+    /// The default covers room temperatures from 10 °C to 30 °C.
+    pub fn set_temperature_gauge(&mut self, gauge: TemperatureGauge) {
+        self.gauge = gauge;
+    }
+
+    /// Stores the latest weather report.
+    pub fn set_weather(&mut self, weather: Weather) {
+        self.weather = Some(weather);
+    }
+
+    /// Makes the clock face show the weather for one cycle every `interval`,
+    /// or never with `None`.
+    pub fn set_weather_interval(&mut self, interval: Option<Duration>) {
+        self.weather_interval_ms =
+            interval.map(|i| u32::try_from(i.as_millis()).unwrap_or(u32::MAX));
+    }
+
+    /// Switches to a dim ambient glow after `timeout` without activity, or never with `None`.
+    ///
+    /// Button presses, mode changes, and reported presence count as activity.
+    pub fn set_screensaver(&mut self, timeout: Option<Duration>) {
+        let now_ms = self.uptime_ms();
+        self.screensaver = timeout.map(|t| {
+            let mut screensaver =
+                Screensaver::new(u32::try_from(t.as_millis()).unwrap_or(u32::MAX));
+            screensaver.wake(now_ms);
+            screensaver
         });
-        debug!("Showing state: {:?}", pixels);
-        self.driver.set_pixels_slice(pixels.as_slice())?;
-        Ok(())
     }
-}
 
-/// Runs a rainbow startup animation in a background thread.
-///
-/// Uses `RainbowEffect` from `ferriswheel` to create a smooth rainbow
-/// animation that rotates around the clock face until cancelled.
-/// The animation is cancelled automatically when the first MQTT time
-/// message is received (which sets the cancellation flag).
-///
-/// # Arguments
-/// * `clock` - Shared reference to the RGB clock
-/// * `cancel` - Shared cancellation flag
-///
-/// # Returns
-/// A join handle for the animation thread
-pub fn run_startup_animation(
-    clock: Arc<Mutex<RGBClock<'static>>>,
-    cancel: Arc<AtomicBool>,
-) -> std::thread::JoinHandle<()> {
-    std::thread::spawn(move || {
-        use esp_idf_hal::delay::FreeRtos;
-
-        log::info!("Starting rainbow startup animation");
-
-        let mut rainbow = match RainbowEffect::new(12) {
-            Ok(r) => match r.with_speed(RAINBOW_SPEED) {
-                Ok(r) => r
-                    .with_brightness(RAINBOW_BRIGHTNESS)
-                    .with_direction(Direction::Clockwise),
-                Err(e) => {
-                    log::error!("Failed to set rainbow speed: {}", e);
-                    return;
+    /// Records whether WiFi is connected; without it, blue blinks at 12 o'clock.
+    pub fn set_wifi_connected(&mut self, connected: bool) {
+        self.status.wifi_connected = connected;
+    }
+
+    /// Records whether the MQTT broker is connected; without it, purple blinks at 6 o'clock.
+    pub fn set_mqtt_connected(&mut self, connected: bool) {
+        self.status.mqtt_connected = connected;
+    }
+
+    /// Returns the health of the connections and the time source.
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Returns how many frames the render loop drew since boot, wrapping.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    /// Returns the frame on the LEDs, before the output corrections, or
+    /// `None` before the first one was shown.
+    ///
+    /// A face of more than 12 LEDs is given at hour resolution.
+    pub fn shown_frame(&self) -> Option<Frame> {
+        self.front
+    }
+
+    /// Returns the milliseconds since the last tick arrived, if one did.
+    pub fn tick_age_ms(&self) -> Option<u32> {
+        self.timekeeper.age_ms(self.uptime_ms())
+    }
+
+    /// Records whether someone is present, e.g. from a motion or occupancy sensor.
+    ///
+    /// The screensaver stays off while someone is present and starts its
+    /// timeout once they leave.
+    pub fn set_presence(&mut self, present: bool) {
+        let now_ms = self.uptime_ms();
+        if let Some(screensaver) = self.screensaver.as_mut() {
+            if screensaver.set_present(present, now_ms) {
+                log::info!("Screensaver ended by presence");
+            }
+        }
+    }
+
+    /// Turns the display on or off.
+    ///
+    /// While off the LEDs stay dark, but time, modes, timers, and effects
+    /// keep running, so turning it back on shows the current picture at
+    /// once. A ringing alarm still shows, so turning the display off never
+    /// silences a wake-up.
+    pub fn set_power(&mut self, on: bool) {
+        self.powered = on;
+    }
+
+    /// Returns true unless the display is switched off.
+    pub fn is_powered(&self) -> bool {
+        self.powered
+    }
+
+    /// Shows a notification over the ring until it runs out, replacing any earlier one.
+    ///
+    /// The notification tints whatever is on the LEDs, including effects, so
+    /// the time stays readable beneath it. During do-not-disturb the
+    /// notification is dropped.
+    pub fn notify(&mut self, notification: Notification) {
+        if self.is_do_not_disturb() {
+            log::info!("Do not disturb: dropping notification");
+            return;
+        }
+        self.notification = Some((notification, self.uptime_ms()));
+    }
+
+    /// Handles a press of the mode button.
+    ///
+    /// While an alarm rings, any press dismisses it, and while the screensaver
+    /// shows, any press wakes the clock. Otherwise a short press cycles
+    /// through the display modes and a long press starts the Pomodoro timer.
+    /// While the timer is shown, a short press skips to
+    /// the next phase and a long press stops it. While the stopwatch is shown,
+    /// a short press starts or stops it and a long press records a lap, or
+    /// resets it when stopped.
+    pub fn press_button(&mut self, press: ButtonPress) {
+        if self.alarms.is_ringing() {
+            self.dismiss_alarm();
+            return;
+        }
+        if self.wake_screensaver() {
+            return;
+        }
+        let stopwatch_running = self.stopwatch.is_running();
+        match (self.mode, press) {
+            (DisplayMode::Stopwatch, ButtonPress::Short) => {
+                let command = if stopwatch_running {
+                    StopwatchCommand::Stop
+                } else {
+                    StopwatchCommand::Start
+                };
+                self.stopwatch_command(command);
+            }
+            (DisplayMode::Stopwatch, ButtonPress::Long) => {
+                let command = if stopwatch_running {
+                    StopwatchCommand::Lap
+                } else {
+                    StopwatchCommand::Reset
+                };
+                self.stopwatch_command(command);
+            }
+            (DisplayMode::Pomodoro, ButtonPress::Short) => {
+                self.pomodoro_command(PomodoroCommand::Skip)
+            }
+            (DisplayMode::Pomodoro, ButtonPress::Long) => {
+                self.pomodoro_command(PomodoroCommand::Stop)
+            }
+            (mode, ButtonPress::Short) => self.set_mode(mode.next()),
+            (_, ButtonPress::Long) => self.pomodoro_command(PomodoroCommand::Start),
+        }
+    }
+
+    /// Returns true if night mode currently dims the face.
+    pub fn is_night(&self) -> bool {
+        self.timekeeper
+            .now(self.uptime_ms())
+            .is_some_and(|time| self.night.is_active(time))
+    }
+
+    /// Returns the playback speed and frame rate of effects.
+    pub fn animation_config(&self) -> AnimationConfig {
+        self.animation
+    }
+
+    /// Sets the playback speed and frame rate of effects.
+    ///
+    /// Effects that are already playing keep their settings until they end;
+    /// the demo mode follows at once.
+    pub fn set_animation_config(&mut self, config: AnimationConfig) {
+        self.animation = config;
+    }
+
+    /// Switches gamma correction of the output on or off.
+    ///
+    /// WS2812 LEDs are linear in their PWM level, so without it mid levels
+    /// look brighter on the ring than on a screen, e.g. in the simulator.
+    /// Corrected, low brightness levels come out much darker; lit channels
+    /// never drop below level 1.
+    pub fn set_gamma_correction(&mut self, gamma: bool) {
+        self.gamma = gamma;
+        self.front = None;
+    }
+
+    /// Returns the per-LED brightness calibration.
+    pub fn calibration(&self) -> Calibration {
+        self.calibration
+    }
+
+    /// Sets the per-LED brightness calibration.
+    ///
+    /// It applies to everything shown, and the LEDs are rewritten with the
+    /// next frame even if the picture itself has not changed.
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+        self.front = None;
+    }
+
+    /// Returns the LEDs marked as dead.
+    pub fn dead_pixels(&self) -> DeadPixels {
+        self.dead_pixels
+    }
+
+    /// Marks LEDs as dead, so their content shows on working ones.
+    ///
+    /// Like the calibration, this applies to everything shown, from the next
+    /// frame on.
+    pub fn set_dead_pixels(&mut self, dead: DeadPixels) {
+        self.dead_pixels = dead;
+        self.front = None;
+    }
+
+    /// Sets the order in which the LEDs of the ring are wired.
+    ///
+    /// Everything else, including calibration and dead LEDs, refers to
+    /// positions on the face; only the output to the strip is reordered.
+    pub fn set_wiring_order(&mut self, wiring: WiringOrder) {
+        self.wiring = wiring;
+        self.front = None;
+    }
+
+    /// Returns true while the startup animation should keep playing.
+    ///
+    /// Startup ends with the first time received, or earlier through
+    /// [`RGBClock::end_startup`].
+    pub fn is_starting_up(&self) -> bool {
+        self.starting_up && self.timekeeper.last_time().is_none()
+    }
+
+    /// Ends the startup animation without waiting for a time, e.g. when there is no network.
+    pub fn end_startup(&mut self) {
+        self.starting_up = false;
+    }
+
+    /// Plays `sequence` in place of the display mode, replacing any effect
+    /// that is playing.
+    ///
+    /// The sequence runs at the animation speed set when it starts. `done`
+    /// is told whether it played to the end (`true`) or was replaced by
+    /// another effect (`false`).
+    pub fn play_sequence(&mut self, sequence: Sequence, done: Sender<bool>) {
+        log::info!("Starting effect sequence");
+        self.start_effect(Playback::Sequence(sequence), Some(done));
+    }
+
+    /// Plays a startup animation while the clock starts up.
+    ///
+    /// The animation fades in and loops until the clock has started up (see
+    /// [`RGBClock::is_starting_up`]), then fades out, and the display mode
+    /// fades in instead of replacing the last animation frame at once.
+    pub fn play_startup_animation(&mut self, animation: StartupAnimation) {
+        log::info!("Starting {} startup animation", animation.name());
+        let hands = self
+            .colors
+            .to_array()
+            .map(|color| fade_color(color, DEFAULT_BRIGHTNESS));
+        let effect = animation
+            .effect(hands, STARTUP_BRIGHTNESS)
+            .faded(STARTUP_FADE_IN_MS, STARTUP_FADE_OUT_MS);
+        self.start_effect(Playback::Startup(effect), None);
+    }
+
+    fn start_effect(&mut self, playback: Playback, done: Option<Sender<bool>>) {
+        if let Some(replaced) = self.effect.take() {
+            log::info!("Effect replaced");
+            replaced.notify(false);
+        }
+        self.transition = None;
+        self.hand_leds = None;
+        self.effect = Some(PlayingEffect {
+            playback,
+            clock: MonotonicClock::new(),
+            config: self.animation,
+            done,
+        });
+    }
+
+    /// Renders the playing effect into the back buffer and shows it.
+    ///
+    /// A startup animation starts fading out once the clock has started up.
+    /// A finished effect gives the ring back to the display mode, fading
+    /// over to it after a startup animation.
+    fn draw_effect(&mut self) -> Result<()> {
+        let starting_up = self.is_starting_up();
+        let Some(playing) = self.effect.as_mut() else {
+            return Ok(());
+        };
+        let elapsed_ms = playing.config.scale(playing.clock.now_ms());
+        let effect: &mut dyn Effect = match &mut playing.playback {
+            Playback::Sequence(sequence) => sequence,
+            Playback::Startup(animation) => {
+                if !starting_up && !animation.is_released() {
+                    log::info!("Startup finished, fading out the animation");
+                    animation.release(elapsed_ms);
                 }
-            },
-            Err(e) => {
-                log::error!("Failed to create rainbow effect: {}", e);
-                return;
+                animation
             }
         };
+        if effect.duration_ms().is_some_and(|d| elapsed_ms >= d) {
+            return self.finish_effect();
+        }
+        effect.render(elapsed_ms, self.back.pixels_mut());
+        self.show()
+    }
 
-        let mut buffer = [RGB8::default(); 12];
+    /// Ends the playing effect and draws the display mode.
+    fn finish_effect(&mut self) -> Result<()> {
+        let Some(finished) = self.effect.take() else {
+            return Ok(());
+        };
+        log::info!("Effect finished");
+        if let Playback::Startup(_) = finished.playback {
+            let from = self.front.unwrap_or_default();
+            self.transition = Some(Transition::new(from, self.uptime_ms(), STARTUP_HANDOFF_MS));
+        }
+        finished.notify(true);
+        self.refresh()
+    }
 
-        loop {
-            if cancel.load(Ordering::Relaxed) {
-                log::info!("Rainbow animation cancelled");
-                return;
+    /// Returns true if no time was received within `max_age`, or none at all yet.
+    pub fn is_stale(&self, max_age: Duration) -> bool {
+        let max_age_ms = u32::try_from(max_age.as_millis()).unwrap_or(u32::MAX);
+        self.timekeeper.is_stale(self.uptime_ms(), max_age_ms)
+    }
+
+    /// Redraws the ring for the current display mode.
+    ///
+    /// The clock face shows the locally kept current time, and a ringing
+    /// alarm, or the sunrise leading up to it, replaces whatever the mode
+    /// shows, as does the screensaver once the clock is idle. A playing
+    /// effect replaces all of them but a ringing alarm, which ends it; in
+    /// clock mode the face stays dark until the first time arrives.
+    pub fn refresh(&mut self) -> Result<()> {
+        // Alarms only match their exact minute, so they are checked even while an effect plays
+        self.update_alarms();
+        if self.alarms.is_ringing() {
+            if let Some(cancelled) = self.effect.take() {
+                log::info!("Alarm ringing, stopping the effect");
+                cancelled.notify(false);
+            }
+        }
+        if self.effect.is_some() {
+            return self.draw_effect();
+        }
+        if self.draw_alarm() || self.draw_sunrise() || self.draw_screensaver() {
+            return self.show();
+        }
+
+        let elapsed_ms = self.uptime_ms().wrapping_sub(self.mode_started_ms);
+        let brightness = self.current_brightness();
+        let colors = self.face_colors();
+        let mut frame = Frame::new();
+        match self.mode {
+            DisplayMode::Clock => {
+                if self.weather_due() {
+                    log::info!("Showing the weather");
+                    self.set_mode(DisplayMode::Weather);
+                    self.weather_brief = true;
+                    self.weather_shown_ms = self.uptime_ms();
+                    return self.refresh();
+                }
+                let Some(time) = self.timekeeper.now(self.uptime_ms()) else {
+                    self.clear()?;
+                    return self.show();
+                };
+                return self.draw_time(time);
             }
+            DisplayMode::Countdown { duration_ms } => {
+                if elapsed_ms >= duration_ms {
+                    log::info!("Countdown finished");
+                    self.set_mode(DisplayMode::Clock);
+                    return self.refresh();
+                }
+                let remaining_ms = duration_ms - elapsed_ms;
+                render_progress(remaining_ms, duration_ms, colors.second, frame.pixels_mut());
+            }
+            DisplayMode::Progress { percent } => {
+                render_progress(percent.into(), 100, colors.minute, frame.pixels_mut());
+            }
+            DisplayMode::Ambient { color } => frame.pixels_mut().fill(color),
+            DisplayMode::Off => {}
+            DisplayMode::Demo => {
+                // Effects take their brightness separately, so render them unscaled
+                let animation = StartupAnimation::ALL
+                    [(elapsed_ms / DEMO_EFFECT_MS) as usize % StartupAnimation::ALL.len()];
+                let hands = colors.to_array().map(|color| fade_color(color, brightness));
+                let mut effect = animation.effect(hands, brightness);
+                let effect_ms = self.animation.scale(elapsed_ms % DEMO_EFFECT_MS);
+                return self.render_effect(&mut effect, effect_ms);
+            }
+            DisplayMode::Pomodoro => {
+                let now_ms = self.uptime_ms();
+                if let Some(phase) = self.pomodoro.update(now_ms) {
+                    log::info!("Pomodoro {} phase started", phase.name());
+                }
+                self.pomodoro.render(now_ms, frame.pixels_mut());
+            }
+            DisplayMode::Stopwatch => {
+                self.stopwatch.render(
+                    self.uptime_ms(),
+                    colors.minute,
+                    colors.second,
+                    frame.pixels_mut(),
+                );
+            }
+            DisplayMode::Weather => {
+                if self.weather_brief && elapsed_ms >= WEATHER_CYCLE_MS {
+                    self.set_mode(DisplayMode::Clock);
+                    return self.refresh();
+                }
+                if let Some(weather) = &self.weather {
+                    weather.render(elapsed_ms, frame.pixels_mut());
+                }
+            }
+            DisplayMode::Temperature => {
+                if let Some(temperature) = self.temperature {
+                    self.gauge.render(temperature, frame.pixels_mut());
+                }
+            }
+        }
+
+        self.back = frame.map(|color| fade_color(color, brightness));
+        self.show()
+    }
+
+    /// Returns true if the clock face should show the weather by itself now.
+    fn weather_due(&self) -> bool {
+        let since_shown = self.uptime_ms().wrapping_sub(self.weather_shown_ms);
+        self.weather.is_some()
+            && self
+                .weather_interval_ms
+                .is_some_and(|interval| since_shown >= interval)
+    }
 
-            if let Err(e) = rainbow.update(&mut buffer) {
-                log::warn!("Rainbow update error: {}", e);
-                return;
+    /// Starts alarms that are due and switches the alarm output along with them.
+    fn update_alarms(&mut self) {
+        let now_ms = self.uptime_ms();
+        if let (Some(date), Some(time)) =
+            (self.timekeeper.today(now_ms), self.timekeeper.now(now_ms))
+        {
+            if let Some(slot) = self.alarms.update(date, time, now_ms) {
+                log::info!("Alarm {} ringing", slot);
             }
+        }
+        if let Some(output) = &mut self.alarm_output {
+            if let Err(e) = output.set(self.alarms.is_ringing()) {
+                log::warn!("Failed to switch the alarm output: {:?}", e);
+            }
+        }
+    }
 
-            match clock.lock() {
-                Ok(mut c) => {
-                    if let Err(e) = c.set_pixels(&buffer) {
-                        log::warn!("Animation display error: {:?}", e);
-                    }
+    /// Draws a ringing alarm into the back buffer.
+    ///
+    /// Alarms ring at the day brightness, so night mode cannot hide them.
+    /// Returns false, leaving the back buffer alone, if no alarm is ringing.
+    fn draw_alarm(&mut self) -> bool {
+        if !self.alarms.is_ringing() {
+            return false;
+        }
+
+        let now_ms = self.uptime_ms();
+        let mut frame = Frame::new();
+        self.alarms.render(now_ms, frame.pixels_mut());
+        self.back = frame.map(|color| fade_color(color, self.brightness));
+        true
+    }
+
+    /// Draws the sunrise into the back buffer if an alarm rings within the sunrise duration.
+    ///
+    /// Like the alarm itself, the sunrise ignores night mode. Returns false,
+    /// leaving the back buffer alone, if no sunrise is due.
+    fn draw_sunrise(&mut self) -> bool {
+        let Some(sunrise_ms) = self.sunrise_ms else {
+            return false;
+        };
+        let now_ms = self.uptime_ms();
+        let (Some(date), Some(millis)) = (
+            self.timekeeper.today(now_ms),
+            self.timekeeper.millis_since_midnight(now_ms),
+        ) else {
+            return false;
+        };
+        let until_ms = match self.alarms.ms_until_next(date, millis) {
+            Some(until_ms) if until_ms <= sunrise_ms => until_ms,
+            _ => return false,
+        };
+
+        let mut frame = Frame::new();
+        Keyframes::new(&SUNRISE)
+            .with_duration_ms(sunrise_ms)
+            .render(sunrise_ms - until_ms, frame.pixels_mut());
+        self.back = frame.map(|color| fade_color(color, self.brightness));
+        true
+    }
+
+    /// Records activity, returning true if it ended the screensaver.
+    fn wake_screensaver(&mut self) -> bool {
+        let now_ms = self.uptime_ms();
+        let woken = self
+            .screensaver
+            .as_mut()
+            .is_some_and(|screensaver| screensaver.wake(now_ms));
+        if woken {
+            log::info!("Screensaver ended");
+        }
+        woken
+    }
+
+    /// Draws the screensaver glow into the back buffer if the clock has been idle long enough.
+    ///
+    /// Returns false, leaving the back buffer alone, if the screensaver is off.
+    fn draw_screensaver(&mut self) -> bool {
+        let now_ms = self.uptime_ms();
+        let Some(screensaver) = self.screensaver.filter(|s| s.is_idle(now_ms)) else {
+            return false;
+        };
+        let brightness = self.current_brightness().min(SCREENSAVER_BRIGHTNESS);
+        let mut frame = Frame::new();
+        screensaver.render(now_ms, frame.pixels_mut());
+        self.back = frame.map(|color| fade_color(color, brightness));
+        true
+    }
+
+    /// Returns the hand colors for today, taken from the holiday themes if one applies.
+    fn face_colors(&self) -> HandColors {
+        self.timekeeper
+            .today(self.uptime_ms())
+            .and_then(|date| self.themes.colors_for(date))
+            .unwrap_or(self.colors)
+    }
+
+    /// Returns the brightness to show now, easing towards the brightness for
+    /// the current time whenever that changes.
+    fn current_brightness(&mut self) -> u8 {
+        let now_ms = self.uptime_ms();
+        let target = self.target_brightness();
+        if target != self.brightness_ramp.target() {
+            self.brightness_ramp
+                .retarget(target, now_ms, BRIGHTNESS_RAMP_MS);
+        }
+        self.brightness_ramp.level(now_ms)
+    }
+
+    /// Returns the brightness for the current time, dimmed at night and
+    /// capped during do-not-disturb.
+    fn target_brightness(&self) -> u8 {
+        match self.timekeeper.now(self.uptime_ms()) {
+            Some(time) => {
+                let brightness = self.night.brightness_at(time, self.brightness);
+                self.dnd.brightness_at(time, brightness)
+            }
+            None => self.brightness,
+        }
+    }
+
+    /// Milliseconds since the clock was created, wrapping after about 49 days.
+    fn uptime_ms(&self) -> u32 {
+        self.started.elapsed().as_millis() as u32
+    }
+
+    /// Renders an effect into the back buffer at `elapsed_ms` and shows it.
+    fn render_effect(&mut self, effect: &mut impl Effect, elapsed_ms: u32) -> Result<()> {
+        effect.render(elapsed_ms, self.back.pixels_mut());
+        self.show()
+    }
+
+    /// Draws the clock hands for `time` and shows them.
+    ///
+    /// Hands on the same LED are combined according to the overlap policy,
+    /// and the hour markers, if enabled, fill in beneath them. A smooth second
+    /// hand is added on top, anti-aliased between LEDs.
+    /// When the time is stale, the second hand blinks to show that the clock
+    /// is no longer receiving updates. When the hour or minute hand moves to
+    /// the next LED, the face cross-fades instead of jumping.
+    fn draw_time(&mut self, time: LocalTime) -> Result<()> {
+        let uptime_ms = self.uptime_ms();
+        let colors = self.face_colors();
+        let hands = [
+            (hour_to_index(time.hour), colors.hour),
+            (minute_to_index(time.minute), colors.minute),
+            (second_to_index(time.second), colors.second),
+        ];
+
+        // Leave out the second hand during the off phase of the stale blink
+        let blink_off = (uptime_ms / STALE_BLINK_INTERVAL_MS) % 2 == 1;
+        let show_seconds = !(blink_off && self.is_stale(STALE_AFTER));
+        let millis = self
+            .timekeeper
+            .millis_since_midnight(uptime_ms)
+            .unwrap_or(time.to_seconds() * 1000);
+        let second = match (show_seconds, self.smooth_seconds) {
+            (false, _) => SecondHand::Hidden,
+            (true, false) => SecondHand::Step,
+            (true, true) => SecondHand::Sweep(millis % SWEEP_PERIOD_MS),
+        };
+
+        // An outer ring showing seconds takes the second hand off the face
+        let mut face = Frame::new();
+        let face_seconds = show_seconds && !self.outer_ring.is_some_and(|ring| ring.hands.second);
+        let visible = if face_seconds && !self.smooth_seconds {
+            &hands[..]
+        } else {
+            &hands[..2]
+        };
+        render_hands(visible, self.overlap, uptime_ms, face.pixels_mut());
+        if face_seconds && self.smooth_seconds {
+            render_sweep(
+                millis % SWEEP_PERIOD_MS,
+                SWEEP_PERIOD_MS,
+                colors.second,
+                face.pixels_mut(),
+            );
+        }
+        if let Some(markers) = &self.markers {
+            markers.render(face.pixels_mut());
+        }
+
+        let brightness = self.current_brightness();
+        self.back = face.map(|color| fade_color(color, brightness));
+        if self.face.leds() > LED_COUNT {
+            let second = if face_seconds {
+                second
+            } else {
+                SecondHand::Hidden
+            };
+            let mut pixels = [(0, 0, 0); MAX_FACE_LEDS];
+            let native = &mut pixels[..self.face.leds()];
+            self.face
+                .render(time, colors, second, self.overlap, uptime_ms, native);
+            if let Some(markers) = &self.markers {
+                markers.render(native);
+            }
+            self.face_back = Some(pixels.map(|color| fade_color(color, brightness)));
+        }
+        if let Some(ring) = self.outer_ring {
+            let mut outer = [(0, 0, 0); OUTER_LED_COUNT];
+            ring.render(time, colors, second, self.overlap, uptime_ms, &mut outer);
+            self.outer_back = outer.map(|color| fade_color(color, brightness));
+        }
+
+        let hand_leds = (hands[0].0, hands[1].0);
+        let moved = self.hand_leds.is_some_and(|leds| leds != hand_leds);
+        self.hand_leds = Some(hand_leds);
+        // A face of more than 12 LEDs steps its hands too finely to cross-fade
+        if moved && self.transition.is_none() && self.face.leds() == LED_COUNT {
+            let from = self.front.unwrap_or_default();
+            self.transition = Some(Transition::new(from, uptime_ms, HAND_MOVE_MS));
+        }
+        self.show()
+    }
+
+    /// Clears the back buffer by setting all LEDs to black (off).
+    ///
+    /// The LEDs are not updated until the next `show()`.
+    pub fn clear(&mut self) -> Result<()> {
+        self.back = Frame::new();
+        Ok(())
+    }
+
+    /// Presents the back buffer on the physical LEDs, with any running
+    /// notification drawn over it, followed by the outer ring if there is one.
+    ///
+    /// A face of more than 12 LEDs shows the hands drawn at its resolution,
+    /// unless something is drawn over them, and the frame spread over it
+    /// otherwise. Nothing is written if the result matches what is already
+    /// shown. The hands and the outer ring's back buffer are cleared, so they
+    /// only show if the next frame draws them again.
+    pub fn show(&mut self) -> Result<()> {
+        let mut frame = self.compose();
+        if let Some(transition) = self.transition {
+            let now_ms = self.uptime_ms();
+            transition.apply(now_ms, &mut frame);
+            if transition.is_finished(now_ms) {
+                self.transition = None;
+            }
+        }
+        let mut hands = self.face_back.take();
+        let mut outer = core::mem::replace(&mut self.outer_back, [(0, 0, 0); OUTER_LED_COUNT]);
+        if !self.powered && !self.alarms.is_ringing() {
+            frame = Frame::new();
+            hands = None;
+            outer = [(0, 0, 0); OUTER_LED_COUNT];
+        }
+        let face_leds = self.face.leds();
+        let mut native = [(0, 0, 0); MAX_FACE_LEDS];
+        match hands {
+            // Anything drawn over the hands, or a fade, shows at hour resolution
+            Some(hands) if frame == self.back && self.transition.is_none() => native = hands,
+            _ => self.face.scale(&frame, &mut native),
+        }
+        let dark = frame == Frame::new()
+            && native[..face_leds].iter().all(|&pixel| pixel == (0, 0, 0))
+            && outer.iter().all(|&pixel| pixel == (0, 0, 0));
+        self.gate_power(dark)?;
+        let face_unchanged = face_leds == LED_COUNT || self.face_front == Some(native);
+        let outer_unchanged = self.outer_ring.is_none() || self.outer_front == Some(outer);
+        match &self.front {
+            Some(front) if *front == frame && face_unchanged && outer_unchanged => return Ok(()),
+            Some(front) => {
+                for (idx, color) in frame.diff(front) {
+                    debug!("LED {} -> {:?}", idx, color);
                 }
-                Err(e) => log::error!("Clock mutex poisoned: {:?}", e),
             }
+            None => debug!("Showing state: {:?}", frame.pixels()),
+        }
 
-            FreeRtos::delay_ms(RAINBOW_FRAME_DELAY_MS);
+        // Both rings share the current budget, wherever the outer one is connected
+        let mut chain = [(0, 0, 0); MAX_FACE_LEDS + OUTER_LED_COUNT];
+        let outer_leds = self.outer_ring.map_or(0, |ring| ring.leds());
+        let chain = &mut chain[..face_leds + outer_leds];
+        chain[face_leds..].copy_from_slice(&outer[..outer_leds]);
+        // Dead LEDs, calibration, and the wiring order describe a 12-LED face
+        let corrected = if face_leds == LED_COUNT {
+            let mut output = frame;
+            self.dead_pixels.apply(&mut output);
+            if self.gamma {
+                output = output.map(gamma_correct_lit);
+            }
+            self.calibration.apply(&mut output);
+            chain[..LED_COUNT].copy_from_slice(self.wiring.apply(&output).pixels());
+            LED_COUNT
+        } else {
+            chain[..face_leds].copy_from_slice(&native[..face_leds]);
+            0
+        };
+        if self.gamma {
+            for pixel in &mut chain[corrected..] {
+                *pixel = gamma_correct_lit(*pixel);
+            }
         }
-    })
+        if let Some(budget) = self.power_budget {
+            let limited = budget.apply(chain);
+            if limited != self.current_limited {
+                if limited {
+                    log::info!("Dimming frames to stay within {} mA", budget.max_ma());
+                } else {
+                    log::info!("Frames within {} mA again", budget.max_ma());
+                }
+                self.current_limited = limited;
+            }
+        }
+        let pixels: Vec<RGB8> = chain
+            .iter()
+            .map(|&color| {
+                let (r, g, b) = self.channel_order.apply(color);
+                RGB8::new(r, g, b)
+            })
+            .collect();
+        match self.outer_driver.as_mut().filter(|_| outer_leds > 0) {
+            Some(outer_driver) => {
+                let (face, ring) = pixels.split_at(face_leds);
+                self.driver.write_pixels(face)?;
+                outer_driver.write_pixels(ring)?;
+            }
+            None => self.driver.write_pixels(&pixels)?,
+        }
+        self.front = Some(frame);
+        if face_leds > LED_COUNT {
+            self.face_front = Some(native);
+        }
+        if self.outer_ring.is_some() {
+            self.outer_front = Some(outer);
+        }
+        Ok(())
+    }
+
+    /// Switches the ring's power off once it has been dark for a while, and
+    /// back on before a lit frame is shown.
+    ///
+    /// The LEDs come up dark after power-up, so the shown frames are
+    /// forgotten and the next one is written in full.
+    fn gate_power(&mut self, dark: bool) -> Result<()> {
+        let now_ms = self.uptime_ms();
+        let Some(power) = &mut self.ring_power else {
+            return Ok(());
+        };
+        match self.power_gate.update(dark, now_ms) {
+            Some(true) => {
+                debug!("Switching the ring on");
+                power.set(true)?;
+                std::thread::sleep(RING_POWER_UP);
+                self.front = None;
+                self.face_front = None;
+                self.outer_front = None;
+            }
+            Some(false) => {
+                debug!("Switching the dark ring off");
+                power.set(false)?;
+            }
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Returns the back buffer with the status pattern and the running
+    /// notification, if any, drawn over it.
+    ///
+    /// Both are dimmed like the face; the status pattern is left out while an
+    /// effect plays, and a finished notification is dropped.
+    fn compose(&mut self) -> Frame {
+        let mut frame = self.back;
+        let brightness = self.current_brightness();
+        if let Some(fault) = self.status.fault().filter(|_| self.effect.is_none()) {
+            fault.overlay(self.uptime_ms(), brightness, frame.pixels_mut());
+        }
+        let Some((notification, started)) = self.notification else {
+            return frame;
+        };
+        let elapsed_ms = self.uptime_ms().wrapping_sub(started);
+        if notification.is_finished(elapsed_ms) {
+            log::info!("Notification finished");
+            self.notification = None;
+            return frame;
+        }
+
+        let dimmed = Notification {
+            color: fade_color(notification.color, brightness),
+            ..notification
+        };
+        dimmed.overlay(elapsed_ms, frame.pixels_mut());
+        frame
+    }
+}
+
+/// Builds an [`RGBClock`] with the settings it starts with.
+///
+/// # Defaults
+/// - A 12-LED face
+/// - Hours: Blue, Minutes: Green, Seconds: Red, with the built-in holiday themes
+/// - Brightness 10
+/// - No current limit
+/// - Standard WS2812 channel order (GRB)
+pub struct RGBClockBuilder<'a> {
+    driver: Box<dyn LedDriver + 'a>,
+    face: ClockFace,
+    colors: HandColors,
+    themes: ThemeCalendar,
+    brightness: u8,
+    power_budget: Option<PowerBudget>,
+    channel_order: ChannelOrder,
 }
 
-/// Represents a local time with hour, minute, and second components.
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
-pub struct LocalTime {
-    pub hour: u8,
-    pub minute: u8,
-    pub second: u8,
+impl<'a> RGBClockBuilder<'a> {
+    /// Sets the size of the clock face ring.
+    ///
+    /// On a face of 24 or 60 LEDs, the clock hands are drawn at the ring's
+    /// resolution; effects, display modes, and notifications are spread over
+    /// it, each hour position covering the LEDs up to it.
+    pub fn face(mut self, face: ClockFace) -> Self {
+        self.face = face;
+        self
+    }
+
+    /// Sets the hand colors.
+    pub fn hand_colors(mut self, colors: HandColors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Sets the calendar of holiday themes.
+    ///
+    /// By default the built-in holiday themes apply; an empty calendar keeps
+    /// the hand colors all year.
+    pub fn theme_calendar(mut self, themes: ThemeCalendar) -> Self {
+        self.themes = themes;
+        self
+    }
+
+    /// Sets the brightness level (0-255) the clock starts at, without easing in.
+    pub fn brightness(mut self, brightness: u8) -> Self {
+        self.brightness = brightness;
+        self
+    }
+
+    /// Limits the current the ring may draw.
+    ///
+    /// Frames that would draw more are dimmed as a whole just before they
+    /// are sent to the LEDs, after calibration.
+    pub fn power_budget(mut self, budget: PowerBudget) -> Self {
+        self.power_budget = Some(budget);
+        self
+    }
+
+    /// Sets the order the strip takes its color channels in, for strips
+    /// that show colors swapped.
+    pub fn channel_order(mut self, order: ChannelOrder) -> Self {
+        self.channel_order = order;
+        self
+    }
+
+    /// Creates the clock.
+    pub fn build(self) -> Result<RGBClock<'a>> {
+        let clock = RGBClock {
+            colors: self.colors,
+            overlap: OverlapPolicy::default(),
+            markers: None,
+            smooth_seconds: false,
+            brightness: self.brightness,
+            themes: self.themes,
+            night: NightMode::new(DEFAULT_NIGHT_WINDOW, DEFAULT_NIGHT_BRIGHTNESS)
+                .with_switch(NightSwitch::Off),
+            dnd: DoNotDisturb::new(DEFAULT_NIGHT_WINDOW, DEFAULT_DND_BRIGHTNESS),
+            mode: DisplayMode::Clock,
+            mode_started_ms: 0,
+            pomodoro: Pomodoro::new(),
+            stopwatch: Stopwatch::new(),
+            alarms: Alarms::new(),
+            alarm_output: None,
+            notification: None,
+            temperature: None,
+            gauge: TemperatureGauge::default(),
+            weather: None,
+            weather_interval_ms: None,
+            sunrise_ms: None,
+            weather_shown_ms: 0,
+            weather_brief: false,
+            screensaver: None,
+            // Nothing is connected until main reports otherwise
+            status: Status {
+                wifi_connected: false,
+                mqtt_connected: false,
+                time_stale: false,
+            },
+            animation: AnimationConfig::new(FRAME_INTERVAL_MS),
+            brightness_ramp: Ramp::at(self.brightness),
+            powered: true,
+            gamma: false,
+            calibration: Calibration::default(),
+            dead_pixels: DeadPixels::none(),
+            wiring: WiringOrder::default(),
+            power_budget: self.power_budget,
+            current_limited: false,
+            ring_power: None,
+            power_gate: PowerGate::new(),
+            channel_order: self.channel_order,
+            driver: self.driver,
+            back: Frame::new(),
+            front: None,
+            face: self.face,
+            face_back: None,
+            face_front: None,
+            outer_ring: None,
+            outer_driver: None,
+            outer_back: [(0, 0, 0); OUTER_LED_COUNT],
+            outer_front: None,
+            timekeeper: TimeKeeper::new(),
+            started: Instant::now(),
+            effect: None,
+            transition: None,
+            starting_up: true,
+            hand_leds: None,
+            frames: 0,
+        };
+
+        Ok(clock)
+    }
 }
 
-/// Error type for LocalTime conversion failures.
-#[derive(Debug)]
-pub enum ConvertError {
-    /// The provided data is not valid UTF-8
-    InvalidUtf8,
-    /// The JSON data could not be parsed into a LocalTime
-    InvalidJson,
+/// What a playing effect is and how it ends.
+enum Playback {
+    /// Plays to its end, or until replaced
+    Sequence(Sequence),
+    /// Loops while the clock starts up, then fades out
+    Startup(Envelope<StartupEffect>),
 }
 
-impl std::fmt::Display for ConvertError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ConvertError::InvalidUtf8 => write!(f, "Invalid UTF-8 in message data"),
-            ConvertError::InvalidJson => write!(f, "Failed to parse JSON into LocalTime"),
+/// An effect shown in place of the display mode.
+struct PlayingEffect {
+    playback: Playback,
+    /// Times the effect from its start.
+    clock: MonotonicClock,
+    /// Speed at the start; changes apply to the next effect.
+    config: AnimationConfig,
+    /// Told whether the effect played to the end, if anyone asked.
+    done: Option<Sender<bool>>,
+}
+
+impl PlayingEffect {
+    fn notify(self, completed: bool) {
+        if let Some(done) = self.done {
+            // Nobody may be waiting any more
+            let _ = done.send(completed);
         }
     }
 }
 
-impl std::error::Error for ConvertError {}
+/// Draws and shows frames in a background thread at the animation frame rate.
+///
+/// The thread owns the clock: MQTT callbacks, the button, and the main loop
+/// send it [`Command`]s, which are applied before each frame, and the next
+/// frame shows the result. Time advances locally between ticks, so every
+/// frame moves the hands, blinks the second hand once ticks stop arriving,
+/// and advances effects and cross-fades. Frames are paced by a `Ticker`, so
+/// the frame rate stays steady however long drawing takes; it follows the
+/// animation settings.
+///
+/// # Arguments
+/// * `clock` - The RGB clock, owned by the render thread from now on
+/// * `commands` - Commands for the clock, see [`ClockHandle`](crate::command::ClockHandle)
+///
+/// # Returns
+/// A join handle for the render thread
+pub fn run_render_loop(
+    mut clock: RGBClock<'static>,
+    commands: Receiver<Command>,
+) -> std::thread::JoinHandle<()> {
+    use esp_idf_hal::delay::FreeRtos;
+
+    std::thread::spawn(move || {
+        let frame_clock = MonotonicClock::new();
+        let mut ticker = Ticker::new(FRAME_INTERVAL_MS);
+        let mut was_stale = false;
+        let mut was_night = false;
+        let mut was_fault = None;
+        loop {
+            for command in commands.try_iter() {
+                command.apply(&mut clock);
+            }
 
-impl TryFrom<&[u8]> for LocalTime {
-    type Error = ConvertError;
+            let stale = clock.timekeeper.last_time().is_some() && clock.is_stale(STALE_AFTER);
+            if stale != was_stale {
+                if stale {
+                    log::warn!("No time received for {:?}, time is stale", STALE_AFTER);
+                } else {
+                    log::info!("Time updates resumed");
+                }
+                was_stale = stale;
+            }
+            clock.status.time_stale = stale;
+            let fault = clock.status.fault();
+            if fault != was_fault {
+                match fault {
+                    Some(fault) => log::warn!("Showing status: {}", fault.name()),
+                    None => log::info!("Status OK"),
+                }
+                was_fault = fault;
+            }
+            let night = clock.is_night();
+            if night != was_night {
+                log::info!("Night mode {}", if night { "active" } else { "ended" });
+                was_night = night;
+            }
+            if let Err(e) = clock.refresh() {
+                log::warn!("Failed to draw frame: {:?}", e);
+            }
+            clock.frames = clock.frames.wrapping_add(1);
 
-    fn try_from(message: &[u8]) -> Result<Self, Self::Error> {
-        let json = std::str::from_utf8(message).map_err(|_| ConvertError::InvalidUtf8)?;
-        let local_time: LocalTime =
-            serde_json::from_str(json).map_err(|_| ConvertError::InvalidJson)?;
-        Ok(local_time)
-    }
+            let frame_interval_ms = clock.animation_config().frame_interval_ms;
+            if frame_interval_ms != ticker.interval_ms() {
+                log::info!("Drawing a frame every {} ms", frame_interval_ms);
+                ticker = Ticker::new(frame_interval_ms);
+            }
+            FreeRtos::delay_ms(ticker.delay_until_next(frame_clock.now_ms()));
+        }
+    })
 }
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use clock_pure::{
+    decode_ds3231, encode_ds3231, LocalDateTime, DS3231_ADDRESS, DS3231_OSF,
+    DS3231_STATUS_REGISTER, DS3231_TIME_REGISTER,
+};
+use esp_idf_hal::delay::BLOCK;
+use esp_idf_hal::gpio::{InputPin, OutputPin};
+use esp_idf_hal::i2c::{I2c, I2cConfig, I2cDriver};
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_hal::units::FromValueType;
+use std::time::{Duration, Instant};
+
+// How often network time is written to the RTC; it drifts about a second a week
+const SET_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// How long without network time before the RTC takes over, below the 90 s after which the time counts as stale
+const FALLBACK_AFTER: Duration = Duration::from_secs(60);
+
+/// A DS3231 real-time clock on the I2C bus.
+///
+/// The RTC keeps the local time on its backup battery, so the clock can
+/// show the time at boot before the network is up.
+pub struct Ds3231<'d> {
+    i2c: I2cDriver<'d>,
+}
+
+impl<'d> Ds3231<'d> {
+    /// Creates a driver for a DS3231 on `sda` and `scl`, at 100 kHz.
+    pub fn new(
+        i2c: impl Peripheral<P = impl I2c> + 'd,
+        sda: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+        scl: impl Peripheral<P = impl InputPin + OutputPin> + 'd,
+    ) -> Result<Self> {
+        let config = I2cConfig::new().baudrate(100.kHz().into());
+        let i2c = I2cDriver::new(i2c, sda, scl, &config)?;
+        Ok(Self { i2c })
+    }
+
+    /// Reads the date and time, or `None` if the RTC lost power since it
+    /// was last set.
+    pub fn read(&mut self) -> Result<Option<LocalDateTime>> {
+        if self.status()? & DS3231_OSF != 0 {
+            return Ok(None);
+        }
+        let mut registers = [0; 7];
+        self.i2c.write_read(
+            DS3231_ADDRESS,
+            &[DS3231_TIME_REGISTER],
+            &mut registers,
+            BLOCK,
+        )?;
+        let time = decode_ds3231(&registers).map_err(|e| anyhow!("Invalid RTC time: {}", e))?;
+        Ok(Some(time))
+    }
+
+    /// Sets the date and time and marks it valid; times without a date are
+    /// not written.
+    pub fn write(&mut self, time: LocalDateTime) -> Result<()> {
+        let date = time.date.ok_or_else(|| anyhow!("Time has no date"))?;
+        let registers =
+            encode_ds3231(time.time, date).map_err(|e| anyhow!("Cannot set RTC: {}", e))?;
+        let mut bytes = [DS3231_TIME_REGISTER; 8];
+        bytes[1..].copy_from_slice(&registers);
+        self.i2c.write(DS3231_ADDRESS, &bytes, BLOCK)?;
+        let status = self.status()? & !DS3231_OSF;
+        self.i2c
+            .write(DS3231_ADDRESS, &[DS3231_STATUS_REGISTER, status], BLOCK)?;
+        Ok(())
+    }
+
+    fn status(&mut self) -> Result<u8> {
+        let mut status = [0];
+        self.i2c.write_read(
+            DS3231_ADDRESS,
+            &[DS3231_STATUS_REGISTER],
+            &mut status,
+            BLOCK,
+        )?;
+        Ok(status[0])
+    }
+}
+
+/// Keeps a DS3231 and network time in step.
+///
+/// Network ticks that carry a date are trusted and set the RTC, at most once
+/// an hour. When no tick arrived for a minute, the RTC's time stands in for
+/// them, so the clock stays accurate without the network.
+pub struct RtcSync<'d> {
+    rtc: Ds3231<'d>,
+    /// When the last time reached the clock, from the network or the RTC
+    last_time: Instant,
+    last_set: Option<Instant>,
+}
+
+impl<'d> RtcSync<'d> {
+    /// Starts keeping `rtc` in step, counting from now.
+    pub fn new(rtc: Ds3231<'d>) -> Self {
+        Self {
+            rtc,
+            last_time: Instant::now(),
+            last_set: None,
+        }
+    }
+
+    /// Records a tick received from the network, setting the RTC if it is due.
+    pub fn network_tick(&mut self, tick: LocalDateTime) {
+        self.last_time = Instant::now();
+        if tick.date.is_none() || self.last_set.is_some_and(|at| at.elapsed() < SET_INTERVAL) {
+            return;
+        }
+        match self.rtc.write(tick) {
+            Ok(()) => {
+                log::info!("Set the RTC to {:?} {:?}", tick.date, tick.time);
+                self.last_set = Some(Instant::now());
+            }
+            Err(e) => log::warn!("Failed to set the RTC: {:?}", e),
+        }
+    }
+
+    /// Returns the RTC's time when no time reached the clock for a while.
+    pub fn fallback(&mut self) -> Option<LocalDateTime> {
+        if self.last_time.elapsed() < FALLBACK_AFTER {
+            return None;
+        }
+        self.last_time = Instant::now();
+        match self.rtc.read() {
+            Ok(time) => time,
+            Err(e) => {
+                log::warn!("Failed to read the RTC: {:?}", e);
+                None
+            }
+        }
+    }
+}
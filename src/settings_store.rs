@@ -0,0 +1,283 @@
+use anyhow::Result;
+use clock_pure::effects::StartupChoice;
+use clock_pure::{
+    Alarms, Calibration, DeadPixels, DoNotDisturb, HandColors, NetworkSettings, Scenes, Schedule,
+    TimeZone,
+};
+use esp_idf_svc::nvs::{EspDefaultNvsPartition, EspNvs, NvsDefault};
+
+// NVS namespace and keys for persisted settings (keys are at most 15 characters)
+const NAMESPACE: &str = "clock";
+const HAND_COLORS_KEY: &str = "hand_colors";
+const ALARMS_KEY: &str = "alarms";
+const DND_KEY: &str = "dnd";
+const STARTUP_KEY: &str = "startup";
+const SCENES_KEY: &str = "scenes";
+const SCHEDULE_KEY: &str = "schedule";
+const CALIBRATION_KEY: &str = "calibration";
+const DEAD_PIXELS_KEY: &str = "dead_pixels";
+const TIME_ZONE_KEY: &str = "time_zone";
+const NETWORK_KEY: &str = "network";
+
+/// Keeps settings changed at runtime in NVS so they survive a power cycle.
+pub struct SettingsStore {
+    nvs: EspNvs<NvsDefault>,
+}
+
+impl SettingsStore {
+    /// Opens the settings namespace in the default NVS partition.
+    pub fn new(partition: EspDefaultNvsPartition) -> Result<Self> {
+        let nvs = EspNvs::new(partition, NAMESPACE, true)?;
+        Ok(Self { nvs })
+    }
+
+    /// Loads the stored hand colors, or `None` if none were saved or they are unreadable.
+    pub fn load_hand_colors(&self) -> Option<HandColors> {
+        let mut buf = [0u8; HandColors::BYTES];
+        match self.nvs.get_raw(HAND_COLORS_KEY, &mut buf) {
+            Ok(Some(bytes)) => match HandColors::from_bytes(bytes) {
+                Ok(colors) => Some(colors),
+                Err(e) => {
+                    log::warn!("Ignoring stored hand colors: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored hand colors: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the hand colors.
+    pub fn save_hand_colors(&mut self, colors: &HandColors) -> Result<()> {
+        self.nvs.set_raw(HAND_COLORS_KEY, &colors.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored alarm slots, or `None` if none were saved or they are unreadable.
+    pub fn load_alarms(&self) -> Option<Alarms> {
+        let mut buf = [0u8; Alarms::BYTES];
+        match self.nvs.get_raw(ALARMS_KEY, &mut buf) {
+            Ok(Some(bytes)) => match Alarms::from_bytes(bytes) {
+                Ok(alarms) => Some(alarms),
+                Err(e) => {
+                    log::warn!("Ignoring stored alarms: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored alarms: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the alarm slots.
+    pub fn save_alarms(&mut self, alarms: &Alarms) -> Result<()> {
+        self.nvs.set_raw(ALARMS_KEY, &alarms.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored do-not-disturb settings, or `None` if none were saved or they are unreadable.
+    pub fn load_dnd(&self) -> Option<DoNotDisturb> {
+        let mut buf = [0u8; DoNotDisturb::BYTES];
+        match self.nvs.get_raw(DND_KEY, &mut buf) {
+            Ok(Some(bytes)) => match DoNotDisturb::from_bytes(bytes) {
+                Ok(dnd) => Some(dnd),
+                Err(e) => {
+                    log::warn!("Ignoring stored do-not-disturb settings: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored do-not-disturb settings: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the do-not-disturb settings.
+    pub fn save_dnd(&mut self, dnd: &DoNotDisturb) -> Result<()> {
+        self.nvs.set_raw(DND_KEY, &dnd.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored startup animation choice, or `None` if none was saved or it is unreadable.
+    pub fn load_startup(&self) -> Option<StartupChoice> {
+        let mut buf = [0u8; StartupChoice::BYTES];
+        match self.nvs.get_raw(STARTUP_KEY, &mut buf) {
+            Ok(Some(bytes)) => match StartupChoice::from_bytes(bytes) {
+                Ok(choice) => Some(choice),
+                Err(e) => {
+                    log::warn!("Ignoring stored startup animation: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored startup animation: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the startup animation choice.
+    pub fn save_startup(&mut self, choice: StartupChoice) -> Result<()> {
+        self.nvs.set_raw(STARTUP_KEY, &choice.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored scenes, or `None` if none were saved or they are unreadable.
+    pub fn load_scenes(&self) -> Option<Scenes> {
+        let mut buf = [0u8; Scenes::BYTES];
+        match self.nvs.get_raw(SCENES_KEY, &mut buf) {
+            Ok(Some(bytes)) => match Scenes::from_bytes(bytes) {
+                Ok(scenes) => Some(scenes),
+                Err(e) => {
+                    log::warn!("Ignoring stored scenes: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored scenes: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the scenes.
+    pub fn save_scenes(&mut self, scenes: &Scenes) -> Result<()> {
+        self.nvs.set_raw(SCENES_KEY, &scenes.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored schedule, or `None` if none was saved or it is unreadable.
+    pub fn load_schedule(&self) -> Option<Schedule> {
+        let mut buf = [0u8; Schedule::BYTES];
+        match self.nvs.get_raw(SCHEDULE_KEY, &mut buf) {
+            Ok(Some(bytes)) => match Schedule::from_bytes(bytes) {
+                Ok(schedule) => Some(schedule),
+                Err(e) => {
+                    log::warn!("Ignoring stored schedule: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored schedule: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the schedule.
+    pub fn save_schedule(&mut self, schedule: &Schedule) -> Result<()> {
+        self.nvs.set_raw(SCHEDULE_KEY, &schedule.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored LED calibration, or `None` if none was saved or it is unreadable.
+    pub fn load_calibration(&self) -> Option<Calibration> {
+        let mut buf = [0u8; Calibration::BYTES];
+        match self.nvs.get_raw(CALIBRATION_KEY, &mut buf) {
+            Ok(Some(bytes)) => match Calibration::from_bytes(bytes) {
+                Ok(calibration) => Some(calibration),
+                Err(e) => {
+                    log::warn!("Ignoring stored LED calibration: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored LED calibration: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the LED calibration.
+    pub fn save_calibration(&mut self, calibration: &Calibration) -> Result<()> {
+        self.nvs.set_raw(CALIBRATION_KEY, &calibration.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored dead LEDs, or `None` if none were saved or they are unreadable.
+    pub fn load_dead_pixels(&self) -> Option<DeadPixels> {
+        let mut buf = [0u8; DeadPixels::BYTES];
+        match self.nvs.get_raw(DEAD_PIXELS_KEY, &mut buf) {
+            Ok(Some(bytes)) => match DeadPixels::from_bytes(bytes) {
+                Ok(dead) => Some(dead),
+                Err(e) => {
+                    log::warn!("Ignoring stored dead LEDs: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored dead LEDs: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the dead LEDs.
+    pub fn save_dead_pixels(&mut self, dead: &DeadPixels) -> Result<()> {
+        self.nvs.set_raw(DEAD_PIXELS_KEY, &dead.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored time zone, or `None` if none was saved or it is unreadable.
+    pub fn load_time_zone(&self) -> Option<TimeZone> {
+        let mut buf = [0u8; TimeZone::BYTES];
+        match self.nvs.get_raw(TIME_ZONE_KEY, &mut buf) {
+            Ok(Some(bytes)) => match TimeZone::from_bytes(bytes) {
+                Ok(zone) => Some(zone),
+                Err(e) => {
+                    log::warn!("Ignoring stored time zone: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored time zone: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the time zone.
+    pub fn save_time_zone(&mut self, zone: &TimeZone) -> Result<()> {
+        self.nvs.set_raw(TIME_ZONE_KEY, &zone.to_bytes())?;
+        Ok(())
+    }
+
+    /// Loads the stored network settings, or `None` if none were saved or they are unreadable.
+    pub fn load_network(&self) -> Option<NetworkSettings> {
+        let mut buf = [0u8; NetworkSettings::BYTES];
+        match self.nvs.get_raw(NETWORK_KEY, &mut buf) {
+            Ok(Some(bytes)) => match NetworkSettings::from_bytes(bytes) {
+                Ok(network) => Some(network),
+                Err(e) => {
+                    log::warn!("Ignoring stored network settings: {}", e);
+                    None
+                }
+            },
+            Ok(None) => None,
+            Err(e) => {
+                log::warn!("Failed to read stored network settings: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// Saves the network settings.
+    pub fn save_network(&mut self, network: &NetworkSettings) -> Result<()> {
+        self.nvs.set_raw(NETWORK_KEY, &network.to_bytes())?;
+        Ok(())
+    }
+}
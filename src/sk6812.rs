@@ -0,0 +1,73 @@
+use anyhow::Result;
+use clock_pure::{encode_sk6812, Frame, WhitePolicy, LED_COUNT};
+use esp_idf_hal::gpio::OutputPin;
+use esp_idf_hal::peripheral::Peripheral;
+use esp_idf_hal::rmt::config::TransmitConfig;
+use esp_idf_hal::rmt::{FixedLengthSignal, PinState, Pulse, RmtChannel, TxRmtDriver};
+use rgb::RGB8;
+use smart_leds_trait::SmartLedsWrite;
+use std::time::Duration;
+
+// SK6812 bit timing: a 0 is a short high pulse, a 1 a long one, 1.2 µs per bit
+const T0H: Duration = Duration::from_nanos(300);
+const T0L: Duration = Duration::from_nanos(900);
+const T1H: Duration = Duration::from_nanos(600);
+const T1L: Duration = Duration::from_nanos(600);
+
+const FRAME_BITS: usize = 32 * LED_COUNT;
+
+/// A driver for rings of SK6812 RGBW LEDs on an RMT channel.
+///
+/// The clock draws in RGB; the white-channel policy decides how much of each
+/// color the LEDs show on their white die. Built with the `sk6812` feature,
+/// the clock uses it in place of the WS2812 driver.
+pub struct Sk6812Driver<'d> {
+    tx: TxRmtDriver<'d>,
+    policy: WhitePolicy,
+    /// High and low pulses for a 0 and a 1 bit
+    bits: [(Pulse, Pulse); 2],
+}
+
+impl<'d> Sk6812Driver<'d> {
+    /// Creates a driver sending frames on `pin`.
+    pub fn new(
+        channel: impl Peripheral<P = impl RmtChannel> + 'd,
+        pin: impl Peripheral<P = impl OutputPin> + 'd,
+        policy: WhitePolicy,
+    ) -> Result<Self> {
+        let tx = TxRmtDriver::new(channel, pin, &TransmitConfig::new().clock_divider(1))?;
+        let ticks_hz = tx.counter_clock()?;
+        let pulse = |state, duration| Pulse::new_with_duration(ticks_hz, state, &duration);
+        let bits = [
+            (pulse(PinState::High, T0H)?, pulse(PinState::Low, T0L)?),
+            (pulse(PinState::High, T1H)?, pulse(PinState::Low, T1L)?),
+        ];
+        Ok(Self { tx, policy, bits })
+    }
+}
+
+impl SmartLedsWrite for Sk6812Driver<'_> {
+    type Error = anyhow::Error;
+    type Color = RGB8;
+
+    fn write<T, I>(&mut self, iterator: T) -> Result<(), Self::Error>
+    where
+        T: IntoIterator<Item = I>,
+        I: Into<Self::Color>,
+    {
+        let mut frame = Frame::new();
+        for (pixel, color) in frame.pixels_mut().iter_mut().zip(iterator) {
+            let color = color.into();
+            *pixel = (color.r, color.g, color.b);
+        }
+        let mut signal = FixedLengthSignal::<FRAME_BITS>::new();
+        for (byte_index, byte) in encode_sk6812(&frame, self.policy).into_iter().enumerate() {
+            for bit in 0..8 {
+                let one = byte & (0x80 >> bit) != 0;
+                signal.set(byte_index * 8 + bit, &self.bits[usize::from(one)])?;
+            }
+        }
+        self.tx.start_blocking(&signal)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use anyhow::Result;
+use clock_pure::{LocalDateTime, TimeSource, TimeZone};
+use esp_idf_svc::sntp::{EspSntp, SntpConf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Keeps the system time in step with NTP servers and decides whether it or
+/// the network ticks are shown.
+///
+/// The preferred source is shown while it is fresh and the other one stands
+/// in when it is not, see [`TimeSource::pick`].
+pub struct SntpSync {
+    _sntp: EspSntp<'static>,
+    preferred: TimeSource,
+    /// When the system time was last synced
+    last_sync: Arc<Mutex<Option<Instant>>>,
+    last_tick: Option<Instant>,
+}
+
+impl SntpSync {
+    /// Starts syncing with `servers`, as many as ESP-IDF is configured for.
+    pub fn start(servers: &[&'static str], preferred: TimeSource) -> Result<Self> {
+        let mut conf = SntpConf::default();
+        // Repeated servers fill spare slots, which would otherwise keep the default pool
+        for (slot, server) in conf.servers.iter_mut().zip(servers.iter().cycle()) {
+            *slot = *server;
+        }
+        if servers.len() > conf.servers.len() {
+            log::warn!(
+                "Using only the first {} SNTP servers, see CONFIG_LWIP_SNTP_MAX_SERVERS",
+                conf.servers.len()
+            );
+        }
+        let last_sync = Arc::new(Mutex::new(None));
+        let synced = Arc::clone(&last_sync);
+        let sntp = EspSntp::new_with_callback(&conf, move |_| {
+            log::info!("Synced the time with SNTP");
+            if let Ok(mut last_sync) = synced.lock() {
+                *last_sync = Some(Instant::now());
+            }
+        })?;
+        Ok(Self {
+            _sntp: sntp,
+            preferred,
+            last_sync,
+            last_tick: None,
+        })
+    }
+
+    /// Records a tick received from the network and returns whether to show it.
+    pub fn network_tick(&mut self) -> bool {
+        self.last_tick = Some(Instant::now());
+        self.pick() == Some(TimeSource::Tick)
+    }
+
+    /// Returns the SNTP time in `zone` when it is to be shown.
+    pub fn time(&self, zone: &TimeZone) -> Option<LocalDateTime> {
+        if self.pick() != Some(TimeSource::Sntp) {
+            return None;
+        }
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?;
+        Some(zone.local_date_time(now.as_secs() as i64))
+    }
+
+    fn pick(&self) -> Option<TimeSource> {
+        let age = |at: Instant| at.elapsed().as_millis().min(u32::MAX.into()) as u32;
+        let last_sync = self.last_sync.lock().ok().and_then(|last_sync| *last_sync);
+        self.preferred
+            .pick(self.last_tick.map(age), last_sync.map(age))
+    }
+}
@@ -0,0 +1,39 @@
+use anyhow::Result;
+use esp_idf_hal::gpio::{AnyOutputPin, Level, Output, PinDriver};
+
+/// A GPIO switching something on and off, e.g. a relay driving an external
+/// bell or a MOSFET cutting the power of the ring.
+pub struct Switch<'d> {
+    pin: PinDriver<'d, AnyOutputPin, Output>,
+    /// Whether the pin is pulled low to switch on, as many relay boards expect
+    active_low: bool,
+    on: bool,
+}
+
+impl<'d> Switch<'d> {
+    /// Takes `pin` as an output, switched to `on`.
+    pub fn new(pin: AnyOutputPin, active_low: bool, on: bool) -> Result<Switch<'d>> {
+        let mut switch = Self {
+            pin: PinDriver::output(pin)?,
+            active_low,
+            on: !on,
+        };
+        switch.set(on)?;
+        Ok(switch)
+    }
+
+    /// Switches on or off; does nothing if already switched so.
+    pub fn set(&mut self, on: bool) -> Result<()> {
+        if on == self.on {
+            return Ok(());
+        }
+        let level = if on != self.active_low {
+            Level::High
+        } else {
+            Level::Low
+        };
+        self.pin.set_level(level)?;
+        self.on = on;
+        Ok(())
+    }
+}